@@ -91,7 +91,11 @@ mod update {
             return;
         }
 
-        let mut atlas = engine.create_atlas(1024, 1024);
+        let Ok(mut atlas) = engine.create_atlas(1024, 1024) else {
+            #[cfg(feature = "env_logging")]
+            log::warn!("No texture slot left for the icon atlas");
+            return;
+        };
         let mut handles = Vec::new();
 
         if let Ok(entries) = std::fs::read_dir("assets/open-iconic/png/") {
@@ -144,21 +148,12 @@ mod update {
         if state.background.is_some() {
             return;
         }
-        if let Ok(reader) = image::ImageReader::open("assets/background.jpg")
-            && let Ok(img) = reader.decode()
-        {
-            let rgba = img.to_rgba8();
-            let (w, h) = rgba.dimensions();
-
-            #[cfg(feature = "env_logging")]
-            log::info!("Loaded image with dimensions: {}x{}", w, h);
-
-            let handle = engine.load_texture_rgba8(w, h, rgba.as_raw());
-
-            state.background = Some(handle);
-        } else {
-            #[cfg(feature = "env_logging")]
-            log::warn!("Couldn't load image!");
+        match engine.load_texture_from_path("assets/background.jpg") {
+            Ok(handle) => state.background = Some(handle),
+            Err(_e) => {
+                #[cfg(feature = "env_logging")]
+                log::warn!("Couldn't load background: {_e}");
+            }
         }
     }
 
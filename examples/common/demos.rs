@@ -2,7 +2,10 @@ use super::{Message, State};
 use ui::{
     graphics::TargetId,
     model::*,
-    widget::{Button, Column, Container, Element, Length, Rectangle, Row, Spacer, Text, Widget},
+    widget::{
+        Alignment, Button, Column, Container, Element, Length, Rectangle, Row, Spacer, Stack, Text,
+        Widget,
+    },
 };
 
 fn small_block(r: u8, g: u8, b: u8) -> Element<Message> {
@@ -28,43 +31,43 @@ pub mod layout {
             .spacing(8)
             .padding(Vec4::splat(0))
             .color(Color::rgb(240, 240, 240))
-            .size(Size::new(Grow, Fixed(70)))
+            .size(Size::new(Grow(1), Fixed(70)))
             .einto(),
 
             /* 2) Fixed + Grow + Fixed; height fixed, width grow (checks single-grow distribution) */
             Row::new(vec![
-                Rectangle::new(Size::new(Fixed(60), Grow), Color::rgb(255, 200, 0)).einto(),
-                Rectangle::new(Size::new(Grow, Grow), Color::rgb(0, 180, 180)).einto(),
-                Rectangle::new(Size::new(Fixed(60), Grow), Color::rgb(255, 200, 0)).einto(),
+                Rectangle::new(Size::new(Fixed(60), Grow(1)), Color::rgb(255, 200, 0)).einto(),
+                Rectangle::new(Size::new(Grow(1), Grow(1)), Color::rgb(0, 180, 180)).einto(),
+                Rectangle::new(Size::new(Fixed(60), Grow(1)), Color::rgb(255, 200, 0)).einto(),
             ])
             .spacing(10)
             .padding(Vec4::splat(10))
             .color(Color::rgb(220, 220, 240))
-            .size(Size::new(Grow, Fixed(80)))
+            .size(Size::new(Grow(1), Fixed(80)))
             .einto(),
 
             /* 3) Multiple Grow children in a Row (checks equalization logic) */
             Row::new(vec![
-                Rectangle::new(Size::new(Grow, Fixed(50)), Color::rgb(200, 50, 50)).einto(),
-                Rectangle::new(Size::new(Grow, Fixed(50)), Color::rgb(50, 200, 50)).einto(),
-                Rectangle::new(Size::new(Grow, Fixed(50)), Color::rgb(50, 50, 200)).einto(),
+                Rectangle::new(Size::new(Grow(1), Fixed(50)), Color::rgb(200, 50, 50)).einto(),
+                Rectangle::new(Size::new(Grow(1), Fixed(50)), Color::rgb(50, 200, 50)).einto(),
+                Rectangle::new(Size::new(Grow(1), Fixed(50)), Color::rgb(50, 50, 200)).einto(),
             ])
             .spacing(6)
             .padding(Vec4::splat(10))
             .color(Color::rgb(240, 220, 220))
-            .size(Size::new(Grow, Fixed(70)))
+            .size(Size::new(Grow(1), Fixed(70)))
             .einto(),
 
             /* 4) Column with Grow height distribution and fixed caps at top/bottom */
             Column::new(vec![
-                Rectangle::new(Size::new(Grow, Fixed(20)), Color::rgb(80, 80, 80)).einto(),
-                Rectangle::new(Size::new(Grow, Grow), Color::rgb(100, 200, 100)).einto(),
-                Rectangle::new(Size::new(Grow, Fixed(20)), Color::rgb(80, 80, 150)).einto(),
+                Rectangle::new(Size::new(Grow(1), Fixed(20)), Color::rgb(80, 80, 80)).einto(),
+                Rectangle::new(Size::new(Grow(1), Grow(1)), Color::rgb(100, 200, 100)).einto(),
+                Rectangle::new(Size::new(Grow(1), Fixed(20)), Color::rgb(80, 80, 150)).einto(),
             ])
             .spacing(8)
             .padding(Vec4::splat(10))
             .color(Color::rgb(240, 240, 220))
-            .size(Size::new(Grow, Fixed(100)))
+            .size(Size::new(Grow(1), Fixed(100)))
             .einto(),
 
             /* 5) Fit sizing demo: Column(Fit,Fit) measured by fixed children, next to a Grow rectangle */
@@ -80,39 +83,39 @@ pub mod layout {
                 .size(Size::new(Fit, Fit))
                 .color(Color::rgb(230, 200, 230))
                 .einto(),
-                Rectangle::new(Size::new(Grow, Fixed(60)), Color::rgb(180, 180, 180)).einto(),
+                Rectangle::new(Size::new(Grow(1), Fixed(60)), Color::rgb(180, 180, 180)).einto(),
             ])
             .spacing(10)
             .padding(Vec4::splat(10))
             .color(Color::rgb(220, 240, 240))
-            .size(Size::new(Grow, Fixed(80)))
+            .size(Size::new(Grow(1), Fixed(80)))
             .einto(),
 
             /* 6) Nested grow: Row of two Columns; left fixed width, right flexible */
             Row::new(vec![
                 Column::new(vec![
-                    Rectangle::new(Size::new(Grow, Fixed(18)), Color::rgb(160, 160, 0)).einto(),
-                    Rectangle::new(Size::new(Grow, Grow), Color::rgb(160, 100, 0)).einto(),
+                    Rectangle::new(Size::new(Grow(1), Fixed(18)), Color::rgb(160, 160, 0)).einto(),
+                    Rectangle::new(Size::new(Grow(1), Grow(1)), Color::rgb(160, 100, 0)).einto(),
                 ])
                 .spacing(6)
                 .padding(Vec4::splat(6))
-                .size(Size::new(Fixed(200), Grow))
+                .size(Size::new(Fixed(200), Grow(1)))
                 .color(Color::rgb(250, 240, 200))
                 .einto(),
                 Column::new(vec![
-                    Rectangle::new(Size::new(Grow, Grow), Color::rgb(0, 120, 160)).einto(),
-                    Rectangle::new(Size::new(Grow, Fixed(24)), Color::rgb(0, 80, 120)).einto(),
+                    Rectangle::new(Size::new(Grow(1), Grow(1)), Color::rgb(0, 120, 160)).einto(),
+                    Rectangle::new(Size::new(Grow(1), Fixed(24)), Color::rgb(0, 80, 120)).einto(),
                 ])
                 .spacing(6)
                 .padding(Vec4::splat(6))
-                .size(Size::new(Grow, Grow))
+                .size(Size::new(Grow(1), Grow(1)))
                 .color(Color::rgb(200, 240, 250))
                 .einto(),
             ])
             .spacing(10)
             .padding(Vec4::splat(10))
             .color(Color::rgb(240, 230, 230))
-            .size(Size::new(Grow, Fixed(100)))
+            .size(Size::new(Grow(1), Fixed(100)))
             .einto(),
 
             /* 7) Spacing extremes: zero vs nonzero, plus a Grow filler */
@@ -137,12 +140,12 @@ pub mod layout {
                 .size(Size::new(Fixed(120), Fixed(40)))
                 .color(Color::rgb(220, 220, 220))
                 .einto(),
-                Rectangle::new(Size::new(Grow, Fixed(40)), Color::rgb(200, 200, 200)).einto(),
+                Rectangle::new(Size::new(Grow(1), Fixed(40)), Color::rgb(200, 200, 200)).einto(),
             ])
             .spacing(10)
             .padding(Vec4::splat(10))
             .color(Color::rgb(220, 220, 240))
-            .size(Size::new(Grow, Fixed(60)))
+            .size(Size::new(Grow(1), Fixed(60)))
             .einto(),
 
             /* 8) Many children + padding stress */
@@ -153,16 +156,16 @@ pub mod layout {
             .spacing(6)
             .padding(Vec4::splat(16))
             .color(Color::rgb(245, 245, 220))
-            .size(Size::new(Grow, Fixed(56)))
+            .size(Size::new(Grow(1), Fixed(56)))
             .einto(),
 
             /* 9) Test clamping */
             Row::new(vec![
-                Rectangle::new(Size::new(Length::Grow, Length::Fixed(24)), Color::GREEN)
+                Rectangle::new(Size::new(Length::Grow(1), Length::Fixed(24)), Color::GREEN)
                     .min(Size::new(120, 24))       // >= 120px wide, one line tall
                     .max(Size::new(300, 24))       // <= 300px wide
                     .einto(),
-                Rectangle::new(Size::new(Length::Fixed(100), Length::Grow), Color::BLUE)
+                Rectangle::new(Size::new(Length::Fixed(100), Length::Grow(1)), Color::BLUE)
                     .min(Size::new(100, 60))       // at least 60px tall
                     .max(Size::new(100, 120))      // at most 120px tall
                     .einto(),
@@ -170,35 +173,35 @@ pub mod layout {
             .spacing(6)
             .padding(Vec4::splat(16))
             .color(Color::rgb(245, 245, 220))
-            .size(Size::new(Length::Grow, Length::Grow))
+            .size(Size::new(Length::Grow(1), Length::Grow(1)))
             .einto(),
 
             /* 10) Transparent container background over content below */
             Column::new(vec![
-                Rectangle::new(Size::new(Grow, Fixed(20)), Color::rgb(30, 200, 30)).einto(),
-                Rectangle::new(Size::new(Grow, Fixed(20)), Color::rgb(30, 30, 200)).einto(),
+                Rectangle::new(Size::new(Grow(1), Fixed(20)), Color::rgb(30, 200, 30)).einto(),
+                Rectangle::new(Size::new(Grow(1), Fixed(20)), Color::rgb(30, 30, 200)).einto(),
             ])
             .spacing(6)
             .padding(Vec4::splat(10))
             .color(Color::TRANSPARENT)
-            .size(Size::new(Grow, Fixed(60)))
+            .size(Size::new(Grow(1), Fixed(60)))
             .einto(),
 
             /* 11) Container with background, padding, and a single child */
             Container::new(vec![
-                Rectangle::new(Size::new(Grow, Grow), Color::rgb(220, 240, 255)).einto(),
+                Rectangle::new(Size::new(Grow(1), Grow(1)), Color::rgb(220, 240, 255)).einto(),
                 Rectangle::new(Size::new(Fixed(60), Fixed(60)), Color::rgb(255, 0, 0)).einto(),
             ])
             .padding(Vec4::splat(10))
             .color(Color::rgb(210, 210, 210))
-            .size(Size::new(Grow, Fixed(60)))
+            .size(Size::new(Grow(1), Fixed(60)))
             .einto(),
 
         ])
         .color(Color::rgb(100, 80, 100))
         .padding(Vec4::splat(16))
         .spacing(14)
-        .size(Size::new(Grow, Grow))
+        .size(Size::new(Grow(1), Grow(1)))
         .einto()
     }
 }
@@ -231,30 +234,30 @@ pub mod interaction {
                         .collect(),
                 )
                 .color(Color::TRANSPARENT)
-                .size(Size::new(Fit, Grow))
+                .size(Size::new(Fit, Grow(1)))
                 .einto(),
             ])
             .padding(Vec4::splat(10))
             .spacing(10)
             .color(Color::rgb(220, 220, 240))
-            .size(Size::new(Grow, Fixed(60)))
+            .size(Size::new(Grow(1), Fixed(60)))
             .einto(),
             /* 2) button with text */
             Row::new(vec![
                 Button::new_with(
                     Column::new(vec![
-                        Spacer::new(Size::new(Grow, Grow)).einto(),
+                        Spacer::new(Size::new(Grow(1), Grow(1))).einto(),
                         Text::new("Click Me!", 18.0).einto(),
-                        Spacer::new(Size::new(Grow, Grow)).einto(),
+                        Spacer::new(Size::new(Grow(1), Grow(1))).einto(),
                     ])
-                    .size(Size::new(Fit, Grow))
+                    .size(Size::new(Fit, Grow(1)))
                     .einto(),
                 )
                 .color(Color::rgb(200, 50, 50))
                 .hover_color(Color::rgb(50, 200, 50))
                 .pressed_color(Color::rgb(50, 50, 200))
                 .on_press(Message::ButtonPressed)
-                .size(Size::new(Fit, Grow))
+                .size(Size::new(Fit, Grow(1)))
                 .einto(),
                 Row::new(
                     (0..(target.counter % 6))
@@ -265,19 +268,19 @@ pub mod interaction {
                         .collect(),
                 )
                 .color(Color::TRANSPARENT)
-                .size(Size::new(Fit, Grow))
+                .size(Size::new(Fit, Grow(1)))
                 .einto(),
             ])
             .padding(Vec4::splat(10))
             .spacing(10)
             .color(Color::rgb(220, 220, 240))
-            .size(Size::new(Grow, Fixed(60)))
+            .size(Size::new(Grow(1), Fixed(60)))
             .einto(),
         ])
         .color(Color::rgb(100, 80, 100))
         .padding(Vec4::splat(16))
         .spacing(14)
-        .size(Size::new(Grow, Grow))
+        .size(Size::new(Grow(1), Grow(1)))
         .einto()
     }
 }
@@ -297,7 +300,7 @@ pub mod pipeline {
         };
         Container::new(vec![
             SimpleCanvas::new(
-                Size::new(Grow, Grow),
+                Size::new(Grow(1), Grow(1)),
                 "planet",
                 Some(|cx| {
                     cx.ui.request_redraw();
@@ -305,7 +308,7 @@ pub mod pipeline {
             )
             .einto(),
             Row::new(vec![
-                Spacer::new(Size::new(Grow, Fit)).einto(),
+                Spacer::new(Size::new(Grow(1), Fit)).einto(),
                 Text::new(
                     format!(
                         "{:.0}",
@@ -318,12 +321,12 @@ pub mod pipeline {
                 .einto(),
             ])
             .padding(Vec4::splat(10))
-            .size(Size::new(Grow, Fit))
+            .size(Size::new(Grow(1), Fit))
             .einto(),
         ])
         .color(Color::rgb(20, 20, 40))
         .padding(Vec4::splat(0))
-        .size(Size::new(Grow, Grow))
+        .size(Size::new(Grow(1), Grow(1)))
         .einto()
     }
 }
@@ -350,36 +353,43 @@ pub mod texture {
                 Row::new(cells)
                     .spacing(8)
                     .padding(Vec4::splat(8))
-                    .size(Size::new(Grow, Fixed(64)))
+                    .size(Size::new(Grow(1), Fixed(64)))
                     .einto(),
             );
         }
 
-        Container::new(vec![
-            Image::new(Size::new(Grow, Grow), state.background.unwrap_or_default()).einto(),
-            Column::new(vec![
-                Rectangle::new(Size::new(Fixed(70), Fixed(20)), Color::rgb(100, 0, 100)).einto(),
-                Rectangle::new(Size::new(Fixed(40), Fixed(30)), Color::rgb(140, 0, 140)).einto(),
-            ])
-            .spacing(10)
-            .padding(Vec4::splat(10))
-            .color(Color::rgba(220, 240, 240, 1))
-            .size(Size::new(Fixed(70), Fixed(80)))
+        Stack::new(vec![
+            Image::new(
+                Size::new(Grow(1), Grow(1)),
+                state.background.unwrap_or_default(),
+            )
             .einto(),
             Container::new(vec![
                 Column::new(rows)
                     .spacing(8)
                     .padding(Vec4::splat(10))
                     .color(Color::splat(204))
-                    .size(Size::new(Grow, Grow))
+                    .size(Size::new(Grow(1), Grow(1)))
                     .einto(),
             ])
             .padding(Vec4::splat(120))
-            .size(Size::new(Grow, Grow))
+            .size(Size::new(Grow(1), Grow(1)))
             .einto(),
         ])
+        .push(
+            Column::new(vec![
+                Rectangle::new(Size::new(Fixed(70), Fixed(20)), Color::rgb(100, 0, 100)).einto(),
+                Rectangle::new(Size::new(Fixed(40), Fixed(30)), Color::rgb(140, 0, 140)).einto(),
+            ])
+            .spacing(10)
+            .padding(Vec4::splat(10))
+            .color(Color::rgba(220, 240, 240, 1))
+            .size(Size::new(Fixed(70), Fixed(80)))
+            .einto(),
+            Alignment::TopRight,
+        )
         .padding(Vec4::splat(0))
-        .size(Size::new(Grow, Grow))
+        .size(Size::new(Grow(1), Grow(1)))
         .einto()
     }
 }
@@ -407,7 +417,7 @@ pub mod text {
             ])
             .padding(Vec4::new(16, 16, 16, 8))
             .color(Color::TRANSPARENT)
-            .size(Size::new(Grow, Fixed(40)))
+            .size(Size::new(Grow(1), Fixed(40)))
             .einto(),
             // Sidebar items
             Column::new(vec![
@@ -418,29 +428,29 @@ pub mod text {
             .spacing(8)
             .padding(Vec4::new(16, 8, 16, 16))
             .color(Color::TRANSPARENT)
-            .size(Size::new(Grow, Fit))
+            .size(Size::new(Grow(1), Fit))
             .einto(),
         ])
         .spacing(6)
         .padding(Vec4::splat(8))
         .color(bg_panel)
-        .size(Size::new(Fixed(220), Grow))
+        .size(Size::new(Fixed(220), Grow(1)))
         .einto();
 
         // --- Top bar (fixed height) ---
         let topbar = Row::new(vec![
             Text::new("Dashboard", 22.0).color(fg_title).einto(),
-            Spacer::new(Size::new(Grow, Grow)).einto(),
+            Spacer::new(Size::new(Grow(1), Grow(1))).einto(),
             // a little “pill” on the right
             Container::new(vec![Text::new("LIVE", 14.0).weight(Weight::BLACK).einto()])
                 .padding(Vec4::new(10, 6, 10, 6))
                 .color(accent)
-                .size(Size::new(Fit, Grow))
+                .size(Size::new(Fit, Grow(1)))
                 .einto(),
         ])
         .padding(Vec4::new(16, 10, 16, 10))
         .color(bg_panel_alt)
-        .size(Size::new(Grow, Fixed(52)))
+        .size(Size::new(Grow(1), Fixed(52)))
         .einto();
 
         // --- Main content ---
@@ -453,38 +463,38 @@ pub mod text {
         let content = Column::new(vec![
             // Title
             Text::new("Welcome to the Showcase", 20.0)
-                .size(Size::new(Grow, Fit))
+                .size(Size::new(Grow(1), Fit))
                 .color(fg_title)
                 .einto(),
             // Body (multiline)
             Row::new(vec![
                 Text::new(hero_text, 16.0)
-                    .size(Size::new(Grow, Fit))
+                    .size(Size::new(Grow(1), Fit))
                     .color(fg_text)
                     .einto(),
             ])
-            .size(Size::new(Grow, Fit))
+            .size(Size::new(Grow(1), Fit))
             .einto(),
             // Body (fit checks)
             Column::new(vec![
                 Row::new(vec![
-                    Text::new(long, 16.0).size(Size::new(Grow, Fit)).einto(),
-                    Text::new(long, 16.0).size(Size::new(Grow, Fit)).einto(),
+                    Text::new(long, 16.0).size(Size::new(Grow(1), Fit)).einto(),
+                    Text::new(long, 16.0).size(Size::new(Grow(1), Fit)).einto(),
                 ])
-                .size(Size::new(Grow, Fit))
+                .size(Size::new(Grow(1), Fit))
                 .spacing(12)
                 .einto(),
                 Row::new(vec![
-                    Text::new(long, 16.0).size(Size::new(Grow, Fit)).einto(),
+                    Text::new(long, 16.0).size(Size::new(Grow(1), Fit)).einto(),
                 ])
-                .size(Size::new(Grow, Fit))
+                .size(Size::new(Grow(1), Fit))
                 .einto(),
             ])
-            .size(Size::new(Grow, Fit))
+            .size(Size::new(Grow(1), Fit))
             .spacing(12)
             .einto(),
             // Image/preview placeholder
-            Rectangle::new(Size::new(Grow, Fixed(240)), Color::rgb(72, 78, 90)).einto(),
+            Rectangle::new(Size::new(Grow(1), Fixed(240)), Color::rgb(72, 78, 90)).einto(),
             // A couple of stat tiles
             Row::new(vec![
                 Column::new(vec![
@@ -493,7 +503,7 @@ pub mod text {
                 ])
                 .padding(Vec4::splat(12))
                 .color(bg_panel)
-                .size(Size::new(Grow, Fixed(88)))
+                .size(Size::new(Grow(1), Fixed(88)))
                 .einto(),
                 Column::new(vec![
                     Text::new("Warnings", 16.0).color(fg_text).einto(),
@@ -501,7 +511,7 @@ pub mod text {
                 ])
                 .padding(Vec4::splat(12))
                 .color(bg_panel)
-                .size(Size::new(Grow, Fixed(88)))
+                .size(Size::new(Grow(1), Fixed(88)))
                 .einto(),
                 Column::new(vec![
                     Text::new("Errors", 16.0).color(fg_text).einto(),
@@ -509,19 +519,19 @@ pub mod text {
                 ])
                 .padding(Vec4::splat(12))
                 .color(bg_panel)
-                .size(Size::new(Grow, Fixed(88)))
+                .size(Size::new(Grow(1), Fixed(88)))
                 .einto(),
             ])
             .spacing(12)
             .padding(Vec4::splat(0))
             .color(Color::TRANSPARENT)
-            .size(Size::new(Grow, Fit))
+            .size(Size::new(Grow(1), Fit))
             .einto(),
         ])
         .spacing(12)
         .padding(Vec4::splat(16))
         .color(Color::TRANSPARENT)
-        .size(Size::splat(Grow))
+        .size(Size::splat(Grow(1)))
         .einto();
 
         // --- Page layout: sidebar | (topbar + content) ---
@@ -530,13 +540,13 @@ pub mod text {
             Column::new(vec![topbar, content])
                 .spacing(12)
                 .color(Color::TRANSPARENT)
-                .size(Size::new(Grow, Grow))
+                .size(Size::new(Grow(1), Grow(1)))
                 .einto(),
         ])
         .spacing(12)
         .padding(Vec4::splat(12))
         .color(bg_app)
-        .size(Size::new(Grow, Grow))
+        .size(Size::new(Grow(1), Grow(1)))
         .einto()
     }
 }
@@ -38,14 +38,16 @@ impl Pipeline for PlanetPipeline {
             .device
             .create_shader_module(wgpu::ShaderModuleDescriptor {
                 label: Some("Planet Shader"),
-                source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/planet.wgsl").into()),
+                source: wgpu::ShaderSource::Wgsl(
+                    gpu.globals.wgsl(include_str!("../shaders/planet.wgsl"), 0),
+                ),
             });
 
         let layout = gpu
             .device
             .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Planet Layout"),
-                bind_group_layouts: &[],
+                bind_group_layouts: &gpu.globals.layout().into_iter().collect::<Vec<_>>(),
                 push_constant_ranges,
             });
 
@@ -89,12 +91,12 @@ impl Pipeline for PlanetPipeline {
                     unclipped_depth: false,
                     conservative: false,
                 },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
+                // Opaque, so UI behind it can be hidden by depth.
+                depth_stencil: gpu.depth_stencil().map(|state| wgpu::DepthStencilState {
+                    depth_write_enabled: true,
+                    ..state
+                }),
+                multisample: gpu.multisample(),
                 multiview: None,
                 cache: None,
             },
@@ -103,15 +105,12 @@ impl Pipeline for PlanetPipeline {
 
     fn apply_pipeline(
         &self,
+        gpu: &Gpu,
         globals: &Globals,
         _texture_bindgroup: &wgpu::BindGroup,
         render_pass: &mut wgpu::RenderPass<'_>,
     ) {
         render_pass.set_pipeline(self.render_pipeline.as_ref().unwrap());
-        render_pass.set_push_constants(
-            wgpu::ShaderStages::VERTEX_FRAGMENT,
-            0,
-            bytemuck::bytes_of(globals),
-        );
+        gpu.globals.apply(render_pass, 0, globals);
     }
 }
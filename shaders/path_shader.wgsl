@@ -0,0 +1,59 @@
+struct VertexInput {
+    // instance buffer; position and size are the triangle's bounds
+    @location(0) position: vec2<f32>,
+    @location(1) size: vec2<f32>,
+    @location(2) ab: vec4<u32>,
+    @location(3) c: vec4<u32>,
+    // 0 in front to 1 at the back, tested against the depth buffer when there is one
+    @location(5) depth: f32,
+
+    // vertex buffer
+    @location(10) uv: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+struct Globals {
+    window_size: vec2<f32>,
+    mouse_pos: vec2<f32>,
+    mouse_buttons: u32,
+    time: f32,
+    delta_time: f32,
+    frame: u32,
+};
+
+var<push_constant> globals: Globals;
+
+// Each instance is one triangle with its corners stored as f32 bits. The quad's first
+// triangle maps onto them and its second collapses onto the third corner.
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    let a = vec2<f32>(bitcast<f32>(in.ab.x), bitcast<f32>(in.ab.y));
+    let b = vec2<f32>(bitcast<f32>(in.ab.z), bitcast<f32>(in.ab.w));
+    let c = vec2<f32>(bitcast<f32>(in.c.x), bitcast<f32>(in.c.y));
+
+    var p = a;
+    if in.uv.y > 0.5 {
+        p = c;
+    } else if in.uv.x > 0.5 {
+        p = b;
+    }
+
+    let ndc = vec2<f32>(
+        (p.x / globals.window_size.x) * 2.0 - 1.0,
+        1.0 - (p.y / globals.window_size.y) * 2.0
+    );
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(ndc, in.depth, 1.0);
+    out.color = unpack4x8unorm(in.c.z);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -0,0 +1,114 @@
+struct VertexInput {
+    // instance buffer, laid out like textured UI instances
+    @location(0) position: vec2<f32>,
+    @location(1) size: vec2<f32>,
+    @location(2) color: vec4<u32>,
+    @location(3) tex: vec4<u32>,
+    // grow and blur in pixels as f32 bits, for outlines and shadows
+    @location(4) edge: vec4<u32>,
+    // 0 in front to 1 at the back, tested against the depth buffer when there is one
+    @location(5) depth: f32,
+
+    // vertex buffer
+    @location(10) uv: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv_tex: vec2<f32>,
+    @location(2) @interpolate(flat) slot_plus_one: u32,
+    @location(3) @interpolate(flat) gen: u32,
+    @location(4) @interpolate(flat) edge: vec2<f32>,
+};
+
+struct Globals {
+    window_size: vec2<f32>,
+    mouse_pos: vec2<f32>,
+    mouse_buttons: u32,
+    time: f32,
+    delta_time: f32,
+    frame: u32,
+};
+
+var<push_constant> globals: Globals;
+
+@group(0) @binding(0) var tex_arr: binding_array<texture_2d<f32>>;
+@group(0) @binding(1) var samp: sampler;
+@group(0) @binding(2) var<storage, read> gens: array<u32>;
+
+// Texels between the edge and where the distance field saturates; `SDF_SPREAD` in
+// `render/text.rs`.
+const SPREAD: f32 = 8.0;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    let uv = vec2<f32>(in.uv.x, 1.0 - in.uv.y);
+
+    let local_pos = uv * in.size;
+
+    // Rotation (radians, clockwise) and scale minus one around the quad's center.
+    let angle = bitcast<f32>(in.color[2]);
+    let scale = 1.0 + bitcast<f32>(in.color[3]);
+    let half = in.size * 0.5;
+    let d = (local_pos - half) * scale;
+    let turned = vec2<f32>(d.x * cos(angle) - d.y * sin(angle), d.x * sin(angle) + d.y * cos(angle));
+    let world_pos = in.position + half + turned;
+    let ndc = vec2<f32>(
+        (world_pos.x / globals.window_size.x) * 2.0 - 1.0,
+        1.0 - (world_pos.y / globals.window_size.y) * 2.0
+    );
+
+    let tex_scale = unpack2x16unorm(in.tex.z);
+    let offs = unpack2x16unorm(in.tex.w);
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(ndc, in.depth, 1.0);
+    out.color = unpack4x8unorm(in.color[0]);
+    out.uv_tex = uv * tex_scale + offs;
+    out.slot_plus_one = in.tex.x;
+    out.gen = in.tex.y;
+    out.edge = bitcast<vec2<f32>>(in.edge.xy);
+    return out;
+}
+
+// The shared sampler doesn't filter, so neighbouring distances are blended here.
+fn distance_at(idx: u32, texel: vec2<f32>) -> f32 {
+    let dims = vec2<i32>(textureDimensions(tex_arr[idx]));
+    let p = texel - vec2<f32>(0.5);
+    let base = vec2<i32>(floor(p));
+    let f = p - floor(p);
+    let hi = dims - vec2<i32>(1);
+    let d00 = textureLoad(tex_arr[idx], clamp(base, vec2<i32>(0), hi), 0).a;
+    let d10 = textureLoad(tex_arr[idx], clamp(base + vec2<i32>(1, 0), vec2<i32>(0), hi), 0).a;
+    let d01 = textureLoad(tex_arr[idx], clamp(base + vec2<i32>(0, 1), vec2<i32>(0), hi), 0).a;
+    let d11 = textureLoad(tex_arr[idx], clamp(base + vec2<i32>(1, 1), vec2<i32>(0), hi), 0).a;
+    return mix(mix(d00, d10, f.x), mix(d01, d11, f.x), f.y);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Derivatives need uniform control flow, so they're taken before anything returns.
+    let uv_width = fwidth(in.uv_tex);
+    if in.slot_plus_one == 0u {
+        return vec4<f32>(0.0);
+    }
+    let idx = in.slot_plus_one - 1u;
+    if gens[idx] != in.gen {
+        return vec4<f32>(0.0);
+    }
+
+    let dims = vec2<f32>(textureDimensions(tex_arr[idx]));
+    let texel = in.uv_tex * dims;
+    // Screen pixels per distance field texel, however the quad is scaled.
+    let per_texel = 1.0 / max(length(uv_width * dims) * 0.70710678, 1e-4);
+
+    // Distances are stored with the edge at one half and `SPREAD` texels per half.
+    let d = distance_at(idx, texel);
+    let px = (d - 0.5) * 2.0 * SPREAD * per_texel + in.edge.x;
+    let blur = in.edge.y;
+    if blur > 0.0 {
+        return in.color * smoothstep(-blur * 0.5, blur * 0.5, px);
+    }
+    return in.color * clamp(px + 0.5, 0.0, 1.0);
+}
@@ -4,6 +4,10 @@ struct VertexInput {
     @location(1) size: vec2<f32>,
     @location(2) color: vec4<u32>,
     @location(3) tex: vec4<u32>,
+    // corner radii as f32 bits: top-left, top-right, bottom-right, bottom-left
+    @location(4) radii: vec4<u32>,
+    // 0 in front to 1 at the back, tested against the depth buffer when there is one
+    @location(5) depth: f32,
 
     // vertex buffer
     @location(10) uv: vec2<f32>,
@@ -15,14 +19,26 @@ struct VertexOutput {
     @location(1) uv_tex: vec2<f32>,
     @location(2) slot_plus_one: u32,
     @location(3) gen: u32,
+    @location(4) local: vec2<f32>,
+    @location(5) @interpolate(flat) size: vec2<f32>,
+    @location(6) @interpolate(flat) radii: vec4<f32>,
+    // Effect parameters of untextured instances, whose `gen` names the effect.
+    @location(7) @interpolate(flat) params: vec2<u32>,
+    // Border widths: left, top, right, bottom.
+    @location(8) @interpolate(flat) widths: vec4<f32>,
 };
 
+const EFFECT_SHADOW: u32 = 1u;
+const EFFECT_LINEAR: u32 = 2u;
+const EFFECT_RADIAL: u32 = 3u;
+const EFFECT_BORDER: u32 = 4u;
+
 struct Globals {
     window_size: vec2<f32>,
-    time: f32,
-    delta_time: f32,
     mouse_pos: vec2<f32>,
     mouse_buttons: u32,
+    time: f32,
+    delta_time: f32,
     frame: u32,
 };
 
@@ -37,29 +53,160 @@ fn vs_main(in: VertexInput) -> VertexOutput {
     let uv = vec2<f32>(in.uv.x, 1.0 - in.uv.y);
 
     let local_pos = uv * in.size;
-    let world_pos = in.position + local_pos;
+
+    // Rotation (radians, clockwise) and scale minus one around the quad's center.
+    let angle = bitcast<f32>(in.color[2]);
+    let scale = 1.0 + bitcast<f32>(in.color[3]);
+    let half = in.size * 0.5;
+    let d = (local_pos - half) * scale;
+    let turned = vec2<f32>(d.x * cos(angle) - d.y * sin(angle), d.x * sin(angle) + d.y * cos(angle));
+    let world_pos = in.position + half + turned;
     let ndc = vec2<f32>(
         (world_pos.x / globals.window_size.x) * 2.0 - 1.0,
         1.0 - (world_pos.y / globals.window_size.y) * 2.0
     );
 
-    let scale = unpack2x16unorm(in.tex.z);
+    let tex_scale = unpack2x16unorm(in.tex.z);
     let offs = unpack2x16unorm(in.tex.w);
-    let uv_tex = uv * scale + offs;
+    let uv_tex = uv * tex_scale + offs;
 
     var out: VertexOutput;
-    out.position = vec4<f32>(ndc, 0.0, 1.0);
+    out.position = vec4<f32>(ndc, in.depth, 1.0);
     out.color = unpack4x8unorm(in.color[0]);
     out.uv_tex = uv_tex;
     out.slot_plus_one = in.tex.x;
     out.gen = in.tex.y;
+    out.local = local_pos;
+    out.size = in.size;
+    out.radii = bitcast<vec4<f32>>(in.radii);
+    out.widths = unpack4x8unorm(in.color[1]) * 255.0;
+    out.params = in.tex.zw;
     return out;
 }
 
+// Signed distance to a rounded rect with half extents `half` and corner `radii`, at
+// `centered` pixels from its center.
+fn rounded_distance(centered: vec2<f32>, half: vec2<f32>, radii: vec4<f32>) -> f32 {
+    let column = select(vec2<f32>(radii.x, radii.w), vec2<f32>(radii.y, radii.z), centered.x > 0.0);
+    let radius = select(column.x, column.y, centered.y > 0.0);
+    let r = max(min(radius, min(half.x, half.y)), 0.0);
+    let q = abs(centered) - (half - vec2<f32>(r));
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - r;
+}
+
+// Antialiased coverage of a rounded rect with corner `radii`, at `local` pixels from its
+// top-left corner.
+fn rounded_coverage(local: vec2<f32>, size: vec2<f32>, radii: vec4<f32>) -> f32 {
+    let half = abs(size) * 0.5;
+    let d = rounded_distance(abs(local) - half, half, radii);
+    return clamp(0.5 - d, 0.0, 1.0);
+}
+
+// Coverage of the band `widths` thick along the inside of a rounded rect. With `side` below 4
+// only the part closest to that side (relative to its width) is covered, so corners are split
+// diagonally between neighbouring sides.
+fn border_coverage(local: vec2<f32>, size: vec2<f32>, radii: vec4<f32>, widths: vec4<f32>, side: u32) -> f32 {
+    let p = abs(local);
+    let extent = abs(size);
+    let half = extent * 0.5;
+    let outer = rounded_distance(p - half, half, radii);
+
+    let inner_half = max(half - vec2<f32>(widths.x + widths.z, widths.y + widths.w) * 0.5, vec2<f32>(0.0));
+    let inner_center = p - half - vec2<f32>(widths.x - widths.z, widths.y - widths.w) * 0.5;
+    let inner_radii = max(radii - vec4<f32>(
+        max(widths.x, widths.y),
+        max(widths.z, widths.y),
+        max(widths.z, widths.w),
+        max(widths.x, widths.w),
+    ), vec4<f32>(0.0));
+    let inner = rounded_distance(inner_center, inner_half, inner_radii);
+    let cover = clamp(0.5 - outer, 0.0, 1.0) * clamp(0.5 + inner, 0.0, 1.0);
+
+    if side < 4u {
+        // How deep into each side's band the fragment is; sides without a band never win.
+        let depth = vec4<f32>(p.x, p.y, extent.x - p.x, extent.y - p.y)
+            / max(widths, vec4<f32>(1e-4))
+            + select(vec4<f32>(0.0), vec4<f32>(1e9), widths <= vec4<f32>(0.0));
+        var nearest = 0u;
+        for (var i = 1u; i < 4u; i++) {
+            if depth[i] < depth[nearest] {
+                nearest = i;
+            }
+        }
+        if nearest != side {
+            return 0.0;
+        }
+    }
+    return cover;
+}
+
+// Abramowitz and Stegun 7.1.27, good to about 5e-4.
+fn erf(x: f32) -> f32 {
+    let a = abs(x);
+    let t = 1.0 + (0.278393 + (0.230389 + 0.078108 * a * a) * a) * a;
+    let t2 = t * t;
+    return sign(x) * (1.0 - 1.0 / (t2 * t2));
+}
+
+// Coverage of a rounded rect inset by `margin` into the quad, convolved with a gaussian of
+// half `blur` standard deviation, approximated along the distance field.
+fn shadow_coverage(local: vec2<f32>, size: vec2<f32>, radii: vec4<f32>, blur: f32, margin: f32) -> f32 {
+    let half = abs(size) * 0.5;
+    let d = rounded_distance(abs(local) - half, half - vec2<f32>(margin), radii);
+    if blur <= 0.0 {
+        return clamp(0.5 - d, 0.0, 1.0);
+    }
+    let sigma = blur * 0.5;
+    return 0.5 - 0.5 * erf(d / (sigma * 1.41421356));
+}
+
+// Color of a gradient from `start` to the end color in `params.x`; linear ones run along the
+// angle in `params.y`, radial ones out from the center.
+fn gradient_color(local: vec2<f32>, size: vec2<f32>, start: vec4<f32>, effect: u32, params: vec2<u32>) -> vec4<f32> {
+    let half = max(abs(size) * 0.5, vec2<f32>(0.5));
+    let centered = abs(local) - half;
+    var t: f32;
+    if effect == EFFECT_LINEAR {
+        let angle = bitcast<f32>(params.y);
+        let dir = vec2<f32>(cos(angle), sin(angle));
+        let extent = abs(half.x * dir.x) + abs(half.y * dir.y);
+        t = dot(centered, dir) / (2.0 * extent) + 0.5;
+    } else {
+        t = length(centered / half);
+    }
+    return mix(start, unpack4x8unorm(params.x), clamp(t, 0.0, 1.0));
+}
+
+// Outlines of rounded clips only mark the stencil buffer, wherever they cover the pixel.
+@fragment
+fn fs_mask(in: VertexOutput) -> @location(0) vec4<f32> {
+    if rounded_coverage(in.local, in.size, in.radii) < 0.5 {
+        discard;
+    }
+    return vec4<f32>(0.0);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if in.slot_plus_one == 0u && in.gen == EFFECT_SHADOW {
+        let blur = bitcast<f32>(in.params.x);
+        let margin = bitcast<f32>(in.params.y);
+        return in.color * shadow_coverage(in.local, in.size, in.radii, blur, margin);
+    }
+    if in.slot_plus_one == 0u && in.gen == EFFECT_BORDER {
+        return in.color * border_coverage(in.local, in.size, in.radii, in.widths, in.params.x);
+    }
+
+    var cover = 1.0;
+    if any(in.radii > vec4<f32>(0.0)) {
+        cover = rounded_coverage(in.local, in.size, in.radii);
+    }
+
     if in.slot_plus_one == 0u {
-        return in.color;
+        if in.gen == EFFECT_LINEAR || in.gen == EFFECT_RADIAL {
+            return gradient_color(in.local, in.size, in.color, in.gen, in.params) * cover;
+        }
+        return in.color * cover;
     }
 
     let idx = in.slot_plus_one - 1u;
@@ -68,5 +215,5 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     }
 
     let c = textureSample(tex_arr[idx], samp, in.uv_tex);
-    return c * in.color;
+    return c * in.color * cover;
 }
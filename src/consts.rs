@@ -1,17 +1,21 @@
 pub const DEFAULT_MAX_TEXTURES: u32 = 128;
+/// Texture slots the registry grows to once the default ones are taken, where the device
+/// allows it.
+pub const MAX_TEXTURES: u32 = 4096;
 pub const DEFAULT_MAX_INSTANCES: u64 = 10_000;
+/// Time between frames where the display's refresh rate isn't known.
+pub const DEFAULT_FRAME_INTERVAL: std::time::Duration =
+    std::time::Duration::from_nanos(1_000_000_000 / 60);
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+pub const DEPTH_STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
 
 pub(crate) fn feature_backends() -> wgpu::Backends {
     if cfg!(any(feature = "metal", feature = "vulkan")) {
-        let mut b = wgpu::Backends::empty();
+        let b = wgpu::Backends::empty();
         #[cfg(feature = "vulkan")]
-        {
-            b |= wgpu::Backends::VULKAN;
-        }
+        let b = b | wgpu::Backends::VULKAN;
         #[cfg(feature = "metal")]
-        {
-            b |= wgpu::Backends::METAL;
-        }
+        let b = b | wgpu::Backends::METAL;
         b
     } else {
         wgpu::Backends::PRIMARY
@@ -37,6 +41,18 @@ pub(crate) fn default_backends() -> wgpu::Backends {
     env_override_backends(feature_backends())
 }
 
+/// `UI_GLOBALS=uniform` forces the uniform-buffer path for `Globals` even when the adapter
+/// supports push constants, which makes the fallback testable on any machine.
+pub(crate) fn force_uniform_globals() -> bool {
+    std::env::var("UI_GLOBALS").is_ok_and(|v| v.eq_ignore_ascii_case("uniform"))
+}
+
+/// `UI_REDUCED_MOTION=1` disables momentum, rubber-banding and animated snapping.
+pub(crate) fn reduced_motion() -> bool {
+    std::env::var("UI_REDUCED_MOTION")
+        .is_ok_and(|v| matches!(v.to_ascii_lowercase().as_str(), "1" | "on" | "true" | "yes"))
+}
+
 pub(crate) fn default_instance_flags() -> wgpu::InstanceFlags {
     let mut flags = wgpu::InstanceFlags::empty();
 
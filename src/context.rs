@@ -1,7 +1,15 @@
+use std::{any::Any, collections::HashMap, path::PathBuf};
+
 use crate::{
+    event::{
+        CursorIcon, KeyEvent, KeyState, LogicalKey, Modifiers, MouseButton, Preedit, RoutedInput,
+        ScrollDelta, Shortcut, TouchPhase,
+    },
     graphics::{Globals, Gpu},
-    model::Position,
+    model::{Color, Position, Size, Vec4},
+    primitive::{Instance, Mask},
     render::{text::TextSystem, texture::TextureRegistry},
+    widget::{Direction, Toast},
 };
 
 pub type Id = u64;
@@ -16,18 +24,89 @@ pub fn reset_ids_for_frame() {
     NEXT_ID.store(1, Ordering::Relaxed);
 }
 
+/// Runs `f` with ids handed out from a range derived from `scope` only, so a subtree built
+/// inside gets the same ids whatever was built before it. Scoped ranges live in the upper
+/// half of the id space, away from the per-frame sequence.
+pub(crate) fn with_id_scope<R>(scope: Id, f: impl FnOnce() -> R) -> R {
+    // splitmix64 finalizer, to spread neighbouring scopes apart.
+    let mut z = scope.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    let base = (z | (1 << 63)) & !0xFFFF_FFFF;
+
+    let prev = NEXT_ID.swap(base, Ordering::Relaxed);
+    let r = f();
+    NEXT_ID.store(prev, Ordering::Relaxed);
+    r
+}
+
+pub(crate) type ShortcutBinding<M> = (Shortcut, Box<dyn Fn() -> M>);
+
 pub struct Context<M> {
     pub mouse_pos: Position<f32>,
     pub mouse_down: bool,
     pub mouse_pressed: bool,
     pub mouse_released: bool,
+    pub right_down: bool,
+    pub right_pressed: bool,
+    pub right_released: bool,
+    // Bits of `MouseButton::bit` for every button, like the fields above.
+    buttons_down: u32,
+    buttons_pressed: u32,
+    buttons_released: u32,
 
     pub hot_item: Option<Id>,
     pub active_item: Option<Id>,
+    // Widget that gets the pointer to itself until released, see `capture_pointer`.
+    captured: Option<Id>,
     pub kbd_focus_item: Option<Id>,
+    pub modifiers: Modifiers,
+
+    keys: Vec<KeyEvent>,
+    text: String,
+    preedit: Option<Preedit>,
+    // Caret rect the focused text widget asked input method popups to stay clear of during
+    // the current event pass.
+    ime_area: Option<(Position<i32>, Size<i32>)>,
+    cursor: CursorIcon,
+    // Text copied by a widget, waiting for the platform to put it on the clipboard.
+    copied: Option<String>,
+    // Whether a widget asked for the clipboard's text, delivered as committed text.
+    paste_requested: bool,
+    // Top and bottom edge of a span a widget wants scrolled into view.
+    reveal: Option<(i32, i32)>,
+    wheel: Vec<(ScrollDelta, TouchPhase)>,
+    // Files dragged over the target, and those dropped since the last event pass.
+    hovered_files: Vec<PathBuf>,
+    dropped_files: Vec<PathBuf>,
+    // Whether the pointer moved, scrolled or changed buttons since the last event pass.
+    pointer_input: bool,
+    // Routed input widgets consumed during the current event pass.
+    consumed: Vec<RoutedInput>,
+    widget_state: HashMap<Id, Box<dyn Any>>,
+    // Overlay rects registered during the current event pass, and those of the previous one
+    // that widgets handled earlier in the tree test against.
+    overlays: Vec<Overlay>,
+    prev_overlays: Vec<Overlay>,
+    // Overlay layer the widgets currently being handled live in; 0 is the main tree.
+    layer: u32,
+    // Focusable widgets in the order they registered during the current event pass, and the
+    // order of the previous one that Tab moves through.
+    focus_order: Vec<Id>,
+    prev_focus_order: Vec<Id>,
+    // Tab presses of the current event pass, true going forward, and whether a widget kept
+    // them from moving focus.
+    tabs: Vec<bool>,
+    tab_claimed: bool,
+    direction: Direction,
+    // Offsets pushed by translated subtrees being handled, innermost last.
+    translations: Vec<Position<i32>>,
 
     messages: Vec<M>,
+    toasts: Vec<Toast>,
     redraw_requested: bool,
+    redraw_at: Option<f32>,
 }
 
 impl<M> Default for Context<M> {
@@ -43,13 +122,47 @@ impl<M> Context<M> {
             mouse_down: false,
             mouse_pressed: false,
             mouse_released: false,
+            right_down: false,
+            right_pressed: false,
+            right_released: false,
+            buttons_down: 0,
+            buttons_pressed: 0,
+            buttons_released: 0,
 
             hot_item: None,
             active_item: None,
+            captured: None,
             kbd_focus_item: None,
+            modifiers: Modifiers::default(),
+
+            keys: Vec::new(),
+            text: String::new(),
+            preedit: None,
+            ime_area: None,
+            cursor: CursorIcon::Default,
+            copied: None,
+            paste_requested: false,
+            reveal: None,
+            wheel: Vec::new(),
+            hovered_files: Vec::new(),
+            dropped_files: Vec::new(),
+            pointer_input: false,
+            consumed: Vec::new(),
+            widget_state: HashMap::new(),
+            overlays: Vec::new(),
+            prev_overlays: Vec::new(),
+            layer: 0,
+            focus_order: Vec::new(),
+            prev_focus_order: Vec::new(),
+            tabs: Vec::new(),
+            tab_claimed: false,
+            direction: Direction::LeftToRight,
+            translations: Vec::new(),
 
             messages: Vec::new(),
+            toasts: Vec::new(),
             redraw_requested: false,
+            redraw_at: None,
         }
     }
 
@@ -61,6 +174,464 @@ impl<M> Context<M> {
         self.messages.push(msg);
     }
 
+    pub fn button_down(&self, button: MouseButton) -> bool {
+        self.buttons_down & button.bit() != 0
+    }
+
+    /// Whether `button` went down with its latest event.
+    pub fn button_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed & button.bit() != 0
+    }
+
+    /// Whether `button` went up with its latest event.
+    pub fn button_released(&self, button: MouseButton) -> bool {
+        self.buttons_released & button.bit() != 0
+    }
+
+    /// Bits of `MouseButton::bit` for the buttons held down.
+    pub fn buttons(&self) -> u32 {
+        self.buttons_down
+    }
+
+    pub(crate) fn set_button(&mut self, button: MouseButton, down: bool) {
+        let bit = button.bit();
+        let was_down = self.buttons_down & bit != 0;
+        let (pressed, released) = (!was_down && down, was_down && !down);
+
+        let set = |bits: u32, on: bool| if on { bits | bit } else { bits & !bit };
+        self.buttons_down = set(self.buttons_down, down);
+        self.buttons_pressed = set(self.buttons_pressed, pressed);
+        self.buttons_released = set(self.buttons_released, released);
+        self.pointer_input = true;
+
+        match button {
+            MouseButton::Left => {
+                self.mouse_down = down;
+                self.mouse_pressed = pressed;
+                self.mouse_released = released;
+            }
+            MouseButton::Right => {
+                self.right_down = down;
+                self.right_pressed = pressed;
+                self.right_released = released;
+            }
+            _ => {}
+        }
+    }
+
+    /// Key events received since the last event pass.
+    pub fn keys(&self) -> &[KeyEvent] {
+        &self.keys
+    }
+
+    /// Text committed by an input method since the last event pass.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Wheel and touchpad scrolling received since the last event pass, unless a widget handled
+    /// earlier took it.
+    pub fn wheel(&self) -> &[(ScrollDelta, TouchPhase)] {
+        &self.wheel
+    }
+
+    /// Takes the pending scrolling so widgets handled later, such as an enclosing scroll view,
+    /// don't react to it too.
+    pub fn take_wheel(&mut self) -> Vec<(ScrollDelta, TouchPhase)> {
+        std::mem::take(&mut self.wheel)
+    }
+
+    pub(crate) fn push_wheel(&mut self, delta: ScrollDelta, phase: TouchPhase) {
+        self.wheel.push((delta, phase));
+        self.pointer_input = true;
+    }
+
+    /// Files being dragged over the target, not yet dropped.
+    pub fn hovered_files(&self) -> &[PathBuf] {
+        &self.hovered_files
+    }
+
+    /// Files dropped onto the target since the last event pass, unless a widget handled
+    /// earlier took them.
+    pub fn dropped_files(&self) -> &[PathBuf] {
+        &self.dropped_files
+    }
+
+    /// Takes the dropped files, so a drop zone under the pointer accepts them for itself.
+    pub fn take_dropped_files(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.dropped_files)
+    }
+
+    pub(crate) fn hover_file(&mut self, path: PathBuf) {
+        self.hovered_files.push(path);
+        self.pointer_input = true;
+    }
+
+    pub(crate) fn drop_file(&mut self, path: PathBuf) {
+        self.hovered_files.retain(|p| *p != path);
+        self.dropped_files.push(path);
+        self.pointer_input = true;
+    }
+
+    pub(crate) fn cancel_file_hover(&mut self) {
+        self.hovered_files.clear();
+        self.pointer_input = true;
+    }
+
+    pub(crate) fn move_pointer(&mut self, position: Position<f32>) {
+        self.mouse_pos = position;
+        self.pointer_input = true;
+    }
+
+    /// Whether there is `input` this event pass for widgets to react to.
+    pub fn has_input(&self, input: RoutedInput) -> bool {
+        !self.is_consumed(input)
+            && match input {
+                RoutedInput::Pointer => self.pointer_input,
+                RoutedInput::Keys => !self.keys.is_empty() || !self.text.is_empty(),
+            }
+    }
+
+    /// Stops `input` from going any further this event pass: routing ends, and widgets
+    /// handled afterwards see no button presses or releases and no scrolling, or no keys and
+    /// text, respectively.
+    pub fn consume(&mut self, input: RoutedInput) {
+        if self.is_consumed(input) {
+            return;
+        }
+        self.consumed.push(input);
+        match input {
+            RoutedInput::Pointer => {
+                self.buttons_pressed = 0;
+                self.buttons_released = 0;
+                self.mouse_pressed = false;
+                self.mouse_released = false;
+                self.right_pressed = false;
+                self.right_released = false;
+                self.wheel.clear();
+                self.dropped_files.clear();
+            }
+            RoutedInput::Keys => {
+                self.keys.clear();
+                self.text.clear();
+            }
+        }
+    }
+
+    pub fn is_consumed(&self, input: RoutedInput) -> bool {
+        self.consumed.contains(&input)
+    }
+
+    pub(crate) fn push_key(&mut self, mut key: KeyEvent) {
+        if key.modifiers == Modifiers::default() {
+            key.modifiers = self.modifiers;
+        }
+        self.keys.push(key);
+    }
+
+    /// Turns the pending key presses that match a shortcut into its message, so widgets don't
+    /// see them.
+    pub(crate) fn apply_shortcuts(&mut self, shortcuts: &[ShortcutBinding<M>]) {
+        if shortcuts.is_empty() {
+            return;
+        }
+        let messages = &mut self.messages;
+        self.keys
+            .retain(|key| match shortcuts.iter().find(|(s, _)| s.matches(key)) {
+                Some((_, message)) => {
+                    messages.push(message());
+                    false
+                }
+                None => true,
+            });
+    }
+
+    pub(crate) fn push_text(&mut self, text: &str) {
+        self.text.push_str(text);
+        self.preedit = None;
+    }
+
+    /// What the input method is composing, for the focused text widget to show at its caret.
+    pub fn preedit(&self) -> Option<&Preedit> {
+        self.preedit.as_ref()
+    }
+
+    pub(crate) fn set_preedit(&mut self, preedit: Preedit) {
+        self.preedit = (!preedit.text.is_empty()).then_some(preedit);
+    }
+
+    /// Turns the input method on for this event pass, with its popups placed around the caret
+    /// rect given in layout coordinates. Focused text widgets call this from `handle`.
+    pub fn request_ime(&mut self, position: Position<i32>, size: Size<i32>) {
+        self.ime_area = Some((position + self.translation(), size));
+    }
+
+    /// The caret rect requested during the last event pass, in target coordinates, or `None`
+    /// when no widget wants text input.
+    pub fn ime_area(&self) -> Option<(Position<i32>, Size<i32>)> {
+        self.ime_area
+    }
+
+    /// Shows `icon` as the pointer for this event pass. Hovered widgets call this from
+    /// `handle`; the last call wins.
+    pub fn set_cursor(&mut self, icon: CursorIcon) {
+        self.cursor = icon;
+    }
+
+    /// The pointer shape asked for during the last event pass.
+    pub fn cursor(&self) -> CursorIcon {
+        self.cursor
+    }
+
+    /// Puts `text` on the clipboard once the platform gets to it, replacing anything copied
+    /// earlier in the same frame.
+    pub fn copy(&mut self, text: impl Into<String>) {
+        self.copied = Some(text.into());
+    }
+
+    pub(crate) fn take_copied(&mut self) -> Option<String> {
+        self.copied.take()
+    }
+
+    /// Asks the platform for the clipboard's text, which arrives as committed text in a later
+    /// event pass and so goes to the focused widget.
+    pub fn request_paste(&mut self) {
+        self.paste_requested = true;
+    }
+
+    pub(crate) fn take_paste_request(&mut self) -> bool {
+        std::mem::take(&mut self.paste_requested)
+    }
+
+    pub(crate) fn clear_input(&mut self) {
+        self.keys.clear();
+        self.text.clear();
+        self.wheel.clear();
+        self.dropped_files.clear();
+        self.pointer_input = false;
+        self.consumed.clear();
+        if self.buttons_down == 0 {
+            self.captured = None;
+        }
+    }
+
+    /// Adds `id` to the keyboard focus order. Focusable widgets call this from every `handle`,
+    /// so Tab and Shift+Tab visit them in tree order, which is layout order.
+    pub fn register_focusable(&mut self, id: Id) {
+        self.focus_order.push(id);
+    }
+
+    /// Asks the closest scrollable around the calling widget to bring the rows from `top` to
+    /// `bottom` (screen coordinates) into view, e.g. after keyboard navigation moved to them.
+    pub fn request_reveal(&mut self, top: i32, bottom: i32) {
+        let t = self.translation();
+        self.reveal = Some((top + t.y, bottom + t.y));
+    }
+
+    /// Takes the span asked for with [`Context::request_reveal`], in the same coordinates as
+    /// the calling widget.
+    pub fn take_reveal(&mut self) -> Option<(i32, i32)> {
+        let t = self.translation();
+        self.reveal
+            .take()
+            .map(|(top, bottom)| (top - t.y, bottom - t.y))
+    }
+
+    pub fn is_focused(&self, id: Id) -> bool {
+        self.kbd_focus_item == Some(id)
+    }
+
+    /// Gives `id` keyboard focus. Key events go to whichever widget has it.
+    pub fn focus(&mut self, id: Id) {
+        if self.kbd_focus_item != Some(id) {
+            self.kbd_focus_item = Some(id);
+            self.redraw_requested = true;
+        }
+    }
+
+    pub fn blur(&mut self) {
+        if self.kbd_focus_item.take().is_some() {
+            self.redraw_requested = true;
+        }
+    }
+
+    /// Keeps this event pass's Tab presses from moving focus, for a focused widget that uses
+    /// them itself, e.g. to indent.
+    pub fn claim_tab(&mut self) {
+        self.tab_claimed = true;
+    }
+
+    /// Moves focus to the next focusable widget, wrapping around, or the first one if none of
+    /// them has it.
+    pub fn focus_next(&mut self) {
+        self.step_focus(true);
+    }
+
+    /// Moves focus to the previous focusable widget, wrapping around, or the last one if none
+    /// of them has it.
+    pub fn focus_prev(&mut self) {
+        self.step_focus(false);
+    }
+
+    fn step_focus(&mut self, forward: bool) {
+        let order = &self.prev_focus_order;
+        let n = order.len();
+        if n == 0 {
+            return;
+        }
+        let current = self
+            .kbd_focus_item
+            .and_then(|id| order.iter().position(|&o| o == id));
+        let next = match (current, forward) {
+            (Some(i), true) => (i + 1) % n,
+            (Some(i), false) => (i + n - 1) % n,
+            (None, true) => 0,
+            (None, false) => n - 1,
+        };
+        self.focus(order[next]);
+    }
+
+    /// Direction of the widget being laid out or handled: the target's, unless a subtree
+    /// overrides it.
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Sets the layout direction of the whole target.
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+        self.redraw_requested = true;
+    }
+
+    pub(crate) fn replace_direction(&mut self, direction: Direction) -> Direction {
+        std::mem::replace(&mut self.direction, direction)
+    }
+
+    /// Handles the widgets called between this and [`Context::pop_translation`] as if drawn
+    /// `offset` pixels away from where they were laid out, e.g. the content of a scrolled
+    /// region. `mouse_pos` is moved the other way in the meantime, so widgets keep hit testing
+    /// against their own layout.
+    pub fn push_translation(&mut self, offset: Position<i32>) {
+        self.translations.push(offset);
+        self.mouse_pos.x -= offset.x as f32;
+        self.mouse_pos.y -= offset.y as f32;
+    }
+
+    pub fn pop_translation(&mut self) {
+        if let Some(offset) = self.translations.pop() {
+            self.mouse_pos.x += offset.x as f32;
+            self.mouse_pos.y += offset.y as f32;
+        }
+    }
+
+    /// Sum of the translations currently pushed: where layout coordinates end up on screen.
+    pub fn translation(&self) -> Position<i32> {
+        self.translations
+            .iter()
+            .fold(Position::splat(0), |sum, &offset| sum + offset)
+    }
+
+    /// State that outlives the per-frame widget tree, keyed by widget id. Created with
+    /// `Default` on first access or when the id was last used by another type.
+    pub fn state_mut<T: Default + 'static>(&mut self, id: Id) -> &mut T {
+        let slot = self
+            .widget_state
+            .entry(id)
+            .or_insert_with(|| Box::new(T::default()));
+        if !slot.is::<T>() {
+            *slot = Box::new(T::default());
+        }
+        slot.downcast_mut().expect("type checked above")
+    }
+
+    /// Routes the pointer to `id` alone until [`Context::release_pointer`] or, at the latest,
+    /// until every mouse button is up again at the end of an event pass. Meant for drags that
+    /// have to keep going once the cursor leaves the widget: other widgets see the pointer as
+    /// occluded meanwhile, see [`Context::is_occluded`].
+    pub fn capture_pointer(&mut self, id: Id) {
+        self.captured = Some(id);
+    }
+
+    pub fn release_pointer(&mut self) {
+        self.captured = None;
+    }
+
+    /// The widget holding the pointer capture, if any.
+    pub fn pointer_capture(&self) -> Option<Id> {
+        self.captured
+    }
+
+    /// Marks a rect as covered by a floating layer owned by `owner` (a popup, a menu) for the
+    /// next event pass. The layer sits one above the one currently being handled. Must be
+    /// re-registered every pass while the layer is shown.
+    pub fn add_overlay(&mut self, owner: Id, position: Position<i32>, size: Size<i32>) {
+        self.overlays.push(Overlay {
+            owner,
+            layer: self.layer + 1,
+            position: position + self.translation(),
+            size,
+        });
+    }
+
+    /// Whether `p` lies under an overlay above the current layer owned by a widget other than
+    /// `id`, or another widget captured the pointer, meaning `id` must not react to the pointer
+    /// there.
+    pub fn is_occluded(&self, id: Id, p: Position<f32>) -> bool {
+        if let Some(owner) = self.captured {
+            return owner != id;
+        }
+        let t = self.translation();
+        let p = Position::new(p.x + t.x as f32, p.y + t.y as f32);
+        self.prev_overlays
+            .iter()
+            .chain(self.overlays.iter())
+            .any(|o| o.owner != id && o.layer > self.layer && o.contains(p))
+    }
+
+    /// Handles the widgets called between this and [`Context::pop_layer`] as part of the next
+    /// overlay layer, e.g. the content of a modal. Overlays registered by their owner one
+    /// layer down don't occlude them.
+    pub fn push_layer(&mut self) {
+        self.layer += 1;
+    }
+
+    pub fn pop_layer(&mut self) {
+        self.layer = self.layer.saturating_sub(1);
+    }
+
+    pub(crate) fn begin_event_pass(&mut self) {
+        self.prev_overlays = std::mem::take(&mut self.overlays);
+        self.reveal = None;
+        self.layer = 0;
+        self.ime_area = None;
+        self.cursor = CursorIcon::Default;
+
+        // Tab presses go to the focused widget first and move focus once the pass is over.
+        self.prev_focus_order = std::mem::take(&mut self.focus_order);
+        self.tab_claimed = false;
+        self.tabs = self
+            .keys
+            .iter()
+            .filter(|k| k.logical_key == LogicalKey::Tab && k.state == KeyState::Pressed)
+            .map(|k| !k.modifiers.shift)
+            .collect();
+
+        while !self.translations.is_empty() {
+            self.pop_translation();
+        }
+    }
+
+    /// Moves focus for the Tab presses no widget claimed or consumed with the other keys.
+    pub(crate) fn end_event_pass(&mut self) {
+        let tabs = std::mem::take(&mut self.tabs);
+        if self.tab_claimed || self.is_consumed(RoutedInput::Keys) {
+            return;
+        }
+        for forward in tabs {
+            self.step_focus(forward);
+        }
+    }
+
     pub fn request_redraw(&mut self) {
         self.redraw_requested = true;
     }
@@ -70,12 +641,64 @@ impl<M> Context<M> {
         self.redraw_requested = false;
         r
     }
+
+    /// Requests a redraw once `Globals::time` reaches `time` (in seconds), e.g. to reveal
+    /// something after a delay. The earliest pending deadline wins.
+    pub fn request_redraw_at(&mut self, time: f32) {
+        self.redraw_at = Some(self.redraw_at.map_or(time, |t| t.min(time)));
+    }
+
+    /// Queues a toast for the `ToastHost` in the tree to pick up on the next layout.
+    pub fn toast(&mut self, toast: impl Into<Toast>) {
+        self.toasts.push(toast.into());
+        self.request_redraw();
+    }
+
+    pub(crate) fn take_toasts(&mut self) -> Vec<Toast> {
+        std::mem::take(&mut self.toasts)
+    }
+
+    pub(crate) fn redraw_pending(&self) -> bool {
+        self.redraw_requested
+    }
+
+    pub(crate) fn redraw_deadline(&self) -> Option<f32> {
+        self.redraw_at
+    }
+
+    pub(crate) fn take_due_redraw(&mut self, now: f32) -> bool {
+        match self.redraw_at {
+            Some(t) if t <= now => {
+                self.redraw_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+struct Overlay {
+    owner: Id,
+    layer: u32,
+    position: Position<i32>,
+    size: Size<i32>,
+}
+
+impl Overlay {
+    fn contains(&self, p: Position<f32>) -> bool {
+        p.x >= self.position.x as f32
+            && p.y >= self.position.y as f32
+            && p.x < (self.position.x + self.size.width) as f32
+            && p.y < (self.position.y + self.size.height) as f32
+    }
 }
 
 pub struct LayoutCtx<'a, M> {
     pub globals: &'a Globals,
     pub ui: &'a mut Context<M>,
     pub text: &'a mut TextSystem,
+    #[cfg(feature = "svg")]
+    pub svg: &'a mut crate::render::svg::SvgCache,
 }
 
 pub struct PaintCtx<'a> {
@@ -83,9 +706,219 @@ pub struct PaintCtx<'a> {
     pub text: &'a mut TextSystem,
     pub gpu: &'a Gpu,
     pub texture: &'a mut TextureRegistry,
+    #[cfg(feature = "svg")]
+    pub svg: &'a mut crate::render::svg::SvgCache,
+    // Instances of each overlay layer, painted in order after the main tree.
+    pub(crate) layers: Vec<Vec<Instance>>,
+    pub(crate) depth: usize,
+    // Subtrees painted with a z-index, waiting to be merged into their layer.
+    pub(crate) raised: Vec<Raised>,
+    // Offsets of the translated subtrees being painted, innermost last.
+    pub(crate) translations: Vec<Position<i32>>,
+}
+
+pub(crate) struct Raised {
+    depth: usize,
+    z: i32,
+    instances: Vec<Instance>,
+}
+
+impl PaintCtx<'_> {
+    /// Draws with `f` into the next overlay layer, which is painted on top of the whole tree
+    /// and of every lower layer. Overlays drawn from within `f` stack above it.
+    pub fn overlay(&mut self, f: impl FnOnce(&mut Self, &mut Vec<Instance>)) {
+        if self.layers.len() <= self.depth {
+            self.layers.push(Vec::new());
+        }
+        let mut layer = std::mem::take(&mut self.layers[self.depth]);
+        let mark = self.raised.len();
+        self.depth += 1;
+        f(self, &mut layer);
+        self.merge_raised(mark, &mut layer);
+        self.depth -= 1;
+        self.layers[self.depth] = layer;
+    }
+
+    /// Paints with `f` moved by `offset` pixels: everything it emits, including into overlay
+    /// layers and raised subtrees, is shifted once it returns. Nests, so `offset` is relative to
+    /// any translation already applied.
+    pub fn translated(
+        &mut self,
+        offset: Position<i32>,
+        instances: &mut Vec<Instance>,
+        f: impl FnOnce(&mut Self, &mut Vec<Instance>),
+    ) {
+        self.translations.push(offset);
+        self.map_emitted(instances, f, |i| i.translate(offset));
+        self.translations.pop();
+    }
+
+    /// Paints with `f` rotated by `rotation` radians clockwise and scaled by `scale` around
+    /// `pivot`. Like [`PaintCtx::translated`], this covers everything `f` emits.
+    pub fn transformed(
+        &mut self,
+        rotation: f32,
+        scale: f32,
+        pivot: Position<f32>,
+        instances: &mut Vec<Instance>,
+        f: impl FnOnce(&mut Self, &mut Vec<Instance>),
+    ) {
+        self.map_emitted(instances, f, |i| i.transform(rotation, scale, pivot));
+    }
+
+    /// Paints with `f` clipped to the rect at `position` with `size`, within any clip already
+    /// applied. Like [`PaintCtx::translated`], this covers everything `f` emits.
+    pub fn clipped(
+        &mut self,
+        position: Position<i32>,
+        size: Size<i32>,
+        instances: &mut Vec<Instance>,
+        f: impl FnOnce(&mut Self, &mut Vec<Instance>),
+    ) {
+        self.map_emitted(instances, f, |i| i.clip(position, size));
+    }
+
+    /// Paints with `f` clipped to the rect at `position` with `size` rounded by `radii`, as in
+    /// [`Instance::radii`]. The corners are only cut off with stencil clipping on, see
+    /// [`Engine::set_stencil_clipping`]; without it this clips to the rect like
+    /// [`PaintCtx::clipped`]. Overlays drawn from within `f` are clipped to the rect alone.
+    ///
+    /// [`Engine::set_stencil_clipping`]: crate::graphics::Engine::set_stencil_clipping
+    pub fn clipped_rounded(
+        &mut self,
+        position: Position<i32>,
+        size: Size<i32>,
+        radii: Vec4<f32>,
+        instances: &mut Vec<Instance>,
+        f: impl FnOnce(&mut Self, &mut Vec<Instance>),
+    ) {
+        let square = <[f32; 4]>::from(radii).iter().all(|r| *r <= 0.0);
+        if square || !self.gpu.stencil_clipping() {
+            return self.clipped(position, size, instances, f);
+        }
+
+        // The outline raises the stencil value under it, so what `f` draws is only kept there.
+        // Stencil values are relative until enclosing clips raise them all.
+        let outline = |mask| {
+            Instance::ui(position, size, Color::WHITE)
+                .radii(radii)
+                .masking(mask)
+        };
+        instances.push(outline(Mask::Push));
+        let mark = instances.len();
+        self.clipped(position, size, instances, f);
+        for i in &mut instances[mark..] {
+            i.stencil = i.stencil.saturating_add(1);
+        }
+        let mut pop = outline(Mask::Pop);
+        pop.stencil = 1;
+        instances.push(pop);
+    }
+
+    /// Paints with `f` at `depth`, see [`Instance::depth`]. Like [`PaintCtx::translated`], this
+    /// covers everything `f` emits.
+    pub fn at_depth(
+        &mut self,
+        depth: f32,
+        instances: &mut Vec<Instance>,
+        f: impl FnOnce(&mut Self, &mut Vec<Instance>),
+    ) {
+        let depth = depth.clamp(0.0, 1.0);
+        self.map_emitted(instances, f, |i| i.depth = depth);
+    }
+
+    /// Paints with `f` at `opacity`, from 0 (invisible) to 1. Like [`PaintCtx::translated`],
+    /// this covers everything `f` emits. Overlapping instances are each faded on their own
+    /// rather than as a flattened group.
+    pub fn faded(
+        &mut self,
+        opacity: f32,
+        instances: &mut Vec<Instance>,
+        f: impl FnOnce(&mut Self, &mut Vec<Instance>),
+    ) {
+        self.map_emitted(instances, f, |i| i.fade(opacity));
+    }
+
+    // Runs `f`, then applies `g` to each instance it emitted wherever it ended up.
+    fn map_emitted(
+        &mut self,
+        instances: &mut Vec<Instance>,
+        f: impl FnOnce(&mut Self, &mut Vec<Instance>),
+        g: impl Fn(&mut Instance),
+    ) {
+        let mark = instances.len();
+        let layer_marks: Vec<usize> = self.layers.iter().map(Vec::len).collect();
+        let raised_mark = self.raised.len();
+
+        f(self, instances);
+
+        instances[mark..].iter_mut().for_each(&g);
+        for (i, layer) in self.layers.iter_mut().enumerate() {
+            let from = layer_marks.get(i).copied().unwrap_or(0);
+            layer[from..].iter_mut().for_each(&g);
+        }
+        for raised in &mut self.raised[raised_mark..] {
+            raised.instances.iter_mut().for_each(&g);
+        }
+    }
+
+    /// Sum of the translations applied to what is being painted.
+    pub fn translation(&self) -> Position<i32> {
+        self.translations
+            .iter()
+            .fold(Position::splat(0), |sum, &offset| sum + offset)
+    }
+
+    /// Queues `instances` to be drawn at `z` relative to the rest of the current layer.
+    pub(crate) fn raise(&mut self, z: i32, instances: Vec<Instance>) {
+        self.raised.push(Raised {
+            depth: self.depth,
+            z,
+            instances,
+        });
+    }
+
+    /// Merges subtrees raised on the current layer since `mark` into `instances`: negative
+    /// z-indices below them, positive ones above, equal ones in paint order.
+    pub(crate) fn merge_raised(&mut self, mark: usize, instances: &mut Vec<Instance>) {
+        let depth = self.depth;
+        let mut raised = Vec::new();
+        let mut i = mark;
+        while i < self.raised.len() {
+            if self.raised[i].depth == depth {
+                raised.push(self.raised.remove(i));
+            } else {
+                i += 1;
+            }
+        }
+        if raised.is_empty() {
+            return;
+        }
+        raised.sort_by_key(|r| r.z);
+
+        let own = std::mem::take(instances);
+        let split = raised.partition_point(|r| r.z < 0);
+        for r in &mut raised[..split] {
+            instances.append(&mut r.instances);
+        }
+        instances.extend(own);
+        for r in &mut raised[split..] {
+            instances.append(&mut r.instances);
+        }
+    }
 }
 
 pub struct EventCtx<'a, M> {
     pub globals: &'a Globals,
     pub ui: &'a mut Context<M>,
 }
+
+impl<M> EventCtx<'_, M> {
+    /// Handles with `f` as if the widgets it reaches were drawn `offset` pixels away from
+    /// their layout, matching [`PaintCtx::translated`].
+    pub fn translated(&mut self, offset: Position<i32>, f: impl FnOnce(&mut Self)) {
+        self.ui.push_translation(offset);
+        f(self);
+        self.ui.pop_translation();
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt;
+
+use crate::{graphics::TargetId, render::pipeline::PipelineKey};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// What can go wrong in the engine and renderer.
+#[derive(Debug)]
+pub enum Error {
+    /// No adapter matched `EngineOptions`.
+    NoAdapter,
+    /// The adapter can't provide the features or limits the engine and `EngineOptions` need.
+    RequestDevice(wgpu::RequestDeviceError),
+    /// There's no target yet, whose surface format pipelines have to be built for.
+    NoTarget,
+    /// No target has this id, e.g. because it was detached.
+    UnknownTarget(TargetId),
+    /// Instances were drawn with a pipeline nobody registered. They're skipped, the rest of
+    /// the frame is drawn.
+    MissingPipeline(PipelineKey),
+    /// The thread decoding a texture for `Engine::load_texture_with` panicked.
+    LoaderPanicked,
+    /// A texture's format, size or container can't be loaded, e.g. a compressed format the
+    /// device doesn't support.
+    UnsupportedTexture(String),
+    /// Every texture slot is taken and the device can't bind more; unload textures or pack
+    /// them into atlases.
+    TextureSlotsExhausted,
+    /// Frames of targets with this format can't be read back as RGBA8.
+    UnsupportedCapture(wgpu::TextureFormat),
+    /// No surface could be made for a window, e.g. because its handles don't match.
+    CreateSurface(wgpu::CreateSurfaceError),
+    /// The adapter can't present to the window's surface in any format.
+    NoSurfaceFormat,
+    /// The surface couldn't give out a texture to draw the frame into.
+    Surface(wgpu::SurfaceError),
+    /// A file couldn't be read or decoded as an image.
+    #[cfg(feature = "image")]
+    Image(image::ImageError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAdapter => write!(f, "no suitable adapter found"),
+            Self::RequestDevice(e) => write!(f, "failed to request device: {e}"),
+            Self::NoTarget => write!(f, "no target to build pipelines for"),
+            Self::UnknownTarget(tid) => write!(f, "no target with id {tid:?}"),
+            Self::MissingPipeline(key) => write!(f, "no pipeline registered for {key:?}"),
+            Self::LoaderPanicked => write!(f, "texture loader panicked"),
+            Self::UnsupportedTexture(why) => write!(f, "unsupported texture: {why}"),
+            Self::TextureSlotsExhausted => write!(f, "texture slots exhausted"),
+            Self::UnsupportedCapture(format) => {
+                write!(f, "can't capture frames of format {format:?}")
+            }
+            Self::CreateSurface(e) => write!(f, "failed to create surface: {e}"),
+            Self::NoSurfaceFormat => write!(f, "adapter can't present to the surface"),
+            Self::Surface(e) => write!(f, "surface error: {e}"),
+            #[cfg(feature = "image")]
+            Self::Image(e) => write!(f, "image error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Surface(e) => Some(e),
+            Self::RequestDevice(e) => Some(e),
+            Self::CreateSurface(e) => Some(e),
+            #[cfg(feature = "image")]
+            Self::Image(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<wgpu::CreateSurfaceError> for Error {
+    fn from(e: wgpu::CreateSurfaceError) -> Self {
+        Self::CreateSurface(e)
+    }
+}
+
+impl From<wgpu::SurfaceError> for Error {
+    fn from(e: wgpu::SurfaceError) -> Self {
+        Self::Surface(e)
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<image::ImageError> for Error {
+    fn from(e: image::ImageError) -> Self {
+        Self::Image(e)
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use smol_str::SmolStr;
 
 use crate::model::{Position, Size};
@@ -18,6 +20,94 @@ pub struct Modifiers {
     pub num_lock: Option<bool>,
 }
 
+impl Modifiers {
+    pub const NONE: Self = Self {
+        shift: false,
+        control: false,
+        alt: false,
+        super_: false,
+        caps_lock: None,
+        num_lock: None,
+    };
+    pub const SHIFT: Self = Self {
+        shift: true,
+        ..Self::NONE
+    };
+    pub const CONTROL: Self = Self {
+        control: true,
+        ..Self::NONE
+    };
+    pub const ALT: Self = Self {
+        alt: true,
+        ..Self::NONE
+    };
+    pub const SUPER: Self = Self {
+        super_: true,
+        ..Self::NONE
+    };
+
+    /// Whether the same shift, control, alt and super keys are held, whatever the locks.
+    pub fn same_keys(self, other: Modifiers) -> bool {
+        (self.shift, self.control, self.alt, self.super_)
+            == (other.shift, other.control, other.alt, other.super_)
+    }
+}
+
+impl std::ops::BitOr for Modifiers {
+    type Output = Modifiers;
+    fn bitor(self, rhs: Modifiers) -> Modifiers {
+        Modifiers {
+            shift: self.shift || rhs.shift,
+            control: self.control || rhs.control,
+            alt: self.alt || rhs.alt,
+            super_: self.super_ || rhs.super_,
+            caps_lock: None,
+            num_lock: None,
+        }
+    }
+}
+
+impl std::ops::Add<LogicalKey> for Modifiers {
+    type Output = Shortcut;
+    fn add(self, key: LogicalKey) -> Shortcut {
+        Shortcut::new(self, key)
+    }
+}
+
+/// A key and the modifiers held with it, e.g. `Modifiers::CONTROL + LogicalKey::Character("s".into())`.
+/// See [`Engine::bind_shortcut`](crate::graphics::Engine::bind_shortcut).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortcut {
+    pub modifiers: Modifiers,
+    pub key: LogicalKey,
+}
+
+impl Shortcut {
+    pub fn new(modifiers: Modifiers, key: LogicalKey) -> Self {
+        Self { modifiers, key }
+    }
+
+    /// Whether `event` presses this shortcut. Characters match in either case, as shift
+    /// changes the character some platforms report.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        if event.state != KeyState::Pressed || !self.modifiers.same_keys(event.modifiers) {
+            return false;
+        }
+        match (&self.key, &event.logical_key) {
+            (LogicalKey::Character(a), LogicalKey::Character(b)) => {
+                a.to_lowercase() == b.to_lowercase()
+            }
+            (a, b) => a == b,
+        }
+    }
+}
+
+impl From<LogicalKey> for Shortcut {
+    fn from(key: LogicalKey) -> Self {
+        Shortcut::new(Modifiers::NONE, key)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum KeyLocation {
     Standard,
@@ -70,6 +160,105 @@ pub struct TextInput {
     pub text: String, // full UTF-8
 }
 
+/// Text an input method is composing, shown at the caret until committed as [`TextInput`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preedit {
+    pub text: String,
+    /// Byte range of `text` the input method highlights as its cursor, if it shows one.
+    pub cursor: Option<(usize, usize)>,
+}
+
+/// Pointer shape widgets ask for while hovered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CursorIcon {
+    #[default]
+    Default,
+    /// A hand, over links and other things that can be clicked.
+    Pointer,
+    Text,
+    Grab,
+    Grabbing,
+    Move,
+    Crosshair,
+    NotAllowed,
+    EwResize,
+    NsResize,
+}
+
+/// Input of an event pass that is routed through the widget tree, see
+/// [`Widget::on_event`](crate::widget::Widget::on_event).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoutedInput {
+    /// Button changes, scrolling and movement, routed to the widget under the pointer or the
+    /// one that captured it.
+    Pointer,
+    /// Keys and committed text, routed to the focused widget.
+    Keys,
+}
+
+/// Where routed input is on its way through the widgets from the root to its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Going down, at an ancestor of the target.
+    Capture,
+    Target,
+    /// Coming back up, at an ancestor of the target.
+    Bubble,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    /// Side button that usually navigates back.
+    Back,
+    /// Side button that usually navigates forward.
+    Forward,
+}
+
+impl MouseButton {
+    /// The button's bit in `Globals::mouse_buttons`: left 1, right 2, middle 4, back 8 and
+    /// forward 16.
+    pub fn bit(self) -> u32 {
+        match self {
+            MouseButton::Left => 1,
+            MouseButton::Right => 1 << 1,
+            MouseButton::Middle => 1 << 2,
+            MouseButton::Back => 1 << 3,
+            MouseButton::Forward => 1 << 4,
+        }
+    }
+}
+
+/// How far a wheel or touchpad scrolled. Positive values scroll towards the top left, as
+/// platforms report them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollDelta {
+    /// Wheel notches, or lines of text.
+    Lines(Position<f32>),
+    Pixels(Position<f32>),
+}
+
+impl ScrollDelta {
+    /// The delta in pixels, with a line taking `line_height` of them.
+    pub fn to_pixels(self, line_height: f32) -> Position<f32> {
+        match self {
+            ScrollDelta::Lines(p) => Position::new(p.x * line_height, p.y * line_height),
+            ScrollDelta::Pixels(p) => p,
+        }
+    }
+}
+
+/// Where a scroll gesture is, for devices that report it (touchpads). Wheels send `Moved`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchPhase {
+    Started,
+    Moved,
+    Ended,
+    Cancelled,
+}
+
 pub trait ToEvent<M, E: ToEvent<M, E>> {
     fn to_event(&self) -> Event<M, E>;
 }
@@ -77,14 +266,40 @@ pub trait ToEvent<M, E: ToEvent<M, E>> {
 #[derive(Debug)]
 pub enum Event<M, E: ToEvent<M, E>> {
     RedrawRequested,
-    Resized { size: Size<u32> },
-    CursorMoved { position: Position<f32> },
-    MouseInput { mouse_down: bool },
+    Resized {
+        size: Size<u32>,
+    },
+    CursorMoved {
+        position: Position<f32>,
+    },
+    MouseInput {
+        button: MouseButton,
+        mouse_down: bool,
+    },
+    MouseWheel {
+        delta: ScrollDelta,
+        phase: TouchPhase,
+    },
 
-    Key(KeyEvent),               // key press/release (with metadata)
-    Text(TextInput),             // committed text (IME/composition)
+    Key(KeyEvent),   // key press/release (with metadata)
+    Text(TextInput), // committed text (IME/composition)
+    /// Composition in progress; an empty text ends it.
+    ImePreedit(Preedit),
     ModifiersChanged(Modifiers), // track a snapshot in your ctx
 
+    /// A file dragged from outside is over the window, sent once per file.
+    FileHover(PathBuf),
+    /// A file dragged from outside was dropped onto the window, sent once per file.
+    FileDrop(PathBuf),
+    /// The files being dragged left the window without being dropped.
+    FileHoverCancelled,
+
+    /// Text a widget copied, sent by backends that can't put it on the clipboard themselves.
+    Copied(String),
+    /// A widget wants to paste, sent by backends that can't read the clipboard themselves.
+    /// Answer with [`Engine::paste`](crate::graphics::Engine::paste).
+    PasteRequested,
+
     Platform(E),
     Message(M),
 }
@@ -1,18 +1,32 @@
 // TODO: should cache calls when no targets are attached
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        mpsc,
+    },
+    time::{Duration, Instant},
+};
 
 use crate::{
+    Error, Result,
     consts::*,
-    context::{Context, EventCtx, LayoutCtx, PaintCtx},
-    event::{Event, ToEvent},
+    context::{Context, EventCtx, Id, LayoutCtx, PaintCtx, ShortcutBinding},
+    event::{CursorIcon, Event, Shortcut, ToEvent},
     model::*,
-    primitive::{Primitive, Vertex},
+    primitive::{Instance, Primitive, Vertex},
     render::{
+        FrameProfile,
         pipeline::PipelineRegistry,
+        profiler::Profiler,
         renderer::Renderer,
-        texture::{Atlas, TextureHandle},
+        text::{FontFallback, FontMetrics, TextStyle},
+        texture::{AnimatedHandle, AnimationFrame, Atlas, TextureHandle},
     },
-    widget::{Element, internal::PAINT_TOKEN},
+    widget::{Direction, Element, Measurement, SizeClass, Toast, internal::PAINT_TOKEN},
 };
 
 #[derive(Default)]
@@ -33,21 +47,278 @@ impl TargetIdAlloc {
 pub struct Globals {
     window_size: [f32; 2], // pixels
     mouse_pos: [f32; 2],   // pixels
-    mouse_buttons: u32,    // bit 0: left, bit 1: right (etc.)
+    mouse_buttons: u32,    // bits of `MouseButton::bit`
     pub time: f32,         // seconds since start
     pub delta_time: f32,   // seconds since last frame
     pub frame: u32,        // frame counter
 }
 
+impl Globals {
+    pub fn window_size(&self) -> Size<i32> {
+        Size::new(self.window_size[0] as i32, self.window_size[1] as i32)
+    }
+
+    pub fn size_class(&self) -> SizeClass {
+        SizeClass::of(self.window_size().width)
+    }
+}
+
+/// Pixels read back from a target, four bytes per pixel in rows from the top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// What the renderer drew in the last frame and how much room it has for it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Instances drawn, after culling those off the surface.
+    pub instances: usize,
+    pub draw_calls: usize,
+    /// Instances the instance buffer holds before it has to grow.
+    pub instance_capacity: usize,
+    /// Most instances any frame has drawn so far.
+    pub peak_instances: usize,
+}
+
+/// How a target's frames reach the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PresentOptions {
+    /// Modes the surface doesn't support fall back to `AutoVsync` or `AutoNoVsync`, whichever
+    /// is closer.
+    pub present_mode: wgpu::PresentMode,
+    /// Frames queued ahead of the one on screen. More smooths out uneven frame times at the
+    /// cost of input latency.
+    pub max_frame_latency: u32,
+}
+
+impl Default for PresentOptions {
+    fn default() -> Self {
+        Self {
+            present_mode: wgpu::PresentMode::AutoVsync,
+            max_frame_latency: 1,
+        }
+    }
+}
+
+impl PresentOptions {
+    fn apply(&self, config: &mut wgpu::SurfaceConfiguration, caps: &wgpu::SurfaceCapabilities) {
+        use wgpu::PresentMode as P;
+        config.present_mode = match self.present_mode {
+            mode if caps.present_modes.contains(&mode) => mode,
+            P::Fifo | P::FifoRelaxed | P::AutoVsync => P::AutoVsync,
+            P::Mailbox | P::Immediate | P::AutoNoVsync => P::AutoNoVsync,
+        };
+        config.desired_maximum_frame_latency = self.max_frame_latency;
+    }
+}
+
+/// How often a target is polled for input and redrawn. Either way it's only redrawn when
+/// something changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FramePacing {
+    /// Every refresh of the display it's on.
+    #[default]
+    Display,
+    /// This many times a second.
+    Fps(u32),
+    /// Only after input, or while widgets or the application keep asking for redraws, at the
+    /// display's refresh rate. Idle targets don't wake the event loop.
+    OnChange,
+}
+
+/// How `Globals` reach the shaders. Push constants are used when the adapter supports them,
+/// otherwise they are written to a uniform buffer that pipelines bind themselves.
+pub enum GlobalsBinding {
+    PushConstants,
+    Uniform {
+        layout: wgpu::BindGroupLayout,
+        buffer: wgpu::Buffer,
+        bind_group: wgpu::BindGroup,
+    },
+}
+
+impl GlobalsBinding {
+    fn uniform(device: &wgpu::Device) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("UI Globals BGL"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<Globals>() as u64),
+                },
+                count: None,
+            }],
+        });
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("UI Globals Buffer"),
+            size: std::mem::size_of::<Globals>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("UI Globals BG"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self::Uniform {
+            layout,
+            buffer,
+            bind_group,
+        }
+    }
+
+    pub fn is_push_constants(&self) -> bool {
+        matches!(self, Self::PushConstants)
+    }
+
+    /// Layout to append to a pipeline layout when globals come from the uniform buffer.
+    pub fn layout(&self) -> Option<&wgpu::BindGroupLayout> {
+        match self {
+            Self::PushConstants => None,
+            Self::Uniform { layout, .. } => Some(layout),
+        }
+    }
+
+    /// Rewrites the `var<push_constant> globals` declaration of a shader so it reads the
+    /// uniform buffer at `group` instead. Sources are returned untouched on push constants.
+    pub fn wgsl<'s>(&self, source: &'s str, group: u32) -> Cow<'s, str> {
+        match self {
+            Self::PushConstants => Cow::Borrowed(source),
+            Self::Uniform { .. } => Cow::Owned(source.replace(
+                "var<push_constant> globals",
+                &format!("@group({group}) @binding(0) var<uniform> globals"),
+            )),
+        }
+    }
+
+    /// Makes `globals` visible to the pipeline currently set on `pass`.
+    pub fn apply(&self, pass: &mut wgpu::RenderPass<'_>, group: u32, globals: &Globals) {
+        match self {
+            Self::PushConstants => pass.set_push_constants(
+                wgpu::ShaderStages::VERTEX_FRAGMENT,
+                0,
+                bytemuck::bytes_of(globals),
+            ),
+            Self::Uniform { bind_group, .. } => pass.set_bind_group(group, bind_group, &[]),
+        }
+    }
+
+    pub(crate) fn upload(&self, queue: &wgpu::Queue, globals: &Globals) {
+        if let Self::Uniform { buffer, .. } = self {
+            queue.write_buffer(buffer, 0, bytemuck::bytes_of(globals));
+        }
+    }
+}
+
 pub struct Gpu {
     pub instance: wgpu::Instance,
     pub adapter: wgpu::Adapter,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
+    pub globals: GlobalsBinding,
+    samples: AtomicU32,
+    depth: AtomicBool,
+    stencil: AtomicBool,
+}
+
+impl Gpu {
+    /// Samples per pixel targets are rendered with, 1 unless MSAA is on.
+    pub fn sample_count(&self) -> u32 {
+        self.samples.load(Ordering::Relaxed)
+    }
+
+    /// Whether rounded clips mask what they cover through the stencil buffer.
+    pub fn stencil_clipping(&self) -> bool {
+        self.stencil.load(Ordering::Relaxed)
+    }
+
+    /// Format of the targets' depth buffer, if they have one. The stencil buffer shares it.
+    pub fn depth_format(&self) -> Option<wgpu::TextureFormat> {
+        match (self.depth.load(Ordering::Relaxed), self.stencil_clipping()) {
+            (_, true) => Some(crate::consts::DEPTH_STENCIL_FORMAT),
+            (true, false) => Some(crate::consts::DEPTH_FORMAT),
+            (false, false) => None,
+        }
+    }
+
+    /// Depth state of the built-in pipelines: tested against the depth buffer without writing
+    /// to it, so overlapping translucent UI keeps its order, and against the stencil buffer so
+    /// rounded clips mask it. Pipelines drawing opaque content other instances should hide
+    /// behind enable depth writes on top of this.
+    pub fn depth_stencil(&self) -> Option<wgpu::DepthStencilState> {
+        self.depth_stencil_with(wgpu::StencilOperation::Keep, true)
+    }
+
+    // Depth state of the pipelines drawing clip outlines, which apply `op` to the stencil
+    // value under them whatever their depth.
+    pub(crate) fn mask_stencil(
+        &self,
+        op: wgpu::StencilOperation,
+    ) -> Option<wgpu::DepthStencilState> {
+        self.depth_stencil_with(op, false)
+    }
+
+    fn depth_stencil_with(
+        &self,
+        op: wgpu::StencilOperation,
+        depth_test: bool,
+    ) -> Option<wgpu::DepthStencilState> {
+        let stencil = if self.stencil_clipping() {
+            let face = wgpu::StencilFaceState {
+                compare: wgpu::CompareFunction::Equal,
+                fail_op: wgpu::StencilOperation::Keep,
+                depth_fail_op: wgpu::StencilOperation::Keep,
+                pass_op: op,
+            };
+            wgpu::StencilState {
+                front: face,
+                back: face,
+                read_mask: 0xff,
+                write_mask: if op == wgpu::StencilOperation::Keep {
+                    0
+                } else {
+                    0xff
+                },
+            }
+        } else {
+            wgpu::StencilState::default()
+        };
+        let depth_test = depth_test && self.depth.load(Ordering::Relaxed);
+        self.depth_format().map(|format| wgpu::DepthStencilState {
+            format,
+            depth_write_enabled: false,
+            depth_compare: if depth_test {
+                wgpu::CompareFunction::LessEqual
+            } else {
+                wgpu::CompareFunction::Always
+            },
+            stencil,
+            bias: wgpu::DepthBiasState::default(),
+        })
+    }
+
+    /// Multisample state render pipelines have to be created with to draw into targets.
+    pub fn multisample(&self) -> wgpu::MultisampleState {
+        wgpu::MultisampleState {
+            count: self.sample_count(),
+            ..Default::default()
+        }
+    }
 }
 
 pub struct Target<'a, M> {
-    pub surface: wgpu::Surface<'a>,
+    /// `None` for headless targets, which draw into a texture of their own.
+    pub surface: Option<wgpu::Surface<'a>>,
     pub config: wgpu::SurfaceConfiguration,
     pub size: Size<u32>,
     pub scale: i32,
@@ -56,12 +327,120 @@ pub struct Target<'a, M> {
 
     start_time: Instant,
     last_frame_time: Instant,
+    pacing: FramePacing,
+    // Whether events arrived or the last poll wanted a redraw, for `FramePacing::OnChange`.
+    awake: bool,
     root: Option<Element<M>>,
+    // Multisampled color buffer resolved into the surface, with its size and sample count.
+    msaa: Option<(wgpu::Texture, wgpu::TextureView)>,
+    depth: Option<(wgpu::Texture, wgpu::TextureView)>,
+    // What was drawn last, to be drawn again for captures.
+    last_frame: Vec<Instance>,
+    offscreen: Option<wgpu::Texture>,
+}
+
+impl<M> Target<'_, M> {
+    /// View to render into before resolving to the surface, when MSAA is on.
+    pub(crate) fn msaa_view(&self) -> Option<&wgpu::TextureView> {
+        self.msaa.as_ref().map(|(_, view)| view)
+    }
+
+    pub(crate) fn depth_view(&self) -> Option<&wgpu::TextureView> {
+        self.depth.as_ref().map(|(_, view)| view)
+    }
+
+    /// Texture a headless target draws into.
+    pub(crate) fn offscreen(&self) -> Option<&wgpu::Texture> {
+        self.offscreen.as_ref()
+    }
+
+    fn prepare_attachments(&mut self, gpu: &Gpu) {
+        let size = Size::new(self.config.width, self.config.height);
+        let stale = |t: &wgpu::Texture| t.width() != size.width || t.height() != size.height;
+        if self.surface.is_none() && self.offscreen.as_ref().is_none_or(stale) {
+            self.offscreen = Some(gpu.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Headless Target"),
+                size: wgpu::Extent3d {
+                    width: size.width,
+                    height: size.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.config.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::COPY_SRC
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            }));
+        }
+
+        let samples = gpu.sample_count();
+        let color = (samples > 1).then_some(self.config.format);
+        prepare_attachment(
+            gpu,
+            &mut self.msaa,
+            color,
+            size,
+            samples,
+            "MSAA Color Buffer",
+        );
+        let depth = gpu.depth_format();
+        prepare_attachment(gpu, &mut self.depth, depth, size, samples, "Depth Buffer");
+    }
+}
+
+// Keeps `slot` holding a `format` attachment of `size` and `samples`, or nothing without a
+// format.
+fn prepare_attachment(
+    gpu: &Gpu,
+    slot: &mut Option<(wgpu::Texture, wgpu::TextureView)>,
+    format: Option<wgpu::TextureFormat>,
+    size: Size<u32>,
+    samples: u32,
+    label: &str,
+) {
+    let Some(format) = format else {
+        *slot = None;
+        return;
+    };
+    if slot.as_ref().is_some_and(|(t, _)| {
+        t.width() == size.width
+            && t.height() == size.height
+            && t.sample_count() == samples
+            && t.format() == format
+    }) {
+        return;
+    }
+    let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: samples,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    *slot = Some((texture, view));
 }
 
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, PartialOrd, Ord)]
 pub struct TargetId(u32);
 
+// A texture being decoded off the main thread, see `Engine::load_texture_with`.
+struct PendingLoad<M> {
+    tid: TargetId,
+    decoded: mpsc::Receiver<Result<RgbaImage>>,
+    on_ready: Box<dyn FnOnce(Result<TextureHandle>) -> M>,
+}
+
 pub struct Engine<'a, M> {
     debug: bool,
 
@@ -72,64 +451,141 @@ pub struct Engine<'a, M> {
     pub(crate) push_constant_ranges: Vec<wgpu::PushConstantRange>,
     pipeline_registry: PipelineRegistry,
     renderer: Renderer,
+    shortcuts: Vec<ShortcutBinding<M>>,
+    loads: Vec<PendingLoad<M>>,
+    #[cfg(feature = "hot_reload")]
+    shader_watcher: Option<crate::render::hot_reload::ShaderWatcher>,
+    #[cfg(feature = "hot_reload")]
+    shader_error: Option<String>,
 }
 
-impl<'a, M> Default for Engine<'a, M> {
-    fn default() -> Self {
+/// How the engine picks its GPU and what it asks of it, see [`Engine::with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct EngineOptions {
+    /// Backends to look for adapters on. `None` follows the `vulkan`/`metal` features and the
+    /// `UI_BACKEND` variable.
+    pub backends: Option<wgpu::Backends>,
+    pub power_preference: wgpu::PowerPreference,
+    /// Takes the first adapter whose name contains this, ignoring case, over
+    /// `power_preference`.
+    pub adapter_name: Option<String>,
+    /// Features to request on top of those the engine needs, e.g. for custom pipelines.
+    pub features: wgpu::Features,
+    /// Limits to request. The engine raises those it needs more of.
+    pub limits: wgpu::Limits,
+}
+
+impl<'a, M> Engine<'a, M> {
+    /// An engine on the adapter `options` pick, without targets yet.
+    pub fn with_options(options: EngineOptions) -> Result<Self> {
+        let backends = options
+            .backends
+            .unwrap_or_else(crate::consts::default_backends);
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            backends: crate::consts::default_backends(),
+            backends,
             flags: crate::consts::default_instance_flags(),
             ..Default::default()
         });
 
-        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::default(),
-            compatible_surface: None,
-            force_fallback_adapter: false,
-        }))
-        .expect("wgpu: no suitable adapter found for the current surface");
+        let adapter = match &options.adapter_name {
+            Some(name) => {
+                let name = name.to_lowercase();
+                instance
+                    .enumerate_adapters(backends)
+                    .into_iter()
+                    .find(|a| a.get_info().name.to_lowercase().contains(&name))
+                    .ok_or(Error::NoAdapter)?
+            }
+            None => pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: options.power_preference,
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            }))
+            .map_err(|_| Error::NoAdapter)?,
+        };
 
         let is_metal = adapter.get_info().backend == wgpu::Backend::Metal;
-        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
-            label: None,
-            required_features: wgpu::Features::PUSH_CONSTANTS
-                | wgpu::Features::TEXTURE_BINDING_ARRAY
-                | wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
-                | wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER
-                | if !is_metal {
-                    wgpu::Features::PARTIALLY_BOUND_BINDING_ARRAY
-                } else {
-                    wgpu::Features::empty()
+        let globals_size = std::mem::size_of::<Globals>() as u32;
+        let push_constants = !crate::consts::force_uniform_globals()
+            && adapter.features().contains(wgpu::Features::PUSH_CONSTANTS)
+            && adapter.limits().max_push_constant_size >= globals_size;
+
+        let mut required_features = options.features
+            | wgpu::Features::TEXTURE_BINDING_ARRAY
+            | wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
+            | wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER;
+        if push_constants {
+            required_features |= wgpu::Features::PUSH_CONSTANTS;
+        }
+        if !is_metal {
+            required_features |= wgpu::Features::PARTIALLY_BOUND_BINDING_ARRAY;
+        }
+        // Only needed for `Engine::set_profiling` and compressed textures, so they're taken
+        // where offered.
+        required_features |= adapter.features()
+            & (wgpu::Features::TIMESTAMP_QUERY
+                | wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES
+                | wgpu::Features::TEXTURE_COMPRESSION_BC
+                | wgpu::Features::TEXTURE_COMPRESSION_ETC2
+                | wgpu::Features::TEXTURE_COMPRESSION_ASTC);
+
+        let (device, queue) = pollster::block_on(
+            adapter.request_device(&wgpu::DeviceDescriptor {
+                label: None,
+                required_features,
+                required_limits: wgpu::Limits {
+                    max_push_constant_size: if push_constants {
+                        options.limits.max_push_constant_size.max(128)
+                    } else {
+                        0
+                    },
+                    // Room for the texture array to grow into, see `MAX_TEXTURES`.
+                    max_binding_array_elements_per_shader_stage: options
+                        .limits
+                        .max_binding_array_elements_per_shader_stage
+                        .max(
+                            adapter
+                                .limits()
+                                .max_binding_array_elements_per_shader_stage
+                                .min(MAX_TEXTURES),
+                        )
+                        .max(DEFAULT_MAX_TEXTURES),
+                    ..options.limits
                 },
-            required_limits: wgpu::Limits {
-                max_push_constant_size: 128,
-                max_binding_array_elements_per_shader_stage: DEFAULT_MAX_TEXTURES,
-                ..Default::default()
-            },
-            memory_hints: wgpu::MemoryHints::MemoryUsage,
-            trace: wgpu::Trace::Off,
-        }))
-        .expect("wgpu: failed to request logical device/queue (feature set unsupported?)");
+                memory_hints: wgpu::MemoryHints::MemoryUsage,
+                trace: wgpu::Trace::Off,
+            }),
+        )
+        .map_err(Error::RequestDevice)?;
+
+        let (globals, push_constant_ranges) = if push_constants {
+            let ranges = vec![wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                range: 0..globals_size,
+            }];
+            (GlobalsBinding::PushConstants, ranges)
+        } else {
+            (GlobalsBinding::uniform(&device), Vec::new())
+        };
 
         let gpu = Gpu {
             instance,
             adapter,
             device,
             queue,
+            globals,
+            samples: AtomicU32::new(1),
+            depth: AtomicBool::new(false),
+            stencil: AtomicBool::new(false),
         };
 
-        let push_constant_ranges = vec![wgpu::PushConstantRange {
-            stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
-            range: 0..std::mem::size_of::<Globals>() as u32,
-        }];
-
         let renderer = Renderer::new(&gpu.device);
         let pipeline_registry = PipelineRegistry::new();
 
         let target_alloc = TargetIdAlloc::default();
         let targets = HashMap::with_capacity(1);
 
-        Self {
+        Ok(Self {
             debug: false,
 
             gpu: Arc::new(gpu),
@@ -139,16 +595,23 @@ impl<'a, M> Default for Engine<'a, M> {
             push_constant_ranges,
             pipeline_registry,
             renderer,
-        }
+            shortcuts: Vec::new(),
+            loads: Vec::new(),
+            #[cfg(feature = "hot_reload")]
+            shader_watcher: None,
+            #[cfg(feature = "hot_reload")]
+            shader_error: None,
+        })
     }
 }
 
 impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
-    pub fn new() -> Self {
-        Self::default()
+    /// An engine on the default adapter, see [`Engine::with_options`].
+    pub fn new() -> Result<Self> {
+        Self::with_options(EngineOptions::default())
     }
 
-    pub fn new_for<T>(target: Arc<T>, size: Size<u32>) -> (TargetId, Self)
+    pub fn new_for<T>(target: Arc<T>, size: Size<u32>) -> Result<(TargetId, Self)>
     where
         T: wgpu::rwh::HasWindowHandle
             + wgpu::rwh::HasDisplayHandle
@@ -157,14 +620,42 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
             + std::marker::Send
             + 'a,
     {
-        let mut engine = Self::new();
+        Self::new_for_with(target, size, PresentOptions::default())
+    }
 
-        let target = engine.create_target(target, size);
+    /// Like [`Engine::new_for`], presenting the target's frames as `options` say.
+    pub fn new_for_with<T>(
+        target: Arc<T>,
+        size: Size<u32>,
+        options: PresentOptions,
+    ) -> Result<(TargetId, Self)>
+    where
+        T: wgpu::rwh::HasWindowHandle
+            + wgpu::rwh::HasDisplayHandle
+            + Sized
+            + std::marker::Sync
+            + std::marker::Send
+            + 'a,
+    {
+        let mut engine = Self::new()?;
+        let target = engine.create_target(target, size, options)?;
+        Ok((target, engine))
+    }
 
-        (target, engine)
+    /// An engine with one target that has no window, drawing into a texture instead, for
+    /// rendering on CI machines and servers. Read frames back with [`Engine::capture_frame`].
+    pub fn new_headless(size: Size<u32>) -> Result<(TargetId, Self)> {
+        let mut engine = Self::new()?;
+        let target = engine.create_headless_target(size);
+        Ok((target, engine))
     }
 
-    fn create_target<T>(&mut self, target: Arc<T>, size: Size<u32>) -> TargetId
+    fn create_target<T>(
+        &mut self,
+        target: Arc<T>,
+        size: Size<u32>,
+        options: PresentOptions,
+    ) -> Result<TargetId>
     where
         T: wgpu::rwh::HasWindowHandle
             + wgpu::rwh::HasDisplayHandle
@@ -175,19 +666,17 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
     {
         let size = size.max(Size::new(1, 1));
 
-        let surface = self
-            .gpu
-            .instance
-            .create_surface(target.clone())
-            .expect("wgpu: failed to create surface (window/display handle mismatch?)");
+        let surface = self.gpu.instance.create_surface(target.clone())?;
 
+        // Pipelines are built for one format, so every target shares the first one's where the
+        // surface allows it.
         let surface_caps = surface.get_capabilities(&self.gpu.adapter);
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .unwrap_or(surface_caps.formats[0]);
+        let shared = self.primary_target().map(|t| t.config.format);
+        let surface_format = shared
+            .filter(|f| surface_caps.formats.contains(f))
+            .or_else(|| surface_caps.formats.iter().find(|f| f.is_srgb()).copied())
+            .or_else(|| surface_caps.formats.first().copied())
+            .ok_or(Error::NoSurfaceFormat)?;
         let alpha_mode = if surface_caps
             .alpha_modes
             .contains(&wgpu::CompositeAlphaMode::PreMultiplied)
@@ -199,9 +688,9 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
         {
             wgpu::CompositeAlphaMode::Inherit
         } else {
-            surface_caps.alpha_modes[0]
+            wgpu::CompositeAlphaMode::Auto
         };
-        let config = wgpu::SurfaceConfiguration {
+        let mut config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
             width: size.width,
@@ -211,9 +700,56 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
             view_formats: vec![],
             desired_maximum_frame_latency: 1,
         };
+        options.apply(&mut config, &surface_caps);
 
         surface.configure(&self.gpu.device, &config);
+        let reformat = shared.is_some_and(|f| f != surface_format)
+            && self.targets.values().all(|t| t.surface.is_none());
+        let tid = self.add_target(Some(surface), config);
+        if reformat {
+            self.adopt_format(tid);
+        }
+        Ok(tid)
+    }
+
+    // Moves headless targets, which can render to any format, over to `tid`'s format and
+    // rebuilds the pipelines for it.
+    fn adopt_format(&mut self, tid: TargetId) {
+        let format = self.targets[&tid].config.format;
+        for target in self.targets.values_mut() {
+            if target.surface.is_none() {
+                target.config.format = format;
+                target.offscreen = None;
+                target.msaa = None;
+            }
+        }
+        self.primary_target = Some(tid);
+        self.reload_all();
+    }
+
+    fn create_headless_target(&mut self, size: Size<u32>) -> TargetId {
+        let size = size.max(Size::new(1, 1));
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: self
+                .primary_target()
+                .map_or(wgpu::TextureFormat::Rgba8UnormSrgb, |t| t.config.format),
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::AutoVsync,
+            alpha_mode: wgpu::CompositeAlphaMode::PreMultiplied,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 1,
+        };
+        self.add_target(None, config)
+    }
 
+    fn add_target(
+        &mut self,
+        surface: Option<wgpu::Surface<'a>>,
+        config: wgpu::SurfaceConfiguration,
+    ) -> TargetId {
+        let size = Size::new(config.width, config.height);
         let now = Instant::now();
         let target = Target {
             surface,
@@ -232,8 +768,14 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
 
             start_time: now,
             last_frame_time: now,
+            pacing: FramePacing::default(),
+            awake: true,
 
             root: None,
+            msaa: None,
+            depth: None,
+            last_frame: Vec::new(),
+            offscreen: None,
         };
 
         if !self.pipeline_registry.has_default_pipelines() {
@@ -283,15 +825,260 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
         );
     }
 
+    /// Renders with `samples` samples per pixel, smoothing the edges of rotated and rounded
+    /// shapes. Falls back to the closest lower count the adapter supports, down to 1 which
+    /// turns MSAA off, and returns the count in use. Pipelines are rebuilt to match.
+    pub fn set_msaa(&mut self, samples: u32) -> u32 {
+        let format = self.primary_target().map(|t| t.config.format);
+        let supported = |n: u32| {
+            format.is_none_or(|f| {
+                self.gpu
+                    .adapter
+                    .get_texture_format_features(f)
+                    .flags
+                    .sample_count_supported(n)
+            })
+        };
+        let samples = [8, 4, 2]
+            .into_iter()
+            .find(|&n| n <= samples && supported(n))
+            .unwrap_or(1);
+        if samples != self.gpu.sample_count() {
+            self.gpu.samples.store(samples, Ordering::Relaxed);
+            self.reload_all();
+            self.request_redraw_all();
+        }
+        samples
+    }
+
+    /// Gives targets a depth buffer, so instances can be ordered by [`Instance::depth`] against
+    /// custom pipelines instead of only by the order they're drawn in. Pipelines are rebuilt to
+    /// match; custom ones should use [`Gpu::depth_stencil`] or [`Gpu::depth_format`].
+    ///
+    /// [`Instance::depth`]: crate::primitive::Instance::depth
+    pub fn set_depth_buffer(&mut self, on: bool) {
+        if self.gpu.depth.swap(on, Ordering::Relaxed) != on {
+            self.reload_all();
+            self.request_redraw_all();
+        }
+    }
+
+    /// Masks what rounded clips cover by their outline through the stencil buffer, instead of
+    /// only by the rect around them. Pipelines are rebuilt to match; custom ones should use
+    /// [`Gpu::depth_stencil`] to be masked too.
+    pub fn set_stencil_clipping(&mut self, on: bool) {
+        if self.gpu.stencil.swap(on, Ordering::Relaxed) != on {
+            self.reload_all();
+            self.request_redraw_all();
+        }
+    }
+
     pub fn toggle_debug(&mut self) {
         self.debug = !self.debug;
     }
 
+    /// Queues a toast on the target's `ToastHost`, e.g. from `update`.
+    pub fn toast(&mut self, tid: &TargetId, toast: impl Into<Toast>) {
+        if let Some(t) = self.targets.get_mut(tid) {
+            t.ctx.toast(toast);
+        }
+    }
+
+    /// Sets whether the target lays out left-to-right or right-to-left.
+    pub fn set_direction(&mut self, tid: &TargetId, direction: Direction) {
+        if let Some(t) = self.targets.get_mut(tid) {
+            t.ctx.set_direction(direction);
+        }
+    }
+
+    /// The innermost widget under `position` in the target's last laid out tree.
+    pub fn hit_test(&self, tid: &TargetId, position: Position<f32>) -> Option<Id> {
+        let root = self.targets.get(tid)?.root.as_ref()?;
+        crate::widget::query::hit_test(root.as_ref(), position)
+    }
+
+    /// Top-left corner and size `id` was laid out at in the target's last frame.
+    pub fn widget_bounds(&self, tid: &TargetId, id: Id) -> Option<(Position<i32>, Size<i32>)> {
+        let root = self.targets.get(tid)?.root.as_ref()?;
+        crate::widget::query::bounds(root.as_ref(), id)
+    }
+
+    /// Caret rect of the focused text widget in the target's last event pass, for the platform
+    /// to enable input methods and place their popups; `None` turns them off.
+    pub fn ime_area(&self, tid: &TargetId) -> Option<(Position<i32>, Size<i32>)> {
+        self.targets.get(tid)?.ctx.ime_area()
+    }
+
+    /// Pointer shape the target's widgets asked for in its last event pass.
+    pub fn cursor(&self, tid: &TargetId) -> CursorIcon {
+        self.targets
+            .get(tid)
+            .map_or(CursorIcon::Default, |t| t.ctx.cursor())
+    }
+
+    /// Text a widget copied since the last call, for the platform to put on the clipboard.
+    pub fn take_copied(&mut self, tid: &TargetId) -> Option<String> {
+        self.targets.get_mut(tid)?.ctx.take_copied()
+    }
+
+    /// Whether a widget asked for the clipboard since the last call. The platform answers with
+    /// [`Engine::paste`].
+    pub fn take_paste_request(&mut self, tid: &TargetId) -> bool {
+        self.targets
+            .get_mut(tid)
+            .is_some_and(|t| t.ctx.take_paste_request())
+    }
+
+    /// Hands clipboard text to the target's focused widget, as if it had been typed.
+    pub fn paste(&mut self, tid: &TargetId, text: &str) {
+        if let Some(target) = self.targets.get_mut(tid) {
+            target.awake = true;
+            target.ctx.push_text(text);
+            target.ctx.request_redraw();
+        }
+    }
+
+    /// Measures `element` against the target's text system and state without drawing it. See
+    /// [`measure`](crate::widget::measure).
+    pub fn measure(
+        &mut self,
+        tid: &TargetId,
+        element: &mut Element<M>,
+        max: Size<i32>,
+    ) -> Option<Measurement> {
+        let target = self.targets.get_mut(tid)?;
+        let mut layout_ctx = LayoutCtx {
+            globals: &target.globals,
+            ui: &mut target.ctx,
+            text: &mut self.renderer.text,
+            #[cfg(feature = "svg")]
+            svg: &mut self.renderer.svg,
+        };
+        Some(crate::widget::measure(element, &mut layout_ctx, max))
+    }
+
+    /// Size of `content` laid out in `style`, wrapped at `max_width` if given, without building
+    /// a widget for it.
+    pub fn measure_text(
+        &mut self,
+        content: &str,
+        style: &TextStyle,
+        max_width: Option<f32>,
+    ) -> Size<f32> {
+        self.renderer.text.measure(content, style, max_width)
+    }
+
+    /// Reads back the target's last rendered frame, for screenshots and visual tests. Blocks
+    /// until the GPU is done. 8-bit, `Rgb10a2Unorm` and `Rgba16Float` targets are converted to
+    /// 8-bit RGBA; other formats give [`Error::UnsupportedCapture`].
+    pub fn capture_frame(&mut self, tid: &TargetId) -> Result<RgbaImage> {
+        let target = self.targets.get(tid).ok_or(Error::UnknownTarget(*tid))?;
+        self.renderer.capture(
+            &self.gpu,
+            target,
+            &self.pipeline_registry,
+            &target.globals,
+            &target.last_frame,
+        )
+    }
+
+    /// Device and queue the engine renders with, e.g. to make buffers and textures for
+    /// [`Engine::set_bind_group`].
+    pub fn gpu(&self) -> &Gpu {
+        &self.gpu
+    }
+
+    /// Counts from the last rendered frame of any target.
+    pub fn render_stats(&self) -> RenderStats {
+        self.renderer.stats
+    }
+
+    /// Times frames on the GPU with timestamp queries, see [`Engine::frame_profile`]. Returns
+    /// false if the adapter can't, in which case profiling stays off.
+    pub fn set_profiling(&mut self, on: bool) -> bool {
+        if !on {
+            self.renderer.profiler = None;
+            return true;
+        }
+        if self.renderer.profiler.is_none() {
+            self.renderer.profiler = Profiler::new(&self.gpu);
+        }
+        self.renderer.profiler.is_some()
+    }
+
+    /// GPU times of a recent frame of any target, once profiling is on. They're read back
+    /// without stalling, so they trail the frames being rendered by one or two.
+    pub fn frame_profile(&self) -> Option<&FrameProfile> {
+        self.renderer.profiler.as_ref()?.last.as_ref()
+    }
+
+    /// Metrics of the font `style` resolves to, for lining content up with text baselines.
+    pub fn font_metrics(&mut self, style: &TextStyle) -> Option<FontMetrics> {
+        self.renderer.text.font_metrics(style)
+    }
+
+    /// Emits `message` whenever `shortcut` is pressed in any target, before any widget sees
+    /// the key. Binding a shortcut again replaces its message.
+    pub fn bind_shortcut(&mut self, shortcut: impl Into<Shortcut>, message: M)
+    where
+        M: Clone,
+    {
+        let shortcut = shortcut.into();
+        self.unbind_shortcut(&shortcut);
+        self.shortcuts
+            .push((shortcut, Box::new(move || message.clone())));
+    }
+
+    /// Removes `shortcut`, returning whether it was bound.
+    pub fn unbind_shortcut(&mut self, shortcut: &Shortcut) -> bool {
+        let len = self.shortcuts.len();
+        self.shortcuts.retain(|(s, _)| s != shortcut);
+        self.shortcuts.len() != len
+    }
+
     pub fn globals(&self, tid: TargetId) -> Option<&Globals> {
         self.targets.get(&tid).map(|t| &t.globals)
     }
 
-    pub fn attach_target<T>(&mut self, target: Arc<T>, size: Size<u32>) -> TargetId
+    /// Targets start out paced by [`FramePacing::Display`].
+    pub fn set_frame_pacing(&mut self, tid: &TargetId, pacing: FramePacing) {
+        if let Some(target) = self.targets.get_mut(tid) {
+            target.pacing = pacing;
+            target.awake = true;
+        }
+    }
+
+    pub fn frame_pacing(&self, tid: &TargetId) -> Option<FramePacing> {
+        self.targets.get(tid).map(|t| t.pacing)
+    }
+
+    /// When the backend should next poll `tid`, given the display's time between frames if
+    /// it's known. `None` if it can wait for the next event.
+    pub fn next_frame(&self, tid: &TargetId, refresh: Option<Duration>) -> Option<Instant> {
+        let target = self.targets.get(tid)?;
+        let interval = match target.pacing {
+            FramePacing::Fps(fps) => Duration::from_secs(1) / fps.max(1),
+            FramePacing::Display | FramePacing::OnChange => {
+                refresh.unwrap_or(DEFAULT_FRAME_INTERVAL)
+            }
+        };
+        let next = target.last_frame_time + interval;
+        match target.pacing {
+            FramePacing::OnChange
+                if !target.awake
+                    && !target.ctx.redraw_pending()
+                    && !self.loads.iter().any(|load| load.tid == *tid) =>
+            {
+                target
+                    .ctx
+                    .redraw_deadline()
+                    .map(|t| (target.start_time + Duration::from_secs_f32(t.max(0.0))).max(next))
+            }
+            _ => Some(next),
+        }
+    }
+
+    pub fn attach_target<T>(&mut self, target: Arc<T>, size: Size<u32>) -> Result<TargetId>
     where
         T: wgpu::rwh::HasWindowHandle
             + wgpu::rwh::HasDisplayHandle
@@ -300,7 +1087,60 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
             + std::marker::Send
             + 'a,
     {
-        self.create_target(target, size)
+        self.create_target(target, size, PresentOptions::default())
+    }
+
+    /// Like [`Engine::attach_target`], presenting the target's frames as `options` say.
+    pub fn attach_target_with<T>(
+        &mut self,
+        target: Arc<T>,
+        size: Size<u32>,
+        options: PresentOptions,
+    ) -> Result<TargetId>
+    where
+        T: wgpu::rwh::HasWindowHandle
+            + wgpu::rwh::HasDisplayHandle
+            + Sized
+            + std::marker::Sync
+            + std::marker::Send
+            + 'a,
+    {
+        self.create_target(target, size, options)
+    }
+
+    /// Reconfigures how the target's frames are presented. Headless targets have nothing to
+    /// present, so it's ignored for them.
+    pub fn set_present_options(&mut self, tid: &TargetId, options: PresentOptions) -> Result<()> {
+        let target = self
+            .targets
+            .get_mut(tid)
+            .ok_or(Error::UnknownTarget(*tid))?;
+        if let Some(surface) = &target.surface {
+            options.apply(
+                &mut target.config,
+                &surface.get_capabilities(&self.gpu.adapter),
+            );
+            surface.configure(&self.gpu.device, &target.config);
+        }
+        Ok(())
+    }
+
+    /// How the target's frames are presented, with the present mode the surface settled on.
+    pub fn present_options(&self, tid: &TargetId) -> Option<PresentOptions> {
+        self.targets.get(tid).map(|t| PresentOptions {
+            present_mode: t.config.present_mode,
+            max_frame_latency: t.config.desired_maximum_frame_latency,
+        })
+    }
+
+    /// Adds a target without a window, see [`Engine::new_headless`].
+    pub fn attach_headless_target(&mut self, size: Size<u32>) -> Result<TargetId> {
+        Ok(self.create_headless_target(size))
+    }
+
+    /// Texture a headless target's frames are drawn into, once it has rendered.
+    pub fn target_texture(&self, tid: &TargetId) -> Option<&wgpu::Texture> {
+        self.targets.get(tid)?.offscreen()
     }
 
     pub fn detach_target(&mut self, tid: &TargetId) {
@@ -313,16 +1153,14 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
         }
     }
 
+    /// Builds a pipeline with `pipeline_factory` for the primary target's surface format and
+    /// draws instances of `key` with it. Fails without a target to take the format from.
     pub fn register_pipeline(
         &mut self,
         key: crate::render::pipeline::PipelineKey,
         pipeline_factory: crate::render::PipelineFactoryFn,
-    ) {
-        let fmt = if let Some(t) = self.primary_target() {
-            t.config.format
-        } else {
-            return; // TODO: we should definitely return a result here
-        };
+    ) -> Result<()> {
+        let fmt = self.primary_target().ok_or(Error::NoTarget)?.config.format;
 
         let pipeline = pipeline_factory(
             &self.gpu,
@@ -332,19 +1170,176 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
             &self.push_constant_ranges,
         );
         self.pipeline_registry.register_pipeline(key, pipeline);
+        Ok(())
+    }
+
+    /// Runs the compute work `factory` makes before every frame, after what was registered
+    /// earlier. Registering under a `name` already in use replaces that work in its place.
+    pub fn register_compute(
+        &mut self,
+        name: &'static str,
+        factory: crate::render::ComputeFactoryFn,
+    ) {
+        let pipeline = factory(&self.gpu);
+        self.pipeline_registry.register_compute(name, pipeline);
+    }
+
+    /// Stops the compute work registered under `name`, returning whether there was any.
+    pub fn remove_compute(&mut self, name: &str) -> bool {
+        self.pipeline_registry.remove_compute(name)
+    }
+
+    /// Gives the pipeline at `key` a group `index` made from `entries`, against the layout it
+    /// declares in [`Pipeline::bind_group_layouts`], and binds it whenever the pipeline draws.
+    /// Replaces the group it had at `index`. Returns false if there's no such pipeline or it
+    /// declares no such group.
+    ///
+    /// [`Pipeline::bind_group_layouts`]: crate::render::pipeline::Pipeline::bind_group_layouts
+    pub fn set_bind_group(
+        &mut self,
+        key: crate::render::pipeline::PipelineKey,
+        index: u32,
+        entries: &[wgpu::BindGroupEntry],
+    ) -> bool {
+        self.pipeline_registry
+            .set_bind_group(&self.gpu, key, index, entries)
     }
 
-    pub fn load_texture_rgba8(&mut self, width: u32, height: u32, pixels: &[u8]) -> TextureHandle {
+    /// Stops binding group `index` of the pipeline at `key`, returning whether it had one.
+    pub fn remove_bind_group(
+        &mut self,
+        key: &crate::render::pipeline::PipelineKey,
+        index: u32,
+    ) -> bool {
+        self.pipeline_registry.remove_bind_group(key, index)
+    }
+
+    pub fn load_texture_rgba8(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Result<TextureHandle> {
         self.renderer
             .textures
             .load_rgba8(&self.gpu, width, height, pixels)
     }
 
+    /// Decodes the image at `path` and uploads it as RGBA8.
+    #[cfg(feature = "image")]
+    pub fn load_texture_from_path(&mut self, path: impl AsRef<Path>) -> Result<TextureHandle> {
+        let rgba = image::open(path)?.into_rgba8();
+        self.load_texture_rgba8(rgba.width(), rgba.height(), rgba.as_raw())
+    }
+
+    /// Runs `decode` on a thread of its own and uploads what it returns before `tid`'s next
+    /// frame, emitting the message `on_ready` makes of the handle to `tid`. Keeps large images
+    /// from stalling rendering while they decode.
+    pub fn load_texture_with(
+        &mut self,
+        tid: &TargetId,
+        decode: impl FnOnce() -> Result<RgbaImage> + Send + 'static,
+        on_ready: impl FnOnce(Result<TextureHandle>) -> M + 'static,
+    ) {
+        let (tx, decoded) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(decode());
+        });
+        self.loads.push(PendingLoad {
+            tid: *tid,
+            decoded,
+            on_ready: Box::new(on_ready),
+        });
+    }
+
+    /// [`Engine::load_texture_from_path`] with the decoding done by
+    /// [`Engine::load_texture_with`].
+    #[cfg(feature = "image")]
+    pub fn load_texture_from_path_async(
+        &mut self,
+        tid: &TargetId,
+        path: impl Into<std::path::PathBuf>,
+        on_ready: impl FnOnce(Result<TextureHandle>) -> M + 'static,
+    ) {
+        let path = path.into();
+        self.load_texture_with(
+            tid,
+            move || {
+                let rgba = image::open(path)?.into_rgba8();
+                Ok(RgbaImage {
+                    width: rgba.width(),
+                    height: rgba.height(),
+                    pixels: rgba.into_raw(),
+                })
+            },
+            on_ready,
+        );
+    }
+
+    // Uploads textures whose decoding finished and tells their targets.
+    fn finish_loads(&mut self) {
+        for load in std::mem::take(&mut self.loads) {
+            let decoded = match load.decoded.try_recv() {
+                Ok(decoded) => decoded,
+                Err(mpsc::TryRecvError::Empty) => {
+                    self.loads.push(load);
+                    continue;
+                }
+                Err(mpsc::TryRecvError::Disconnected) => Err(Error::LoaderPanicked),
+            };
+            let handle = decoded.and_then(|image| {
+                self.load_texture_rgba8(image.width, image.height, &image.pixels)
+            });
+            if let Some(target) = self.targets.get_mut(&load.tid) {
+                target.ctx.emit((load.on_ready)(handle));
+            }
+        }
+    }
+
+    /// Uploads block-compressed texture data, see [`TextureRegistry::load_compressed`].
+    ///
+    /// [`TextureRegistry::load_compressed`]: crate::render::texture::TextureRegistry::load_compressed
+    pub fn load_texture_compressed(
+        &mut self,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        levels: &[&[u8]],
+    ) -> Result<TextureHandle> {
+        self.renderer
+            .textures
+            .load_compressed(&self.gpu, format, width, height, levels)
+    }
+
+    /// Loads a KTX2 file's image, compressed or not, with its mip levels.
+    #[cfg(feature = "ktx2")]
+    pub fn load_texture_ktx2(&mut self, bytes: &[u8]) -> Result<TextureHandle> {
+        self.renderer.textures.load_ktx2(&self.gpu, bytes)
+    }
+
     pub fn unload_texture(&mut self, handle: TextureHandle) -> bool {
-        self.renderer.textures.unload(&self.gpu, handle)
+        self.renderer.textures.unload(handle)
+    }
+
+    pub fn load_animation(&mut self, frames: &[AnimationFrame]) -> Result<AnimatedHandle> {
+        self.renderer.textures.load_animation(&self.gpu, frames)
     }
 
-    pub fn create_atlas(&mut self, width: u32, height: u32) -> Atlas {
+    pub fn load_animation_into_atlas(
+        &mut self,
+        atlas: &mut Atlas,
+        frames: &[AnimationFrame],
+    ) -> Option<AnimatedHandle> {
+        self.renderer
+            .textures
+            .load_animation_into_atlas(&self.gpu, atlas, frames)
+    }
+
+    pub fn unload_animation(&mut self, handle: &AnimatedHandle) {
+        self.renderer.textures.unload_animation(handle)
+    }
+
+    pub fn create_atlas(&mut self, width: u32, height: u32) -> Result<Atlas> {
         self.renderer
             .textures
             .create_atlas(&self.gpu, width, height)
@@ -362,8 +1357,105 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
             .load_into_atlas(&self.gpu, atlas, width, height, pixels)
     }
 
+    /// Reclaims the space of entries freed with [`Atlas::free`], see
+    /// [`TextureRegistry::repack_atlas`].
+    ///
+    /// [`TextureRegistry::repack_atlas`]: crate::render::texture::TextureRegistry::repack_atlas
+    pub fn repack_atlas(
+        &mut self,
+        atlas: &mut Atlas,
+    ) -> Option<Vec<(TextureHandle, TextureHandle)>> {
+        self.renderer.textures.repack_atlas(&self.gpu, atlas)
+    }
+
     pub fn destroy_atlas(&mut self, atlas: &mut Atlas) {
-        self.renderer.textures.destroy_atlas(&self.gpu, atlas)
+        self.renderer.textures.destroy_atlas(atlas)
+    }
+
+    /// Registers a font shipped with the app, returning the families it adds for use with
+    /// `Family::Name`.
+    pub fn load_font_bytes(&mut self, data: impl Into<Vec<u8>>) -> Vec<String> {
+        let families = self.renderer.text.load_font(data.into());
+        self.request_redraw_all();
+        families
+    }
+
+    /// Reads and registers a font file, see [`Engine::load_font_bytes`].
+    pub fn load_font_path(&mut self, path: impl AsRef<Path>) -> std::io::Result<Vec<String>> {
+        let data = std::fs::read(path)?;
+        Ok(self.load_font_bytes(data))
+    }
+
+    /// Uses `family` for all text that doesn't pick a family of its own.
+    pub fn set_default_family(&mut self, family: impl Into<String>) {
+        self.renderer.text.set_default_family(family);
+        self.request_redraw_all();
+    }
+
+    /// Sets the families tried when a font lacks a glyph, see [`FontFallback`].
+    pub fn set_font_fallback(&mut self, chain: &FontFallback) {
+        self.renderer.text.set_fallback(chain);
+        self.request_redraw_all();
+    }
+
+    /// Reloads every pipeline whenever the WGSL file at `path` changes, so pipelines reading
+    /// it through [`shader_source`] pick up edits without a restart. Changes are picked up the
+    /// next time the engine polls. A file that doesn't compile is reported by
+    /// [`Engine::shader_error`] and leaves the pipelines as they were.
+    ///
+    /// [`shader_source`]: crate::render::shader_source
+    #[cfg(feature = "hot_reload")]
+    pub fn watch_shader(&mut self, path: impl AsRef<Path>) -> notify::Result<()> {
+        let watcher = match &mut self.shader_watcher {
+            Some(watcher) => watcher,
+            None => self
+                .shader_watcher
+                .insert(crate::render::hot_reload::ShaderWatcher::new()?),
+        };
+        watcher.watch(path.as_ref())
+    }
+
+    /// Why the last reload of a watched shader failed, until one succeeds.
+    #[cfg(feature = "hot_reload")]
+    pub fn shader_error(&self) -> Option<&str> {
+        self.shader_error.as_deref()
+    }
+
+    #[cfg(feature = "hot_reload")]
+    fn reload_changed_shaders(&mut self) {
+        let Some(watcher) = &mut self.shader_watcher else {
+            return;
+        };
+        let changed = watcher.changed();
+        if changed.is_empty() {
+            return;
+        }
+
+        // Broken files are caught before anything is rebuilt, since a pipeline can't go back
+        // to what it was once reloaded.
+        for path in &changed {
+            let checked = std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| crate::render::hot_reload::validate(&source));
+            if let Err(e) = checked {
+                self.shader_error = Some(format!("{}: {e}", path.display()));
+                return;
+            }
+        }
+
+        self.gpu
+            .device
+            .push_error_scope(wgpu::ErrorFilter::Validation);
+        self.reload_all();
+        self.shader_error =
+            pollster::block_on(self.gpu.device.pop_error_scope()).map(|e| e.to_string());
+        self.request_redraw_all();
+    }
+
+    fn request_redraw_all(&mut self) {
+        for target in self.targets.values_mut() {
+            target.ctx.request_redraw();
+        }
     }
 
     pub fn poll<S, P, E: ToEvent<M, E> + std::fmt::Debug>(
@@ -373,6 +1465,10 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
         state: &mut S,
         params: &P,
     ) -> bool {
+        #[cfg(feature = "hot_reload")]
+        self.reload_changed_shaders();
+        self.finish_loads();
+
         let target = if let Some(t) = self.targets.get_mut(tid) {
             t
         } else {
@@ -389,16 +1485,22 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
         let mut require_redraw = false;
 
         if let Some(root) = target.root.as_mut() {
+            target.ctx.apply_shortcuts(&self.shortcuts);
+            target.ctx.begin_event_pass();
             let mut event_cx = EventCtx {
                 globals: &target.globals,
                 ui: &mut target.ctx,
             };
+            crate::widget::query::dispatch(root, &mut event_cx);
             root.handle(&mut event_cx);
+            target.ctx.end_event_pass();
         } else {
             require_redraw = true;
         }
 
+        target.ctx.clear_input();
         require_redraw |= target.ctx.take_redraw();
+        require_redraw |= target.ctx.take_due_redraw(target.globals.time);
 
         for message in target.ctx.take() {
             require_redraw |= update(self, &Event::Message(message), state, params);
@@ -406,6 +1508,9 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
 
         require_redraw |= update(self, &Event::RedrawRequested, state, params);
 
+        if let Some(target) = self.targets.get_mut(tid) {
+            target.awake = require_redraw;
+        }
         require_redraw
     }
 
@@ -415,15 +1520,14 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
         need: bool,
         view: &impl Fn(&TargetId, &S) -> Element<M>,
         state: &mut S,
-    ) {
-        let target = if let Some(t) = self.targets.get_mut(tid) {
-            t
-        } else {
-            return; // TODO: maybe return a result instead
-        };
+    ) -> Result<()> {
+        let target = self
+            .targets
+            .get_mut(tid)
+            .ok_or(Error::UnknownTarget(*tid))?;
 
         if !need {
-            return;
+            return Ok(());
         }
 
         // TODO: this should eventually be removed, as it is not accurate way to have id's
@@ -440,11 +1544,15 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
         )
         .max(Size::new(1, 1));
 
+        // The view is built anew, so every widget is laid out again; only `Memo` subtrees keep
+        // their layout while unchanged.
         {
             let mut layout_ctx = LayoutCtx {
                 globals: &target.globals,
                 ui: &mut target.ctx,
                 text: &mut self.renderer.text,
+                #[cfg(feature = "svg")]
+                svg: &mut self.renderer.svg,
             };
             _ = root.fit_width(&mut layout_ctx);
             root.grow_width(&mut layout_ctx, max.width);
@@ -461,7 +1569,11 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
         };
 
         // TODO: split handle into prepare and other steps so we don't need to force a take_redraw
+        event_ctx.ui.apply_shortcuts(&self.shortcuts);
+        event_ctx.ui.begin_event_pass();
+        crate::widget::query::dispatch(root, &mut event_ctx);
         root.handle(&mut event_ctx);
+        target.ctx.end_event_pass();
         target.ctx.take_redraw();
 
         let mut instances = Vec::new();
@@ -471,19 +1583,58 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
                 text: &mut self.renderer.text,
                 gpu: &self.gpu.clone(),
                 texture: &mut self.renderer.textures,
+                #[cfg(feature = "svg")]
+                svg: &mut self.renderer.svg,
+                layers: Vec::new(),
+                depth: 0,
+                raised: Vec::new(),
+                translations: Vec::new(),
             };
             root.__paint(&mut paint_ctx, &mut instances, &PAINT_TOKEN, self.debug);
+            paint_ctx.merge_raised(0, &mut instances);
+            for mut layer in paint_ctx.layers.drain(..) {
+                instances.append(&mut layer);
+            }
+        }
+
+        // Loads while painting may have grown the texture array the pipelines are built for.
+        if self.renderer.textures.take_resized() {
+            self.reload_all();
         }
+        let target = self.targets.get_mut(tid).expect("target looked up above");
 
         target.globals.frame = target.globals.frame.wrapping_add(1);
+        target.prepare_attachments(&self.gpu);
+        if let Some(profiler) = &mut self.renderer.profiler {
+            profiler.begin_frame(&self.gpu);
+        }
+        let timestamps = self
+            .renderer
+            .profiler
+            .as_ref()
+            .and_then(Profiler::compute_timestamps);
+        if self
+            .pipeline_registry
+            .dispatch_compute(&self.gpu, &target.globals, timestamps)
+            && let Some(profiler) = &mut self.renderer.profiler
+        {
+            profiler.computed();
+        }
 
-        let _ = self.renderer.render(
+        let drawn = self.renderer.render(
             &self.gpu,
             target,
             &self.pipeline_registry,
             &target.globals,
             &instances,
         );
+        target.last_frame = instances;
+        self.renderer
+            .text
+            .end_frame(&self.gpu, &mut self.renderer.textures);
+        #[cfg(feature = "svg")]
+        self.renderer.svg.end_frame(&mut self.renderer.textures);
+        drawn
     }
 
     pub fn handle_platform_event<S, P, E: ToEvent<M, E> + std::fmt::Debug>(
@@ -493,16 +1644,14 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
         update: &mut impl FnMut(&mut Self, &Event<M, E>, &mut S, &P) -> bool,
         state: &mut S,
         params: &P,
-    ) {
-        let target = match self.targets.get_mut(target_id) {
-            Some(t) => t,
-            None => {
-                return; // TODO: maybe return a result instead
-            }
-        };
+    ) -> Result<()> {
+        let target = self
+            .targets
+            .get_mut(target_id)
+            .ok_or(Error::UnknownTarget(*target_id))?;
+        target.awake = true;
 
         let event = event.to_event();
-        let prev_mouse_down = target.ctx.mouse_down;
 
         match event {
             Event::Resized { size } => {
@@ -510,24 +1659,50 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
                     target.config.width = size.width;
                     target.config.height = size.height;
                     target.globals.window_size = [size.width as f32, size.height as f32];
-                    target.surface.configure(&self.gpu.device, &target.config);
+                    if let Some(surface) = &target.surface {
+                        surface.configure(&self.gpu.device, &target.config);
+                    }
                 }
                 target.ctx.request_redraw();
             }
             Event::CursorMoved { position } => {
-                target.ctx.mouse_pos = position;
+                target.ctx.move_pointer(position);
                 target.globals.mouse_pos = [position.x, position.y];
             }
-            Event::MouseInput { mouse_down } => {
-                target.ctx.mouse_down = mouse_down;
-                target.ctx.mouse_pressed = !prev_mouse_down && mouse_down;
-                target.ctx.mouse_released = prev_mouse_down && !mouse_down;
-
-                if mouse_down {
-                    target.globals.mouse_buttons |= 1;
-                } else {
-                    target.globals.mouse_buttons &= !1;
-                }
+            Event::MouseInput { button, mouse_down } => {
+                target.ctx.set_button(button, mouse_down);
+                target.globals.mouse_buttons = target.ctx.buttons();
+            }
+            Event::MouseWheel { delta, phase } => {
+                target.ctx.push_wheel(delta, phase);
+                target.ctx.request_redraw();
+            }
+            Event::Key(ref key) => {
+                target.ctx.push_key(key.clone());
+                target.ctx.request_redraw();
+            }
+            Event::Text(ref input) => {
+                target.ctx.push_text(&input.text);
+                target.ctx.request_redraw();
+            }
+            Event::ImePreedit(ref preedit) => {
+                target.ctx.set_preedit(preedit.clone());
+                target.ctx.request_redraw();
+            }
+            Event::ModifiersChanged(modifiers) => {
+                target.ctx.modifiers = modifiers;
+            }
+            Event::FileHover(ref path) => {
+                target.ctx.hover_file(path.clone());
+                target.ctx.request_redraw();
+            }
+            Event::FileDrop(ref path) => {
+                target.ctx.drop_file(path.clone());
+                target.ctx.request_redraw();
+            }
+            Event::FileHoverCancelled => {
+                target.ctx.cancel_file_hover();
+                target.ctx.request_redraw();
             }
             _ => (),
         }
@@ -537,5 +1712,6 @@ impl<'a, M: std::fmt::Debug + 'static> Engine<'a, M> {
         {
             target.ctx.request_redraw();
         }
+        Ok(())
     }
 }
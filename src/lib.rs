@@ -2,6 +2,7 @@ use crate::model::*;
 
 pub(crate) mod consts;
 pub mod context;
+mod error;
 pub mod event;
 pub mod graphics;
 pub mod model;
@@ -13,6 +14,8 @@ pub mod widget;
 #[cfg(feature = "winit")]
 pub mod winit;
 
+pub use error::{Error, Result};
+
 #[macro_export]
 macro_rules! pipeline_factories {
     ( $( $name:literal => $ty:path ),+ $(,)? ) => {{
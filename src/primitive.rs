@@ -1,5 +1,5 @@
 use crate::{
-    model::{Color, Position, Size},
+    model::{Color, Position, Size, Vec4},
     render::{pipeline::PipelineKey, texture::TextureHandle},
 };
 
@@ -38,6 +38,8 @@ pub struct Primitive {
     pub size: [f32; 2],
     pub data1: [u32; 4],
     pub data2: [u32; 4],
+    pub data3: [u32; 4],
+    pub depth: f32,
 }
 
 impl Primitive {
@@ -47,6 +49,8 @@ impl Primitive {
             size: [size.width as f32, size.height as f32],
             data1,
             data2,
+            data3: [0; 4],
+            depth: 0.0,
         }
     }
 }
@@ -77,11 +81,36 @@ impl Primitive {
                     shader_location: 3,
                     format: wgpu::VertexFormat::Uint32x4,
                 },
+                wgpu::VertexAttribute {
+                    offset: 48,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Uint32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: 64,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32,
+                },
             ],
         }
     }
 }
 
+/// Effects of untextured `PipelineKey::Ui` instances, stored where textured ones keep the
+/// texture generation.
+const EFFECT_SHADOW: u32 = 1;
+const EFFECT_LINEAR: u32 = 2;
+const EFFECT_RADIAL: u32 = 3;
+const EFFECT_BORDER: u32 = 4;
+
+/// Side of a border instance to draw, in the order of `Vec4` padding: left, top, right,
+/// bottom; anything else draws all four.
+const ALL_SIDES: u32 = 4;
+
+fn ordered(a: i32, b: i32) -> (i32, i32) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
 #[derive(Debug)]
 pub struct Instance {
     pub(crate) kind: PipelineKey,
@@ -89,6 +118,27 @@ pub struct Instance {
     size: Size<i32>,
     data1: [u32; 4],
     data2: [u32; 4],
+    // Corner radii of `PipelineKey::Ui` instances as f32 bits: top-left, top-right,
+    // bottom-right, bottom-left.
+    data3: [u32; 4],
+    // Scissor rect (top-left, size) in window pixels; nothing outside it is drawn.
+    pub(crate) clip: Option<(Position<i32>, Size<i32>)>,
+    // Depth tested against the depth buffer, from 0 (front) to 1 (back).
+    pub(crate) depth: f32,
+    // Number of rounded clips the instance is nested in, which the stencil buffer has to match
+    // under it for it to be drawn.
+    pub(crate) stencil: u8,
+    // Set on the outline of a rounded clip, which is drawn into the stencil buffer instead.
+    pub(crate) mask: Option<Mask>,
+}
+
+/// What an instance holding a rounded clip's outline does to the stencil buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Mask {
+    /// Starts the clip, before what it covers is drawn.
+    Push,
+    /// Ends the clip again.
+    Pop,
 }
 
 impl Instance {
@@ -105,9 +155,75 @@ impl Instance {
             size,
             data1,
             data2,
+            data3: [0; 4],
+            clip: None,
+            depth: 0.0,
+            stencil: 0,
+            mask: None,
         }
     }
 
+    /// Whether any part of the instance overlaps `bounds` placed at the origin and its clip
+    /// rect, as drawn: turned and scaled by its transform and grown by its SDF edge. Negative
+    /// sizes extend the rect up/left of `position`, as the debug outlines do.
+    pub(crate) fn overlaps(&self, bounds: Size<i32>) -> bool {
+        let (x0, x1) = ordered(self.position.x, self.position.x + self.size.width);
+        let (y0, y1) = ordered(self.position.y, self.position.y + self.size.height);
+        if x0 == x1 || y0 == y1 {
+            return false;
+        }
+        let (x0, y0, x1, y1) = self.drawn_bounds(x0, y0, x1, y1);
+        let (mut bx0, mut by0, mut bx1, mut by1) = (0, 0, bounds.width, bounds.height);
+        if let Some((p, s)) = self.clip {
+            (bx0, by0) = (bx0.max(p.x), by0.max(p.y));
+            (bx1, by1) = (bx1.min(p.x + s.width), by1.min(p.y + s.height));
+        }
+        x0 < bx1 && y0 < by1 && x1 > bx0 && y1 > by0
+    }
+
+    /// The box around the rect from `(x0, y0)` to `(x1, y1)` once the shaders have turned and
+    /// scaled it around its center and spread an SDF edge past it.
+    fn drawn_bounds(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> (i32, i32, i32, i32) {
+        let (rotation, scale) = self.own_transform();
+        let (mut hw, mut hh) = ((x1 - x0) as f32 * 0.5, (y1 - y0) as f32 * 0.5);
+        if rotation != 0.0 || scale != 1.0 {
+            let (sin, cos) = rotation.sin_cos();
+            let scale = scale.abs();
+            (hw, hh) = (
+                (hw * cos.abs() + hh * sin.abs()) * scale,
+                (hw * sin.abs() + hh * cos.abs()) * scale,
+            );
+        }
+        if self.kind == PipelineKey::Sdf {
+            // A grown or blurred glyph edge reaches past the rect by up to the sum of both.
+            let grow = f32::from_bits(self.data3[0]).max(0.0);
+            let blur = f32::from_bits(self.data3[1]).max(0.0);
+            (hw, hh) = (hw + grow + blur, hh + grow + blur);
+        }
+        let (cx, cy) = ((x0 + x1) as f32 * 0.5, (y0 + y1) as f32 * 0.5);
+        (
+            (cx - hw).floor() as i32,
+            (cy - hh).floor() as i32,
+            (cx + hw).ceil() as i32,
+            (cy + hh).ceil() as i32,
+        )
+    }
+
+    /// Restricts drawing to the rect at `position` with `size`, on top of any clip the instance
+    /// already has.
+    pub fn clip(&mut self, position: Position<i32>, size: Size<i32>) {
+        let (mut x0, mut y0) = (position.x, position.y);
+        let (mut x1, mut y1) = (x0 + size.width.max(0), y0 + size.height.max(0));
+        if let Some((p, s)) = self.clip {
+            (x0, y0) = (x0.max(p.x), y0.max(p.y));
+            (x1, y1) = (x1.min(p.x + s.width), y1.min(p.y + s.height));
+        }
+        self.clip = Some((
+            Position::new(x0, y0),
+            Size::new((x1 - x0).max(0), (y1 - y0).max(0)),
+        ));
+    }
+
     pub fn ui(position: Position<i32>, size: Size<i32>, color: Color) -> Self {
         Self {
             kind: PipelineKey::Ui,
@@ -115,6 +231,184 @@ impl Instance {
             size,
             data1: [color.0, 0, 0, 0],
             data2: [0, 0, 0, 0],
+            data3: [0; 4],
+            clip: None,
+            depth: 0.0,
+            stencil: 0,
+            mask: None,
+        }
+    }
+
+    /// A glyph drawn from the distance field `handle` in `color`, staying sharp at any size and
+    /// under [`Instance::transform`] scaling. Drawn by `PipelineKey::Sdf`.
+    pub fn sdf(
+        position: Position<i32>,
+        size: Size<i32>,
+        color: Color,
+        handle: TextureHandle,
+    ) -> Self {
+        Self {
+            kind: PipelineKey::Sdf,
+            ..Self::ui_tex(position, size, color, handle)
+        }
+    }
+
+    /// Grows a [`Instance::sdf`] glyph by `grow` pixels and softens its edge over `blur`
+    /// pixels, for outlines and shadows drawn behind text. Both are limited by how far the
+    /// distance field reaches, a sixth of the font size.
+    pub fn sdf_edge(mut self, grow: f32, blur: f32) -> Self {
+        self.data3 = [grow.to_bits(), blur.max(0.0).to_bits(), 0, 0];
+        self
+    }
+
+    /// Puts the instance at `depth`, from 0 (the default, in front) to 1 (at the back). With
+    /// the depth buffer on, it's hidden behind whatever custom pipelines wrote nearer to the
+    /// front, whatever order they were drawn in. See [`Engine::set_depth_buffer`].
+    ///
+    /// [`Engine::set_depth_buffer`]: crate::graphics::Engine::set_depth_buffer
+    pub fn depth(mut self, depth: f32) -> Self {
+        self.depth = depth.clamp(0.0, 1.0);
+        self
+    }
+
+    /// A solid triangle with corners in window pixels, drawn by `PipelineKey::Path`.
+    pub fn triangle(a: Position<f32>, b: Position<f32>, c: Position<f32>, color: Color) -> Self {
+        let x0 = a.x.min(b.x).min(c.x).floor() as i32;
+        let y0 = a.y.min(b.y).min(c.y).floor() as i32;
+        let x1 = a.x.max(b.x).max(c.x).ceil() as i32;
+        let y1 = a.y.max(b.y).max(c.y).ceil() as i32;
+        Self {
+            kind: PipelineKey::Path,
+            position: Position::new(x0, y0),
+            size: Size::new((x1 - x0).max(1), (y1 - y0).max(1)),
+            data1: [a.x.to_bits(), a.y.to_bits(), b.x.to_bits(), b.y.to_bits()],
+            data2: [c.x.to_bits(), c.y.to_bits(), color.0, 0],
+            data3: [0; 4],
+            clip: None,
+            depth: 0.0,
+            stencil: 0,
+            mask: None,
+        }
+    }
+
+    /// A soft shadow of the rect at `position` with `size`, blurred over about `blur` pixels
+    /// like a CSS box shadow. The instance covers the rect grown by the blur on each side;
+    /// round the shadowed rect with [`Instance::radii`].
+    pub fn shadow(position: Position<i32>, size: Size<i32>, blur: f32, color: Color) -> Self {
+        let blur = blur.max(0.0);
+        // Three standard deviations, with the deviation at half the blur radius.
+        let margin = (blur * 1.5).ceil() as i32;
+        Self {
+            kind: PipelineKey::Ui,
+            position: Position::new(position.x - margin, position.y - margin),
+            size: Size::new(size.width + 2 * margin, size.height + 2 * margin),
+            data1: [color.0, 0, 0, 0],
+            data2: [0, EFFECT_SHADOW, blur.to_bits(), (margin as f32).to_bits()],
+            data3: [0; 4],
+            clip: None,
+            depth: 0.0,
+            stencil: 0,
+            mask: None,
+        }
+    }
+
+    /// A rect filled from `start` to `end` along `angle` radians clockwise from the positive x
+    /// axis, so 0 runs left to right.
+    pub fn linear_gradient(
+        position: Position<i32>,
+        size: Size<i32>,
+        start: Color,
+        end: Color,
+        angle: f32,
+    ) -> Self {
+        Self {
+            kind: PipelineKey::Ui,
+            position,
+            size,
+            data1: [start.0, 0, 0, 0],
+            data2: [0, EFFECT_LINEAR, end.0, angle.to_bits()],
+            data3: [0; 4],
+            clip: None,
+            depth: 0.0,
+            stencil: 0,
+            mask: None,
+        }
+    }
+
+    /// A rect filled from `inner` at its center to `outer` at the middle of its edges.
+    pub fn radial_gradient(
+        position: Position<i32>,
+        size: Size<i32>,
+        inner: Color,
+        outer: Color,
+    ) -> Self {
+        Self {
+            kind: PipelineKey::Ui,
+            position,
+            size,
+            data1: [inner.0, 0, 0, 0],
+            data2: [0, EFFECT_RADIAL, outer.0, 0],
+            data3: [0; 4],
+            clip: None,
+            depth: 0.0,
+            stencil: 0,
+            mask: None,
+        }
+    }
+
+    /// Rounds the corners of a `PipelineKey::Ui` instance by `radius` pixels.
+    pub fn radius(self, radius: f32) -> Self {
+        self.radii(Vec4::splat(radius))
+    }
+
+    /// Rounds each corner of a `PipelineKey::Ui` instance on its own: top-left, top-right,
+    /// bottom-right and bottom-left.
+    pub fn radii(mut self, radii: Vec4<f32>) -> Self {
+        self.data3 = <[f32; 4]>::from(radii).map(|r| r.max(0.0).to_bits());
+        self
+    }
+
+    /// Fills the third data slot, which shaders get at location 4 like `data1` and `data2` at
+    /// 2 and 3. Built-in pipelines keep their own parameters there.
+    pub fn data3(mut self, data3: [u32; 4]) -> Self {
+        self.data3 = data3;
+        self
+    }
+
+    // Turns the instance into the outline of a rounded clip, see `PaintCtx::clipped_rounded`.
+    pub(crate) fn masking(mut self, mask: Mask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// A border `widths` pixels thick (left, top, right, bottom, at most 255) along the inside
+    /// of the rect at `position` with `size`. With `side` set to an index into `widths` only
+    /// that side is drawn, split from its neighbours diagonally at the corners, so each side
+    /// can get its own color. Round it with [`Instance::radii`].
+    pub fn border(
+        position: Position<i32>,
+        size: Size<i32>,
+        widths: Vec4<i32>,
+        color: Color,
+        side: Option<usize>,
+    ) -> Self {
+        let [l, t, r, b] = <[i32; 4]>::from(widths).map(|w| w.clamp(0, 255) as u8);
+        Self {
+            kind: PipelineKey::Ui,
+            position,
+            size,
+            data1: [color.0, u32::from_le_bytes([l, t, r, b]), 0, 0],
+            data2: [
+                0,
+                EFFECT_BORDER,
+                side.map_or(ALL_SIDES, |s| s.min(ALL_SIDES as usize) as u32),
+                0,
+            ],
+            data3: [0; 4],
+            clip: None,
+            depth: 0.0,
+            stencil: 0,
+            mask: None,
         }
     }
 
@@ -135,10 +429,134 @@ impl Instance {
                 handle.scale_packed,
                 handle.offset_packed,
             ],
+            data3: [0; 4],
+            clip: None,
+            depth: 0.0,
+            stencil: 0,
+            mask: None,
+        }
+    }
+
+    /// Moves the instance by `offset` pixels, including the corners of path triangles.
+    pub fn translate(&mut self, offset: Position<i32>) {
+        self.position += offset;
+        if let Some((p, _)) = &mut self.clip {
+            *p += offset;
+        }
+        if self.kind == PipelineKey::Path {
+            let (dx, dy) = (offset.x as f32, offset.y as f32);
+            let shift = |bits: &mut u32, by: f32| *bits = (f32::from_bits(*bits) + by).to_bits();
+            shift(&mut self.data1[0], dx);
+            shift(&mut self.data1[1], dy);
+            shift(&mut self.data1[2], dx);
+            shift(&mut self.data1[3], dy);
+            shift(&mut self.data2[0], dx);
+            shift(&mut self.data2[1], dy);
+        }
+    }
+
+    /// Rotates the instance by `rotation` radians clockwise and scales it by `scale` around
+    /// `pivot`, in window pixels. Path triangles are transformed exactly; other instances are
+    /// moved so their center follows and, for `PipelineKey::Ui` and `PipelineKey::Sdf`, turned
+    /// and scaled around it by the shader. Other pipelines keep their own orientation.
+    pub fn transform(&mut self, rotation: f32, scale: f32, pivot: Position<f32>) {
+        let (sin, cos) = rotation.sin_cos();
+        let map = |x: f32, y: f32| {
+            let (dx, dy) = ((x - pivot.x) * scale, (y - pivot.y) * scale);
+            (pivot.x + dx * cos - dy * sin, pivot.y + dx * sin + dy * cos)
+        };
+
+        // An axis-aligned clip can't turn with the content, so it grows to cover where it went.
+        if let Some((p, s)) = self.clip {
+            let corners = [
+                map(p.x as f32, p.y as f32),
+                map((p.x + s.width) as f32, p.y as f32),
+                map(p.x as f32, (p.y + s.height) as f32),
+                map((p.x + s.width) as f32, (p.y + s.height) as f32),
+            ];
+            let (xs, ys) = (corners.map(|c| c.0), corners.map(|c| c.1));
+            let min = |v: [f32; 4]| v.into_iter().fold(f32::INFINITY, f32::min);
+            let max = |v: [f32; 4]| v.into_iter().fold(f32::NEG_INFINITY, f32::max);
+            let (x0, y0) = (min(xs).floor() as i32, min(ys).floor() as i32);
+            let (x1, y1) = (max(xs).ceil() as i32, max(ys).ceil() as i32);
+            self.clip = Some((Position::new(x0, y0), Size::new(x1 - x0, y1 - y0)));
+        }
+
+        if self.kind == PipelineKey::Path {
+            let corner = |bits: &[u32]| map(f32::from_bits(bits[0]), f32::from_bits(bits[1]));
+            let (a, b, c) = (
+                corner(&self.data1[0..2]),
+                corner(&self.data1[2..4]),
+                corner(&self.data2[0..2]),
+            );
+            let color = Color(self.data2[2]);
+            *self = Self {
+                clip: self.clip,
+                depth: self.depth,
+                stencil: self.stencil,
+                ..Self::triangle(
+                    Position::new(a.0, a.1),
+                    Position::new(b.0, b.1),
+                    Position::new(c.0, c.1),
+                    color,
+                )
+            };
+            return;
+        }
+
+        let (w, h) = (self.size.width as f32, self.size.height as f32);
+        let (cx, cy) = map(
+            self.position.x as f32 + w / 2.0,
+            self.position.y as f32 + h / 2.0,
+        );
+        self.position = Position::new((cx - w / 2.0).round() as i32, (cy - h / 2.0).round() as i32);
+
+        if matches!(self.kind, PipelineKey::Ui | PipelineKey::Sdf) {
+            let (own_rotation, own_scale) = self.own_transform();
+            self.data1[2] = (own_rotation + rotation).to_bits();
+            // Stored off by one so zeroed data means no scaling.
+            self.data1[3] = (own_scale * scale - 1.0).to_bits();
+        }
+    }
+
+    /// Multiplies the instance's color, alpha included, by `opacity`. Colors are blended as
+    /// premultiplied, so this fades the instance out evenly.
+    pub fn fade(&mut self, opacity: f32) {
+        let opacity = opacity.clamp(0.0, 1.0);
+        let fade = |color: &mut u32| {
+            let [r, g, b, a] = color
+                .to_le_bytes()
+                .map(|c| (c as f32 * opacity).round() as u8);
+            *color = u32::from_le_bytes([r, g, b, a]);
+        };
+        match self.kind {
+            PipelineKey::Ui => {
+                fade(&mut self.data1[0]);
+                if self.data2[0] == 0 && matches!(self.data2[1], EFFECT_LINEAR | EFFECT_RADIAL) {
+                    fade(&mut self.data2[2]);
+                }
+            }
+            PipelineKey::Sdf => fade(&mut self.data1[0]),
+            PipelineKey::Path => fade(&mut self.data2[2]),
+            PipelineKey::Other(_) => {}
+        }
+    }
+
+    fn own_transform(&self) -> (f32, f32) {
+        match self.kind {
+            PipelineKey::Ui | PipelineKey::Sdf => (
+                f32::from_bits(self.data1[2]),
+                1.0 + f32::from_bits(self.data1[3]),
+            ),
+            _ => (0.0, 1.0),
         }
     }
 
     pub(crate) fn to_primitive(&self) -> Primitive {
-        Primitive::new(self.position, self.size, self.data1, self.data2)
+        Primitive {
+            data3: self.data3,
+            depth: self.depth,
+            ..Primitive::new(self.position, self.size, self.data1, self.data2)
+        }
     }
 }
@@ -0,0 +1,66 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::mpsc,
+};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+
+/// Watches WGSL files for changes. Their directories are watched rather than the files, since
+/// editors often save by replacing a file instead of writing to it.
+pub(crate) struct ShaderWatcher {
+    watcher: notify::RecommendedWatcher,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+    files: HashSet<PathBuf>,
+}
+
+impl ShaderWatcher {
+    pub(crate) fn new() -> notify::Result<Self> {
+        let (tx, events) = mpsc::channel();
+        let watcher = notify::recommended_watcher(move |event| {
+            let _ = tx.send(event);
+        })?;
+        Ok(Self {
+            watcher,
+            events,
+            files: HashSet::new(),
+        })
+    }
+
+    pub(crate) fn watch(&mut self, path: &Path) -> notify::Result<()> {
+        let path = path.canonicalize()?;
+        if let Some(dir) = path.parent() {
+            self.watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+        self.files.insert(path);
+        Ok(())
+    }
+
+    /// Watched files written to since the last call.
+    pub(crate) fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for event in self.events.try_iter().flatten() {
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                continue;
+            }
+            for path in event.paths {
+                if self.files.contains(&path) && !changed.contains(&path) {
+                    changed.push(path);
+                }
+            }
+        }
+        changed
+    }
+}
+
+/// Compiles `source` on its own, so a broken shader can be reported before any pipeline is
+/// rebuilt with it.
+pub(crate) fn validate(source: &str) -> Result<(), String> {
+    use wgpu::naga::{front::wgsl, valid};
+
+    let module = wgsl::parse_str(source).map_err(|e| e.emit_to_string(source))?;
+    valid::Validator::new(valid::ValidationFlags::all(), valid::Capabilities::all())
+        .validate(&module)
+        .map_err(|e| e.emit_to_string(source))?;
+    Ok(())
+}
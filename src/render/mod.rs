@@ -1,8 +1,34 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "hot_reload")]
+pub(crate) mod hot_reload;
 pub mod pipeline;
+pub(crate) mod profiler;
 pub(crate) mod renderer;
+#[cfg(feature = "svg")]
+pub mod svg;
 pub mod text;
 pub mod texture;
 
+pub use profiler::FrameProfile;
+
+/// Source of the WGSL shader at `path` for a pipeline to build with. With the `hot_reload`
+/// feature it's read from disk, so edits show up once pipelines reload, see
+/// `Engine::watch_shader`. Otherwise, or if the file can't be read, it's `embedded`, usually
+/// `include_str!` of the same file.
+pub fn shader_source(
+    path: impl AsRef<std::path::Path>,
+    embedded: &'static str,
+) -> Cow<'static, str> {
+    #[cfg(feature = "hot_reload")]
+    if let Ok(source) = std::fs::read_to_string(path) {
+        return Cow::Owned(source);
+    }
+    #[cfg(not(feature = "hot_reload"))]
+    let _ = path;
+    Cow::Borrowed(embedded)
+}
+
 pub type PipelineFactoryFn = fn(
     &crate::graphics::Gpu,
     &wgpu::TextureFormat,
@@ -10,3 +36,5 @@ pub type PipelineFactoryFn = fn(
     &wgpu::BindGroupLayout,
     &[wgpu::PushConstantRange],
 ) -> Box<dyn pipeline::Pipeline>;
+
+pub type ComputeFactoryFn = fn(&crate::graphics::Gpu) -> Box<dyn pipeline::ComputePipeline>;
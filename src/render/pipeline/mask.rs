@@ -0,0 +1,95 @@
+use crate::{
+    graphics::{Globals, Gpu},
+    primitive::Mask,
+};
+use wgpu::RenderPipeline;
+
+/// Bind group index of `Globals` when they are delivered through the uniform fallback.
+const GLOBALS_GROUP: u32 = 1;
+
+/// Draws the rounded outlines of clips into the stencil buffer instead of the color target:
+/// a pushed mask raises the stencil value under it by one and a popped one lowers it again.
+pub(crate) struct MaskPipeline {
+    push: RenderPipeline,
+    pop: RenderPipeline,
+}
+
+impl MaskPipeline {
+    pub(crate) fn new(
+        gpu: &Gpu,
+        surface_format: &wgpu::TextureFormat,
+        buffers: &[wgpu::VertexBufferLayout],
+        texture_bgl: &wgpu::BindGroupLayout,
+        push_constant_ranges: &[wgpu::PushConstantRange],
+    ) -> Option<Self> {
+        gpu.stencil_clipping().then(|| {
+            let source = include_str!("../../../shaders/ui_shader.wgsl");
+            let shader_module = gpu
+                .device
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some("Clip Mask"),
+                    source: wgpu::ShaderSource::Wgsl(gpu.globals.wgsl(source, GLOBALS_GROUP)),
+                });
+
+            let mut bind_group_layouts = vec![texture_bgl];
+            bind_group_layouts.extend(gpu.globals.layout());
+            let layout = gpu
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Clip Mask"),
+                    push_constant_ranges,
+                    bind_group_layouts: &bind_group_layouts,
+                });
+
+            let create = |op| {
+                gpu.device
+                    .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                        label: Some("Clip Mask"),
+                        layout: Some(&layout),
+                        vertex: wgpu::VertexState {
+                            module: &shader_module,
+                            entry_point: Some("vs_main"),
+                            buffers,
+                            compilation_options: wgpu::PipelineCompilationOptions::default(),
+                        },
+                        fragment: Some(wgpu::FragmentState {
+                            module: &shader_module,
+                            entry_point: Some("fs_mask"),
+                            targets: &[Some(wgpu::ColorTargetState {
+                                format: *surface_format,
+                                blend: None,
+                                write_mask: wgpu::ColorWrites::empty(),
+                            })],
+                            compilation_options: wgpu::PipelineCompilationOptions::default(),
+                        }),
+                        primitive: wgpu::PrimitiveState::default(),
+                        depth_stencil: gpu.mask_stencil(op),
+                        multisample: gpu.multisample(),
+                        multiview: None,
+                        cache: None,
+                    })
+            };
+
+            Self {
+                push: create(wgpu::StencilOperation::IncrementClamp),
+                pop: create(wgpu::StencilOperation::DecrementClamp),
+            }
+        })
+    }
+
+    pub(crate) fn apply(
+        &self,
+        gpu: &Gpu,
+        mask: Mask,
+        globals: &Globals,
+        texture_bindgroup: &wgpu::BindGroup,
+        render_pass: &mut wgpu::RenderPass<'_>,
+    ) {
+        render_pass.set_bind_group(0, texture_bindgroup, &[]);
+        render_pass.set_pipeline(match mask {
+            Mask::Push => &self.push,
+            Mask::Pop => &self.pop,
+        });
+        gpu.globals.apply(render_pass, GLOBALS_GROUP, globals);
+    }
+}
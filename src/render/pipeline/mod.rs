@@ -1,13 +1,30 @@
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap};
 
-use crate::graphics::{Globals, Gpu};
+use crate::{
+    Error, Result,
+    graphics::{Globals, Gpu},
+    primitive::Mask,
+};
 
+mod mask;
 mod ui;
 
-#[derive(Eq, Hash, PartialEq, Debug)]
+#[derive(Eq, Hash, PartialEq, Debug, Clone)]
 pub enum PipelineKey {
     Ui,
-    Other(&'static str),
+    /// Solid triangles, see `Instance::triangle`.
+    Path,
+    /// Distance field glyphs, see `Instance::sdf`.
+    Sdf,
+    /// A custom pipeline by name. Names only known at runtime, e.g. from a config file, are
+    /// owned; static ones are borrowed, see [`PipelineKey::other`].
+    Other(Cow<'static, str>),
+}
+
+impl PipelineKey {
+    pub fn other(name: impl Into<Cow<'static, str>>) -> Self {
+        Self::Other(name.into())
+    }
 }
 
 pub trait Pipeline {
@@ -32,20 +49,50 @@ pub trait Pipeline {
 
     fn apply_pipeline(
         &self,
+        gpu: &Gpu,
         globals: &Globals,
         texture_bindgroup: &wgpu::BindGroup,
         render_pass: &mut wgpu::RenderPass<'_>,
     );
+
+    /// Bind groups the pipeline reads besides the shared ones, as group index and layout.
+    /// Groups for them are made with `Engine::set_bind_group` and bound after
+    /// [`Pipeline::apply_pipeline`]. Layouts should keep their shape across reloads, since
+    /// groups made before are bound to the reloaded pipeline.
+    fn bind_group_layouts(&self) -> Vec<(u32, &wgpu::BindGroupLayout)> {
+        Vec::new()
+    }
+}
+
+/// Compute work run on the engine's device before each frame is drawn, e.g. stepping a
+/// simulation whose buffers a render pipeline reads through its own bind groups.
+pub trait ComputePipeline {
+    fn new(gpu: &Gpu) -> Self
+    where
+        Self: Sized;
+
+    /// Records this frame's work into `pass`. It runs before every frame of every target,
+    /// with that target's `globals`.
+    fn dispatch(&mut self, gpu: &Gpu, globals: &Globals, pass: &mut wgpu::ComputePass<'_>);
 }
 
 pub(crate) struct PipelineRegistry {
     pipelines: HashMap<PipelineKey, Box<dyn Pipeline>>,
+    // Only there while rounded clips go through the stencil buffer.
+    masks: Option<mask::MaskPipeline>,
+    // Groups made for the pipelines' own layouts, by group index.
+    bind_groups: HashMap<PipelineKey, Vec<(u32, wgpu::BindGroup)>>,
+    // Dispatched in the order they were registered.
+    computes: Vec<(&'static str, Box<dyn ComputePipeline>)>,
 }
 
 impl PipelineRegistry {
     pub(crate) fn new() -> Self {
         Self {
             pipelines: HashMap::new(),
+            masks: None,
+            bind_groups: HashMap::new(),
+            computes: Vec::new(),
         }
     }
 
@@ -67,18 +114,136 @@ impl PipelineRegistry {
                 push_constant_ranges,
             )),
         );
+        self.register_pipeline(
+            PipelineKey::Path,
+            Box::new(ui::UiPipeline::with_shader(
+                gpu,
+                surface_format,
+                buffers,
+                texture_bgl,
+                push_constant_ranges,
+                "Path",
+                include_str!("../../../shaders/path_shader.wgsl"),
+            )),
+        );
+        self.register_pipeline(
+            PipelineKey::Sdf,
+            Box::new(ui::UiPipeline::with_shader(
+                gpu,
+                surface_format,
+                buffers,
+                texture_bgl,
+                push_constant_ranges,
+                "Sdf",
+                include_str!("../../../shaders/sdf_shader.wgsl"),
+            )),
+        );
+        self.masks = mask::MaskPipeline::new(
+            gpu,
+            surface_format,
+            buffers,
+            texture_bgl,
+            push_constant_ranges,
+        );
     }
 
     pub(crate) fn has_default_pipelines(&self) -> bool {
-        [PipelineKey::Ui]
+        [PipelineKey::Ui, PipelineKey::Path, PipelineKey::Sdf]
             .iter()
             .all(|k| self.pipelines.contains_key(k))
     }
 
     pub fn register_pipeline(&mut self, key: PipelineKey, pipeline: Box<dyn Pipeline>) {
+        self.bind_groups.remove(&key);
         self.pipelines.insert(key, pipeline);
     }
 
+    /// Adds compute work under `name`, replacing what was registered under it in place.
+    pub(crate) fn register_compute(
+        &mut self,
+        name: &'static str,
+        pipeline: Box<dyn ComputePipeline>,
+    ) {
+        match self.computes.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, p)) => *p = pipeline,
+            None => self.computes.push((name, pipeline)),
+        }
+    }
+
+    pub(crate) fn remove_compute(&mut self, name: &str) -> bool {
+        let len = self.computes.len();
+        self.computes.retain(|(n, _)| *n != name);
+        self.computes.len() != len
+    }
+
+    /// Submits the compute work ahead of a frame drawn with `globals`, so the queue finishes it
+    /// before drawing.
+    /// Returns whether there was anything to dispatch, and so whether `timestamp_writes` were
+    /// written.
+    pub(crate) fn dispatch_compute(
+        &mut self,
+        gpu: &Gpu,
+        globals: &Globals,
+        timestamp_writes: Option<wgpu::ComputePassTimestampWrites>,
+    ) -> bool {
+        if self.computes.is_empty() {
+            return false;
+        }
+        let mut encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Compute Encoder"),
+            });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Compute Pass"),
+                timestamp_writes,
+            });
+            for (_, pipeline) in self.computes.iter_mut() {
+                pipeline.dispatch(gpu, globals, &mut pass);
+            }
+        }
+        gpu.queue.submit(std::iter::once(encoder.finish()));
+        true
+    }
+
+    /// Makes group `index` of the pipeline at `key` from `entries`, replacing the one it had.
+    /// Returns false if there's no such pipeline or it declares no such group.
+    pub(crate) fn set_bind_group(
+        &mut self,
+        gpu: &Gpu,
+        key: PipelineKey,
+        index: u32,
+        entries: &[wgpu::BindGroupEntry],
+    ) -> bool {
+        let Some(pipeline) = self.pipelines.get(&key) else {
+            return false;
+        };
+        let layouts = pipeline.bind_group_layouts();
+        let Some((_, layout)) = layouts.iter().find(|(i, _)| *i == index) else {
+            return false;
+        };
+        let group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Pipeline Bind Group"),
+            layout,
+            entries,
+        });
+
+        let groups = self.bind_groups.entry(key).or_default();
+        groups.retain(|(i, _)| *i != index);
+        groups.push((index, group));
+        true
+    }
+
+    pub(crate) fn remove_bind_group(&mut self, key: &PipelineKey, index: u32) -> bool {
+        let Some(groups) = self.bind_groups.get_mut(key) else {
+            return false;
+        };
+        let len = groups.len();
+        groups.retain(|(i, _)| *i != index);
+        groups.len() != len
+    }
+
     pub(crate) fn reload(
         &mut self,
         gpu: &Gpu,
@@ -96,19 +261,47 @@ impl PipelineRegistry {
                 push_constant_ranges,
             );
         }
+        self.masks = mask::MaskPipeline::new(
+            gpu,
+            surface_format,
+            buffers,
+            texture_bgl,
+            push_constant_ranges,
+        );
+    }
+
+    /// Sets up drawing a clip outline into the stencil buffer, returning false if rounded
+    /// clips don't use one.
+    pub(crate) fn apply_mask(
+        &self,
+        gpu: &Gpu,
+        mask: Mask,
+        globals: &Globals,
+        texture_bindgroup: &wgpu::BindGroup,
+        pass: &mut wgpu::RenderPass<'_>,
+    ) -> bool {
+        let Some(masks) = &self.masks else {
+            return false;
+        };
+        masks.apply(gpu, mask, globals, texture_bindgroup, pass);
+        true
     }
 
     pub(crate) fn apply_pipeline(
         &self,
+        gpu: &Gpu,
         key: &PipelineKey,
         globals: &Globals,
         texture_bindgroup: &wgpu::BindGroup,
         pass: &mut wgpu::RenderPass<'_>,
-    ) {
+    ) -> Result<()> {
         self.pipelines
             .get(key)
-            .expect("Pipeline not registered!")
-            .as_ref()
-            .apply_pipeline(globals, texture_bindgroup, pass);
+            .ok_or_else(|| Error::MissingPipeline(key.clone()))?
+            .apply_pipeline(gpu, globals, texture_bindgroup, pass);
+        for (index, group) in self.bind_groups.get(key).into_iter().flatten() {
+            pass.set_bind_group(*index, group, &[]);
+        }
+        Ok(())
     }
 }
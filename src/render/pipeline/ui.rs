@@ -4,22 +4,33 @@ use crate::{
 };
 use wgpu::RenderPipeline;
 
+/// Bind group index of `Globals` when they are delivered through the uniform fallback.
+const GLOBALS_GROUP: u32 = 1;
+
 pub(super) struct UiPipeline {
     render_pipeline: Option<RenderPipeline>,
     layout: Option<wgpu::PipelineLayout>,
+    label: &'static str,
+    source: &'static str,
 }
 
-impl Pipeline for UiPipeline {
-    fn new(
+impl UiPipeline {
+    /// A pipeline over the shared quad and instance buffers drawn with another shader that
+    /// takes the same inputs and globals as the UI shader.
+    pub(super) fn with_shader(
         gpu: &Gpu,
         surface_format: &wgpu::TextureFormat,
         buffers: &[wgpu::VertexBufferLayout],
         texture_bgl: &wgpu::BindGroupLayout,
         push_constant_ranges: &[wgpu::PushConstantRange],
+        label: &'static str,
+        source: &'static str,
     ) -> Self {
         let mut pipeline = Self {
             render_pipeline: None,
             layout: None,
+            label,
+            source,
         };
         pipeline.reload(
             gpu,
@@ -31,6 +42,26 @@ impl Pipeline for UiPipeline {
 
         pipeline
     }
+}
+
+impl Pipeline for UiPipeline {
+    fn new(
+        gpu: &Gpu,
+        surface_format: &wgpu::TextureFormat,
+        buffers: &[wgpu::VertexBufferLayout],
+        texture_bgl: &wgpu::BindGroupLayout,
+        push_constant_ranges: &[wgpu::PushConstantRange],
+    ) -> Self {
+        Self::with_shader(
+            gpu,
+            surface_format,
+            buffers,
+            texture_bgl,
+            push_constant_ranges,
+            "UI",
+            include_str!("../../../shaders/ui_shader.wgsl"),
+        )
+    }
 
     fn reload(
         &mut self,
@@ -43,24 +74,25 @@ impl Pipeline for UiPipeline {
         let shader_module = gpu
             .device
             .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("UI Shader"),
-                source: wgpu::ShaderSource::Wgsl(
-                    include_str!("../../../shaders/ui_shader.wgsl").into(),
-                ),
+                label: Some(self.label),
+                source: wgpu::ShaderSource::Wgsl(gpu.globals.wgsl(self.source, GLOBALS_GROUP)),
             });
 
+        let mut bind_group_layouts = vec![texture_bgl];
+        bind_group_layouts.extend(gpu.globals.layout());
+
         let layout = gpu
             .device
             .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("UI Render Pipeline Layout"),
+                label: Some(self.label),
                 push_constant_ranges,
-                bind_group_layouts: &[texture_bgl],
+                bind_group_layouts: &bind_group_layouts,
             });
         self.layout = Some(layout);
 
         self.render_pipeline = Some(gpu.device.create_render_pipeline(
             &wgpu::RenderPipelineDescriptor {
-                label: Some("UI Render Pipeline"),
+                label: Some(self.label),
                 layout: self.layout.as_ref(),
                 vertex: wgpu::VertexState {
                     module: &shader_module,
@@ -90,8 +122,8 @@ impl Pipeline for UiPipeline {
                     compilation_options: wgpu::PipelineCompilationOptions::default(),
                 }),
                 primitive: wgpu::PrimitiveState::default(),
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState::default(),
+                depth_stencil: gpu.depth_stencil(),
+                multisample: gpu.multisample(),
                 multiview: None,
                 cache: None,
             },
@@ -100,16 +132,13 @@ impl Pipeline for UiPipeline {
 
     fn apply_pipeline(
         &self,
+        gpu: &Gpu,
         globals: &Globals,
         texture_bindgroup: &wgpu::BindGroup,
         render_pass: &mut wgpu::RenderPass<'_>,
     ) {
         render_pass.set_bind_group(0, texture_bindgroup, &[]);
         render_pass.set_pipeline(self.render_pipeline.as_ref().unwrap());
-        render_pass.set_push_constants(
-            wgpu::ShaderStages::VERTEX_FRAGMENT,
-            0,
-            bytemuck::bytes_of(globals),
-        );
+        gpu.globals.apply(render_pass, GLOBALS_GROUP, globals);
     }
 }
@@ -0,0 +1,209 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use crate::{graphics::Gpu, render::pipeline::PipelineKey};
+
+/// Where a frame's GPU time went, measured with timestamp queries. See
+/// `Engine::set_profiling`.
+#[derive(Debug, Clone, Default)]
+pub struct FrameProfile {
+    /// Compute work registered with `Engine::register_compute`, if any ran.
+    pub compute: Option<Duration>,
+    /// The pass drawing the UI and custom pipelines.
+    pub render: Duration,
+    /// Each pipeline's share of the render pass, in the order they first drew. Empty where the
+    /// adapter can't time work inside a pass.
+    pub pipelines: Vec<(PipelineKey, Duration)>,
+}
+
+// Query indices: a begin and end pair for each pass, then one at the start of every timed draw
+// and one after the last.
+const COMPUTE: u32 = 0;
+const RENDER: u32 = 2;
+const DRAWS: u32 = 4;
+const MAX_TIMED_DRAWS: u32 = 256;
+const QUERY_COUNT: u32 = DRAWS + MAX_TIMED_DRAWS + 1;
+const QUERY_SIZE: u64 = std::mem::size_of::<u64>() as u64;
+
+// What a frame's queries measured.
+struct Recorded {
+    compute: bool,
+    draws: Vec<PipelineKey>,
+}
+
+/// Times frames on the GPU. Results are read back without waiting, so they arrive a frame or
+/// two late, and frames recorded while one is still being read back aren't timed.
+pub(crate) struct Profiler {
+    queries: wgpu::QuerySet,
+    resolve: wgpu::Buffer,
+    readback: wgpu::Buffer,
+    inside_passes: bool,
+    // Nanoseconds per timestamp tick.
+    period: f32,
+    recording: Option<Recorded>,
+    pending: Option<(Recorded, Arc<AtomicBool>)>,
+    pub(crate) last: Option<FrameProfile>,
+}
+
+impl Profiler {
+    /// `None` if the device can't write timestamps.
+    pub(crate) fn new(gpu: &Gpu) -> Option<Self> {
+        let features = gpu.device.features();
+        if !features.contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+        let queries = gpu.device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Frame Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: QUERY_COUNT,
+        });
+        let size = QUERY_COUNT as u64 * QUERY_SIZE;
+        let resolve = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Resolve Buffer"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Readback Buffer"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Some(Self {
+            queries,
+            resolve,
+            readback,
+            inside_passes: features.contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES),
+            period: gpu.queue.get_timestamp_period(),
+            recording: None,
+            pending: None,
+            last: None,
+        })
+    }
+
+    /// Picks up a finished readback, and starts timing the next frame unless one is still
+    /// being read back.
+    pub(crate) fn begin_frame(&mut self, gpu: &Gpu) {
+        let _ = gpu.device.poll(wgpu::PollType::Poll);
+        if let Some((_, mapped)) = &self.pending
+            && mapped.load(Ordering::Acquire)
+            && let Some((recorded, _)) = self.pending.take()
+        {
+            let ticks: Vec<u64> =
+                bytemuck::cast_slice(&self.readback.slice(..).get_mapped_range()).to_vec();
+            self.readback.unmap();
+            self.last = Some(self.profile(&recorded, &ticks));
+        }
+        self.recording = self.pending.is_none().then_some(Recorded {
+            compute: false,
+            draws: Vec::new(),
+        });
+    }
+
+    fn profile(&self, recorded: &Recorded, ticks: &[u64]) -> FrameProfile {
+        let span = |begin: u32| {
+            let ticks = ticks[begin as usize + 1].saturating_sub(ticks[begin as usize]);
+            Duration::from_nanos((ticks as f64 * self.period as f64) as u64)
+        };
+        let mut pipelines: Vec<(PipelineKey, Duration)> = Vec::new();
+        for (i, key) in recorded.draws.iter().enumerate() {
+            let time = span(DRAWS + i as u32);
+            match pipelines.iter_mut().find(|(k, _)| k == key) {
+                Some((_, total)) => *total += time,
+                None => pipelines.push((key.clone(), time)),
+            }
+        }
+        FrameProfile {
+            compute: recorded.compute.then(|| span(COMPUTE)),
+            render: span(RENDER),
+            pipelines,
+        }
+    }
+
+    pub(crate) fn compute_timestamps(&self) -> Option<wgpu::ComputePassTimestampWrites<'_>> {
+        self.recording
+            .as_ref()
+            .map(|_| wgpu::ComputePassTimestampWrites {
+                query_set: &self.queries,
+                beginning_of_pass_write_index: Some(COMPUTE),
+                end_of_pass_write_index: Some(COMPUTE + 1),
+            })
+    }
+
+    /// Marks the compute pass as having run this frame.
+    pub(crate) fn computed(&mut self) {
+        if let Some(recording) = &mut self.recording {
+            recording.compute = true;
+        }
+    }
+
+    pub(crate) fn render_timestamps(&self) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        self.recording
+            .as_ref()
+            .map(|_| wgpu::RenderPassTimestampWrites {
+                query_set: &self.queries,
+                beginning_of_pass_write_index: Some(RENDER),
+                end_of_pass_write_index: Some(RENDER + 1),
+            })
+    }
+
+    /// Starts timing a draw with the pipeline at `key`, ending the one before.
+    pub(crate) fn time_draw(&mut self, pass: &mut wgpu::RenderPass<'_>, key: &PipelineKey) {
+        let Some(recording) = &mut self.recording else {
+            return;
+        };
+        if self.inside_passes && (recording.draws.len() as u32) < MAX_TIMED_DRAWS {
+            pass.write_timestamp(&self.queries, DRAWS + recording.draws.len() as u32);
+            recording.draws.push(key.clone());
+        }
+    }
+
+    /// Ends the last timed draw.
+    pub(crate) fn end_draws(&mut self, pass: &mut wgpu::RenderPass<'_>) {
+        if let Some(recording) = &self.recording
+            && !recording.draws.is_empty()
+        {
+            pass.write_timestamp(&self.queries, DRAWS + recording.draws.len() as u32);
+        }
+    }
+
+    /// Copies this frame's timestamps where they can be read back once `encoder` is submitted.
+    pub(crate) fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        let Some(recording) = &self.recording else {
+            return;
+        };
+        let used = match recording.draws.len() as u32 {
+            0 => DRAWS,
+            n => DRAWS + n + 1,
+        };
+        encoder.resolve_query_set(&self.queries, 0..used, &self.resolve, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve,
+            0,
+            &self.readback,
+            0,
+            used as u64 * QUERY_SIZE,
+        );
+    }
+
+    /// Starts reading back the frame once its commands are submitted.
+    pub(crate) fn end_frame(&mut self) {
+        let Some(recording) = self.recording.take() else {
+            return;
+        };
+        let mapped = Arc::new(AtomicBool::new(false));
+        let flag = mapped.clone();
+        self.readback
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                flag.store(result.is_ok(), Ordering::Release);
+            });
+        self.pending = Some((recording, mapped));
+    }
+}
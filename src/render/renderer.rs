@@ -1,18 +1,35 @@
-use wgpu::util::DeviceExt;
+use wgpu::util::{DeviceExt, StagingBelt};
 
 use crate::{
+    Error, Result,
     consts::DEFAULT_MAX_INSTANCES,
-    graphics::{Globals, Gpu, Target},
-    primitive::{Instance, Primitive, QUAD_INDICES, QUAD_VERTICES},
+    graphics::{Globals, Gpu, RenderStats, RgbaImage, Target},
+    model::{Position, Size},
+    primitive::{Instance, Mask, Primitive, QUAD_INDICES, QUAD_VERTICES},
     render::{
         pipeline::{PipelineKey, PipelineRegistry},
+        profiler::Profiler,
         text::TextSystem,
         texture::TextureRegistry,
     },
 };
 
+const UPLOAD_CHUNK_SIZE: u64 = std::mem::size_of::<Primitive>() as u64 * 1024;
+
+fn create_instance_buffer(device: &wgpu::Device, capacity: u64) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::wgt::BufferDescriptor {
+        label: Some("Pipeline Instance Buffer"),
+        size: std::mem::size_of::<Primitive>() as u64 * capacity,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
 struct DrawCommand<'a> {
     pipe: &'a PipelineKey,
+    clip: Option<(Position<i32>, Size<i32>)>,
+    stencil: u8,
+    mask: Option<Mask>,
     base: u32,
     amount: u32,
 }
@@ -22,9 +39,17 @@ pub(crate) struct Renderer {
     index_buffer: wgpu::Buffer,
     number_of_indices: u32,
     instance_buffer: wgpu::Buffer,
+    instance_capacity: u64,
+    // Mirrors what the instance buffer currently holds so only changed ranges are uploaded.
+    uploaded: Vec<Primitive>,
+    belt: StagingBelt,
+    pub(crate) stats: RenderStats,
+    pub(crate) profiler: Option<Profiler>,
 
     pub(crate) textures: TextureRegistry,
     pub(crate) text: TextSystem,
+    #[cfg(feature = "svg")]
+    pub(crate) svg: crate::render::svg::SvgCache,
 }
 
 impl Renderer {
@@ -42,42 +67,125 @@ impl Renderer {
         });
         let number_of_indices = QUAD_INDICES.len() as u32;
 
-        let instance_buffer = device.create_buffer(&wgpu::wgt::BufferDescriptor {
-            label: Some("Pipeline Instance Buffer"),
-            size: std::mem::size_of::<Primitive>() as u64 * DEFAULT_MAX_INSTANCES,
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+        let instance_buffer = create_instance_buffer(device, DEFAULT_MAX_INSTANCES);
 
         Self {
             vertex_buffer,
             index_buffer,
             number_of_indices,
             instance_buffer,
+            instance_capacity: DEFAULT_MAX_INSTANCES,
+            uploaded: Vec::new(),
+            belt: StagingBelt::new(UPLOAD_CHUNK_SIZE),
+            stats: RenderStats {
+                instance_capacity: DEFAULT_MAX_INSTANCES as usize,
+                ..Default::default()
+            },
+            profiler: None,
             textures: TextureRegistry::new(device),
             text: TextSystem::default(),
+            #[cfg(feature = "svg")]
+            svg: Default::default(),
+        }
+    }
+
+    /// Copies the range of `primitives` that differs from the last upload into the instance
+    /// buffer through the staging belt.
+    fn upload_instances(
+        &mut self,
+        gpu: &Gpu,
+        encoder: &mut wgpu::CommandEncoder,
+        primitives: &[Primitive],
+    ) {
+        // A buffer too small for the frame is replaced by one twice as large as needed, and
+        // filled from scratch.
+        if primitives.len() as u64 > self.instance_capacity {
+            self.instance_capacity = (primitives.len() as u64 * 2).next_power_of_two();
+            self.instance_buffer = create_instance_buffer(&gpu.device, self.instance_capacity);
+            self.uploaded.clear();
+        }
+
+        let same = |a: &Primitive, b: &Primitive| bytemuck::bytes_of(a) == bytemuck::bytes_of(b);
+
+        let common = self.uploaded.len().min(primitives.len());
+        let first = (0..common)
+            .find(|&i| !same(&self.uploaded[i], &primitives[i]))
+            .unwrap_or(common);
+        let end = if primitives.len() > common {
+            primitives.len()
+        } else {
+            (first..common)
+                .rev()
+                .find(|&i| !same(&self.uploaded[i], &primitives[i]))
+                .map_or(first, |i| i + 1)
+        };
+
+        self.uploaded.truncate(primitives.len());
+        if first == end {
+            return;
+        }
+
+        // Chunks keep large uploads from making the belt allocate oversized staging buffers.
+        let bytes: &[u8] = bytemuck::cast_slice(&primitives[first..end]);
+        let stride = std::mem::size_of::<Primitive>() as u64;
+        let mut offset = first as u64 * stride;
+        for chunk in bytes.chunks(UPLOAD_CHUNK_SIZE as usize) {
+            let size = wgpu::BufferSize::new(chunk.len() as u64).expect("non-empty chunk");
+            self.belt
+                .write_buffer(encoder, &self.instance_buffer, offset, size, &gpu.device)
+                .copy_from_slice(chunk);
+            offset += chunk.len() as u64;
         }
+
+        self.uploaded.truncate(first);
+        self.uploaded.extend_from_slice(&primitives[first..]);
     }
 
     pub fn render<'a, M>(
-        &self,
+        &mut self,
         gpu: &Gpu,
         target: &Target<'a, M>,
         pipeline_registry: &PipelineRegistry,
         globals: &Globals,
         instances: &[Instance],
-    ) -> Result<(), wgpu::SurfaceError> {
-        let output = match target.surface.get_current_texture() {
+    ) -> Result<()> {
+        let Some(surface) = &target.surface else {
+            let mut drawn = Ok(());
+            if let Some(texture) = target.offscreen() {
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                let mut encoder =
+                    gpu.device
+                        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                            label: Some("Headless Encoder"),
+                        });
+                drawn = self.encode(
+                    gpu,
+                    target,
+                    pipeline_registry,
+                    globals,
+                    instances,
+                    &view,
+                    &mut encoder,
+                );
+                gpu.queue.submit(std::iter::once(encoder.finish()));
+                self.belt.recall();
+                if let Some(profiler) = &mut self.profiler {
+                    profiler.end_frame();
+                }
+            }
+            return drawn;
+        };
+        let output = match surface.get_current_texture() {
             Ok(o) => o,
             Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
-                target.surface.configure(&gpu.device, &target.config);
-                target.surface.get_current_texture()?
+                surface.configure(&gpu.device, &target.config);
+                surface.get_current_texture()?
             }
             Err(wgpu::SurfaceError::Timeout) => return Ok(()),
-            Err(e) => return Err(e),
+            Err(e) => return Err(e.into()),
         };
 
-        let view = &output
+        let view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
@@ -86,58 +194,267 @@ impl Renderer {
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             });
+        let drawn = self.encode(
+            gpu,
+            target,
+            pipeline_registry,
+            globals,
+            instances,
+            &view,
+            &mut encoder,
+        );
+
+        gpu.queue.submit(std::iter::once(encoder.finish()));
+        self.belt.recall();
+        output.present();
+        if let Some(profiler) = &mut self.profiler {
+            profiler.end_frame();
+        }
+
+        drawn
+    }
+
+    /// Draws `instances` into a texture like the target's surface and reads it back.
+    pub fn capture<'a, M>(
+        &mut self,
+        gpu: &Gpu,
+        target: &Target<'a, M>,
+        pipeline_registry: &PipelineRegistry,
+        globals: &Globals,
+        instances: &[Instance],
+    ) -> Result<RgbaImage> {
+        let (width, height) = (target.config.width, target.config.height);
+        let format = target.config.format;
+        let texel = match format {
+            wgpu::TextureFormat::Rgba16Float => 8,
+            wgpu::TextureFormat::Rgba8Unorm
+            | wgpu::TextureFormat::Rgba8UnormSrgb
+            | wgpu::TextureFormat::Bgra8Unorm
+            | wgpu::TextureFormat::Bgra8UnormSrgb
+            | wgpu::TextureFormat::Rgb10a2Unorm => 4,
+            _ => return Err(Error::UnsupportedCapture(format)),
+        };
+        let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Capture Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
+        // Rows of a texture copy have to be aligned.
+        let row = width * texel;
+        let padded_row =
+            row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Capture Buffer"),
+            size: padded_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Capture Encoder"),
+            });
+        // Missing pipelines were reported when the frame was first rendered, and captures
+        // aren't profiled.
+        let profiler = self.profiler.take();
+        let _ = self.encode(
+            gpu,
+            target,
+            pipeline_registry,
+            globals,
+            instances,
+            &view,
+            &mut encoder,
+        );
+        self.profiler = profiler;
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        gpu.queue.submit(std::iter::once(encoder.finish()));
+        self.belt.recall();
+
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        let _ = gpu.device.poll(wgpu::PollType::Wait);
+
+        let mut pixels = Vec::with_capacity((width * 4 * height) as usize);
+        {
+            let data = slice.get_mapped_range();
+            for line in data.chunks(padded_row as usize) {
+                let line = &line[..row as usize];
+                match format {
+                    wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+                        for p in line.chunks_exact(4) {
+                            pixels.extend_from_slice(&[p[2], p[1], p[0], p[3]]);
+                        }
+                    }
+                    wgpu::TextureFormat::Rgb10a2Unorm => {
+                        for p in line.chunks_exact(4) {
+                            let bits = u32::from_le_bytes([p[0], p[1], p[2], p[3]]);
+                            let channel = |shift: u32| ((bits >> shift) & 0x3ff) as f32 / 1023.0;
+                            pixels.extend_from_slice(&[
+                                to_u8(channel(0)),
+                                to_u8(channel(10)),
+                                to_u8(channel(20)),
+                                to_u8((bits >> 30) as f32 / 3.0),
+                            ]);
+                        }
+                    }
+                    // Float targets hold linear light, encoded to sRGB like the 8-bit ones.
+                    wgpu::TextureFormat::Rgba16Float => {
+                        for p in line.chunks_exact(8) {
+                            let channel =
+                                |i: usize| half_to_f32(u16::from_le_bytes([p[i], p[i + 1]]));
+                            pixels.extend_from_slice(&[
+                                to_u8(linear_to_srgb(channel(0))),
+                                to_u8(linear_to_srgb(channel(2))),
+                                to_u8(linear_to_srgb(channel(4))),
+                                to_u8(channel(6)),
+                            ]);
+                        }
+                    }
+                    _ => pixels.extend_from_slice(line),
+                }
+            }
+        }
+        buffer.unmap();
+
+        Ok(RgbaImage {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    // Records drawing `instances` into `view`, through the target's MSAA buffer if it has one.
+    // Instances of unregistered pipelines are skipped, and the first such pipeline reported.
+    #[allow(clippy::too_many_arguments)]
+    fn encode<'a, M>(
+        &mut self,
+        gpu: &Gpu,
+        target: &Target<'a, M>,
+        pipeline_registry: &PipelineRegistry,
+        globals: &Globals,
+        instances: &[Instance],
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+    ) -> Result<()> {
         let mut draw_commands = Vec::<DrawCommand>::new();
         let mut primitives = Vec::<Primitive>::with_capacity(instances.len());
 
-        let mut base = 0u32;
-        let mut current_key: Option<&PipelineKey> = None;
-        for (i, instance) in instances.iter().enumerate() {
+        // Anything entirely outside the surface can't produce fragments, so it never reaches
+        // the instance buffer.
+        let viewport = Size::new(target.config.width as i32, target.config.height as i32);
+        let visible = instances
+            .iter()
+            .filter(|instance| instance.overlaps(viewport));
+
+        // Instances are batched for as long as they're drawn the same way.
+        let mut current: Option<DrawCommand> = None;
+        for (i, instance) in visible.enumerate() {
             primitives.push(instance.to_primitive());
 
-            if current_key.is_none() {
-                current_key = Some(&instance.kind);
-                base = i as u32;
-            } else if let Some(key) = current_key
-                && key != &instance.kind
+            if let Some(c) = &current
+                && c.pipe == &instance.kind
+                && c.clip == instance.clip
+                && c.stencil == instance.stencil
+                && c.mask == instance.mask
             {
-                draw_commands.push(DrawCommand {
-                    pipe: key,
-                    base,
-                    amount: i as u32 - base,
-                });
-                current_key = Some(&instance.kind);
-                base = i as u32;
+                continue;
             }
-        }
-        if let Some(key) = current_key {
-            draw_commands.push(DrawCommand {
-                pipe: key,
-                base,
-                amount: instances.len() as u32 - base,
+            if let Some(mut c) = current.take() {
+                c.amount = i as u32 - c.base;
+                draw_commands.push(c);
+            }
+            current = Some(DrawCommand {
+                pipe: &instance.kind,
+                clip: instance.clip,
+                stencil: instance.stencil,
+                mask: instance.mask,
+                base: i as u32,
+                amount: 0,
             });
         }
+        if let Some(mut c) = current {
+            c.amount = primitives.len() as u32 - c.base;
+            draw_commands.push(c);
+        }
 
-        gpu.queue.write_buffer(
-            &self.instance_buffer,
-            0,
-            bytemuck::cast_slice(primitives.as_slice()),
-        );
+        gpu.globals.upload(&gpu.queue, globals);
+        self.textures.flush(gpu);
+        self.upload_instances(gpu, encoder, &primitives);
+        self.belt.finish();
+
+        self.stats = RenderStats {
+            instances: primitives.len(),
+            draw_calls: draw_commands.len(),
+            instance_capacity: self.instance_capacity as usize,
+            peak_instances: self.stats.peak_instances.max(primitives.len()),
+        };
 
+        let mut drawn = Ok(());
         {
             let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
-                        store: wgpu::StoreOp::Store,
+                // With MSAA the samples only need to live until they're resolved.
+                color_attachments: &[Some(match target.msaa_view() {
+                    Some(msaa) => wgpu::RenderPassColorAttachment {
+                        view: msaa,
+                        resolve_target: Some(view),
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Discard,
+                        },
+                    },
+                    None => wgpu::RenderPassColorAttachment {
+                        view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Store,
+                        },
                     },
                 })],
-                depth_stencil_attachment: None,
+                depth_stencil_attachment: target.depth_view().map(|view| {
+                    wgpu::RenderPassDepthStencilAttachment {
+                        view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(1.0),
+                            store: wgpu::StoreOp::Discard,
+                        }),
+                        stencil_ops: gpu.stencil_clipping().then_some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(0),
+                            store: wgpu::StoreOp::Discard,
+                        }),
+                    }
+                }),
                 occlusion_query_set: None,
-                timestamp_writes: None,
+                timestamp_writes: self.profiler.as_ref().and_then(Profiler::render_timestamps),
             });
 
             pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
@@ -145,23 +462,87 @@ impl Renderer {
             pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 
             for command in draw_commands.iter() {
-                pipeline_registry.apply_pipeline(
+                // Scissor rects have to lie within the surface; instances clipped away entirely
+                // were filtered out above.
+                let (x0, y0, x1, y1) = match command.clip {
+                    Some((p, s)) => (
+                        p.x.max(0),
+                        p.y.max(0),
+                        (p.x + s.width).min(viewport.width),
+                        (p.y + s.height).min(viewport.height),
+                    ),
+                    None => (0, 0, viewport.width, viewport.height),
+                };
+                if x1 <= x0 || y1 <= y0 {
+                    continue;
+                }
+                pass.set_scissor_rect(x0 as u32, y0 as u32, (x1 - x0) as u32, (y1 - y0) as u32);
+                pass.set_stencil_reference(command.stencil as u32);
+
+                if let Some(mask) = command.mask {
+                    // Without stencil clipping the rect around the outline is all there is.
+                    if !pipeline_registry.apply_mask(
+                        gpu,
+                        mask,
+                        globals,
+                        self.textures.bind_group(),
+                        &mut pass,
+                    ) {
+                        continue;
+                    }
+                } else if let Err(e) = pipeline_registry.apply_pipeline(
+                    gpu,
                     command.pipe,
                     globals,
                     self.textures.bind_group(),
                     &mut pass,
-                );
+                ) {
+                    if drawn.is_ok() {
+                        drawn = Err(e);
+                    }
+                    continue;
+                }
+                if let Some(profiler) = &mut self.profiler {
+                    profiler.time_draw(&mut pass, command.pipe);
+                }
                 pass.draw_indexed(
                     0..self.number_of_indices,
                     0,
                     command.base..(command.base + command.amount),
                 );
             }
+            if let Some(profiler) = &mut self.profiler {
+                profiler.end_draws(&mut pass);
+            }
         }
+        if let Some(profiler) = &self.profiler {
+            profiler.resolve(encoder);
+        }
+        drawn
+    }
+}
 
-        gpu.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+fn to_u8(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
 
-        Ok(())
+// IEEE 754 half-precision bits to f32.
+fn half_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        e => (1.0 + mantissa / 1024.0) * 2f32.powi(e - 15),
     }
 }
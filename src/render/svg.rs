@@ -0,0 +1,130 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
+
+use resvg::{tiny_skia, usvg};
+
+use crate::{
+    graphics::Gpu,
+    model::Size,
+    render::texture::{TextureHandle, TextureRegistry},
+};
+
+// Frames a parsed document survives without being drawn.
+const TREE_STALE_FRAMES: u64 = 120;
+
+struct CachedTree {
+    tree: Option<Arc<usvg::Tree>>,
+    last_used: u64,
+}
+
+struct CachedRaster {
+    handle: TextureHandle,
+    last_used: u64,
+}
+
+/// Parsed SVG documents and their rasterizations, keyed by a hash of the document. A raster
+/// is kept only while it is drawn at the same size, so resizing re-rasterizes at the new
+/// resolution and frees the old texture.
+#[derive(Default)]
+pub struct SvgCache {
+    trees: HashMap<u64, CachedTree>,
+    rasters: HashMap<(u64, Size<u32>), CachedRaster>,
+    frame: u64,
+}
+
+impl SvgCache {
+    pub fn key(data: &[u8]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Parses `data`, or returns the cached document for `key`. `None` if it isn't valid SVG.
+    pub fn tree(&mut self, key: u64, data: &[u8]) -> Option<Arc<usvg::Tree>> {
+        let frame = self.frame;
+        let entry = self.trees.entry(key).or_insert_with(|| CachedTree {
+            tree: usvg::Tree::from_data(data, &usvg::Options::default())
+                .ok()
+                .map(Arc::new),
+            last_used: frame,
+        });
+        entry.last_used = frame;
+        entry.tree.clone()
+    }
+
+    /// Natural size of the document in pixels.
+    pub fn size(&mut self, key: u64, data: &[u8]) -> Option<Size<f32>> {
+        let size = self.tree(key, data)?.size();
+        Some(Size::new(size.width(), size.height()))
+    }
+
+    /// Texture holding the document scaled to fit `size`, centered and keeping its aspect.
+    pub fn raster(
+        &mut self,
+        gpu: &Gpu,
+        textures: &mut TextureRegistry,
+        key: u64,
+        data: &[u8],
+        size: Size<u32>,
+    ) -> Option<TextureHandle> {
+        if size.width == 0 || size.height == 0 {
+            return None;
+        }
+        if let Some(entry) = self.rasters.get_mut(&(key, size)) {
+            entry.last_used = self.frame;
+            return Some(entry.handle);
+        }
+
+        let tree = self.tree(key, data)?;
+        let mut pixmap = tiny_skia::Pixmap::new(size.width, size.height)?;
+        let natural = tree.size();
+        let scale =
+            (size.width as f32 / natural.width()).min(size.height as f32 / natural.height());
+        let dx = (size.width as f32 - natural.width() * scale) / 2.0;
+        let dy = (size.height as f32 - natural.height() * scale) / 2.0;
+        resvg::render(
+            &tree,
+            tiny_skia::Transform::from_row(scale, 0.0, 0.0, scale, dx, dy),
+            &mut pixmap.as_mut(),
+        );
+
+        // The registry expects straight alpha.
+        let pixels: Vec<u8> = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|p| {
+                let c = p.demultiply();
+                [c.red(), c.green(), c.blue(), c.alpha()]
+            })
+            .collect();
+        let handle = textures
+            .load_rgba8(gpu, size.width, size.height, &pixels)
+            .ok()?;
+        self.rasters.insert(
+            (key, size),
+            CachedRaster {
+                handle,
+                last_used: self.frame,
+            },
+        );
+        Some(handle)
+    }
+
+    /// Frees rasters that weren't drawn this frame and documents unused for a while.
+    pub(crate) fn end_frame(&mut self, textures: &mut TextureRegistry) {
+        let frame = self.frame;
+        self.rasters.retain(|_, entry| {
+            let keep = entry.last_used == frame;
+            if !keep {
+                textures.unload(entry.handle);
+            }
+            keep
+        });
+        self.trees
+            .retain(|_, entry| frame.saturating_sub(entry.last_used) < TREE_STALE_FRAMES);
+        self.frame += 1;
+    }
+}
@@ -1,6 +1,15 @@
-use std::collections::{HashMap, VecDeque};
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::Range,
+    sync::Arc,
+};
 
-use cosmic_text::{CacheKey, FontSystem, LayoutGlyph, SwashCache, SwashContent, SwashImage};
+use cosmic_text::{
+    Align, Attrs, AttrsOwned, Buffer, CacheKey, CacheKeyFlags, Cursor, Fallback, Family,
+    FontSystem, LayoutGlyph, LayoutRun, Metrics, PlatformFallback, Shaping, Style, SwashCache,
+    SwashContent, SwashImage, Weight, Wrap, fontdb,
+};
+use unicode_script::Script;
 
 use crate::{
     graphics::Gpu,
@@ -9,17 +18,352 @@ use crate::{
 };
 
 const GLYPH_PAGE_SIZE: u32 = 1024;
+const DEFAULT_LAYOUT_CACHE_CAP: usize = 512;
+// Glyphs not drawn for this many frames don't survive compaction.
+const GLYPH_STALE_FRAMES: u64 = 600;
+// How often the pages are checked for one sparse enough to empty, and how many glyphs are
+// moved out of it each frame until it is.
+const COMPACT_INTERVAL: u64 = 300;
+const COMPACT_BUDGET: usize = 32;
+// Pages holding less than this share of live glyphs are worth emptying.
+const SPARSE_PAGE_FILL: f32 = 0.3;
+// Color glyphs past this size are stored downscaled and stretched when drawn, as their
+// bitmaps would fill pages fast.
+const MAX_COLOR_GLYPH: u32 = 256;
+// Distance fields are generated from glyphs rasterized at this size, whatever size they're
+// drawn at, with `SDF_SPREAD` pixels of falloff around them (`SPREAD` in the SDF shader).
+const SDF_SIZE: f32 = 48.0;
+const SDF_SPREAD: u32 = 8;
+const SDF_PAGE_CAP: usize = 2;
+// Pages are assumed to pack this well when estimating how many the live glyphs need.
+const PAGE_FILL_RATIO: f32 = 0.7;
+
+/// Everything that influences how a string is shaped and wrapped. Widgets asking for equal
+/// keys share one shaped `Buffer` from the [`TextSystem`] layout cache.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LayoutKey {
+    text: String,
+    attrs: AttrsOwned,
+    font_size: u32,
+    line_height: u32,
+    wrap: u8,
+    shaping: Shaping,
+    width: Option<u32>,
+    spans: Vec<(Range<usize>, AttrsOwned)>,
+    align: Option<u8>,
+}
+
+impl LayoutKey {
+    /// `line_height` is relative to `font_size`; `width` of `None` lays out a single
+    /// unconstrained line per paragraph.
+    pub fn new(
+        text: &str,
+        attrs: &Attrs,
+        font_size: f32,
+        line_height: f32,
+        wrap: Wrap,
+        shaping: Shaping,
+        width: Option<f32>,
+    ) -> Self {
+        Self {
+            text: text.to_owned(),
+            attrs: AttrsOwned::new(attrs),
+            font_size: font_size.to_bits(),
+            line_height: line_height.to_bits(),
+            wrap: match wrap {
+                Wrap::None => 0,
+                Wrap::Glyph => 1,
+                Wrap::Word => 2,
+                Wrap::WordOrGlyph => 3,
+            },
+            shaping,
+            width: width.map(f32::to_bits),
+            spans: Vec::new(),
+            align: None,
+        }
+    }
+
+    /// Aligns every line inside the layout width; `None` follows each line's script direction.
+    pub fn align(mut self, align: Option<Align>) -> Self {
+        self.align = align.map(|a| match a {
+            Align::Left => 0,
+            Align::Right => 1,
+            Align::Center => 2,
+            Align::Justified => 3,
+            Align::End => 4,
+        });
+        self
+    }
+
+    /// Styles byte ranges of the text with their own attributes instead of the key's. The
+    /// ranges must be in order, non-overlapping and on char boundaries; gaps between them use
+    /// the key's attributes.
+    pub fn spans<'a>(mut self, spans: impl IntoIterator<Item = (Range<usize>, Attrs<'a>)>) -> Self {
+        self.spans = spans
+            .into_iter()
+            .map(|(range, attrs)| (range, AttrsOwned::new(&attrs)))
+            .collect();
+        self
+    }
+
+    fn alignment(&self) -> Option<Align> {
+        self.align.map(|a| match a {
+            0 => Align::Left,
+            1 => Align::Right,
+            2 => Align::Center,
+            3 => Align::Justified,
+            _ => Align::End,
+        })
+    }
+
+    fn wrap(&self) -> Wrap {
+        match self.wrap {
+            0 => Wrap::None,
+            1 => Wrap::Glyph,
+            3 => Wrap::WordOrGlyph,
+            _ => Wrap::Word,
+        }
+    }
+}
+
+/// Where a font wants its decoration lines, in ems above the baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecorationMetrics {
+    pub underline: f32,
+    pub strikethrough: f32,
+    pub overline: f32,
+    pub thickness: f32,
+}
+
+/// Vertical metrics of the font a [`TextStyle`] resolves to, in pixels at its size. Descent
+/// is measured down from the baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontMetrics {
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_gap: f32,
+    pub cap_height: f32,
+    pub x_height: f32,
+    /// Height of a line of text in the style.
+    pub line_height: f32,
+    /// Distance from the top of a line to its baseline, as text is laid out.
+    pub baseline: f32,
+}
+
+/// How [`TextSystem::measure`] shapes a string, with the same defaults as the `Text` widget.
+#[derive(Clone, Debug)]
+pub struct TextStyle<'a> {
+    attrs: Attrs<'a>,
+    font_size: f32,
+    line_height: f32,
+    letter_spacing: f32,
+    wrap: Wrap,
+    shaping: Shaping,
+}
+
+impl<'a> TextStyle<'a> {
+    pub fn new(font_size: f32) -> Self {
+        Self {
+            attrs: Attrs::new(),
+            font_size,
+            line_height: 1.2,
+            letter_spacing: 0.0,
+            wrap: Wrap::Word,
+            shaping: Shaping::Basic,
+        }
+    }
+
+    /// Line height relative to the font size.
+    pub fn line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+    pub fn family(mut self, family: Family<'a>) -> Self {
+        self.attrs = self.attrs.family(family);
+        self
+    }
+    pub fn weight(mut self, weight: Weight) -> Self {
+        self.attrs = self.attrs.weight(weight);
+        self
+    }
+    pub fn style(mut self, style: Style) -> Self {
+        self.attrs = self.attrs.style(style);
+        self
+    }
+    /// Extra pixels after every character.
+    pub fn letter_spacing(mut self, px: f32) -> Self {
+        self.letter_spacing = px;
+        self
+    }
+    pub fn wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+    pub fn shaping(mut self, shaping: Shaping) -> Self {
+        self.shaping = shaping;
+        self
+    }
+
+    /// Layout cache key of `content` in this style, wrapped at `width` if given.
+    pub fn key(&self, content: &str, width: Option<f32>) -> LayoutKey {
+        let attrs = if self.letter_spacing == 0.0 {
+            self.attrs.clone()
+        } else {
+            self.attrs
+                .clone()
+                .letter_spacing(self.letter_spacing / self.font_size)
+        };
+        LayoutKey::new(
+            content,
+            &attrs,
+            self.font_size,
+            self.line_height,
+            self.wrap,
+            self.shaping,
+            width,
+        )
+    }
+}
+
+// Distance field of a glyph at `SDF_SIZE`, placed like a swash image relative to the origin.
+#[derive(Clone, Copy)]
+struct SdfGlyph {
+    handle: TextureHandle,
+    left: i32,
+    top: i32,
+    size: Size<u32>,
+}
+
+struct CachedLayout {
+    buffer: Arc<Buffer>,
+    last_used: u64,
+}
 
 struct Page {
     id: usize,
     atlas: Atlas,
 }
 
+#[derive(Clone, Copy)]
+struct GlyphEntry {
+    handle: TextureHandle,
+    page: usize,
+    last_used: u64,
+    color: bool,
+}
+
+/// Font families tried, in order, for characters the requested family has no glyph for.
+#[derive(Debug, Clone)]
+pub struct FontFallback {
+    families: Vec<String>,
+    monospace: Option<String>,
+    emoji: Option<String>,
+    platform: bool,
+}
+
+impl Default for FontFallback {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FontFallback {
+    /// A chain of just the platform's color emoji font, followed by the platform's own
+    /// fallbacks.
+    pub fn new() -> Self {
+        Self {
+            families: Vec::new(),
+            monospace: None,
+            emoji: Some(Self::PLATFORM_EMOJI.to_owned()),
+            platform: true,
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    const PLATFORM_EMOJI: &str = "Segoe UI Emoji";
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    const PLATFORM_EMOJI: &str = "Apple Color Emoji";
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+    const PLATFORM_EMOJI: &str = "Noto Color Emoji";
+
+    pub fn family(mut self, family: impl Into<String>) -> Self {
+        self.families.push(family.into());
+        self
+    }
+    /// Family `Family::Monospace` resolves to, also tried after the other families.
+    pub fn monospace(mut self, family: impl Into<String>) -> Self {
+        self.monospace = Some(family.into());
+        self
+    }
+    /// Tried last, for emoji no other family in the chain covers.
+    pub fn emoji(mut self, family: impl Into<String>) -> Self {
+        self.emoji = Some(family.into());
+        self
+    }
+    /// Whether cosmic-text's per-platform and per-script lists are tried after the chain.
+    pub fn platform_defaults(mut self, on: bool) -> Self {
+        self.platform = on;
+        self
+    }
+}
+
+// cosmic-text wants `'static` names, so those of a configured chain are leaked; chains are
+// set rarely enough for that not to matter.
+struct ChainFallback {
+    common: Vec<&'static str>,
+    platform: bool,
+}
+
+impl ChainFallback {
+    fn new(chain: &FontFallback) -> Self {
+        let leak = |s: &String| -> &'static str { Box::leak(s.clone().into_boxed_str()) };
+        let mut common: Vec<&'static str> = chain.families.iter().map(leak).collect();
+        common.extend(chain.monospace.iter().map(leak));
+        common.extend(chain.emoji.iter().map(leak));
+        if chain.platform {
+            common.extend_from_slice(PlatformFallback.common_fallback());
+        }
+        Self {
+            common,
+            platform: chain.platform,
+        }
+    }
+}
+
+impl Fallback for ChainFallback {
+    fn common_fallback(&self) -> &[&'static str] {
+        &self.common
+    }
+
+    fn forbidden_fallback(&self) -> &[&'static str] {
+        PlatformFallback.forbidden_fallback()
+    }
+
+    fn script_fallback(&self, script: Script, locale: &str) -> &[&'static str] {
+        if self.platform {
+            PlatformFallback.script_fallback(script, locale)
+        } else {
+            &[]
+        }
+    }
+}
+
 pub struct TextSystem {
     pages: VecDeque<Page>,
     page_cap: usize,
     current_page: usize,
-    glyph_map: HashMap<CacheKey, (TextureHandle, usize)>,
+    glyph_map: HashMap<CacheKey, GlyphEntry>,
+    frame: u64,
+    // Page whose glyphs are being moved to the others, a few each frame, before it's freed.
+    evacuating: Option<usize>,
+
+    layouts: HashMap<LayoutKey, CachedLayout>,
+    layout_cap: usize,
+    layout_tick: u64,
+
+    // Distance fields by glyph at `SDF_SIZE`; `None` for glyphs that can't have one.
+    sdf_pages: Vec<Atlas>,
+    sdf_glyphs: HashMap<CacheKey, Option<SdfGlyph>>,
 
     swash_cache: SwashCache,
     font_system: FontSystem,
@@ -27,15 +371,159 @@ pub struct TextSystem {
 
 impl Default for TextSystem {
     fn default() -> Self {
-        Self {
+        let mut text = Self {
             pages: VecDeque::new(),
             page_cap: 4,
             current_page: 0,
             glyph_map: HashMap::new(),
+            frame: 0,
+            evacuating: None,
+            layouts: HashMap::new(),
+            layout_cap: DEFAULT_LAYOUT_CACHE_CAP,
+            layout_tick: 0,
+            sdf_pages: Vec::new(),
+            sdf_glyphs: HashMap::new(),
             swash_cache: SwashCache::new(),
             font_system: FontSystem::new(),
+        };
+        text.set_fallback(&FontFallback::default());
+        text
+    }
+}
+
+// Squared distance transform of `f` along one line (Felzenszwalb and Huttenlocher), written
+// to `d`; `v` and `z` are scratch space of `f.len()` and one more.
+fn edt_1d(f: &[f32], d: &mut [f32], v: &mut [usize], z: &mut [f32]) {
+    const INF: f32 = 1e20;
+    v[0] = 0;
+    z[0] = -INF;
+    z[1] = INF;
+    let mut k = 0;
+    for q in 1..f.len() {
+        let parabola =
+            |r: usize| ((f[q] + (q * q) as f32) - (f[r] + (r * r) as f32)) / (2 * (q - r)) as f32;
+        let mut s = parabola(v[k]);
+        while s <= z[k] {
+            k -= 1;
+            s = parabola(v[k]);
+        }
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = INF;
+    }
+    k = 0;
+    for (q, out) in d.iter_mut().enumerate() {
+        while z[k + 1] < q as f32 {
+            k += 1;
+        }
+        let r = v[k];
+        *out = (q.abs_diff(r) * q.abs_diff(r)) as f32 + f[r];
+    }
+}
+
+// Squared distance transform of the `w` x `h` grid, columns then rows.
+fn edt_2d(grid: &mut [f32], w: usize, h: usize) {
+    let n = w.max(h);
+    let (mut f, mut d) = (vec![0.0; n], vec![0.0; n]);
+    let (mut v, mut z) = (vec![0; n], vec![0.0; n + 1]);
+    for x in 0..w {
+        for y in 0..h {
+            f[y] = grid[y * w + x];
+        }
+        edt_1d(&f[..h], &mut d[..h], &mut v, &mut z);
+        for y in 0..h {
+            grid[y * w + x] = d[y];
+        }
+    }
+    for y in 0..h {
+        f[..w].copy_from_slice(&grid[y * w..(y + 1) * w]);
+        edt_1d(&f[..w], &mut d[..w], &mut v, &mut z);
+        grid[y * w..(y + 1) * w].copy_from_slice(&d[..w]);
+    }
+}
+
+// Distance field of the coverage mask `alpha`, padded by `SDF_SPREAD` on each side, as RGBA
+// with the distance in alpha: one half on the edge, rising inside. Partly covered pixels
+// place the edge within them, as in Mapbox's TinySDF.
+fn coverage_to_sdf(alpha: &[u8], w: u32, h: u32) -> (Vec<u8>, u32, u32) {
+    const INF: f32 = 1e20;
+    let pad = SDF_SPREAD;
+    let (pw, ph) = (w + 2 * pad, h + 2 * pad);
+    let mut outer = vec![INF; (pw * ph) as usize];
+    let mut inner = vec![0.0; (pw * ph) as usize];
+    for y in 0..h {
+        for x in 0..w {
+            let a = alpha[(y * w + x) as usize] as f32 / 255.0;
+            let i = ((y + pad) * pw + x + pad) as usize;
+            (outer[i], inner[i]) = if a >= 1.0 {
+                (0.0, INF)
+            } else if a > 0.0 {
+                let d = 0.5 - a;
+                (d.max(0.0).powi(2), d.min(0.0).powi(2))
+            } else {
+                (INF, 0.0)
+            };
+        }
+    }
+    // Squared distances to the nearest covered pixel and to the nearest uncovered one.
+    edt_2d(&mut outer, pw as usize, ph as usize);
+    edt_2d(&mut inner, pw as usize, ph as usize);
+
+    let spread = SDF_SPREAD as f32;
+    let rgba = outer
+        .iter()
+        .zip(&inner)
+        .flat_map(|(&o, &i)| {
+            let dist = o.sqrt() - i.sqrt();
+            let v = (0.5 - dist / (2.0 * spread)).clamp(0.0, 1.0);
+            [255, 255, 255, (v * 255.0).round() as u8]
+        })
+        .collect();
+    (rgba, pw, ph)
+}
+
+// Box filters premultiplied `src` down to `dw` x `dh`.
+fn downscale_rgba(src: &[u8], sw: u32, sh: u32, dw: u32, dh: u32) -> Vec<u8> {
+    let mut out = Vec::with_capacity((dw * dh * 4) as usize);
+    for y in 0..dh {
+        let y0 = y * sh / dh;
+        let y1 = ((y + 1) * sh / dh).max(y0 + 1);
+        for x in 0..dw {
+            let x0 = x * sw / dw;
+            let x1 = ((x + 1) * sw / dw).max(x0 + 1);
+            let mut sum = [0u32; 4];
+            for sy in y0..y1 {
+                for sx in x0..x1 {
+                    let i = ((sy * sw + sx) * 4) as usize;
+                    for (c, total) in sum.iter_mut().enumerate() {
+                        *total += src[i + c] as u32;
+                    }
+                }
+            }
+            let n = (y1 - y0) * (x1 - x0);
+            out.extend(sum.map(|total| (total / n) as u8));
         }
     }
+    out
+}
+
+// Premultiplied pixels of `img` and their size, downscaled to what an atlas page takes.
+fn fit_glyph(img: &SwashImage, color: bool) -> (Vec<u8>, u32, u32) {
+    let rgba = premul_rgba(img);
+    let (w, h) = (img.placement.width, img.placement.height);
+    let cap = if color {
+        MAX_COLOR_GLYPH
+    } else {
+        GLYPH_PAGE_SIZE
+    };
+    if w <= cap && h <= cap {
+        return (rgba, w, h);
+    }
+    let scale = cap as f32 / w.max(h) as f32;
+    let dw = ((w as f32 * scale) as u32).max(1);
+    let dh = ((h as f32 * scale) as u32).max(1);
+    (downscale_rgba(&rgba, w, h, dw, dh), dw, dh)
 }
 
 fn premul_rgba(img: &SwashImage) -> Vec<u8> {
@@ -73,6 +561,47 @@ fn premul_rgba(img: &SwashImage) -> Vec<u8> {
     }
 }
 
+// Line and in-line offset of byte `index` of the buffer's text, clamped to its end.
+fn cursor_at(buffer: &Buffer, index: usize) -> Cursor {
+    let mut start = 0;
+    let last = buffer.lines.len().saturating_sub(1);
+    for (i, line) in buffer.lines.iter().enumerate() {
+        let len = line.text().len();
+        if index <= start + len || i == last {
+            return Cursor::new(i, index.saturating_sub(start).min(len));
+        }
+        start += len + line.ending().as_str().len();
+    }
+    Cursor::new(0, 0)
+}
+
+fn index_of(buffer: &Buffer, cursor: Cursor) -> usize {
+    let before: usize = buffer
+        .lines
+        .iter()
+        .take(cursor.line)
+        .map(|l| l.text().len() + l.ending().as_str().len())
+        .sum();
+    before + cursor.index
+}
+
+// Caret offset within `run` before byte `index` of its line, if the run holds that byte or
+// ends right before it. Right-to-left glyphs put the caret on their right edge.
+fn caret_in_run(run: &LayoutRun, index: usize) -> Option<f32> {
+    let inside = run
+        .glyphs
+        .iter()
+        .find(|g| index >= g.start && index < g.end);
+    if let Some(g) = inside {
+        // Inside a ligature; split its advance evenly.
+        let t = (index - g.start) as f32 / (g.end - g.start) as f32;
+        let t = if g.level.is_rtl() { 1.0 - t } else { t };
+        return Some(g.x + g.w * t);
+    }
+    let g = run.glyphs.iter().find(|g| g.end == index)?;
+    Some(if g.level.is_rtl() { g.x } else { g.x + g.w })
+}
+
 impl TextSystem {
     pub fn font_system(&self) -> &FontSystem {
         &self.font_system
@@ -90,14 +619,273 @@ impl TextSystem {
         &mut self.swash_cache
     }
 
+    /// Returns the shaped buffer for `key`, shaping it only if no widget asked for the same
+    /// layout recently. Least recently used layouts are evicted past the cache capacity.
+    pub fn layout(&mut self, key: LayoutKey) -> Arc<Buffer> {
+        self.layout_tick += 1;
+        if let Some(entry) = self.layouts.get_mut(&key) {
+            entry.last_used = self.layout_tick;
+            return entry.buffer.clone();
+        }
+
+        let fs = &mut self.font_system;
+        let metrics = Metrics::relative(
+            f32::from_bits(key.font_size),
+            f32::from_bits(key.line_height),
+        );
+        let mut buffer = Buffer::new(fs, metrics);
+        buffer.set_wrap(fs, key.wrap());
+        if key.spans.is_empty() {
+            buffer.set_text(fs, &key.text, &key.attrs.as_attrs(), key.shaping);
+        } else {
+            let defaults = key.attrs.as_attrs();
+            let mut runs = Vec::with_capacity(key.spans.len() * 2 + 1);
+            let mut end = 0;
+            for (range, attrs) in &key.spans {
+                if range.start > end {
+                    runs.push((&key.text[end..range.start], defaults.clone()));
+                }
+                runs.push((&key.text[range.clone()], attrs.as_attrs()));
+                end = range.end;
+            }
+            if end < key.text.len() {
+                runs.push((&key.text[end..], defaults.clone()));
+            }
+            buffer.set_rich_text(fs, runs, &defaults, key.shaping, None);
+        }
+        if let Some(align) = key.alignment() {
+            for line in buffer.lines.iter_mut() {
+                line.set_align(Some(align));
+            }
+        }
+        buffer.set_size(fs, key.width.map(f32::from_bits), None);
+        buffer.shape_until_scroll(fs, false);
+
+        self.evict_layouts();
+        let buffer = Arc::new(buffer);
+        self.layouts.insert(
+            key,
+            CachedLayout {
+                buffer: buffer.clone(),
+                last_used: self.layout_tick,
+            },
+        );
+        buffer
+    }
+
+    /// Size of `content` laid out in `style`, wrapped at `max_width` if given. Shares the layout
+    /// cache with widgets, so measuring text about to be drawn doesn't shape it twice.
+    pub fn measure(
+        &mut self,
+        content: &str,
+        style: &TextStyle,
+        max_width: Option<f32>,
+    ) -> Size<f32> {
+        let (w, h) = self
+            .layout(style.key(content, max_width))
+            .layout_runs()
+            .fold((0f32, 0f32), |(w, h), run| {
+                (w.max(run.line_w), h + run.line_height)
+            });
+        Size::new(w, h)
+    }
+
+    /// Registers every face in the font file `data`, returning the families they add.
+    pub fn load_font(&mut self, data: Vec<u8>) -> Vec<String> {
+        let db = self.font_system.db_mut();
+        let ids = db.load_font_source(fontdb::Source::Binary(Arc::new(data)));
+        let mut families = Vec::new();
+        for id in ids {
+            if let Some((family, _)) = db.face(id).and_then(|face| face.families.first())
+                && !families.contains(family)
+            {
+                families.push(family.clone());
+            }
+        }
+        // Text shaped before may have fallen back to other fonts.
+        self.layouts.clear();
+        families
+    }
+
+    /// Makes `Family::SansSerif`, the family text uses unless told otherwise, resolve to
+    /// `family`.
+    pub fn set_default_family(&mut self, family: impl Into<String>) {
+        self.font_system.db_mut().set_sans_serif_family(family);
+        self.layouts.clear();
+    }
+
+    /// Replaces the fallback chain, keeping every loaded font.
+    pub fn set_fallback(&mut self, chain: &FontFallback) {
+        let placeholder =
+            FontSystem::new_with_locale_and_db(String::new(), fontdb::Database::new());
+        let (locale, mut db) =
+            std::mem::replace(&mut self.font_system, placeholder).into_locale_and_db();
+        if let Some(monospace) = &chain.monospace {
+            db.set_monospace_family(monospace.clone());
+        }
+        self.font_system =
+            FontSystem::new_with_locale_and_db_and_fallback(locale, db, ChainFallback::new(chain));
+        self.swash_cache = SwashCache::new();
+        self.layouts.clear();
+    }
+
+    pub fn set_layout_cache_capacity(&mut self, entries: usize) {
+        self.layout_cap = entries.max(1);
+        self.evict_layouts();
+    }
+
+    pub fn clear_layout_cache(&mut self) {
+        self.layouts.clear();
+    }
+
+    fn evict_layouts(&mut self) {
+        if self.layouts.len() < self.layout_cap {
+            return;
+        }
+        // Drop an eighth of the cache at once so eviction isn't paid on every miss.
+        let drop = self.layouts.len() + 1 - self.layout_cap + self.layout_cap / 8;
+        let mut ages: Vec<u64> = self.layouts.values().map(|e| e.last_used).collect();
+        let nth = drop.min(ages.len()) - 1;
+        let (_, &mut cutoff, _) = ages.select_nth_unstable(nth);
+        self.layouts.retain(|_, e| e.last_used > cutoff);
+    }
+
+    /// Caret before byte `index` of `buffer`'s text: its top-left corner relative to the
+    /// buffer and its height. Indices past the end put it after the last character.
+    pub fn caret_position(&self, buffer: &Buffer, index: usize) -> Option<(Position<f32>, f32)> {
+        let cursor = cursor_at(buffer, index);
+        let runs = || buffer.layout_runs().filter(|r| r.line_i == cursor.line);
+        // A caret on a wrap goes to the start of the next visual line.
+        let (run, x) = runs()
+            .find(|r| r.glyphs.iter().any(|g| g.start == cursor.index))
+            .or_else(|| runs().find(|r| caret_in_run(r, cursor.index).is_some()))
+            .map(|r| {
+                let x = caret_in_run(&r, cursor.index).unwrap_or(0.0);
+                (r, x)
+            })
+            .or_else(|| {
+                // Empty lines have a run without glyphs.
+                let r = runs().next()?;
+                let x = if r.rtl { r.line_w } else { 0.0 };
+                Some((r, x))
+            })?;
+        Some((Position::new(x, run.line_top), run.line_height))
+    }
+
+    /// Byte index of `buffer`'s text with the caret position closest to `point`, relative to
+    /// the buffer's top-left corner.
+    pub fn index_at(&self, buffer: &Buffer, point: Position<f32>) -> usize {
+        buffer
+            .hit(point.x, point.y)
+            .map_or(0, |cursor| index_of(buffer, cursor))
+    }
+
+    /// Rects relative to the buffer covering the bytes of `range` of its text, one for each
+    /// visual line they're on.
+    pub fn selection_rects(
+        &self,
+        buffer: &Buffer,
+        range: Range<usize>,
+    ) -> Vec<(Position<f32>, Size<f32>)> {
+        let (start, end) = (cursor_at(buffer, range.start), cursor_at(buffer, range.end));
+        if start >= end {
+            return Vec::new();
+        }
+        buffer
+            .layout_runs()
+            .filter(|r| r.line_i >= start.line && r.line_i <= end.line)
+            .filter_map(|run| {
+                let from = if run.line_i == start.line {
+                    start
+                } else {
+                    Cursor::new(run.line_i, 0)
+                };
+                let to = if run.line_i == end.line {
+                    end
+                } else {
+                    Cursor::new(run.line_i, usize::MAX)
+                };
+                let (x, w) = run.highlight(from, to)?;
+                Some((
+                    Position::new(x, run.line_top),
+                    Size::new(w, run.line_height),
+                ))
+            })
+            .collect()
+    }
+
+    /// Metrics of the font `style` resolves to, `None` if no loaded font matches it.
+    pub fn font_metrics(&mut self, style: &TextStyle) -> Option<FontMetrics> {
+        let attrs = &style.attrs;
+        let id = self.font_system.db().query(&fontdb::Query {
+            families: &[attrs.family],
+            weight: attrs.weight,
+            stretch: attrs.stretch,
+            style: attrs.style,
+        })?;
+        let font = self.font_system.get_font(id)?;
+        let face = font.rustybuzz();
+        let px = style.font_size / face.units_per_em() as f32;
+        let ascent = face.ascender() as f32 * px;
+        let descent = -face.descender() as f32 * px;
+        let line_height = style.font_size * style.line_height;
+        Some(FontMetrics {
+            ascent,
+            descent,
+            line_gap: face.line_gap() as f32 * px,
+            cap_height: face
+                .capital_height()
+                .map_or(ascent * 0.7, |h| h as f32 * px),
+            x_height: face.x_height().map_or(ascent * 0.5, |h| h as f32 * px),
+            line_height,
+            // Lines center their glyphs, like `Buffer::layout_runs` does.
+            baseline: (line_height - ascent - descent) / 2.0 + ascent,
+        })
+    }
+
+    /// Decoration metrics of `font`, guessed from its ascender where it has no `post` or
+    /// `OS/2` table saying.
+    pub fn decoration_metrics(&mut self, font: fontdb::ID) -> Option<DecorationMetrics> {
+        let font = self.font_system.get_font(font)?;
+        let face = font.rustybuzz();
+        let em = face.units_per_em() as f32;
+        let ascender = face.ascender() as f32 / em;
+        let underline = face.underline_metrics();
+        let strikeout = face.strikeout_metrics();
+        let thickness = underline
+            .or(strikeout)
+            .map_or(1.0 / 14.0, |m| m.thickness as f32 / em);
+        Some(DecorationMetrics {
+            underline: underline.map_or(-0.1, |m| m.position as f32 / em),
+            strikethrough: strikeout.map_or(ascender * 0.35, |m| m.position as f32 / em),
+            overline: ascender,
+            thickness,
+        })
+    }
+
+    /// Top-left corner in pixels, size and cache key of `glyph` drawn on the line starting at
+    /// `line`. Its position is snapped to a quarter pixel in both directions and the remaining
+    /// fraction is rasterized into the glyph, so moving text glides instead of jumping a whole
+    /// pixel at a time.
     pub fn get_glyph_data(
         &mut self,
         glyph: &LayoutGlyph,
+        line: Position<f32>,
     ) -> Option<(Position<i32>, Size<u32>, CacheKey)> {
-        let phys = glyph.physical((0.0, 0.0), 1.0);
+        // Unlike `LayoutGlyph::physical`, which keeps baselines on whole pixels.
+        let (key, x, y) = CacheKey::new(
+            glyph.font_id,
+            glyph.glyph_id,
+            glyph.font_size,
+            (
+                line.x + glyph.x + glyph.font_size * glyph.x_offset,
+                line.y + glyph.y - glyph.font_size * glyph.y_offset,
+            ),
+            glyph.cache_key_flags,
+        );
         let img = self
             .swash_cache
-            .get_image(&mut self.font_system, phys.cache_key)
+            .get_image(&mut self.font_system, key)
             .as_ref()?;
 
         if img.placement.width == 0 || img.placement.height == 0 {
@@ -108,9 +896,9 @@ impl TextSystem {
         let gh = img.placement.height;
 
         Some((
-            Position::new(img.placement.left, img.placement.top),
+            Position::new(x + img.placement.left, y - img.placement.top),
             Size::new(gw, gh),
-            phys.cache_key,
+            key,
         ))
     }
 
@@ -119,7 +907,9 @@ impl TextSystem {
             return false;
         }
         let id = self.pages.back().map(|p| p.id + 1).unwrap_or(0);
-        let atlas = texture_reg.create_atlas(gpu, GLYPH_PAGE_SIZE, GLYPH_PAGE_SIZE);
+        let Ok(atlas) = texture_reg.create_atlas(gpu, GLYPH_PAGE_SIZE, GLYPH_PAGE_SIZE) else {
+            return false;
+        };
         self.pages.push_back(Page { id, atlas });
         self.current_page = self.pages.len() - 1;
         true
@@ -127,8 +917,11 @@ impl TextSystem {
 
     fn recycle_oldest(&mut self, gpu: &Gpu, texture_reg: &mut TextureRegistry) {
         if let Some(Page { id, mut atlas }) = self.pages.pop_front() {
-            texture_reg.destroy_atlas(gpu, &mut atlas);
-            self.glyph_map.retain(|_, (_, page_id)| *page_id != id);
+            if self.evacuating == Some(id) {
+                self.evacuating = None;
+            }
+            texture_reg.destroy_atlas(&mut atlas);
+            self.glyph_map.retain(|_, entry| entry.page != id);
             let _ = self.create_atlas(gpu, texture_reg);
         }
     }
@@ -144,12 +937,10 @@ impl TextSystem {
         if w == 0 || h == 0 {
             return Some(TextureHandle::default());
         }
-        if w > GLYPH_PAGE_SIZE || h > GLYPH_PAGE_SIZE {
-            return Some(TextureHandle::default());
-        }
 
-        if let Some(&(handle, _)) = self.glyph_map.get(&key) {
-            return Some(handle);
+        if let Some(entry) = self.glyph_map.get_mut(&key) {
+            entry.last_used = self.frame;
+            return Some(entry.handle);
         }
 
         if self.pages.is_empty() && !self.create_atlas(gpu, texture_reg) {
@@ -160,43 +951,307 @@ impl TextSystem {
             .swash_cache
             .get_image(&mut self.font_system, key)
             .as_ref()?;
-        let rgba = premul_rgba(img);
+        let color = img.content == SwashContent::Color;
+        let (rgba, w, h) = fit_glyph(img, color);
+
+        Some(
+            self.place(gpu, texture_reg, key, w, h, &rgba, color)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Distance field of `glyph`, generated and uploaded the first time any size of it is
+    /// asked for. Returns its top-left corner relative to the glyph's pen position, with y
+    /// growing down, its size at the glyph's font size, and its texture. `None` for glyphs
+    /// without an outline, like color emoji, or once the distance field pages are full.
+    pub fn sdf_glyph(
+        &mut self,
+        gpu: &Gpu,
+        texture_reg: &mut TextureRegistry,
+        glyph: &LayoutGlyph,
+    ) -> Option<(Position<f32>, Size<f32>, TextureHandle)> {
+        let (key, _, _) = CacheKey::new(
+            glyph.font_id,
+            glyph.glyph_id,
+            SDF_SIZE,
+            (0.0, 0.0),
+            CacheKeyFlags::empty(),
+        );
+        let sdf = match self.sdf_glyphs.get(&key) {
+            Some(sdf) => *sdf,
+            None => {
+                let sdf = self.generate_sdf(gpu, texture_reg, key);
+                // Running out of pages may pass, unlike a glyph having no outline.
+                if sdf.is_some() || self.sdf_pages.len() < SDF_PAGE_CAP {
+                    self.sdf_glyphs.insert(key, sdf);
+                }
+                sdf
+            }
+        }?;
 
-        // Try current page
-        if let Some(handle) =
-            texture_reg.load_into_atlas(gpu, &mut self.pages[self.current_page].atlas, w, h, &rgba)
+        let scale = glyph.font_size / SDF_SIZE;
+        Some((
+            Position::new(sdf.left as f32 * scale, -sdf.top as f32 * scale),
+            Size::new(
+                sdf.size.width as f32 * scale,
+                sdf.size.height as f32 * scale,
+            ),
+            sdf.handle,
+        ))
+    }
+
+    fn generate_sdf(
+        &mut self,
+        gpu: &Gpu,
+        texture_reg: &mut TextureRegistry,
+        key: CacheKey,
+    ) -> Option<SdfGlyph> {
+        let img = self
+            .swash_cache
+            .get_image_uncached(&mut self.font_system, key)?;
+        if img.content != SwashContent::Mask || img.placement.width == 0 {
+            return None;
+        }
+        let (w, h) = (img.placement.width, img.placement.height);
+        let (rgba, pw, ph) = coverage_to_sdf(&img.data, w, h);
+
+        let mut handle = self
+            .sdf_pages
+            .iter_mut()
+            .find_map(|page| texture_reg.load_into_atlas(gpu, page, pw, ph, &rgba));
+        if handle.is_none()
+            && self.sdf_pages.len() < SDF_PAGE_CAP
+            && let Ok(mut page) = texture_reg.create_atlas(gpu, GLYPH_PAGE_SIZE, GLYPH_PAGE_SIZE)
         {
-            let id = self.pages[self.current_page].id;
-            self.glyph_map.insert(key, (handle, id));
+            handle = texture_reg.load_into_atlas(gpu, &mut page, pw, ph, &rgba);
+            self.sdf_pages.push(page);
+        }
+
+        let pad = SDF_SPREAD as i32;
+        Some(SdfGlyph {
+            handle: handle?,
+            left: img.placement.left - pad,
+            top: img.placement.top + pad,
+            size: Size::new(pw, ph),
+        })
+    }
+
+    /// Whether the uploaded glyph `key` has colors of its own, like emoji, rather than
+    /// taking the text's color.
+    pub fn is_color_glyph(&self, key: CacheKey) -> bool {
+        self.glyph_map.get(&key).is_some_and(|e| e.color)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn place(
+        &mut self,
+        gpu: &Gpu,
+        texture_reg: &mut TextureRegistry,
+        key: CacheKey,
+        w: u32,
+        h: u32,
+        rgba: &[u8],
+        color: bool,
+    ) -> Option<TextureHandle> {
+        if let Some(handle) = self.place_existing(gpu, texture_reg, key, w, h, rgba, color) {
             return Some(handle);
         }
 
-        // Try other pages
-        for idx in 0..self.pages.len() {
-            if idx == self.current_page {
-                continue;
-            }
+        // Allocate or recycle, then place
+        if !self.create_atlas(gpu, texture_reg) {
+            self.recycle_oldest(gpu, texture_reg);
+        }
+        let page = &mut self.pages[self.current_page];
+        let handle = texture_reg.load_into_atlas(gpu, &mut page.atlas, w, h, rgba)?;
+        let id = page.id;
+        self.insert_glyph(key, handle, id, color);
+        Some(handle)
+    }
+
+    /// Places a glyph on a page that's already there, leaving out one being emptied.
+    #[allow(clippy::too_many_arguments)]
+    fn place_existing(
+        &mut self,
+        gpu: &Gpu,
+        texture_reg: &mut TextureRegistry,
+        key: CacheKey,
+        w: u32,
+        h: u32,
+        rgba: &[u8],
+        color: bool,
+    ) -> Option<TextureHandle> {
+        // Try current page first, then the others
+        let order = std::iter::once(self.current_page)
+            .chain((0..self.pages.len()).filter(|&idx| idx != self.current_page))
+            .filter(|&idx| idx < self.pages.len() && Some(self.pages[idx].id) != self.evacuating);
+        for idx in order.collect::<Vec<_>>() {
             if let Some(handle) =
-                texture_reg.load_into_atlas(gpu, &mut self.pages[idx].atlas, w, h, &rgba)
+                texture_reg.load_into_atlas(gpu, &mut self.pages[idx].atlas, w, h, rgba)
             {
-                let id = self.pages[idx].id;
-                self.glyph_map.insert(key, (handle, id));
+                self.insert_glyph(key, handle, self.pages[idx].id, color);
                 return Some(handle);
             }
         }
+        None
+    }
 
-        // Allocate or recycle, then place
-        if !self.create_atlas(gpu, texture_reg) {
-            self.recycle_oldest(gpu, texture_reg);
+    fn insert_glyph(&mut self, key: CacheKey, handle: TextureHandle, page: usize, color: bool) {
+        self.glyph_map.insert(
+            key,
+            GlyphEntry {
+                handle,
+                page,
+                last_used: self.frame,
+                color,
+            },
+        );
+    }
+
+    /// Per-frame bookkeeping. Every so often drops stale glyphs and, once a page is sparse
+    /// enough that the rest can take its glyphs, empties it over the following frames.
+    pub(crate) fn end_frame(&mut self, gpu: &Gpu, texture_reg: &mut TextureRegistry) {
+        self.frame += 1;
+        if self.evacuating.is_some() {
+            self.evacuate(gpu, texture_reg);
+        } else if self.frame.is_multiple_of(COMPACT_INTERVAL) {
+            self.drop_stale();
+            self.evacuating = self.sparse_page();
         }
-        if let Some(handle) =
-            texture_reg.load_into_atlas(gpu, &mut self.pages[self.current_page].atlas, w, h, &rgba)
-        {
-            let id = self.pages[self.current_page].id;
-            self.glyph_map.insert(key, (handle, id));
-            return Some(handle);
+    }
+
+    fn drop_stale(&mut self) {
+        let frame = self.frame;
+        let pages = &mut self.pages;
+        self.glyph_map.retain(|_, entry| {
+            let live = frame.saturating_sub(entry.last_used) < GLYPH_STALE_FRAMES;
+            if !live && let Some(page) = pages.iter_mut().find(|p| p.id == entry.page) {
+                page.atlas.free(entry.handle);
+            }
+            live
+        });
+    }
+
+    /// The emptiest page besides the one being filled, if it's sparse and the live glyphs
+    /// would fit in one page less.
+    fn sparse_page(&self) -> Option<usize> {
+        if self.pages.len() < 2 {
+            return None;
+        }
+        let mut live = vec![0u64; self.pages.len()];
+        for entry in self.glyph_map.values() {
+            if let Some(idx) = self.pages.iter().position(|p| p.id == entry.page) {
+                live[idx] += entry.handle.size_px.width as u64 * entry.handle.size_px.height as u64;
+            }
+        }
+        let page_area = (GLYPH_PAGE_SIZE as f32).powi(2);
+        let total: u64 = live.iter().sum();
+        let needed = (total as f32 / (page_area * PAGE_FILL_RATIO)).ceil() as usize;
+        if needed >= self.pages.len() {
+            return None;
+        }
+        (0..self.pages.len())
+            .filter(|&idx| idx != self.current_page)
+            .min_by_key(|&idx| live[idx])
+            .filter(|&idx| (live[idx] as f32) < page_area * SPARSE_PAGE_FILL)
+            .map(|idx| self.pages[idx].id)
+    }
+
+    /// Moves up to `COMPACT_BUDGET` glyphs off the page being emptied, freeing it once none
+    /// are left. Gives up if the other pages have no room for them.
+    fn evacuate(&mut self, gpu: &Gpu, texture_reg: &mut TextureRegistry) {
+        let Some(id) = self.evacuating else {
+            return;
+        };
+        let Some(idx) = self.pages.iter().position(|p| p.id == id) else {
+            self.evacuating = None;
+            return;
+        };
+        let moving: Vec<(CacheKey, GlyphEntry)> = self
+            .glyph_map
+            .iter()
+            .filter(|(_, e)| e.page == id)
+            .take(COMPACT_BUDGET)
+            .map(|(k, e)| (*k, *e))
+            .collect();
+
+        if moving.is_empty() {
+            if let Some(mut page) = self.pages.remove(idx) {
+                texture_reg.destroy_atlas(&mut page.atlas);
+            }
+            if self.current_page > idx {
+                self.current_page -= 1;
+            }
+            self.current_page = self.current_page.min(self.pages.len().saturating_sub(1));
+            self.evacuating = None;
+            return;
         }
 
-        Some(TextureHandle::default())
+        for (key, entry) in moving {
+            let Some(img) = self
+                .swash_cache
+                .get_image(&mut self.font_system, key)
+                .as_ref()
+            else {
+                self.glyph_map.remove(&key);
+                continue;
+            };
+            let (rgba, w, h) = fit_glyph(img, entry.color);
+            let placed = self.place_existing(gpu, texture_reg, key, w, h, &rgba, entry.color);
+            let Some(placed) = placed.and_then(|_| self.glyph_map.get_mut(&key)) else {
+                self.evacuating = None;
+                return;
+            };
+            placed.last_used = entry.last_used;
+        }
+    }
+
+    /// Drops glyphs that haven't been drawn recently and, when the survivors fit in fewer
+    /// pages, repacks them so the freed pages go back to the texture registry.
+    /// Unlike the compaction done a little at a time as frames end, this redoes every page
+    /// at once.
+    pub fn compact(&mut self, gpu: &Gpu, texture_reg: &mut TextureRegistry) {
+        self.evacuating = None;
+        self.drop_stale();
+
+        let live_area: u64 = self
+            .glyph_map
+            .values()
+            .map(|e| e.handle.size_px.width as u64 * e.handle.size_px.height as u64)
+            .sum();
+        let page_area = (GLYPH_PAGE_SIZE as f32).powi(2) * PAGE_FILL_RATIO;
+        let needed = (live_area as f32 / page_area).ceil() as usize;
+        if needed >= self.pages.len() {
+            return;
+        }
+
+        let mut survivors: Vec<(CacheKey, GlyphEntry)> = self.glyph_map.drain().collect();
+        // Tallest first keeps the shelves tight.
+        survivors.sort_by_key(|(_, e)| std::cmp::Reverse(e.handle.size_px.height));
+
+        for mut page in self.pages.drain(..) {
+            texture_reg.destroy_atlas(&mut page.atlas);
+        }
+        self.current_page = 0;
+
+        for (key, entry) in survivors {
+            let Some(img) = self
+                .swash_cache
+                .get_image(&mut self.font_system, key)
+                .as_ref()
+            else {
+                continue;
+            };
+            let (rgba, w, h) = fit_glyph(img, entry.color);
+            if self.pages.is_empty() && !self.create_atlas(gpu, texture_reg) {
+                break;
+            }
+            if self
+                .place(gpu, texture_reg, key, w, h, &rgba, entry.color)
+                .is_some()
+                && let Some(placed) = self.glyph_map.get_mut(&key)
+            {
+                placed.last_used = entry.last_used;
+            }
+        }
     }
 }
@@ -1,4 +1,11 @@
-use crate::{consts::DEFAULT_MAX_TEXTURES, graphics::Gpu, model::Size};
+use std::sync::Arc;
+
+use crate::{
+    Error, Result,
+    consts::{DEFAULT_MAX_TEXTURES, MAX_TEXTURES},
+    graphics::Gpu,
+    model::Size,
+};
 
 fn dummy_bind_group(device: &wgpu::Device) -> wgpu::BindGroup {
     let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -23,6 +30,7 @@ pub fn unpack_unorm2x16(p: u32) -> [f32; 2] {
     [(p & 0xFFFF) as f32 / 65535.0, (p >> 16) as f32 / 65535.0]
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct AtlasRect {
     pub x: u32,
     pub y: u32,
@@ -30,13 +38,22 @@ pub struct AtlasRect {
     pub h: u32,
 }
 
+// A stretch of the skyline: everything below `y` from `x` to `x + w` is taken.
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    x: u32,
+    y: u32,
+    w: u32,
+}
+
 pub struct Atlas {
     slot_index: usize,
     generation: u32,
     size_px: Size<u32>,
-    cursor_x: u32,
-    cursor_y: u32,
-    row_h: u32,
+    // Covers the atlas's width left to right.
+    skyline: Vec<Segment>,
+    // Entries that haven't been freed, to move over when repacking.
+    live: Vec<(TextureHandle, AtlasRect)>,
 }
 
 impl Atlas {
@@ -45,38 +62,296 @@ impl Atlas {
             slot_index,
             generation,
             size_px,
-            cursor_x: 0,
-            cursor_y: 0,
-            row_h: 0,
+            skyline: vec![Segment {
+                x: 0,
+                y: 0,
+                w: size_px.width,
+            }],
+            live: Vec::new(),
+        }
+    }
+
+    fn handle(&self, rect: &AtlasRect) -> TextureHandle {
+        let scale = [
+            rect.w as f32 / self.size_px.width as f32,
+            rect.h as f32 / self.size_px.height as f32,
+        ];
+        let offs = [
+            rect.x as f32 / self.size_px.width as f32,
+            rect.y as f32 / self.size_px.height as f32,
+        ];
+        TextureHandle {
+            index: self.slot_index as u32,
+            generation: self.generation,
+            scale_packed: pack_unorm2x16(scale),
+            offset_packed: pack_unorm2x16(offs),
+            size_px: Size::new(rect.w, rect.h),
+        }
+    }
+
+    /// Marks the entry `handle` points to as unused. Its space is only reclaimed by
+    /// [`TextureRegistry::repack_atlas`].
+    pub fn free(&mut self, handle: TextureHandle) -> bool {
+        let before = self.live.len();
+        self.live.retain(|(h, _)| *h != handle);
+        self.live.len() != before
+    }
+
+    /// Share of the space allocated so far that freed entries left unused, from 0 to 1. Worth
+    /// repacking once it's high and allocations start failing.
+    pub fn fragmentation(&self) -> f32 {
+        let used: u32 = self.skyline.iter().map(|s| s.w * s.y).sum();
+        if used == 0 {
+            return 0.0;
         }
+        let live: u32 = self.live.iter().map(|(_, r)| r.w * r.h).sum();
+        1.0 - live as f32 / used as f32
     }
 
+    // Skyline bottom-left packing: each rect goes where its top ends up lowest, leftmost on
+    // ties, resting on the highest segment it spans.
     // TODO: alloc using LRU
     fn alloc(&mut self, w: u32, h: u32) -> Option<AtlasRect> {
         if w > self.size_px.width || h > self.size_px.height {
             return None;
         }
-        if self.cursor_x + w > self.size_px.width {
-            self.cursor_x = 0;
-            self.cursor_y += self.row_h;
-            self.row_h = 0;
+        if w == 0 || h == 0 {
+            return Some(AtlasRect { x: 0, y: 0, w, h });
         }
-        if self.cursor_y + h > self.size_px.height {
-            return None;
+
+        let mut best: Option<(usize, u32, u32)> = None;
+        for (i, segment) in self.skyline.iter().enumerate() {
+            let x = segment.x;
+            if x + w > self.size_px.width {
+                break;
+            }
+            let mut y = 0;
+            let mut covered = 0;
+            for s in &self.skyline[i..] {
+                if covered >= w {
+                    break;
+                }
+                y = y.max(s.y);
+                covered += s.w;
+            }
+            if y + h <= self.size_px.height && best.is_none_or(|(_, _, best_y)| y < best_y) {
+                best = Some((i, x, y));
+            }
+        }
+        let (i, x, y) = best?;
+
+        // The rect's top replaces the segments under it, cutting into the last one.
+        self.skyline.insert(i, Segment { x, y: y + h, w });
+        let end = x + w;
+        while let Some(next) = self.skyline.get_mut(i + 1)
+            && next.x < end
+        {
+            let next_end = next.x + next.w;
+            if next_end <= end {
+                self.skyline.remove(i + 1);
+            } else {
+                next.w = next_end - end;
+                next.x = end;
+                break;
+            }
+        }
+        // Neighbours at the same height merge, so wider rects can rest on them.
+        let mut j = 0;
+        while j + 1 < self.skyline.len() {
+            if self.skyline[j].y == self.skyline[j + 1].y {
+                self.skyline[j].w += self.skyline[j + 1].w;
+                self.skyline.remove(j + 1);
+            } else {
+                j += 1;
+            }
         }
 
-        let rect = AtlasRect {
-            x: self.cursor_x,
-            y: self.cursor_y,
-            w,
-            h,
-        };
-        self.cursor_x += w;
-        if h > self.row_h {
-            self.row_h = h;
+        Some(AtlasRect { x, y, w, h })
+    }
+}
+
+/// One decoded frame of an animated image, shown for `delay` seconds.
+#[derive(Clone, Debug)]
+pub struct AnimationFrame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+    pub delay: f32,
+}
+
+/// Uploaded frames of an animated image and how long each is shown. Cheap to clone.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct AnimatedHandle {
+    frames: Arc<[(TextureHandle, f32)]>,
+    duration: f32,
+}
+
+impl AnimatedHandle {
+    fn new(frames: Vec<(TextureHandle, f32)>) -> Self {
+        let duration = frames.iter().map(|(_, d)| d).sum();
+        Self {
+            frames: frames.into(),
+            duration,
         }
-        Some(rect)
     }
+
+    pub fn frames(&self) -> impl Iterator<Item = TextureHandle> + '_ {
+        self.frames.iter().map(|(h, _)| *h)
+    }
+
+    /// Length of one loop in seconds.
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// Frame shown `time` seconds into a looping playback, and the time the next frame is due.
+    pub fn frame_at(&self, time: f32) -> Option<(TextureHandle, f32)> {
+        let (first, _) = *self.frames.first()?;
+        if self.frames.len() == 1 || self.duration <= 0.0 {
+            return Some((first, f32::INFINITY));
+        }
+        let loop_start = time - time.rem_euclid(self.duration);
+        let mut end = loop_start;
+        for &(handle, delay) in self.frames.iter() {
+            end += delay;
+            if time < end {
+                return Some((handle, end));
+            }
+        }
+        Some((first, end))
+    }
+}
+
+/// Decodes every frame of a GIF or APNG, composited to the full canvas. Still PNGs decode to
+/// a single frame.
+#[cfg(feature = "animated")]
+pub fn decode_animation(bytes: &[u8]) -> image::ImageResult<Vec<AnimationFrame>> {
+    use image::{
+        AnimationDecoder, ImageFormat,
+        codecs::{gif::GifDecoder, png::PngDecoder},
+    };
+    use std::io::Cursor;
+
+    let frames = match image::guess_format(bytes)? {
+        ImageFormat::Gif => GifDecoder::new(Cursor::new(bytes))?
+            .into_frames()
+            .collect_frames()?,
+        ImageFormat::Png => {
+            let decoder = PngDecoder::new(Cursor::new(bytes))?;
+            if decoder.is_apng()? {
+                decoder.apng()?.into_frames().collect_frames()?
+            } else {
+                let still = image::load_from_memory_with_format(bytes, ImageFormat::Png)?;
+                vec![image::Frame::new(still.into_rgba8())]
+            }
+        }
+        format => {
+            return Err(image::ImageError::Unsupported(
+                image::error::UnsupportedError::from(image::error::ImageFormatHint::Exact(format)),
+            ));
+        }
+    };
+
+    Ok(frames
+        .into_iter()
+        .map(|frame| {
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            let delay = numer as f32 / denom.max(1) as f32 / 1000.0;
+            let buffer = frame.into_buffer();
+            AnimationFrame {
+                width: buffer.width(),
+                height: buffer.height(),
+                pixels: buffer.into_raw(),
+                // Like browsers, treat near-zero delays as 100ms.
+                delay: if delay < 0.02 { 0.1 } else { delay },
+            }
+        })
+        .collect())
+}
+
+/// The texture format a KTX2 format is loaded as, for those the registry can sample.
+#[cfg(feature = "ktx2")]
+fn ktx2_format(format: ktx2::Format) -> Option<wgpu::TextureFormat> {
+    use ktx2::Format as K;
+    use wgpu::{AstcBlock, AstcChannel, TextureFormat as T};
+
+    Some(match format {
+        K::R8G8B8A8_UNORM => T::Rgba8Unorm,
+        K::R8G8B8A8_SRGB => T::Rgba8UnormSrgb,
+        K::B8G8R8A8_UNORM => T::Bgra8Unorm,
+        K::B8G8R8A8_SRGB => T::Bgra8UnormSrgb,
+        K::BC1_RGBA_UNORM_BLOCK | K::BC1_RGB_UNORM_BLOCK => T::Bc1RgbaUnorm,
+        K::BC1_RGBA_SRGB_BLOCK | K::BC1_RGB_SRGB_BLOCK => T::Bc1RgbaUnormSrgb,
+        K::BC2_UNORM_BLOCK => T::Bc2RgbaUnorm,
+        K::BC2_SRGB_BLOCK => T::Bc2RgbaUnormSrgb,
+        K::BC3_UNORM_BLOCK => T::Bc3RgbaUnorm,
+        K::BC3_SRGB_BLOCK => T::Bc3RgbaUnormSrgb,
+        K::BC4_UNORM_BLOCK => T::Bc4RUnorm,
+        K::BC4_SNORM_BLOCK => T::Bc4RSnorm,
+        K::BC5_UNORM_BLOCK => T::Bc5RgUnorm,
+        K::BC5_SNORM_BLOCK => T::Bc5RgSnorm,
+        K::BC6H_UFLOAT_BLOCK => T::Bc6hRgbUfloat,
+        K::BC6H_SFLOAT_BLOCK => T::Bc6hRgbFloat,
+        K::BC7_UNORM_BLOCK => T::Bc7RgbaUnorm,
+        K::BC7_SRGB_BLOCK => T::Bc7RgbaUnormSrgb,
+        K::ETC2_R8G8B8_UNORM_BLOCK => T::Etc2Rgb8Unorm,
+        K::ETC2_R8G8B8_SRGB_BLOCK => T::Etc2Rgb8UnormSrgb,
+        K::ETC2_R8G8B8A1_UNORM_BLOCK => T::Etc2Rgb8A1Unorm,
+        K::ETC2_R8G8B8A1_SRGB_BLOCK => T::Etc2Rgb8A1UnormSrgb,
+        K::ETC2_R8G8B8A8_UNORM_BLOCK => T::Etc2Rgba8Unorm,
+        K::ETC2_R8G8B8A8_SRGB_BLOCK => T::Etc2Rgba8UnormSrgb,
+        _ => {
+            // ASTC formats come in unorm/sRGB pairs, one per block size.
+            const BLOCKS: [AstcBlock; 14] = [
+                AstcBlock::B4x4,
+                AstcBlock::B5x4,
+                AstcBlock::B5x5,
+                AstcBlock::B6x5,
+                AstcBlock::B6x6,
+                AstcBlock::B8x5,
+                AstcBlock::B8x6,
+                AstcBlock::B8x8,
+                AstcBlock::B10x5,
+                AstcBlock::B10x6,
+                AstcBlock::B10x8,
+                AstcBlock::B10x10,
+                AstcBlock::B12x10,
+                AstcBlock::B12x12,
+            ];
+            let offset = format
+                .value()
+                .checked_sub(K::ASTC_4x4_UNORM_BLOCK.value())?;
+            T::Astc {
+                block: *BLOCKS.get(offset as usize / 2)?,
+                channel: match offset % 2 {
+                    0 => AstcChannel::Unorm,
+                    _ => AstcChannel::UnormSrgb,
+                },
+            }
+        }
+    })
+}
+
+fn atlas_slot(gpu: &Gpu, size: Size<u32>) -> TexSlot {
+    let tex = gpu.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("UI Atlas"),
+        size: wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        // Copied from when repacking.
+        usage: wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = tex.create_view(&Default::default());
+    TexSlot { tex, view }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
@@ -94,6 +369,49 @@ struct TexSlot {
     view: wgpu::TextureView,
 }
 
+fn create_layout(device: &wgpu::Device, slots: usize) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("UI Texture Array BGL"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: std::num::NonZeroU32::new(slots as u32),
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    })
+}
+
+fn create_gens_buffer(device: &wgpu::Device, slots: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("UI Texture Generations Buffer"),
+        size: (std::mem::size_of::<u32>() * slots) as u64,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
 pub struct TextureRegistry {
     layout: wgpu::BindGroupLayout,
     bind_group: wgpu::BindGroup,
@@ -104,42 +422,19 @@ pub struct TextureRegistry {
     gens_buffer: wgpu::Buffer,
 
     free: Vec<usize>,
+    // Slots the array may grow to, within the device's binding array limit.
+    max_slots: usize,
+    // Whether the array grew since `take_resized`, so pipelines need the new layout.
+    resized: bool,
     placeholder_view: wgpu::TextureView,
+    // Slot changes since the last `flush`; the bind group and generations are rebuilt once.
+    dirty: bool,
 }
 
 impl TextureRegistry {
     pub fn new(device: &wgpu::Device) -> Self {
-        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("UI Texture Array BGL"),
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: std::num::NonZeroU32::new(DEFAULT_MAX_TEXTURES),
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 2,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: true },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-            ],
-        });
+        let n = DEFAULT_MAX_TEXTURES as usize;
+        let layout = create_layout(device, n);
 
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("UI Texture Sampler"),
@@ -162,16 +457,9 @@ impl TextureRegistry {
         });
         let placeholder_view = placeholder.create_view(&Default::default());
 
-        let n = DEFAULT_MAX_TEXTURES as usize;
         let views = vec![None; n];
         let gens = vec![0u32; n];
-
-        let gens_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("UI Texture Generations Buffer"),
-            size: (std::mem::size_of::<u32>() * n) as u64,
-            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+        let gens_buffer = create_gens_buffer(device, n);
 
         let mut reg = Self {
             layout,
@@ -182,7 +470,12 @@ impl TextureRegistry {
             gens,
             gens_buffer,
             free: (0..n).rev().collect(),
+            max_slots: (device.limits().max_binding_array_elements_per_shader_stage as usize)
+                .min(MAX_TEXTURES as usize)
+                .max(n),
+            resized: false,
             placeholder_view,
+            dirty: false,
         };
         reg.update_bind_group(device);
         reg
@@ -218,6 +511,44 @@ impl TextureRegistry {
         });
     }
 
+    /// Applies every load and unload since the last call with a single bind-group rebuild and
+    /// generations upload. Runs once per frame before drawing.
+    pub(crate) fn flush(&mut self, gpu: &Gpu) {
+        if !self.dirty {
+            return;
+        }
+        gpu.queue
+            .write_buffer(&self.gens_buffer, 0, bytemuck::cast_slice(&self.gens));
+        self.update_bind_group(&gpu.device);
+        self.dirty = false;
+    }
+
+    // Takes a free slot, doubling the array when they're all in use.
+    fn take_slot(&mut self, device: &wgpu::Device) -> Result<usize> {
+        if let Some(idx) = self.free.pop() {
+            return Ok(idx);
+        }
+        let len = self.views.len();
+        let grown = (len * 2).min(self.max_slots);
+        if grown == len {
+            return Err(Error::TextureSlotsExhausted);
+        }
+        self.views.resize(grown, None);
+        self.gens.resize(grown, 0);
+        self.free.extend((len + 1..grown).rev());
+        self.layout = create_layout(device, grown);
+        self.gens_buffer = create_gens_buffer(device, grown);
+        self.resized = true;
+        self.dirty = true;
+        Ok(len)
+    }
+
+    /// Whether the texture array grew since the last call. Pipelines are built against its
+    /// layout, so they have to be rebuilt before the next frame.
+    pub(crate) fn take_resized(&mut self) -> bool {
+        std::mem::take(&mut self.resized)
+    }
+
     pub fn layout(&self) -> &wgpu::BindGroupLayout {
         &self.layout
     }
@@ -231,61 +562,164 @@ impl TextureRegistry {
         width: u32,
         height: u32,
         pixels_rgba8: &[u8],
-    ) -> TextureHandle {
-        let idx = self
-            .free
-            .pop()
-            .expect("Texture slots exhausted; bump DEFAULT_MAX_TEXTURES");
+    ) -> Result<TextureHandle> {
+        self.load_levels(
+            gpu,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            width,
+            height,
+            &[pixels_rgba8],
+        )
+    }
+
+    /// Uploads block-compressed (BC, ETC2 or ASTC) data, one slice per mip level from the full
+    /// size down. Fails if the device can't sample `format`.
+    pub fn load_compressed(
+        &mut self,
+        gpu: &Gpu,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        levels: &[&[u8]],
+    ) -> Result<TextureHandle> {
+        if !format.is_compressed() {
+            return Err(Error::UnsupportedTexture(format!(
+                "{format:?} isn't block-compressed"
+            )));
+        }
+        self.load_levels(gpu, format, width, height, levels)
+    }
+
+    /// Loads a KTX2 container holding a single 2D image, with its mip levels. Supercompressed
+    /// files aren't supported.
+    #[cfg(feature = "ktx2")]
+    pub fn load_ktx2(&mut self, gpu: &Gpu, bytes: &[u8]) -> Result<TextureHandle> {
+        let reader = ktx2::Reader::new(bytes)
+            .map_err(|e| Error::UnsupportedTexture(format!("invalid KTX2: {e}")))?;
+        let header = reader.header();
+        if let Some(scheme) = header.supercompression_scheme {
+            return Err(Error::UnsupportedTexture(format!(
+                "KTX2 supercompression {scheme:?}"
+            )));
+        }
+        if header.pixel_depth > 1 || header.layer_count > 1 || header.face_count > 1 {
+            return Err(Error::UnsupportedTexture(
+                "KTX2 with more than one 2D image".into(),
+            ));
+        }
+        let format = header
+            .format
+            .and_then(ktx2_format)
+            .ok_or_else(|| Error::UnsupportedTexture(format!("KTX2 format {:?}", header.format)))?;
+        let levels: Vec<&[u8]> = reader.levels().map(|level| level.data).collect();
+        self.load_levels(
+            gpu,
+            format,
+            header.pixel_width,
+            header.pixel_height.max(1),
+            &levels,
+        )
+    }
+
+    fn load_levels(
+        &mut self,
+        gpu: &Gpu,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        levels: &[&[u8]],
+    ) -> Result<TextureHandle> {
+        if !gpu.device.features().contains(format.required_features()) {
+            return Err(Error::UnsupportedTexture(format!(
+                "the device can't sample {format:?}"
+            )));
+        }
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let (block_width, block_height) = format.block_dimensions();
+        if !width.is_multiple_of(block_width) || !height.is_multiple_of(block_height) {
+            return Err(Error::UnsupportedTexture(format!(
+                "{width}x{height} isn't made of whole {block_width}x{block_height} blocks"
+            )));
+        }
+        if levels.is_empty() || levels.len() as u32 > size.max_mips(wgpu::TextureDimension::D2) {
+            return Err(Error::UnsupportedTexture(format!(
+                "{} mip levels for {width}x{height}",
+                levels.len()
+            )));
+        }
+        let block_size = format
+            .block_copy_size(None)
+            .expect("sampled color formats have a block size");
+        // Bytes per row and rows of blocks in each level.
+        let layouts: Vec<(wgpu::Extent3d, u32, u32)> = (0..levels.len() as u32)
+            .map(|level| {
+                let extent = size
+                    .mip_level_size(level, wgpu::TextureDimension::D2)
+                    .physical_size(format);
+                let bytes_per_row = extent.width / block_width * block_size;
+                (extent, bytes_per_row, extent.height / block_height)
+            })
+            .collect();
+        if let Some(level) = layouts
+            .iter()
+            .zip(levels)
+            .position(|((_, row, rows), data)| data.len() < (row * rows) as usize)
+        {
+            return Err(Error::UnsupportedTexture(format!(
+                "mip level {level} is too short"
+            )));
+        }
+
+        let idx = self.take_slot(&gpu.device)?;
 
         let tex = gpu.device.create_texture(&wgpu::TextureDescriptor {
             label: Some("UI Image"),
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
+            size,
+            mip_level_count: levels.len() as u32,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            format,
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
             view_formats: &[],
         });
-        gpu.queue.write_texture(
-            tex.as_image_copy(),
-            pixels_rgba8,
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * width),
-                rows_per_image: Some(height),
-            },
-            wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-        );
+        for (level, (data, (extent, bytes_per_row, rows))) in levels.iter().zip(layouts).enumerate()
+        {
+            gpu.queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &tex,
+                    mip_level: level as u32,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                data,
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(rows),
+                },
+                extent,
+            );
+        }
         let view = tex.create_view(&Default::default());
 
         self.views[idx] = Some(TexSlot { tex, view });
 
-        gpu.queue.write_buffer(
-            &self.gens_buffer,
-            (std::mem::size_of::<u32>() * idx) as u64,
-            bytemuck::cast_slice(&[self.gens[idx]]),
-        );
-        self.update_bind_group(&gpu.device);
+        self.dirty = true;
 
-        TextureHandle {
+        Ok(TextureHandle {
             index: idx as u32,
             generation: self.gens[idx],
             scale_packed: pack_unorm2x16([1.0, 1.0]),
             offset_packed: pack_unorm2x16([0.0, 0.0]),
             size_px: Size::new(width, height),
-        }
+        })
     }
 
-    pub fn unload(&mut self, gpu: &Gpu, handle: TextureHandle) -> bool {
+    pub fn unload(&mut self, handle: TextureHandle) -> bool {
         let idx = handle.index as usize;
         if idx >= self.views.len() {
             return false;
@@ -298,45 +732,85 @@ impl TextureRegistry {
         self.gens[idx] = self.gens[idx].wrapping_add(1);
         self.free.push(idx);
 
-        gpu.queue.write_buffer(
-            &self.gens_buffer,
-            (std::mem::size_of::<u32>() * idx) as u64,
-            bytemuck::cast_slice(&[self.gens[idx]]),
-        );
-        self.update_bind_group(&gpu.device);
+        self.dirty = true;
         true
     }
 
-    pub fn create_atlas(&mut self, gpu: &Gpu, width: u32, height: u32) -> Atlas {
-        let idx = self
-            .free
-            .pop()
-            .expect("Texture slots exhausted; bump DEFAULT_MAX_TEXTURES");
-        let tex = gpu.device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("UI Atlas"),
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-        });
-        let view = tex.create_view(&Default::default());
-        self.views[idx] = Some(TexSlot { tex, view });
+    pub fn create_atlas(&mut self, gpu: &Gpu, width: u32, height: u32) -> Result<Atlas> {
+        let idx = self.take_slot(&gpu.device)?;
+        self.views[idx] = Some(atlas_slot(gpu, Size::new(width, height)));
 
-        gpu.queue.write_buffer(
-            &self.gens_buffer,
-            (std::mem::size_of::<u32>() * idx) as u64,
-            bytemuck::cast_slice(&[self.gens[idx]]),
-        );
-        self.update_bind_group(&gpu.device);
+        self.dirty = true;
+
+        Ok(Atlas::new(idx, self.gens[idx], Size::new(width, height)))
+    }
+
+    /// Moves the atlas's live entries into a fresh layout without the gaps freed entries left.
+    /// Handles into the atlas go stale, so it returns each old handle with its replacement.
+    /// `None` if the entries wouldn't fit, in which case the atlas is left as it was.
+    pub fn repack_atlas(
+        &mut self,
+        gpu: &Gpu,
+        atlas: &mut Atlas,
+    ) -> Option<Vec<(TextureHandle, TextureHandle)>> {
+        let idx = atlas.slot_index;
+        let old = self.views[idx].as_ref()?;
+
+        // Tallest first leaves the fewest gaps under the skyline.
+        let mut entries = atlas.live.clone();
+        entries.sort_by_key(|(_, rect)| std::cmp::Reverse(rect.h));
+        let mut packed = Atlas::new(idx, self.gens[idx].wrapping_add(1), atlas.size_px);
+        let rects = entries
+            .iter()
+            .map(|(_, rect)| packed.alloc(rect.w, rect.h))
+            .collect::<Option<Vec<_>>>()?;
+
+        let slot = atlas_slot(gpu, atlas.size_px);
+        let mut encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Atlas Repack Encoder"),
+            });
+        let mut moved = Vec::with_capacity(entries.len());
+        for ((handle, from), to) in entries.into_iter().zip(rects) {
+            encoder.copy_texture_to_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &old.tex,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: from.x,
+                        y: from.y,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::TexelCopyTextureInfo {
+                    texture: &slot.tex,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: to.x,
+                        y: to.y,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::Extent3d {
+                    width: from.w,
+                    height: from.h,
+                    depth_or_array_layers: 1,
+                },
+            );
+            let new = packed.handle(&to);
+            packed.live.push((new, to));
+            moved.push((handle, new));
+        }
+        gpu.queue.submit(std::iter::once(encoder.finish()));
 
-        Atlas::new(idx, self.gens[idx], Size::new(width, height))
+        self.gens[idx] = packed.generation;
+        self.views[idx] = Some(slot);
+        self.dirty = true;
+        *atlas = packed;
+        Some(moved)
     }
 
     pub fn load_into_atlas(
@@ -376,42 +850,67 @@ impl TextureRegistry {
             },
         );
 
-        let scale = [
-            w as f32 / atlas.size_px.width as f32,
-            h as f32 / atlas.size_px.height as f32,
-        ];
-        let offs = [
-            rect.x as f32 / atlas.size_px.width as f32,
-            rect.y as f32 / atlas.size_px.height as f32,
-        ];
+        let handle = atlas.handle(&rect);
+        atlas.live.push((handle, rect));
+        Some(handle)
+    }
 
-        Some(TextureHandle {
-            index: atlas.slot_index as u32,
-            generation: atlas.generation,
-            scale_packed: pack_unorm2x16(scale),
-            offset_packed: pack_unorm2x16(offs),
-            size_px: Size::new(w, h),
-        })
+    /// Uploads every frame into its own texture slot. If the slots run out none of the frames
+    /// stay loaded.
+    pub fn load_animation(
+        &mut self,
+        gpu: &Gpu,
+        frames: &[AnimationFrame],
+    ) -> Result<AnimatedHandle> {
+        let mut loaded = Vec::with_capacity(frames.len());
+        for f in frames {
+            match self.load_rgba8(gpu, f.width, f.height, &f.pixels) {
+                Ok(handle) => loaded.push((handle, f.delay)),
+                Err(e) => {
+                    for (handle, _) in loaded {
+                        self.unload(handle);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(AnimatedHandle::new(loaded))
     }
 
-    pub fn destroy_atlas(&mut self, gpu: &Gpu, atlas: &mut Atlas) {
+    /// Uploads every frame into `atlas`; `None` if they don't all fit.
+    pub fn load_animation_into_atlas(
+        &mut self,
+        gpu: &Gpu,
+        atlas: &mut Atlas,
+        frames: &[AnimationFrame],
+    ) -> Option<AnimatedHandle> {
+        let mut loaded = Vec::with_capacity(frames.len());
+        for f in frames {
+            let handle = self.load_into_atlas(gpu, atlas, f.width, f.height, &f.pixels)?;
+            loaded.push((handle, f.delay));
+        }
+        Some(AnimatedHandle::new(loaded))
+    }
+
+    /// Unloads the frames of an animation loaded with [`TextureRegistry::load_animation`].
+    pub fn unload_animation(&mut self, handle: &AnimatedHandle) {
+        for frame in handle.frames() {
+            self.unload(frame);
+        }
+    }
+
+    pub fn destroy_atlas(&mut self, atlas: &mut Atlas) {
         let idx = atlas.slot_index;
 
         self.gens[idx] = self.gens[idx].wrapping_add(1);
-        gpu.queue.write_buffer(
-            &self.gens_buffer,
-            (std::mem::size_of::<u32>() * idx) as u64,
-            bytemuck::cast_slice(&[self.gens[idx]]),
-        );
 
         self.views[idx] = None;
-        self.update_bind_group(&gpu.device);
+        self.dirty = true;
         self.free.push(idx);
 
         atlas.size_px = Size::new(0, 0);
-        atlas.cursor_x = 0;
-        atlas.cursor_y = 0;
-        atlas.row_h = 0;
+        atlas.skyline.clear();
+        atlas.live.clear();
         atlas.generation = self.gens[idx];
     }
 }
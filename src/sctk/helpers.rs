@@ -1,9 +1,32 @@
-use smithay_client_toolkit::{output::OutputState, seat::keyboard::Keysym};
+use std::{
+    ffi::OsString,
+    io::{self, Read},
+    os::unix::ffi::OsStringExt,
+    path::PathBuf,
+    time::Instant,
+};
+
+use smithay_client_toolkit::{
+    data_device_manager::ReadPipe,
+    output::OutputState,
+    reexports::protocols::wp::cursor_shape::v1::client::wp_cursor_shape_device_v1::Shape,
+    seat::{
+        keyboard::{Keysym, Modifiers},
+        pointer::AxisScroll,
+    },
+};
 use smol_str::ToSmolStr;
+use wayland_client::{
+    Connection, EventQueue, backend::WaylandError, protocol::wl_pointer::AxisSource,
+};
 
-use crate::{event::LogicalKey, sctk::OutputSet};
+use crate::{
+    event::{CursorIcon, LogicalKey, MouseButton, ScrollDelta},
+    model::Position,
+    sctk::OutputSet,
+};
 
-use super::OutputSelector;
+use super::{OutputSelector, state::SctkState};
 
 pub(super) fn pick_output(
     outputs: &OutputState,
@@ -76,3 +99,159 @@ pub(super) fn map_keysym_to_logical(k: Keysym, utf8: Option<&str>) -> LogicalKey
             .unwrap_or(LogicalKey::Unknown),
     }
 }
+
+pub(super) fn map_cursor(icon: CursorIcon) -> Shape {
+    match icon {
+        CursorIcon::Default => Shape::Default,
+        CursorIcon::Pointer => Shape::Pointer,
+        CursorIcon::Text => Shape::Text,
+        CursorIcon::Grab => Shape::Grab,
+        CursorIcon::Grabbing => Shape::Grabbing,
+        CursorIcon::Move => Shape::Move,
+        CursorIcon::Crosshair => Shape::Crosshair,
+        CursorIcon::NotAllowed => Shape::NotAllowed,
+        CursorIcon::EwResize => Shape::EwResize,
+        CursorIcon::NsResize => Shape::NsResize,
+    }
+}
+
+/// Maps a linux input event code (`BTN_*`) from `wl_pointer.button`. Unknown buttons act as
+/// the primary button.
+pub(super) fn map_pointer_button(button: u32) -> MouseButton {
+    const BTN_RIGHT: u32 = 0x111;
+    const BTN_MIDDLE: u32 = 0x112;
+    const BTN_SIDE: u32 = 0x113;
+    const BTN_EXTRA: u32 = 0x114;
+    const BTN_FORWARD: u32 = 0x115;
+    const BTN_BACK: u32 = 0x116;
+
+    match button {
+        BTN_RIGHT => MouseButton::Right,
+        BTN_MIDDLE => MouseButton::Middle,
+        BTN_SIDE | BTN_BACK => MouseButton::Back,
+        BTN_EXTRA | BTN_FORWARD => MouseButton::Forward,
+        _ => MouseButton::Left,
+    }
+}
+
+/// Maps a `wl_pointer` axis frame to a delta scrolling up/left when positive, like winit's.
+/// Wheels report steps (in 120ths when the compositor supports high-resolution scrolling);
+/// touchpads only pixels.
+/// Wheels report notches, touchpads and other continuous sources report pixels. A wheel
+/// whose compositor only sends pixels is counted at `WHEEL_STEP_PX` per notch.
+pub(super) fn map_axis_scroll(
+    horizontal: &AxisScroll,
+    vertical: &AxisScroll,
+    source: Option<AxisSource>,
+) -> ScrollDelta {
+    const WHEEL_STEP_PX: f64 = 15.0;
+
+    let steps = |axis: &AxisScroll| {
+        if axis.value120 != 0 {
+            axis.value120 as f32 / 120.0
+        } else {
+            axis.discrete as f32
+        }
+    };
+    let (x, y) = (steps(horizontal), steps(vertical));
+    if x != 0.0 || y != 0.0 {
+        ScrollDelta::Lines(Position::new(-x, -y))
+    } else if matches!(source, Some(AxisSource::Wheel | AxisSource::WheelTilt)) {
+        ScrollDelta::Lines(Position::new(
+            (-horizontal.absolute / WHEEL_STEP_PX) as f32,
+            (-vertical.absolute / WHEEL_STEP_PX) as f32,
+        ))
+    } else {
+        ScrollDelta::Pixels(Position::new(
+            -horizontal.absolute as f32,
+            -vertical.absolute as f32,
+        ))
+    }
+}
+
+/// Reads a `text/uri-list` offer to its end and keeps the local files in it. Blocks until the
+/// source has written everything, so the request has to be flushed to it first.
+pub(super) fn read_uri_list(conn: &Connection, mut pipe: ReadPipe) -> Option<Vec<PathBuf>> {
+    conn.flush().ok()?;
+    let mut list = String::new();
+    pipe.read_to_string(&mut list).ok()?;
+
+    let paths = list
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|uri| uri.strip_prefix("file://"))
+        // Skips the host part, `localhost` or empty.
+        .filter_map(|rest| rest.find('/').map(|i| &rest[i..]))
+        .map(|path| PathBuf::from(OsString::from_vec(percent_decode(path))))
+        .collect();
+    Some(paths)
+}
+
+/// Reads a text offer to its end, with the same flushing and blocking as [`read_uri_list`].
+pub(super) fn read_text(conn: &Connection, mut pipe: ReadPipe) -> Option<String> {
+    conn.flush().ok()?;
+    let mut bytes = Vec::new();
+    pipe.read_to_end(&mut bytes).ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn percent_decode(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(b) => {
+                out.push(b);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+pub(super) fn map_modifiers(m: &Modifiers) -> crate::event::Modifiers {
+    crate::event::Modifiers {
+        shift: m.shift,
+        control: m.ctrl,
+        alt: m.alt,
+        super_: m.logo,
+        caps_lock: Some(m.caps_lock),
+        num_lock: Some(m.num_lock),
+    }
+}
+
+/// Like `EventQueue::blocking_dispatch`, but stops waiting for the compositor at `deadline`.
+/// `poller` must watch the connection's fd.
+pub(super) fn dispatch_until(
+    event_queue: &mut EventQueue<SctkState>,
+    state: &mut SctkState,
+    poller: &mut calloop::EventLoop<'_, ()>,
+    deadline: Instant,
+) -> anyhow::Result<()> {
+    if event_queue.dispatch_pending(state)? > 0 {
+        return Ok(());
+    }
+    event_queue.flush()?;
+
+    if let Some(guard) = event_queue.prepare_read() {
+        poller.dispatch(deadline.saturating_duration_since(Instant::now()), &mut ())?;
+        match guard.read() {
+            Ok(_) => {}
+            Err(WaylandError::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    event_queue.dispatch_pending(state)?;
+    Ok(())
+}
@@ -2,23 +2,30 @@ use std::{
     any::Any,
     collections::HashMap,
     fmt::Debug,
+    path::PathBuf,
     ptr::NonNull,
     sync::{Arc, Mutex, atomic::AtomicBool},
+    time::Instant,
 };
 
 use crate::{
-    event::{Event, KeyEvent, KeyLocation, KeyState, Modifiers, PhysicalKey, ToEvent},
+    event::{
+        Event, KeyEvent, KeyLocation, KeyState, MouseButton, PhysicalKey, Preedit, ScrollDelta,
+        TextInput, ToEvent, TouchPhase,
+    },
     graphics::{Engine, TargetId},
     model::{Position, Size},
     render::PipelineFactoryFn,
     widget::Element,
 };
+use calloop::{Interest, Mode, PostAction, generic::Generic};
 use smithay_client_toolkit::{
     compositor::CompositorState,
+    data_device_manager::DataDeviceManagerState,
     output::OutputState,
     reexports::client::{Connection, QueueHandle, globals::registry_queue_init},
     registry::RegistryState,
-    seat::SeatState,
+    seat::{SeatState, pointer::cursor_shape::CursorShapeManager},
     session_lock::SessionLockState,
     shell::{wlr_layer::LayerShell, xdg::XdgShell},
 };
@@ -131,9 +138,35 @@ pub enum SctkEvent {
     },
     PointerDown {
         surface: SurfaceId,
+        button: MouseButton,
     },
     PointerUp {
         surface: SurfaceId,
+        button: MouseButton,
+    },
+    Wheel {
+        surface: SurfaceId,
+        delta: ScrollDelta,
+        phase: TouchPhase,
+    },
+    FileHover {
+        surface: SurfaceId,
+        path: PathBuf,
+    },
+    FileDrop {
+        surface: SurfaceId,
+        path: PathBuf,
+    },
+    FileHoverCancelled {
+        surface: SurfaceId,
+    },
+    ImePreedit {
+        surface: SurfaceId,
+        preedit: Preedit,
+    },
+    ImeCommit {
+        surface: SurfaceId,
+        text: String,
     },
 
     Key {
@@ -143,6 +176,7 @@ pub enum SctkEvent {
         utf8: Option<String>,
         pressed: bool,
         repeat: bool,
+        modifiers: smithay_client_toolkit::seat::keyboard::Modifiers,
     },
 
     Modifiers(SurfaceId, smithay_client_toolkit::seat::keyboard::Modifiers),
@@ -159,8 +193,14 @@ impl SctkEvent {
         match self {
             SctkEvent::Resized { surface, .. }
             | SctkEvent::PointerMoved { surface, .. }
-            | SctkEvent::PointerDown { surface }
-            | SctkEvent::PointerUp { surface }
+            | SctkEvent::PointerDown { surface, .. }
+            | SctkEvent::PointerUp { surface, .. }
+            | SctkEvent::Wheel { surface, .. }
+            | SctkEvent::FileHover { surface, .. }
+            | SctkEvent::FileDrop { surface, .. }
+            | SctkEvent::FileHoverCancelled { surface }
+            | SctkEvent::ImePreedit { surface, .. }
+            | SctkEvent::ImeCommit { surface, .. }
             | SctkEvent::Key { surface, .. }
             | SctkEvent::Modifiers(surface, ..) => Some(*surface),
             _ => None,
@@ -174,8 +214,23 @@ impl<M: 'static + Send> ToEvent<M, SctkEvent> for SctkEvent {
             SctkEvent::Redraw => Event::RedrawRequested,
             SctkEvent::Resized { size, .. } => Event::Resized { size: *size },
             SctkEvent::PointerMoved { pos, .. } => Event::CursorMoved { position: *pos },
-            SctkEvent::PointerDown { .. } => Event::MouseInput { mouse_down: true },
-            SctkEvent::PointerUp { .. } => Event::MouseInput { mouse_down: false },
+            SctkEvent::PointerDown { button, .. } => Event::MouseInput {
+                button: *button,
+                mouse_down: true,
+            },
+            SctkEvent::PointerUp { button, .. } => Event::MouseInput {
+                button: *button,
+                mouse_down: false,
+            },
+            SctkEvent::Wheel { delta, phase, .. } => Event::MouseWheel {
+                delta: *delta,
+                phase: *phase,
+            },
+            SctkEvent::FileHover { path, .. } => Event::FileHover(path.clone()),
+            SctkEvent::FileDrop { path, .. } => Event::FileDrop(path.clone()),
+            SctkEvent::FileHoverCancelled { .. } => Event::FileHoverCancelled,
+            SctkEvent::ImePreedit { preedit, .. } => Event::ImePreedit(preedit.clone()),
+            SctkEvent::ImeCommit { text, .. } => Event::Text(TextInput { text: text.clone() }),
 
             SctkEvent::Key {
                 raw_code,
@@ -183,6 +238,7 @@ impl<M: 'static + Send> ToEvent<M, SctkEvent> for SctkEvent {
                 utf8,
                 pressed,
                 repeat,
+                modifiers,
                 ..
             } => {
                 let state = if *pressed {
@@ -199,18 +255,11 @@ impl<M: 'static + Send> ToEvent<M, SctkEvent> for SctkEvent {
                     logical_key,
                     physical_key,
                     location: KeyLocation::Standard,
-                    modifiers: Modifiers::default(),
+                    modifiers: helpers::map_modifiers(modifiers),
                 })
             }
 
-            SctkEvent::Modifiers(_, m) => Event::ModifiersChanged(Modifiers {
-                shift: m.shift,
-                control: m.ctrl,
-                alt: m.alt,
-                super_: m.logo,
-                caps_lock: Some(m.caps_lock),
-                num_lock: Some(m.num_lock),
-            }),
+            SctkEvent::Modifiers(_, m) => Event::ModifiersChanged(helpers::map_modifiers(m)),
 
             SctkEvent::Closed => Event::Platform(SctkEvent::Closed),
 
@@ -297,7 +346,7 @@ where
     U: FnMut(TargetId, &mut Engine<'a, M>, &Event<M, SctkEvent>, &mut S, &SctkLoop) -> bool
         + 'static,
     H: handler::SctkHandler<M> + 'static,
-    F: FnOnce(&mut Engine<'a, M>),
+    F: FnOnce(&mut Engine<'a, M>) -> crate::Result<()>,
 {
     // 1) Wayland connection + queue
     let conn = Connection::connect_to_env()?;
@@ -350,6 +399,15 @@ where
             )?
         }
     };
+    if let Ok(data_device_manager) = DataDeviceManagerState::bind(&globals, &qh) {
+        st.set_data_device_manager(data_device_manager);
+    }
+    if let Ok(text_input_manager) = globals.bind(&qh, 1..=1, ()) {
+        st.set_text_input_manager(text_input_manager);
+    }
+    if let Ok(cursor_shape_manager) = CursorShapeManager::bind(&globals, &qh) {
+        st.set_cursor_shape_manager(cursor_shape_manager);
+    }
 
     // 4) Create engine and attach surfaces
     let mut sid_to_tid = HashMap::new();
@@ -360,13 +418,13 @@ where
             .next()
             .expect("At least one surface required");
         let target = Arc::new(RawWaylandHandles::new(&conn, &st.surfaces[sid].wl_surface));
-        let (tid, mut engine) = Engine::new_for(target, st.surfaces[sid].size);
-        post_engine_init(&mut engine);
+        let (tid, mut engine) = Engine::new_for(target, st.surfaces[sid].size)?;
+        post_engine_init(&mut engine)?;
         sid_to_tid.insert(*sid, tid);
 
         for (&sid, rec) in st.surfaces.iter().skip(1) {
             let target = Arc::new(RawWaylandHandles::new(&conn, &rec.wl_surface));
-            let tid = engine.attach_target(target, rec.size);
+            let tid = engine.attach_target(target, rec.size)?;
             sid_to_tid.insert(sid, tid);
         }
         engine
@@ -374,15 +432,37 @@ where
 
     let loop_ctl = SctkLoop::default();
 
+    // Wakes the loop for the compositor while a held key waits to repeat.
+    let mut poller = calloop::EventLoop::<()>::try_new()?;
+    poller
+        .handle()
+        .insert_source(
+            Generic::new(conn.clone(), Interest::READ, Mode::Level),
+            |_, _, _| Ok(PostAction::Continue),
+        )
+        .map_err(|e| e.error)?;
+
     // 5) Main loop
     while !loop_ctl.should_exit() && !st.closed {
-        event_queue.blocking_dispatch(&mut st)?;
+        // Wakes for whichever comes first of a key repeat and a target's next frame.
+        let deadline = sid_to_tid
+            .values()
+            .filter_map(|tid| engine.next_frame(tid, None))
+            .chain(st.key_repeat_deadline())
+            .min();
+        match deadline {
+            Some(deadline) => {
+                helpers::dispatch_until(&mut event_queue, &mut st, &mut poller, deadline)?
+            }
+            None => _ = event_queue.blocking_dispatch(&mut st)?,
+        }
+        st.repeat_keys();
 
         while let Ok(ev) = rx.try_recv() {
             match ev.surface_id() {
                 Some(sid) => {
                     if let Some(tid) = sid_to_tid.get(&sid).copied() {
-                        engine.handle_platform_event(
+                        let _ = engine.handle_platform_event(
                             &tid,
                             &ev,
                             &mut |eng, e, s, ctl| update(tid, eng, e, s, ctl),
@@ -393,7 +473,7 @@ where
                 }
                 None => {
                     for &tid in sid_to_tid.values() {
-                        engine.handle_platform_event(
+                        let _ = engine.handle_platform_event(
                             &tid,
                             &ev,
                             &mut |engine, event, state, loop_ctl| {
@@ -407,9 +487,15 @@ where
             }
         }
 
-        for (_, &tid) in sid_to_tid.iter() {
+        let now = Instant::now();
+        for (&sid, &tid) in sid_to_tid.iter() {
+            let due = engine
+                .next_frame(&tid, None)
+                .is_some_and(|next| next <= now);
             let need = if st.needs_redraw {
                 true
+            } else if !due {
+                continue;
             } else {
                 engine.poll(
                     &tid,
@@ -418,7 +504,22 @@ where
                     &loop_ctl,
                 )
             };
-            engine.render_if_needed(&tid, need, &view, &mut state);
+            // Other failures only cost a frame.
+            if let Err(e @ crate::Error::Surface(wgpu::SurfaceError::OutOfMemory)) =
+                engine.render_if_needed(&tid, need, &view, &mut state)
+            {
+                return Err(e.into());
+            }
+            st.set_ime_area(sid, engine.ime_area(&tid));
+            st.set_cursor(sid, engine.cursor(&tid));
+            if let Some(text) = engine.take_copied(&tid) {
+                st.set_clipboard(&qh, text);
+            }
+            if engine.take_paste_request(&tid)
+                && let Some(text) = st.clipboard_text(&conn)
+            {
+                engine.paste(&tid, &text);
+            }
         }
         st.needs_redraw = false;
     }
@@ -439,7 +540,7 @@ where
     U: FnMut(TargetId, &mut Engine<'a, M>, &Event<M, SctkEvent>, &mut S, &SctkLoop) -> bool
         + 'static,
 {
-    run_app_core::<M, S, V, U, H, _>(state, view, update, Options::Layer(opts), |_| {})
+    run_app_core::<M, S, V, U, H, _>(state, view, update, Options::Layer(opts), |_| Ok(()))
 }
 
 pub fn run_layer_with<'a, M, S, H, V, U, I>(
@@ -461,8 +562,9 @@ where
 
     run_app_core::<M, S, V, U, H, _>(state, view, update, Options::Layer(opts), move |engine| {
         for (key, factory) in pipelines {
-            engine.register_pipeline(crate::render::pipeline::PipelineKey::Other(key), factory);
+            engine.register_pipeline(crate::render::pipeline::PipelineKey::other(key), factory)?;
         }
+        Ok(())
     })
 }
 
@@ -479,7 +581,7 @@ where
     U: FnMut(TargetId, &mut Engine<'a, M>, &Event<M, SctkEvent>, &mut S, &SctkLoop) -> bool
         + 'static,
 {
-    run_app_core::<M, S, V, U, H, _>(state, view, update, Options::Xdg(opts), |_| {})
+    run_app_core::<M, S, V, U, H, _>(state, view, update, Options::Xdg(opts), |_| Ok(()))
 }
 
 pub fn run_app_with<'a, M, S, H, V, U, I>(
@@ -501,7 +603,8 @@ where
 
     run_app_core::<M, S, V, U, H, _>(state, view, update, Options::Xdg(opts), move |engine| {
         for (key, factory) in pipelines.iter().copied() {
-            engine.register_pipeline(crate::render::pipeline::PipelineKey::Other(key), factory);
+            engine.register_pipeline(crate::render::pipeline::PipelineKey::other(key), factory)?;
         }
+        Ok(())
     })
 }
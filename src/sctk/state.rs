@@ -1,17 +1,39 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
 use smithay_client_toolkit::{
     compositor::{CompositorHandler, CompositorState},
-    delegate_compositor, delegate_keyboard, delegate_layer, delegate_output, delegate_pointer,
-    delegate_registry, delegate_seat, delegate_session_lock, delegate_xdg_shell,
+    data_device_manager::{
+        DataDeviceManagerState, WritePipe,
+        data_device::{DataDevice, DataDeviceData, DataDeviceHandler},
+        data_offer::{DataOfferHandler, DragOffer},
+        data_source::{CopyPasteSource, DataSourceHandler},
+    },
+    delegate_compositor, delegate_data_device, delegate_keyboard, delegate_layer, delegate_output,
+    delegate_pointer, delegate_registry, delegate_seat, delegate_session_lock, delegate_xdg_shell,
     delegate_xdg_window,
     output::{OutputHandler, OutputState},
-    reexports::calloop::channel as loop_channel,
+    reexports::{
+        calloop::channel as loop_channel,
+        protocols::wp::{
+            cursor_shape::v1::client::wp_cursor_shape_device_v1::WpCursorShapeDeviceV1,
+            text_input::zv3::client::{
+                zwp_text_input_manager_v3::ZwpTextInputManagerV3,
+                zwp_text_input_v3::{self, ContentHint, ContentPurpose, ZwpTextInputV3},
+            },
+        },
+    },
     registry::{ProvidesRegistryState, RegistryState},
     seat::{
         Capability, SeatHandler, SeatState,
-        keyboard::{KeyEvent, KeyboardHandler, Keysym, Modifiers, RawModifiers},
-        pointer::{PointerEvent, PointerEventKind, PointerHandler},
+        keyboard::{KeyEvent, KeyboardHandler, Keysym, Modifiers, RawModifiers, RepeatInfo},
+        pointer::{
+            PointerEvent, PointerEventKind, PointerHandler, cursor_shape::CursorShapeManager,
+        },
     },
     session_lock::{SessionLock, SessionLockHandler, SessionLockState, SessionLockSurface},
     shell::{
@@ -24,14 +46,21 @@ use smithay_client_toolkit::{
     },
 };
 use wayland_client::{
-    Connection, Proxy, QueueHandle,
+    Connection, Dispatch, Proxy, QueueHandle,
     protocol::{
-        wl_keyboard::WlKeyboard, wl_output::WlOutput, wl_pointer::WlPointer, wl_seat::WlSeat,
+        wl_data_device::WlDataDevice,
+        wl_data_device_manager::DndAction,
+        wl_data_source::WlDataSource,
+        wl_keyboard::WlKeyboard,
+        wl_output::WlOutput,
+        wl_pointer::{AxisSource, WlPointer},
+        wl_seat::WlSeat,
         wl_surface::WlSurface,
     },
 };
 
 use crate::{
+    event::{CursorIcon, Preedit, TouchPhase},
     model::{Position, Size},
     sctk::{LayerOptions, OutputSelector, OutputSet, SurfaceId, XdgOptions},
 };
@@ -51,6 +80,40 @@ pub struct SurfaceRec {
     pub size: Size<u32>,
 }
 
+// Files being dragged over one of our surfaces.
+struct Drag {
+    surface: SurfaceId,
+    paths: Vec<PathBuf>,
+}
+
+// Key held on a focused surface, repeated client side at its seat's rate.
+struct KeyRepeat {
+    keyboard: WlKeyboard,
+    surface: SurfaceId,
+    event: KeyEvent,
+    gap: Duration,
+    next: Instant,
+}
+
+// Cursor shape device of a pointer, and the surface it entered with the serial of the enter.
+struct CursorDevice {
+    device: WpCursorShapeDeviceV1,
+    focus: Option<(SurfaceId, u32)>,
+    shape: Option<CursorIcon>,
+}
+
+// Input method state shared by the seats' text inputs.
+#[derive(Default)]
+struct ImeState {
+    // Surface the text inputs entered, and the caret rects surfaces asked for.
+    surface: Option<SurfaceId>,
+    areas: HashMap<SurfaceId, (Position<i32>, Size<i32>)>,
+    enabled: bool,
+    // Composition and committed text sent since the last `done`.
+    preedit: Option<Preedit>,
+    commit: Option<String>,
+}
+
 pub struct SctkState {
     // sctk state objects
     registry: RegistryState,
@@ -65,6 +128,31 @@ pub struct SctkState {
     pub surfaces: HashMap<SurfaceId, SurfaceRec>,
     by_surface_id: HashMap<u32, SurfaceId>,
     kbd_focus: Option<SurfaceId>,
+    // Whether a touchpad scroll gesture is under way.
+    finger_scroll: bool,
+    // Latest modifiers of each seat, by the protocol id of its keyboard.
+    modifiers: HashMap<u32, Modifiers>,
+    // Repeat rate and delay of each seat, keyed like `modifiers`.
+    repeat_info: HashMap<u32, RepeatInfo>,
+    key_repeat: Option<KeyRepeat>,
+
+    // cursor shapes, by the protocol id of each pointer, and those surfaces asked for
+    cursor_shape_manager: Option<CursorShapeManager>,
+    cursor_devices: HashMap<u32, CursorDevice>,
+    cursors: HashMap<SurfaceId, CursorIcon>,
+
+    // drag and drop
+    data_device_manager: Option<DataDeviceManagerState>,
+    data_devices: Vec<DataDevice>,
+    drag: Option<Drag>,
+    // What we offer as the clipboard selection, and the latest input serial to set it with.
+    selection: Option<(CopyPasteSource, String)>,
+    input_serial: u32,
+
+    // input methods
+    text_input_manager: Option<ZwpTextInputManagerV3>,
+    text_inputs: Vec<ZwpTextInputV3>,
+    ime: ImeState,
 
     // event queue for the generic runner
     handler: Box<dyn SctkErased>,
@@ -98,6 +186,24 @@ impl SctkState {
             surfaces: HashMap::new(),
             by_surface_id: HashMap::new(),
             kbd_focus: None,
+            finger_scroll: false,
+            modifiers: HashMap::new(),
+            repeat_info: HashMap::new(),
+            key_repeat: None,
+
+            cursor_shape_manager: None,
+            cursor_devices: HashMap::new(),
+            cursors: HashMap::new(),
+
+            data_device_manager: None,
+            data_devices: Vec::new(),
+            drag: None,
+            selection: None,
+            input_serial: 0,
+
+            text_input_manager: None,
+            text_inputs: Vec::new(),
+            ime: ImeState::default(),
 
             handler,
             event_tx,
@@ -180,6 +286,24 @@ impl SctkState {
             surfaces,
             by_surface_id,
             kbd_focus: None,
+            finger_scroll: false,
+            modifiers: HashMap::new(),
+            repeat_info: HashMap::new(),
+            key_repeat: None,
+
+            cursor_shape_manager: None,
+            cursor_devices: HashMap::new(),
+            cursors: HashMap::new(),
+
+            data_device_manager: None,
+            data_devices: Vec::new(),
+            drag: None,
+            selection: None,
+            input_serial: 0,
+
+            text_input_manager: None,
+            text_inputs: Vec::new(),
+            ime: ImeState::default(),
 
             handler,
             event_tx,
@@ -242,6 +366,24 @@ impl SctkState {
             surfaces,
             by_surface_id,
             kbd_focus: None,
+            finger_scroll: false,
+            modifiers: HashMap::new(),
+            repeat_info: HashMap::new(),
+            key_repeat: None,
+
+            cursor_shape_manager: None,
+            cursor_devices: HashMap::new(),
+            cursors: HashMap::new(),
+
+            data_device_manager: None,
+            data_devices: Vec::new(),
+            drag: None,
+            selection: None,
+            input_serial: 0,
+
+            text_input_manager: None,
+            text_inputs: Vec::new(),
+            ime: ImeState::default(),
             handler,
             event_tx,
             closed: false,
@@ -249,6 +391,143 @@ impl SctkState {
         })
     }
 
+    pub fn set_data_device_manager(&mut self, manager: DataDeviceManagerState) {
+        self.data_device_manager = Some(manager);
+    }
+
+    pub fn set_cursor_shape_manager(&mut self, manager: CursorShapeManager) {
+        self.cursor_shape_manager = Some(manager);
+    }
+
+    /// Shows `icon` whenever a pointer is over `sid`. Needs the cursor shape protocol;
+    /// without it the compositor's default cursor stays.
+    pub fn set_cursor(&mut self, sid: SurfaceId, icon: CursorIcon) {
+        if self.cursors.insert(sid, icon) != Some(icon) {
+            self.apply_cursors();
+        }
+    }
+
+    fn apply_cursors(&mut self) {
+        for cursor in self.cursor_devices.values_mut() {
+            let Some((sid, serial)) = cursor.focus else {
+                continue;
+            };
+            let icon = self.cursors.get(&sid).copied().unwrap_or_default();
+            if cursor.shape != Some(icon) {
+                cursor.device.set_shape(serial, helpers::map_cursor(icon));
+                cursor.shape = Some(icon);
+            }
+        }
+    }
+
+    pub fn set_text_input_manager(&mut self, manager: ZwpTextInputManagerV3) {
+        self.text_input_manager = Some(manager);
+    }
+
+    /// Enables input methods on `sid` around the caret rect `area`, or disables them.
+    pub fn set_ime_area(&mut self, sid: SurfaceId, area: Option<(Position<i32>, Size<i32>)>) {
+        let changed = match area {
+            Some(area) => self.ime.areas.insert(sid, area) != Some(area),
+            None => self.ime.areas.remove(&sid).is_some(),
+        };
+        if changed && self.ime.surface == Some(sid) {
+            self.sync_text_inputs();
+        }
+    }
+
+    fn sync_text_inputs(&mut self) {
+        let area = self.ime.surface.and_then(|sid| self.ime.areas.get(&sid));
+        for text_input in &self.text_inputs {
+            match area {
+                Some((pos, size)) => {
+                    if !self.ime.enabled {
+                        text_input.enable();
+                        text_input.set_content_type(ContentHint::None, ContentPurpose::Normal);
+                    }
+                    text_input.set_cursor_rectangle(pos.x, pos.y, size.width, size.height);
+                    text_input.commit();
+                }
+                None if self.ime.enabled => {
+                    text_input.disable();
+                    text_input.commit();
+                }
+                None => {}
+            }
+        }
+        self.ime.enabled = area.is_some();
+    }
+
+    /// Offers `text` as the clipboard selection of every seat.
+    pub fn set_clipboard(&mut self, qh: &QueueHandle<Self>, text: String) {
+        let Some(manager) = &self.data_device_manager else {
+            return;
+        };
+        let source = manager.create_copy_paste_source(qh, TEXT_MIMES);
+        for device in &self.data_devices {
+            source.set_selection(device, self.input_serial);
+        }
+        self.selection = Some((source, text));
+    }
+
+    /// Text of the current clipboard selection. Blocks until its source has written it.
+    pub fn clipboard_text(&self, conn: &Connection) -> Option<String> {
+        // Reading our own offer would wait on ourselves to answer it.
+        if let Some((_, text)) = &self.selection {
+            return Some(text.clone());
+        }
+        let offer = self
+            .data_devices
+            .iter()
+            .find_map(|device| device.data().selection_offer())?;
+        let mime = offer.with_mime_types(|types| {
+            TEXT_MIMES
+                .iter()
+                .find(|mime| types.iter().any(|t| t == *mime))
+                .map(|mime| mime.to_string())
+        })?;
+        let pipe = offer.receive(mime).ok()?;
+        helpers::read_text(conn, pipe)
+    }
+
+    fn modifiers_of(&self, keyboard: &WlKeyboard) -> Modifiers {
+        self.modifiers
+            .get(&keyboard.id().protocol_id())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// When the held key repeats next, if any.
+    pub fn key_repeat_deadline(&self) -> Option<Instant> {
+        self.key_repeat.as_ref().map(|r| r.next)
+    }
+
+    /// Emits a repeat of the held key once its deadline has passed.
+    pub fn repeat_keys(&mut self) {
+        let now = Instant::now();
+        let Some(repeat) = self.key_repeat.as_mut().filter(|r| r.next <= now) else {
+            return;
+        };
+        repeat.next += repeat.gap;
+        if repeat.next < now {
+            repeat.next = now + repeat.gap;
+        }
+
+        let ev = SctkEvent::Key {
+            surface: repeat.surface,
+            raw_code: repeat.event.raw_code,
+            keysym: repeat.event.keysym,
+            utf8: repeat.event.utf8.clone(),
+            pressed: true,
+            repeat: true,
+            modifiers: self
+                .modifiers
+                .get(&repeat.keyboard.id().protocol_id())
+                .copied()
+                .unwrap_or_default(),
+        };
+        self.emit_event(ev);
+    }
+
     fn emit_event(&self, ev: SctkEvent) {
         let _ = self.event_tx.send(ev);
     }
@@ -261,9 +540,13 @@ impl SctkState {
     pub fn remove_surface_by_surface_id(&mut self, sid: SurfaceId) {
         if let Some(sid) = self.by_surface_id.remove(&sid.0) {
             self.surfaces.remove(&sid);
+            self.cursors.remove(&sid);
             if self.kbd_focus == Some(sid) {
                 self.kbd_focus = None;
             }
+            if self.key_repeat.as_ref().is_some_and(|r| r.surface == sid) {
+                self.key_repeat = None;
+            }
         }
     }
 
@@ -566,9 +849,13 @@ impl SessionLockHandler for SctkState {
         {
             self.surfaces.remove(&sid);
             self.by_surface_id.remove(&key);
+            self.cursors.remove(&sid);
             if self.kbd_focus == Some(sid) {
                 self.kbd_focus = None;
             }
+            if self.key_repeat.as_ref().is_some_and(|r| r.surface == sid) {
+                self.key_repeat = None;
+            }
         }
         self.handler.finished(conn, qh, session_lock);
     }
@@ -623,10 +910,27 @@ impl SeatHandler for SctkState {
     ) {
         match cap {
             Capability::Pointer => {
-                _ = self.seats.get_pointer(qh, &seat);
+                if let Ok(pointer) = self.seats.get_pointer(qh, &seat)
+                    && let Some(manager) = &self.cursor_shape_manager
+                {
+                    self.cursor_devices.insert(
+                        pointer.id().protocol_id(),
+                        CursorDevice {
+                            device: manager.get_shape_device(&pointer, qh),
+                            focus: None,
+                            shape: None,
+                        },
+                    );
+                }
+                if let Some(manager) = &self.data_device_manager {
+                    self.data_devices.push(manager.get_data_device(qh, &seat));
+                }
             }
             Capability::Keyboard => {
                 _ = self.seats.get_keyboard(qh, &seat, None);
+                if let Some(manager) = &self.text_input_manager {
+                    self.text_inputs.push(manager.get_text_input(&seat, qh, ()));
+                }
             }
             _ => { /* Not supported atm */ }
         }
@@ -647,9 +951,10 @@ impl PointerHandler for SctkState {
         &mut self,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
-        _pointer: &WlPointer,
+        pointer: &WlPointer,
         events: &[PointerEvent],
     ) {
+        let pointer_id = pointer.id().protocol_id();
         for ev in events {
             let sid = match self.by_surface_id.get(&ev.surface.id().protocol_id()) {
                 Some(&sid) => sid,
@@ -657,8 +962,19 @@ impl PointerHandler for SctkState {
             };
 
             match ev.kind {
-                PointerEventKind::Enter { .. } => {}
-                PointerEventKind::Leave { .. } => {}
+                // Entering resets the cursor, so the surface's shape is set again.
+                PointerEventKind::Enter { serial } => {
+                    if let Some(cursor) = self.cursor_devices.get_mut(&pointer_id) {
+                        cursor.focus = Some((sid, serial));
+                        cursor.shape = None;
+                        self.apply_cursors();
+                    }
+                }
+                PointerEventKind::Leave { .. } => {
+                    if let Some(cursor) = self.cursor_devices.get_mut(&pointer_id) {
+                        cursor.focus = None;
+                    }
+                }
                 PointerEventKind::Motion { .. } => {
                     let (x, y) = ev.position;
                     self.emit_event(SctkEvent::PointerMoved {
@@ -666,13 +982,39 @@ impl PointerHandler for SctkState {
                         pos: Position::new(x as f32, y as f32),
                     });
                 }
-                PointerEventKind::Press { .. } => {
-                    self.emit_event(SctkEvent::PointerDown { surface: sid })
+                PointerEventKind::Press { button, serial, .. } => {
+                    self.input_serial = serial;
+                    self.emit_event(SctkEvent::PointerDown {
+                        surface: sid,
+                        button: helpers::map_pointer_button(button),
+                    })
                 }
-                PointerEventKind::Release { .. } => {
-                    self.emit_event(SctkEvent::PointerUp { surface: sid })
+                PointerEventKind::Release { button, .. } => self.emit_event(SctkEvent::PointerUp {
+                    surface: sid,
+                    button: helpers::map_pointer_button(button),
+                }),
+                PointerEventKind::Axis {
+                    horizontal,
+                    vertical,
+                    source,
+                    ..
+                } => {
+                    // Only finger scrolling has a start and a stop, which momentum relies on.
+                    let phase = if horizontal.stop || vertical.stop {
+                        self.finger_scroll = false;
+                        TouchPhase::Ended
+                    } else if source == Some(AxisSource::Finger) && !self.finger_scroll {
+                        self.finger_scroll = true;
+                        TouchPhase::Started
+                    } else {
+                        TouchPhase::Moved
+                    };
+                    self.emit_event(SctkEvent::Wheel {
+                        surface: sid,
+                        delta: helpers::map_axis_scroll(&horizontal, &vertical, source),
+                        phase,
+                    });
                 }
-                PointerEventKind::Axis { .. } => {}
             }
         }
     }
@@ -701,16 +1043,18 @@ impl KeyboardHandler for SctkState {
         _serial: u32,
     ) {
         self.kbd_focus = None;
+        self.key_repeat = None;
     }
 
     fn press_key(
         &mut self,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
-        _keyboard: &WlKeyboard,
-        _serial: u32,
+        keyboard: &WlKeyboard,
+        serial: u32,
         event: KeyEvent,
     ) {
+        self.input_serial = serial;
         if let Some(sid) = self.kbd_focus {
             self.emit_event(SctkEvent::Key {
                 surface: sid,
@@ -719,7 +1063,21 @@ impl KeyboardHandler for SctkState {
                 utf8: event.utf8.clone(),
                 pressed: true,
                 repeat: false,
+                modifiers: self.modifiers_of(keyboard),
             });
+
+            if let Some(&RepeatInfo::Repeat { rate, delay }) =
+                self.repeat_info.get(&keyboard.id().protocol_id())
+                && !event.keysym.is_modifier_key()
+            {
+                self.key_repeat = Some(KeyRepeat {
+                    keyboard: keyboard.clone(),
+                    surface: sid,
+                    event,
+                    gap: Duration::from_micros(1_000_000 / rate.get() as u64),
+                    next: Instant::now() + Duration::from_millis(delay as u64),
+                });
+            }
         }
     }
 
@@ -727,10 +1085,17 @@ impl KeyboardHandler for SctkState {
         &mut self,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
-        _keyboard: &WlKeyboard,
+        keyboard: &WlKeyboard,
         _serial: u32,
         event: KeyEvent,
     ) {
+        if self
+            .key_repeat
+            .as_ref()
+            .is_some_and(|r| &r.keyboard == keyboard && r.event.raw_code == event.raw_code)
+        {
+            self.key_repeat = None;
+        }
         if let Some(sid) = self.kbd_focus {
             self.emit_event(SctkEvent::Key {
                 surface: sid,
@@ -739,6 +1104,7 @@ impl KeyboardHandler for SctkState {
                 utf8: None,
                 pressed: false,
                 repeat: false,
+                modifiers: self.modifiers_of(keyboard),
             });
         }
     }
@@ -747,7 +1113,7 @@ impl KeyboardHandler for SctkState {
         &mut self,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
-        _keyboard: &WlKeyboard,
+        keyboard: &WlKeyboard,
         _serial: u32,
         event: KeyEvent,
     ) {
@@ -759,32 +1125,324 @@ impl KeyboardHandler for SctkState {
                 utf8: event.utf8.clone(),
                 pressed: true,
                 repeat: true,
+                modifiers: self.modifiers_of(keyboard),
             });
         }
     }
 
+    fn update_repeat_info(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        keyboard: &WlKeyboard,
+        info: RepeatInfo,
+    ) {
+        if matches!(info, RepeatInfo::Disable)
+            && self
+                .key_repeat
+                .as_ref()
+                .is_some_and(|r| &r.keyboard == keyboard)
+        {
+            self.key_repeat = None;
+        }
+        self.repeat_info.insert(keyboard.id().protocol_id(), info);
+    }
+
     fn update_modifiers(
         &mut self,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
-        _keyboard: &wayland_client::protocol::wl_keyboard::WlKeyboard,
+        keyboard: &WlKeyboard,
         _serial: u32,
         modifiers: Modifiers,
         _raw_modifiers: RawModifiers,
         _layout: u32,
     ) {
+        self.modifiers
+            .insert(keyboard.id().protocol_id(), modifiers);
         if let Some(sid) = self.kbd_focus {
             self.emit_event(SctkEvent::Modifiers(sid, modifiers));
         }
     }
 }
 
+const URI_LIST: &str = "text/uri-list";
+const TEXT_MIMES: [&str; 5] = [
+    "text/plain;charset=utf-8",
+    "text/plain",
+    "UTF8_STRING",
+    "STRING",
+    "TEXT",
+];
+
+fn drag_offer(data_device: &WlDataDevice) -> Option<DragOffer> {
+    data_device.data::<DataDeviceData>()?.drag_offer()
+}
+
+impl DataDeviceHandler for SctkState {
+    fn enter(
+        &mut self,
+        conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        data_device: &WlDataDevice,
+        x: f64,
+        y: f64,
+        wl_surface: &WlSurface,
+    ) {
+        let Some(&sid) = self.by_surface_id.get(&wl_surface.id().protocol_id()) else {
+            return;
+        };
+        let Some(offer) = drag_offer(data_device) else {
+            return;
+        };
+        if !offer.with_mime_types(|types| types.iter().any(|t| t == URI_LIST)) {
+            offer.accept_mime_type(offer.serial, None);
+            return;
+        }
+        offer.accept_mime_type(offer.serial, Some(URI_LIST.to_string()));
+        offer.set_actions(DndAction::Copy, DndAction::Copy);
+
+        // Only files are accepted, so the list is read up front to report what hovers.
+        let paths = offer
+            .receive(URI_LIST.to_string())
+            .ok()
+            .and_then(|pipe| helpers::read_uri_list(conn, pipe))
+            .unwrap_or_default();
+        for path in &paths {
+            self.emit_event(SctkEvent::FileHover {
+                surface: sid,
+                path: path.clone(),
+            });
+        }
+        self.emit_event(SctkEvent::PointerMoved {
+            surface: sid,
+            pos: Position::new(x as f32, y as f32),
+        });
+        self.drag = Some(Drag {
+            surface: sid,
+            paths,
+        });
+    }
+
+    fn leave(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _data_device: &WlDataDevice) {
+        if let Some(drag) = self.drag.take() {
+            self.emit_event(SctkEvent::FileHoverCancelled {
+                surface: drag.surface,
+            });
+        }
+    }
+
+    fn motion(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _data_device: &WlDataDevice,
+        x: f64,
+        y: f64,
+    ) {
+        if let Some(drag) = &self.drag {
+            self.emit_event(SctkEvent::PointerMoved {
+                surface: drag.surface,
+                pos: Position::new(x as f32, y as f32),
+            });
+        }
+    }
+
+    fn selection(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _data_device: &WlDataDevice,
+    ) {
+    }
+
+    fn drop_performed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        data_device: &WlDataDevice,
+    ) {
+        // Taken so the leave that follows a drop doesn't cancel it.
+        let Some(drag) = self.drag.take() else {
+            return;
+        };
+        for path in drag.paths {
+            self.emit_event(SctkEvent::FileDrop {
+                surface: drag.surface,
+                path,
+            });
+        }
+        if let Some(offer) = drag_offer(data_device) {
+            offer.finish();
+            offer.destroy();
+        }
+    }
+}
+
+impl DataOfferHandler for SctkState {
+    fn source_actions(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _offer: &mut DragOffer,
+        _actions: DndAction,
+    ) {
+    }
+
+    fn selected_action(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _offer: &mut DragOffer,
+        _actions: DndAction,
+    ) {
+    }
+}
+
+// We never offer data ourselves, but the data device delegate needs a source handler.
+impl DataSourceHandler for SctkState {
+    fn accept_mime(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _source: &WlDataSource,
+        _mime: Option<String>,
+    ) {
+    }
+
+    fn send_request(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        source: &WlDataSource,
+        _mime: String,
+        mut fd: WritePipe,
+    ) {
+        if let Some((selection, text)) = &self.selection
+            && selection.inner() == source
+        {
+            _ = fd.write_all(text.as_bytes());
+        }
+    }
+
+    fn cancelled(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, source: &WlDataSource) {
+        // Something else was copied since.
+        if self
+            .selection
+            .as_ref()
+            .is_some_and(|(selection, _)| selection.inner() == source)
+        {
+            self.selection = None;
+        }
+    }
+
+    fn dnd_dropped(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _source: &WlDataSource) {
+    }
+
+    fn dnd_finished(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _source: &WlDataSource,
+    ) {
+    }
+
+    fn action(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _source: &WlDataSource,
+        _action: DndAction,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpTextInputManagerV3, ()> for SctkState {
+    fn event(
+        _state: &mut Self,
+        _manager: &ZwpTextInputManagerV3,
+        _event: <ZwpTextInputManagerV3 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpTextInputV3, ()> for SctkState {
+    fn event(
+        state: &mut Self,
+        _text_input: &ZwpTextInputV3,
+        event: zwp_text_input_v3::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        use zwp_text_input_v3::Event as TI;
+
+        match event {
+            TI::Enter { surface } => {
+                state.ime.surface = state
+                    .by_surface_id
+                    .get(&surface.id().protocol_id())
+                    .copied();
+                // Entering resets the text input, it has to be enabled again.
+                state.ime.enabled = false;
+                state.sync_text_inputs();
+            }
+            TI::Leave { .. } => {
+                state.ime.enabled = false;
+                if let Some(sid) = state.ime.surface.take() {
+                    state.emit_event(SctkEvent::ImePreedit {
+                        surface: sid,
+                        preedit: Preedit {
+                            text: String::new(),
+                            cursor: None,
+                        },
+                    });
+                }
+            }
+            TI::PreeditString {
+                text,
+                cursor_begin,
+                cursor_end,
+            } => {
+                state.ime.preedit = Some(Preedit {
+                    text: text.unwrap_or_default(),
+                    cursor: (cursor_begin >= 0 && cursor_end >= 0)
+                        .then_some((cursor_begin as usize, cursor_end as usize)),
+                });
+            }
+            TI::CommitString { text } => state.ime.commit = text,
+            TI::Done { .. } => {
+                let Some(sid) = state.ime.surface else {
+                    return;
+                };
+                if let Some(text) = state.ime.commit.take() {
+                    state.emit_event(SctkEvent::ImeCommit { surface: sid, text });
+                }
+                // A done without a preedit string ends the composition.
+                let preedit = state.ime.preedit.take().unwrap_or(Preedit {
+                    text: String::new(),
+                    cursor: None,
+                });
+                state.emit_event(SctkEvent::ImePreedit {
+                    surface: sid,
+                    preedit,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
 delegate_registry!(SctkState);
 delegate_compositor!(SctkState);
 delegate_output!(SctkState);
 delegate_seat!(SctkState);
 delegate_pointer!(SctkState);
 delegate_keyboard!(SctkState);
+delegate_data_device!(SctkState);
 delegate_layer!(SctkState);
 delegate_session_lock!(SctkState);
 delegate_xdg_shell!(SctkState);
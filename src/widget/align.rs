@@ -0,0 +1,55 @@
+/// Where children of a [`Row`](super::Row) or [`Column`](super::Column) sit on its cross
+/// axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Start,
+    Center,
+    End,
+    /// Children that aren't fixed on the cross axis fill it. A [`Memo`](super::Memo) keeps
+    /// the size its content asks for.
+    Stretch,
+}
+
+impl Align {
+    /// Offset of a child with `free` cross-axis space left beside it.
+    pub fn offset(self, free: i32) -> i32 {
+        match self {
+            Align::Start | Align::Stretch => 0,
+            Align::Center => free.max(0) / 2,
+            Align::End => free.max(0),
+        }
+    }
+}
+
+/// How a [`Row`](super::Row) or [`Column`](super::Column) spreads space its children leave
+/// free along its main axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Justify {
+    #[default]
+    Start,
+    Center,
+    End,
+    /// Equal gaps between children, none at the ends.
+    SpaceBetween,
+    /// Equal space around each child, so the ends get half a gap.
+    SpaceAround,
+}
+
+impl Justify {
+    /// Offset added to each of `count` children when `free` main-axis space is left over.
+    pub fn offsets(self, free: i32, count: usize) -> Vec<i32> {
+        let free = free.max(0) as i64;
+        let n = count as i64;
+        (0..n)
+            .map(|i| match self {
+                Justify::Start => 0,
+                Justify::Center => free / 2,
+                Justify::End => free,
+                Justify::SpaceBetween if n > 1 => free * i / (n - 1),
+                Justify::SpaceBetween => 0,
+                Justify::SpaceAround => free * (2 * i + 1) / (2 * n),
+            } as i32)
+            .collect()
+    }
+}
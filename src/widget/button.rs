@@ -1,4 +1,5 @@
 use super::*;
+use crate::event::{KeyState, LogicalKey};
 
 pub struct Button<M> {
     layout: Option<Layout>,
@@ -11,9 +12,15 @@ pub struct Button<M> {
     normal_color: Color,
     hover_color: Color,
     pressed_color: Color,
+    shadow: Option<Shadow>,
+    radii: Vec4<f32>,
+    border: Option<Border>,
+    gradient: Option<Gradient>,
 
     hovered: bool,
     pressed: bool,
+    focused: bool,
+    focus_ring: FocusRing,
 
     min: Size<i32>,
     max: Size<i32>,
@@ -34,9 +41,15 @@ impl<M: Clone + 'static> Button<M> {
             normal_color: color,
             hover_color: color,
             pressed_color: color,
+            shadow: None,
+            radii: Vec4::splat(0.0),
+            border: None,
+            gradient: None,
 
             hovered: false,
             pressed: false,
+            focused: false,
+            focus_ring: FocusRing::default(),
 
             min: Size::splat(0),
             max: Size::splat(i32::MAX),
@@ -57,9 +70,15 @@ impl<M: Clone + 'static> Button<M> {
             normal_color: Color::TRANSPARENT,
             hover_color: Color::TRANSPARENT,
             pressed_color: Color::TRANSPARENT,
+            shadow: None,
+            radii: Vec4::splat(0.0),
+            border: None,
+            gradient: None,
 
             hovered: false,
             pressed: false,
+            focused: false,
+            focus_ring: FocusRing::default(),
 
             min: Size::splat(0),
             max: Size::splat(i32::MAX),
@@ -80,6 +99,33 @@ impl<M: Clone + 'static> Button<M> {
         self.pressed_color = c;
         self
     }
+    /// Rounds every corner by `radius` pixels.
+    pub fn radius(self, radius: f32) -> Self {
+        self.radii(Vec4::splat(radius))
+    }
+    /// Rounds each corner on its own: top-left, top-right, bottom-right, bottom-left.
+    pub fn radii(mut self, radii: Vec4<f32>) -> Self {
+        self.radii = radii;
+        self
+    }
+    pub fn border(mut self, border: Border) -> Self {
+        self.border = Some(border);
+        self
+    }
+    pub fn shadow(mut self, shadow: Shadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+    /// Fills the button with `gradient` in every state instead of its colors.
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+    /// Ring drawn while the button has keyboard focus.
+    pub fn focus_ring(mut self, ring: FocusRing) -> Self {
+        self.focus_ring = ring;
+        self
+    }
     pub fn size(mut self, size: Size<Length<i32>>) -> Self {
         self.size = size;
         self
@@ -118,6 +164,9 @@ impl<M: Clone + 'static> Widget<M> for Button<M> {
     fn layout(&self) -> &Layout {
         self.layout.as_ref().expect(LAYOUT_ERROR)
     }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
 
     fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
         if let Some(child) = &self.content {
@@ -125,6 +174,12 @@ impl<M: Clone + 'static> Widget<M> for Button<M> {
         }
     }
 
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        if let Some(child) = &mut self.content {
+            f(child.as_mut());
+        }
+    }
+
     fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
         let mut min_w = 0;
         if let Some(child) = self.content.as_mut() {
@@ -152,7 +207,7 @@ impl<M: Clone + 'static> Widget<M> for Button<M> {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
 
         let target_w = match self.size.width {
-            Length::Grow => parent_width,
+            Length::Grow(_) | Length::Percent(_) => parent_width,
             Length::Fixed(w) => w,
             Length::Fit => l.current_size.width,
         }
@@ -200,7 +255,7 @@ impl<M: Clone + 'static> Widget<M> for Button<M> {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
 
         let target_h = match self.size.height {
-            Length::Grow => parent_height,
+            Length::Grow(_) | Length::Percent(_) => parent_height,
             Length::Fixed(h) => h,
             Length::Fit => l.current_size.height,
         }
@@ -234,11 +289,22 @@ impl<M: Clone + 'static> Widget<M> for Button<M> {
             self.normal_color
         };
 
-        instances.push(Instance::ui(
-            self.position,
-            self.layout().current_size,
-            color,
-        ));
+        let size = self.layout().current_size;
+        if let Some(shadow) = &self.shadow {
+            instances.push(shadow.instance(self.position, size, self.radii));
+        }
+        match &self.gradient {
+            Some(gradient) => {
+                instances.push(gradient.instance(self.position, size).radii(self.radii))
+            }
+            None => instances.push(Instance::ui(self.position, size, color).radii(self.radii)),
+        }
+        if let Some(border) = &self.border {
+            border.push(self.position, size, self.radii, instances);
+        }
+        if self.focused {
+            instances.push(self.focus_ring.instance(self.position, size, self.radii));
+        }
     }
 
     fn handle(&mut self, ctx: &mut EventCtx<M>) {
@@ -246,10 +312,15 @@ impl<M: Clone + 'static> Widget<M> for Button<M> {
             child.handle(ctx);
         }
 
+        if self.on_press.is_some() {
+            ctx.ui.register_focusable(self.id);
+        }
+
         let was_hovered = self.hovered;
         let was_pressed = self.pressed;
 
-        let inside = self.contains(ctx.ui.mouse_pos);
+        let inside =
+            self.contains(ctx.ui.mouse_pos) && !ctx.ui.is_occluded(self.id, ctx.ui.mouse_pos);
         self.hovered = inside;
         if inside {
             ctx.ui.hot_item = Some(self.id);
@@ -267,6 +338,27 @@ impl<M: Clone + 'static> Widget<M> for Button<M> {
             ctx.ui.active_item = None;
         }
 
+        if ctx.ui.mouse_pressed && !inside && ctx.ui.is_focused(self.id) {
+            ctx.ui.blur();
+        }
+        self.focused = ctx.ui.is_focused(self.id);
+        if self.focused
+            && let Some(m) = self.on_press.clone()
+        {
+            let activations = ctx
+                .ui
+                .keys()
+                .iter()
+                .filter(|k| {
+                    k.state == KeyState::Pressed
+                        && matches!(k.logical_key, LogicalKey::Enter | LogicalKey::Space)
+                })
+                .count();
+            for _ in 0..activations {
+                ctx.ui.emit(m.clone());
+            }
+        }
+
         if self.hovered != was_hovered || self.pressed != was_pressed {
             ctx.ui.request_redraw();
         }
@@ -0,0 +1,335 @@
+use lyon::{
+    geom::{Angle, Arc, point, vector},
+    path::Path,
+    tessellation::{
+        BuffersBuilder, FillOptions, FillTessellator, FillVertex, StrokeOptions, StrokeTessellator,
+        StrokeVertex, VertexBuffers,
+    },
+};
+
+use super::*;
+
+#[derive(Debug, Clone, Copy)]
+enum Command {
+    MoveTo(Position<f32>),
+    LineTo(Position<f32>),
+    QuadTo(Position<f32>, Position<f32>),
+    CubicTo(Position<f32>, Position<f32>, Position<f32>),
+    Arc {
+        center: Position<f32>,
+        radius: f32,
+        start: f32,
+        sweep: f32,
+    },
+    Close,
+    Fill(Color),
+    Stroke(Color, f32),
+}
+
+/// Draws retained vector paths, tessellated into triangles. Paths are built with the
+/// `move_to`/`line_to`/`arc` family in coordinates local to the canvas and painted with
+/// `fill` and `stroke`. Painting keeps the path so it can be both filled and stroked; the
+/// next path command after painting starts a new one.
+pub struct Canvas {
+    layout: Option<Layout>,
+
+    id: Id,
+    position: Position<i32>,
+    size: Size<Length<i32>>,
+    commands: Vec<Command>,
+    tolerance: f32,
+
+    // Tessellated triangles, relative to the canvas.
+    triangles: Vec<([Position<f32>; 3], Color)>,
+}
+
+impl Canvas {
+    pub fn new(size: Size<Length<i32>>) -> Self {
+        Self {
+            layout: None,
+
+            id: crate::context::next_id(),
+            position: Position::splat(0),
+            size,
+            commands: Vec::new(),
+            tolerance: 0.1,
+
+            triangles: Vec::new(),
+        }
+    }
+
+    /// Starts a new subpath at `(x, y)`.
+    pub fn move_to(mut self, x: f32, y: f32) -> Self {
+        self.commands.push(Command::MoveTo(Position::new(x, y)));
+        self
+    }
+    pub fn line_to(mut self, x: f32, y: f32) -> Self {
+        self.commands.push(Command::LineTo(Position::new(x, y)));
+        self
+    }
+    pub fn quad_to(mut self, ctrl: Position<f32>, to: Position<f32>) -> Self {
+        self.commands.push(Command::QuadTo(ctrl, to));
+        self
+    }
+    pub fn cubic_to(
+        mut self,
+        ctrl1: Position<f32>,
+        ctrl2: Position<f32>,
+        to: Position<f32>,
+    ) -> Self {
+        self.commands.push(Command::CubicTo(ctrl1, ctrl2, to));
+        self
+    }
+    /// Arc around `center` from angle `start` sweeping by `sweep`, in radians clockwise from
+    /// the positive x axis. Connected to the current point by a straight line.
+    pub fn arc(mut self, center: Position<f32>, radius: f32, start: f32, sweep: f32) -> Self {
+        self.commands.push(Command::Arc {
+            center,
+            radius,
+            start,
+            sweep,
+        });
+        self
+    }
+    /// Axis-aligned rectangle as its own closed subpath.
+    pub fn rect(self, x: f32, y: f32, width: f32, height: f32) -> Self {
+        self.move_to(x, y)
+            .line_to(x + width, y)
+            .line_to(x + width, y + height)
+            .line_to(x, y + height)
+            .close()
+    }
+    /// Full circle as its own closed subpath.
+    pub fn circle(self, center: Position<f32>, radius: f32) -> Self {
+        self.move_to(center.x + radius, center.y)
+            .arc(center, radius, 0.0, std::f32::consts::TAU)
+            .close()
+    }
+    /// Connects the current subpath back to its start.
+    pub fn close(mut self) -> Self {
+        self.commands.push(Command::Close);
+        self
+    }
+    /// Fills the current path with the non-zero rule.
+    pub fn fill(mut self, color: Color) -> Self {
+        self.commands.push(Command::Fill(color));
+        self
+    }
+    /// Strokes the current path with lines `width` pixels wide.
+    pub fn stroke(mut self, color: Color, width: f32) -> Self {
+        self.commands.push(Command::Stroke(color, width));
+        self
+    }
+    /// Maximum distance in pixels between curves and their flattened approximation.
+    pub fn tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance.max(0.01);
+        self
+    }
+
+    fn tessellate(&mut self) {
+        self.triangles.clear();
+
+        let mut builder = Path::builder();
+        let mut open = false;
+        let mut painted = false;
+        let mut current = point(0.0, 0.0);
+
+        for &command in &self.commands {
+            if painted && !matches!(command, Command::Fill(_) | Command::Stroke(..)) {
+                builder = Path::builder();
+                open = false;
+                painted = false;
+            }
+            match command {
+                Command::MoveTo(p) => {
+                    if open {
+                        builder.end(false);
+                    }
+                    current = point(p.x, p.y);
+                    builder.begin(current);
+                    open = true;
+                }
+                Command::LineTo(p) => {
+                    current = point(p.x, p.y);
+                    if open {
+                        builder.line_to(current);
+                    } else {
+                        builder.begin(current);
+                        open = true;
+                    }
+                }
+                Command::QuadTo(c, p) => {
+                    if !open {
+                        builder.begin(current);
+                        open = true;
+                    }
+                    current = point(p.x, p.y);
+                    builder.quadratic_bezier_to(point(c.x, c.y), current);
+                }
+                Command::CubicTo(c1, c2, p) => {
+                    if !open {
+                        builder.begin(current);
+                        open = true;
+                    }
+                    current = point(p.x, p.y);
+                    builder.cubic_bezier_to(point(c1.x, c1.y), point(c2.x, c2.y), current);
+                }
+                Command::Arc {
+                    center,
+                    radius,
+                    start,
+                    sweep,
+                } => {
+                    let arc = Arc {
+                        center: point(center.x, center.y),
+                        radii: vector(radius, radius),
+                        start_angle: Angle::radians(start),
+                        sweep_angle: Angle::radians(sweep),
+                        x_rotation: Angle::zero(),
+                    };
+                    if open {
+                        builder.line_to(arc.from());
+                    } else {
+                        builder.begin(arc.from());
+                        open = true;
+                    }
+                    arc.for_each_quadratic_bezier(&mut |segment| {
+                        builder.quadratic_bezier_to(segment.ctrl, segment.to);
+                    });
+                    current = arc.to();
+                }
+                Command::Close => {
+                    if open {
+                        builder.end(true);
+                        open = false;
+                    }
+                }
+                Command::Fill(color) | Command::Stroke(color, _) => {
+                    // Paint a copy so the path can be painted again.
+                    let mut path = builder.clone();
+                    if open {
+                        path.end(false);
+                    }
+                    let path = path.build();
+                    let mut geometry: VertexBuffers<Position<f32>, u32> = VertexBuffers::new();
+                    let result = match command {
+                        Command::Fill(_) => FillTessellator::new().tessellate_path(
+                            &path,
+                            &FillOptions::tolerance(self.tolerance),
+                            &mut BuffersBuilder::new(&mut geometry, |v: FillVertex| {
+                                Position::new(v.position().x, v.position().y)
+                            }),
+                        ),
+                        _ => StrokeTessellator::new().tessellate_path(
+                            &path,
+                            &StrokeOptions::tolerance(self.tolerance).with_line_width(
+                                match command {
+                                    Command::Stroke(_, width) => width,
+                                    _ => 1.0,
+                                },
+                            ),
+                            &mut BuffersBuilder::new(&mut geometry, |v: StrokeVertex| {
+                                Position::new(v.position().x, v.position().y)
+                            }),
+                        ),
+                    };
+                    if result.is_ok() {
+                        for tri in geometry.indices.chunks_exact(3) {
+                            let corner = |i: u32| geometry.vertices[i as usize];
+                            self.triangles
+                                .push(([corner(tri[0]), corner(tri[1]), corner(tri[2])], color));
+                        }
+                    }
+                    painted = true;
+                }
+            }
+        }
+    }
+}
+
+impl<M> Widget<M> for Canvas {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn fit_width(&mut self, _ctx: &mut LayoutCtx<M>) -> Layout {
+        self.tessellate();
+
+        let cur_w = match self.size.width {
+            Length::Fixed(w) => w,
+            _ => 0,
+        };
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(cur_w, 0),
+            min: Size::splat(0),
+            max: Size::splat(i32::MAX),
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, _ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_w = match self.size.width {
+            Length::Grow(_) | Length::Percent(_) => parent_width,
+            Length::Fixed(w) => w,
+            Length::Fit => l.current_size.width,
+        };
+
+        l.current_size.width = target_w.min(parent_width);
+    }
+
+    fn fit_height(&mut self, _ctx: &mut LayoutCtx<M>) -> Layout {
+        let cur_h = match self.size.height {
+            Length::Fixed(h) => h,
+            _ => 0,
+        };
+
+        let cur_w = self.layout.map(|l| l.current_size.width).unwrap_or(0);
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(cur_w, cur_h),
+            min: Size::splat(0),
+            max: Size::splat(i32::MAX),
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_height(&mut self, _ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+        let target_h = match self.size.height {
+            Length::Grow(_) | Length::Percent(_) => parent_height,
+            Length::Fixed(h) => h,
+            Length::Fit => l.current_size.height,
+        };
+
+        l.current_size.height = target_h.min(parent_height);
+    }
+
+    fn place(&mut self, _ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+        <Canvas as Widget<M>>::layout(self).current_size
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        let (ox, oy) = (self.position.x as f32, self.position.y as f32);
+        let at = |p: Position<f32>| Position::new(p.x + ox, p.y + oy);
+        for &([a, b, c], color) in &self.triangles {
+            instances.push(Instance::triangle(at(a), at(b), at(c), color));
+        }
+    }
+}
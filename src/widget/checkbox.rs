@@ -0,0 +1,318 @@
+use super::*;
+use crate::event::{KeyState, LogicalKey};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CheckState {
+    #[default]
+    Unchecked,
+    Checked,
+    /// Neither on nor off, e.g. a parent of partially checked items. Toggles to `Checked`.
+    Indeterminate,
+}
+
+impl From<bool> for CheckState {
+    fn from(checked: bool) -> Self {
+        if checked {
+            CheckState::Checked
+        } else {
+            CheckState::Unchecked
+        }
+    }
+}
+
+pub struct Checkbox<M> {
+    layout: Option<Layout>,
+
+    id: Id,
+    position: Position<i32>,
+    label: Option<Element<M>>,
+
+    state: CheckState,
+    box_size: i32,
+    spacing: i32,
+
+    color: Color,
+    hover_color: Color,
+    border_color: Color,
+    check_color: Color,
+    focus_color: Color,
+
+    hovered: bool,
+    focused: bool,
+
+    on_toggle: Option<Box<dyn Fn(bool) -> M>>,
+}
+
+impl<M: Clone + 'static> Checkbox<M> {
+    pub fn new(state: impl Into<CheckState>) -> Self {
+        Self {
+            layout: None,
+
+            id: crate::context::next_id(),
+            position: Position::splat(0),
+            label: None,
+
+            state: state.into(),
+            box_size: 18,
+            spacing: 8,
+
+            color: Color::rgba(40, 40, 40, 255),
+            hover_color: Color::rgba(55, 55, 55, 255),
+            border_color: Color::rgba(120, 120, 120, 255),
+            check_color: Color::rgba(60, 110, 200, 255),
+            focus_color: Color::rgba(90, 150, 240, 255),
+
+            hovered: false,
+            focused: false,
+
+            on_toggle: None,
+        }
+    }
+
+    pub fn label(mut self, label: Text<'static>) -> Self {
+        self.label = Some(label.einto());
+        self
+    }
+    pub fn box_size(mut self, size: i32) -> Self {
+        self.box_size = size;
+        self
+    }
+    pub fn spacing(mut self, spacing: i32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+    pub fn color(mut self, c: Color) -> Self {
+        self.color = c;
+        self
+    }
+    pub fn hover_color(mut self, c: Color) -> Self {
+        self.hover_color = c;
+        self
+    }
+    pub fn border_color(mut self, c: Color) -> Self {
+        self.border_color = c;
+        self
+    }
+    pub fn check_color(mut self, c: Color) -> Self {
+        self.check_color = c;
+        self
+    }
+    pub fn focus_color(mut self, c: Color) -> Self {
+        self.focus_color = c;
+        self
+    }
+    /// Called with the new checked value when clicked or toggled with Space.
+    pub fn on_toggle(mut self, f: impl Fn(bool) -> M + 'static) -> Self {
+        self.on_toggle = Some(Box::new(f));
+        self
+    }
+
+    fn toggle(&mut self, ctx: &mut EventCtx<M>) {
+        let checked = self.state != CheckState::Checked;
+        self.state = checked.into();
+        if let Some(f) = self.on_toggle.as_ref() {
+            ctx.ui.emit(f(checked));
+        }
+        ctx.ui.request_redraw();
+    }
+
+    fn box_position(&self) -> Position<i32> {
+        let h = self.layout().current_size.height;
+        Position::new(self.position.x, self.position.y + (h - self.box_size) / 2)
+    }
+
+    #[inline]
+    fn contains(&self, p: Position<f32>) -> bool {
+        let sz = self.layout().current_size;
+        let l = self.position.x as f32;
+        let t = self.position.y as f32;
+        let r = l + sz.width as f32;
+        let b = t + sz.height as f32;
+        p.x >= l && p.x < r && p.y >= t && p.y < b
+    }
+}
+
+impl<M: Clone + 'static> Widget<M> for Checkbox<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        if let Some(label) = &self.label {
+            f(label.as_ref());
+        }
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        if let Some(label) = &mut self.label {
+            f(label.as_mut());
+        }
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let mut w = self.box_size;
+        if let Some(label) = self.label.as_mut() {
+            w += self.spacing + label.fit_width(ctx).current_size.width;
+        }
+
+        let l = Layout {
+            size: Size::splat(Length::Fit),
+            current_size: Size::new(w, 0),
+            min: Size::new(w, self.box_size),
+            max: Size::splat(i32::MAX),
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+        let target_w = l.current_size.width.min(parent_width);
+        l.current_size.width = target_w;
+
+        if let Some(label) = self.label.as_mut() {
+            let label_w = (target_w - self.box_size - self.spacing).max(0);
+            label.grow_width(ctx, label_w);
+        }
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let mut h = self.box_size;
+        if let Some(label) = self.label.as_mut() {
+            h = h.max(label.fit_height(ctx).current_size.height);
+        }
+
+        let prev = self.layout.as_ref().expect(LAYOUT_ERROR);
+        let l = Layout {
+            current_size: Size::new(prev.current_size.width, h),
+            min: Size::new(prev.min.width, h),
+            ..*prev
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+        let target_h = l.current_size.height.min(parent_height);
+        l.current_size.height = target_h;
+
+        if let Some(label) = self.label.as_mut() {
+            label.grow_height(ctx, target_h);
+        }
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+        let size = self.layout().current_size;
+
+        if let Some(label) = self.label.as_mut() {
+            let label_h = label.layout().current_size.height;
+            let label_pos = Position::new(
+                position.x + self.box_size + self.spacing,
+                position.y + (size.height - label_h) / 2,
+            );
+            let _ = label.place(ctx, label_pos);
+        }
+
+        size
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        let pos = self.box_position();
+        let border = if self.focused {
+            self.focus_color
+        } else {
+            self.border_color
+        };
+        let fill = if self.hovered {
+            self.hover_color
+        } else {
+            self.color
+        };
+
+        instances.push(Instance::ui(pos, Size::splat(self.box_size), border));
+        instances.push(Instance::ui(
+            pos + Size::splat(1),
+            Size::splat(self.box_size - 2),
+            fill,
+        ));
+
+        let inset = (self.box_size / 4).max(2);
+        let inner = self.box_size - 2 * inset;
+        match self.state {
+            CheckState::Unchecked => {}
+            CheckState::Checked => instances.push(Instance::ui(
+                pos + Size::splat(inset),
+                Size::splat(inner),
+                self.check_color,
+            )),
+            CheckState::Indeterminate => {
+                let bar = (self.box_size / 6).max(2);
+                instances.push(Instance::ui(
+                    Position::new(pos.x + inset, pos.y + (self.box_size - bar) / 2),
+                    Size::new(inner, bar),
+                    self.check_color,
+                ));
+            }
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        ctx.ui.register_focusable(self.id);
+        if let Some(label) = self.label.as_mut() {
+            label.handle(ctx);
+        }
+
+        let was_hovered = self.hovered;
+        let was_focused = self.focused;
+
+        let inside =
+            self.contains(ctx.ui.mouse_pos) && !ctx.ui.is_occluded(self.id, ctx.ui.mouse_pos);
+        self.hovered = inside;
+        if inside {
+            ctx.ui.hot_item = Some(self.id);
+        }
+
+        if ctx.ui.mouse_pressed {
+            if inside {
+                ctx.ui.active_item = Some(self.id);
+                ctx.ui.kbd_focus_item = Some(self.id);
+            } else if ctx.ui.kbd_focus_item == Some(self.id) {
+                ctx.ui.kbd_focus_item = None;
+            }
+        }
+        if ctx.ui.mouse_released && ctx.ui.active_item == Some(self.id) {
+            ctx.ui.active_item = None;
+            if inside {
+                self.toggle(ctx);
+            }
+        }
+
+        self.focused = ctx.ui.kbd_focus_item == Some(self.id);
+        if self.focused {
+            let toggles = ctx
+                .ui
+                .keys()
+                .iter()
+                .filter(|k| k.state == KeyState::Pressed && k.logical_key == LogicalKey::Space)
+                .count();
+            for _ in 0..toggles {
+                self.toggle(ctx);
+            }
+        }
+
+        if self.hovered != was_hovered || self.focused != was_focused {
+            ctx.ui.request_redraw();
+        }
+    }
+}
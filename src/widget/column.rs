@@ -1,5 +1,5 @@
 use super::*;
-use crate::widget::helpers::{Height, equalize_sizes};
+use crate::widget::helpers::{Height, equalize_sizes, offer};
 
 pub struct Column<M> {
     layout: Option<Layout>,
@@ -7,6 +7,8 @@ pub struct Column<M> {
     id: Id,
     children: Vec<Element<M>>,
     spacing: i32,
+    align: Align,
+    justify: Justify,
     position: Position<i32>,
     size: Size<Length<i32>>,
     color: Color,
@@ -23,6 +25,8 @@ impl<M> Column<M> {
             id: crate::context::next_id(),
             children,
             spacing: 0,
+            align: Align::Start,
+            justify: Justify::Start,
             position: Position::splat(0),
             size: Size::splat(Length::Fit),
             color: Color::TRANSPARENT,
@@ -37,6 +41,18 @@ impl<M> Column<M> {
         self
     }
 
+    /// Where children sit across the column.
+    pub fn align_items(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// How space left along the column is spread between children.
+    pub fn justify_content(mut self, justify: Justify) -> Self {
+        self.justify = justify;
+        self
+    }
+
     pub fn size(mut self, size: Size<Length<i32>>) -> Self {
         self.size = size;
         self
@@ -73,6 +89,9 @@ impl<M: 'static> Widget<M> for Column<M> {
     fn layout(&self) -> &Layout {
         self.layout.as_ref().expect(LAYOUT_ERROR)
     }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
 
     fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
         for child in &self.children {
@@ -80,6 +99,12 @@ impl<M: 'static> Widget<M> for Column<M> {
         }
     }
 
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        for child in &mut self.children {
+            f(child.as_mut());
+        }
+    }
+
     fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
         let width_padding = self.padding.x + self.padding.z;
 
@@ -110,7 +135,7 @@ impl<M: 'static> Widget<M> for Column<M> {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
 
         let target_w = match self.size.width {
-            Length::Grow => parent_width,
+            Length::Grow(_) | Length::Percent(_) => parent_width,
             Length::Fixed(w) => w,
             Length::Fit => l.current_size.width,
         }
@@ -120,7 +145,22 @@ impl<M: 'static> Widget<M> for Column<M> {
 
         let inner_w = (target_w - self.padding.x - self.padding.z).max(0);
         for child in self.children.iter_mut() {
-            child.grow_width(ctx, inner_w);
+            let w = offer(child.layout().size.width, inner_w);
+            let stretch = self.align == Align::Stretch
+                && !matches!(
+                    child.layout().size.width,
+                    Length::Fixed(_) | Length::Percent(_)
+                );
+            // Grown from the stretched width, so what the child holds fills it too.
+            let stretch_to = |child: &mut Element<M>| {
+                if stretch && let Some(cl) = child.layout_mut() {
+                    cl.current_size.width =
+                        inner_w.clamp(cl.min.width, cl.max.width.max(cl.min.width));
+                }
+            };
+            stretch_to(child);
+            child.grow_width(ctx, w);
+            stretch_to(child);
         }
 
         l.current_size.width = target_w;
@@ -160,7 +200,7 @@ impl<M: 'static> Widget<M> for Column<M> {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
 
         let target_h = match self.size.height {
-            Length::Grow => parent_height,
+            Length::Grow(_) | Length::Percent(_) => parent_height,
             Length::Fixed(h) => h,
             Length::Fit => l.current_size.height,
         }
@@ -183,15 +223,34 @@ impl<M: 'static> Widget<M> for Column<M> {
 
     fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
         self.position = position;
-        let mut cursor = Position::new(
-            self.position.x + self.padding.x,
-            self.position.y + self.padding.y,
+        let size = self.layout().current_size;
+        let inner = Size::new(
+            size.width - self.padding.x - self.padding.z,
+            size.height - self.padding.y - self.padding.w,
         );
-        for child in self.children.iter_mut() {
+        let used = self
+            .children
+            .iter()
+            .map(|c| c.layout().current_size.height)
+            .sum::<i32>()
+            + (self.children.len() as i32 - 1).max(0) * self.spacing;
+        let offsets = self
+            .justify
+            .offsets(inner.height - used, self.children.len());
+
+        let mut y = self.position.y + self.padding.y;
+        let direction = ctx.ui.direction();
+        for (child, offset) in self.children.iter_mut().zip(offsets) {
+            let child_w = child.layout().current_size.width;
+            let x = self.position.x + self.padding.x + self.align.offset(inner.width - child_w);
+            let cursor = Position::new(
+                direction.flow(position.x, size.width, x, child_w),
+                y + offset,
+            );
             let child_size = child.place(ctx, cursor);
-            cursor.y += child_size.height + self.spacing;
+            y += child_size.height + self.spacing;
         }
-        self.layout().current_size
+        size
     }
 
     fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
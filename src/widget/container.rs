@@ -1,16 +1,23 @@
-use super::*;
+use super::{helpers::offer, *};
 
 pub struct Container<M> {
     layout: Option<Layout>,
 
     id: Id,
     children: Vec<Element<M>>,
+    // Placed at an anchor of the bounds instead of the padded origin; not part of the fit size.
+    absolute: Vec<(Element<M>, Alignment, Position<i32>)>,
     position: Position<i32>,
     size: Size<Length<i32>>,
     color: Color,
     padding: Vec4<i32>,
     min: Size<i32>,
     max: Size<i32>,
+    clip: bool,
+    shadow: Option<Shadow>,
+    radii: Vec4<f32>,
+    border: Option<Border>,
+    gradient: Option<Gradient>,
 }
 
 impl<M> Container<M> {
@@ -20,15 +27,28 @@ impl<M> Container<M> {
 
             id: crate::context::next_id(),
             children,
+            absolute: Vec::new(),
             position: Position::splat(0),
             size: Size::splat(Length::Fit),
             color: Color::TRANSPARENT,
             padding: Vec4::splat(0),
             min: Size::splat(0),
             max: Size::splat(i32::MAX),
+            clip: false,
+            shadow: None,
+            radii: Vec4::splat(0.0),
+            border: None,
+            gradient: None,
         }
     }
 
+    /// Adds a child anchored at `anchor` of the container's bounds and moved by `offset`,
+    /// e.g. a badge at `Alignment::TopRight`. It doesn't affect the container's size.
+    pub fn absolute(mut self, child: Element<M>, anchor: Alignment, offset: Position<i32>) -> Self {
+        self.absolute.push((child, anchor, offset));
+        self
+    }
+
     pub fn size(mut self, size: Size<Length<i32>>) -> Self {
         self.size = size;
         self
@@ -49,6 +69,34 @@ impl<M> Container<M> {
         self.max = size;
         self
     }
+    /// Rounds every corner by `radius` pixels.
+    pub fn radius(self, radius: f32) -> Self {
+        self.radii(Vec4::splat(radius))
+    }
+    /// Rounds each corner on its own: top-left, top-right, bottom-right, bottom-left.
+    pub fn radii(mut self, radii: Vec4<f32>) -> Self {
+        self.radii = radii;
+        self
+    }
+    pub fn border(mut self, border: Border) -> Self {
+        self.border = Some(border);
+        self
+    }
+    pub fn shadow(mut self, shadow: Shadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+    /// Fills the background with `gradient` instead of the color.
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+    /// Cuts off children where they overflow the container's bounds, following its rounded
+    /// corners when stencil clipping is on.
+    pub fn clip(mut self, clip: bool) -> Self {
+        self.clip = clip;
+        self
+    }
 }
 
 impl<M: 'static> Widget<M> for Container<M> {
@@ -61,11 +109,26 @@ impl<M: 'static> Widget<M> for Container<M> {
     fn layout(&self) -> &Layout {
         self.layout.as_ref().expect(LAYOUT_ERROR)
     }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
 
     fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
         for child in &self.children {
             f(child.as_ref());
         }
+        for (child, ..) in &self.absolute {
+            f(child.as_ref());
+        }
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        for child in &mut self.children {
+            f(child.as_mut());
+        }
+        for (child, ..) in &mut self.absolute {
+            f(child.as_mut());
+        }
     }
 
     fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
@@ -77,6 +140,9 @@ impl<M: 'static> Widget<M> for Container<M> {
             min_w = min_w.max(current_size.width);
         }
         min_w += width_padding;
+        for (child, ..) in self.absolute.iter_mut() {
+            child.fit_width(ctx);
+        }
 
         let resolved_w = self
             .size
@@ -98,7 +164,7 @@ impl<M: 'static> Widget<M> for Container<M> {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
 
         let target_w = match self.size.width {
-            Length::Grow => parent_width,
+            Length::Grow(_) | Length::Percent(_) => parent_width,
             Length::Fixed(w) => w,
             Length::Fit => l.current_size.width,
         }
@@ -108,7 +174,12 @@ impl<M: 'static> Widget<M> for Container<M> {
 
         let inner_w = (target_w - self.padding.x - self.padding.z).max(0);
         for child in self.children.iter_mut() {
-            child.grow_width(ctx, inner_w);
+            let w = offer(child.layout().size.width, inner_w);
+            child.grow_width(ctx, w);
+        }
+        for (child, ..) in self.absolute.iter_mut() {
+            let w = offer(child.layout().size.width, target_w);
+            child.grow_width(ctx, w);
         }
 
         l.current_size.width = target_w;
@@ -123,6 +194,9 @@ impl<M: 'static> Widget<M> for Container<M> {
             max_child_h = max_child_h.max(current_size.height);
         }
         let min_h = max_child_h + height_padding;
+        for (child, ..) in self.absolute.iter_mut() {
+            child.fit_height(ctx);
+        }
 
         let prev = self.layout.as_ref().expect(LAYOUT_ERROR);
         let prev_w = prev.current_size.width;
@@ -149,7 +223,7 @@ impl<M: 'static> Widget<M> for Container<M> {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
 
         let target_h = match self.size.height {
-            Length::Grow => parent_height,
+            Length::Grow(_) | Length::Percent(_) => parent_height,
             Length::Fixed(h) => h,
             Length::Fit => l.current_size.height,
         }
@@ -159,7 +233,12 @@ impl<M: 'static> Widget<M> for Container<M> {
 
         let inner_h = (target_h - self.padding.y - self.padding.w).max(0);
         for child in self.children.iter_mut() {
-            child.grow_height(ctx, inner_h);
+            let h = offer(child.layout().size.height, inner_h);
+            child.grow_height(ctx, h);
+        }
+        for (child, ..) in self.absolute.iter_mut() {
+            let h = offer(child.layout().size.height, target_h);
+            child.grow_height(ctx, h);
         }
 
         l.current_size.height = target_h;
@@ -167,23 +246,75 @@ impl<M: 'static> Widget<M> for Container<M> {
 
     fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
         self.position = position;
-        let inner_pos = Position::new(
-            self.position.x + self.padding.x,
-            self.position.y + self.padding.y,
-        );
+        let size = self.layout().current_size;
+        let direction = ctx.ui.direction();
         for child in self.children.iter_mut() {
-            let _ = child.place(ctx, inner_pos);
+            let x = direction.flow(
+                position.x,
+                size.width,
+                position.x + self.padding.x,
+                child.layout().current_size.width,
+            );
+            let _ = child.place(ctx, Position::new(x, position.y + self.padding.y));
+        }
+        for (child, anchor, offset) in self.absolute.iter_mut() {
+            let child_size = child.layout().current_size;
+            let at = anchor.offset(Size::new(
+                size.width - child_size.width,
+                size.height - child_size.height,
+            ));
+            let _ = child.place(
+                ctx,
+                Position::new(
+                    direction.flow(
+                        position.x,
+                        size.width,
+                        position.x + at.x + offset.x,
+                        child_size.width,
+                    ),
+                    position.y + at.y + offset.y,
+                ),
+            );
         }
-        self.layout().current_size
+        size
     }
 
     fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
-        if self.color.a() > 0 {
-            instances.push(Instance::ui(
-                self.position,
-                self.layout().current_size,
-                self.color,
-            ));
+        let size = self.layout().current_size;
+        if let Some(shadow) = &self.shadow {
+            instances.push(shadow.instance(self.position, size, self.radii));
+        }
+        if let Some(gradient) = &self.gradient {
+            instances.push(gradient.instance(self.position, size).radii(self.radii));
+        } else if self.color.a() > 0 {
+            instances.push(Instance::ui(self.position, size, self.color).radii(self.radii));
+        }
+        if let Some(border) = &self.border {
+            border.push(self.position, size, self.radii, instances);
+        }
+    }
+
+    fn __paint(
+        &self,
+        ctx: &mut PaintCtx,
+        instances: &mut Vec<Instance>,
+        t: &internal::PaintToken,
+        debug_on: bool,
+    ) {
+        self.draw_self(ctx, instances);
+        let paint_children = |ctx: &mut PaintCtx, instances: &mut Vec<Instance>| {
+            let mut each = |child: &dyn Widget<M>| child.__paint(ctx, instances, t, debug_on);
+            self.for_each_child(&mut each);
+        };
+        if self.clip {
+            let size = self.layout().current_size;
+            ctx.clipped_rounded(self.position, size, self.radii, instances, paint_children);
+        } else {
+            paint_children(ctx, instances);
+        }
+
+        if debug_on {
+            self.after_draw(ctx, instances, t);
         }
     }
 
@@ -191,5 +322,8 @@ impl<M: 'static> Widget<M> for Container<M> {
         for child in self.children.iter_mut() {
             child.handle(ctx);
         }
+        for (child, ..) in self.absolute.iter_mut() {
+            child.handle(ctx);
+        }
     }
 }
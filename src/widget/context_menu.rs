@@ -0,0 +1,402 @@
+use std::sync::Arc;
+
+use cosmic_text::{Attrs, Buffer, Family, Shaping, Wrap};
+
+use super::{
+    helpers::{measure, push_glyphs},
+    *,
+};
+use crate::{
+    context::Context,
+    event::{KeyState, LogicalKey},
+    render::text::LayoutKey,
+};
+
+#[derive(Default)]
+struct MenuState {
+    // Cursor position the menu was opened at.
+    open_at: Option<Position<f32>>,
+    right_held: bool,
+}
+
+/// The floating menu itself, opened at the cursor by a right-click. Shared by [`ContextMenu`]
+/// and the menus built into the text widgets; choosing an entry hands back its value.
+pub(in crate::widget) struct MenuPopup<T> {
+    id: Id,
+    // Label, value and whether it can be chosen.
+    entries: Vec<(String, T, bool)>,
+    buffers: Vec<Arc<Buffer>>,
+
+    pub(in crate::widget) font_size: f32,
+    pub(in crate::widget) line_height: f32,
+    pub(in crate::widget) padding: i32,
+    pub(in crate::widget) attributes: Attrs<'static>,
+
+    pub(in crate::widget) color: Color,
+    pub(in crate::widget) background: Color,
+    pub(in crate::widget) border_color: Color,
+    pub(in crate::widget) highlight_color: Color,
+
+    width: i32,
+    position: Position<i32>,
+    size: Size<i32>,
+
+    open: bool,
+    hovered: Option<usize>,
+}
+
+impl<T: Clone> MenuPopup<T> {
+    pub(in crate::widget) fn new() -> Self {
+        Self {
+            id: crate::context::next_id(),
+            entries: Vec::new(),
+            buffers: Vec::new(),
+
+            font_size: 14.0,
+            line_height: 1.2,
+            padding: 6,
+            attributes: Attrs::new(),
+
+            color: Color::WHITE,
+            background: Color::rgba(30, 30, 30, 255),
+            border_color: Color::rgba(120, 120, 120, 255),
+            highlight_color: Color::rgba(60, 110, 200, 255),
+
+            width: 0,
+            position: Position::splat(0),
+            size: Size::splat(0),
+
+            open: false,
+            hovered: None,
+        }
+    }
+
+    pub(in crate::widget) fn push(&mut self, label: impl Into<String>, value: T, enabled: bool) {
+        self.entries.push((label.into(), value, enabled));
+    }
+
+    pub(in crate::widget) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn line_px(&self) -> i32 {
+        (self.font_size * self.line_height).ceil() as i32
+    }
+
+    fn row_height(&self) -> i32 {
+        self.line_px() + self.padding
+    }
+
+    /// Whether `p` is over the open menu.
+    pub(in crate::widget) fn contains(&self, p: Position<f32>) -> bool {
+        let l = self.position.x as f32;
+        let t = self.position.y as f32;
+        let r = l + self.size.width as f32;
+        let b = t + self.size.height as f32;
+        self.open && p.x >= l && p.x < r && p.y >= t && p.y < b
+    }
+
+    /// Index of the entry under `p`.
+    fn entry_at(&self, p: Position<f32>) -> Option<usize> {
+        if !self.contains(p) {
+            return None;
+        }
+        let row = ((p.y - self.position.y as f32) / self.row_height() as f32) as usize;
+        (row < self.entries.len()).then_some(row)
+    }
+
+    /// Shapes the labels; call from `fit_width`.
+    pub(in crate::widget) fn layout<M>(&mut self, ctx: &mut LayoutCtx<M>) {
+        let attrs = |c: Color| {
+            self.attributes
+                .clone()
+                .color(cosmic_text::Color::rgba(c.r(), c.g(), c.b(), c.a()))
+        };
+        let color = self.color;
+        let dimmed = Color::rgba(color.r(), color.g(), color.b(), color.a() / 3);
+        let (enabled, disabled) = (attrs(color), attrs(dimmed));
+        self.buffers = self
+            .entries
+            .iter()
+            .map(|(label, _, on)| {
+                ctx.text.layout(LayoutKey::new(
+                    label,
+                    if *on { &enabled } else { &disabled },
+                    self.font_size,
+                    self.line_height,
+                    Wrap::None,
+                    Shaping::Advanced,
+                    None,
+                ))
+            })
+            .collect();
+
+        let text_w = self
+            .buffers
+            .iter()
+            .map(|b| measure(b).0.ceil() as i32)
+            .max()
+            .unwrap_or(0);
+        self.width = text_w + 4 * self.padding;
+    }
+
+    /// Places the menu at the cursor it was opened at; call from `place`.
+    pub(in crate::widget) fn place<M>(&mut self, ctx: &mut LayoutCtx<M>) {
+        // Flipped left or up when the window has no room for it.
+        let window = ctx.globals.window_size();
+        let menu = Size::new(self.width, self.entries.len() as i32 * self.row_height());
+        if let Some(at) = ctx.ui.state_mut::<MenuState>(self.id).open_at {
+            let (ax, ay) = (at.x as i32, at.y as i32);
+            let x = if ax + menu.width > window.width {
+                ax - menu.width
+            } else {
+                ax
+            };
+            let y = if ay + menu.height > window.height {
+                ay - menu.height
+            } else {
+                ay
+            };
+            self.position = Position::new(x.max(0), y.max(0));
+        }
+        self.size = menu;
+    }
+
+    pub(in crate::widget) fn paint(&self, ctx: &mut PaintCtx) {
+        if !self.open {
+            return;
+        }
+        ctx.overlay(|ctx, out| {
+            let pos = self.position;
+            let menu = self.size;
+            out.push(Instance::ui(pos, menu, self.border_color));
+            out.push(Instance::ui(
+                pos + Size::splat(1),
+                menu - 2,
+                self.background,
+            ));
+
+            let row_h = self.row_height();
+            for (i, buffer) in self.buffers.iter().enumerate() {
+                let row = Position::new(pos.x, pos.y + i as i32 * row_h);
+                if self.hovered == Some(i) {
+                    out.push(Instance::ui(
+                        row + Size::new(1, 0),
+                        Size::new(menu.width - 2, row_h),
+                        self.highlight_color,
+                    ));
+                }
+                let origin = Position::new(row.x + 2 * self.padding, row.y + self.padding / 2);
+                let clip = (
+                    origin,
+                    Size::new((menu.width - 4 * self.padding).max(0), self.line_px()),
+                );
+                push_glyphs(
+                    ctx,
+                    buffer,
+                    Position::new(origin.x as f32, origin.y as f32),
+                    Some(clip),
+                    out,
+                );
+            }
+        });
+    }
+
+    /// Opens the menu when `inside` is right-clicked and tracks it while open. Returns the
+    /// value of the entry chosen during this pass.
+    pub(in crate::widget) fn handle<M>(&mut self, ui: &mut Context<M>, inside: bool) -> Option<T> {
+        let mouse = ui.mouse_pos;
+        let right_down = ui.right_down;
+        let right_pressed = ui.right_pressed;
+
+        let state = ui.state_mut::<MenuState>(self.id);
+        let was_open = state.open_at.is_some();
+        let right_click = right_pressed && !state.right_held;
+        state.right_held = right_down;
+        let mut reopened = false;
+        if right_click {
+            state.open_at = None;
+            if inside && !self.entries.is_empty() {
+                state.open_at = Some(mouse);
+                reopened = true;
+            }
+        }
+        let mut open = state.open_at.is_some();
+        // Positioned by the last layout, which only matches once the menu has been placed.
+        self.open = open && !reopened;
+
+        let hovered = self
+            .entry_at(mouse)
+            .filter(|&i| self.open && self.entries[i].2);
+        let mut chosen = None;
+        if self.open {
+            if ui.mouse_pressed && ui.active_item != Some(self.id) {
+                if self.contains(mouse) {
+                    ui.active_item = Some(self.id);
+                } else {
+                    open = false;
+                }
+            }
+            if ui.mouse_released && ui.active_item == Some(self.id) {
+                ui.active_item = None;
+                if let Some(i) = hovered {
+                    chosen = Some(self.entries[i].1.clone());
+                    open = false;
+                }
+            }
+            let escape = ui
+                .keys()
+                .iter()
+                .any(|k| k.state == KeyState::Pressed && k.logical_key == LogicalKey::Escape);
+            if escape {
+                open = false;
+            }
+        }
+
+        if !open {
+            ui.state_mut::<MenuState>(self.id).open_at = None;
+        } else if !reopened {
+            ui.add_overlay(self.id, self.position, self.size);
+        }
+
+        // Opening or moving the menu needs a new layout to place it.
+        if open != was_open || reopened || hovered != self.hovered {
+            ui.request_redraw();
+        }
+        self.open = open && !reopened;
+        self.hovered = hovered;
+        chosen
+    }
+}
+
+/// Opens a menu of entries at the cursor when `child` is right-clicked. Choosing an entry
+/// emits its message; clicking elsewhere or pressing Escape closes the menu.
+pub struct ContextMenu<M> {
+    id: Id,
+    child: Element<M>,
+    menu: MenuPopup<M>,
+}
+
+impl<M: Clone + 'static> ContextMenu<M> {
+    pub fn new(child: Element<M>) -> Self {
+        Self {
+            id: crate::context::next_id(),
+            child,
+            menu: MenuPopup::new(),
+        }
+    }
+
+    /// Adds an entry labelled `label` that emits `msg` when chosen.
+    pub fn entry(mut self, label: impl Into<String>, msg: M) -> Self {
+        self.menu.push(label, msg, true);
+        self
+    }
+    pub fn font_size(mut self, font_size: f32) -> Self {
+        self.menu.font_size = font_size;
+        self
+    }
+    pub fn family(mut self, family: Family<'static>) -> Self {
+        self.menu.attributes.family = family;
+        self
+    }
+    pub fn padding(mut self, padding: i32) -> Self {
+        self.menu.padding = padding;
+        self
+    }
+    pub fn color(mut self, c: Color) -> Self {
+        self.menu.color = c;
+        self
+    }
+    pub fn background(mut self, c: Color) -> Self {
+        self.menu.background = c;
+        self
+    }
+    pub fn border_color(mut self, c: Color) -> Self {
+        self.menu.border_color = c;
+        self
+    }
+    pub fn highlight_color(mut self, c: Color) -> Self {
+        self.menu.highlight_color = c;
+        self
+    }
+
+    #[inline]
+    fn contains(&self, p: Position<f32>) -> bool {
+        let pos = self.child.position();
+        let sz = self.child.layout().current_size;
+        let l = pos.x as f32;
+        let t = pos.y as f32;
+        let r = l + sz.width as f32;
+        let b = t + sz.height as f32;
+        p.x >= l && p.x < r && p.y >= t && p.y < b
+    }
+}
+
+impl<M: Clone + 'static> Widget<M> for ContextMenu<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        self.child.position()
+    }
+    fn layout(&self) -> &Layout {
+        self.child.layout()
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.child.layout_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        f(self.child.as_ref());
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        f(self.child.as_mut());
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.menu.layout(ctx);
+        self.child.fit_width(ctx)
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        self.child.grow_width(ctx, parent_width);
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.child.fit_height(ctx)
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        self.child.grow_height(ctx, parent_height);
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        let size = self.child.place(ctx, position);
+        self.menu.place(ctx);
+        size
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn __paint(
+        &self,
+        ctx: &mut PaintCtx,
+        instances: &mut Vec<Instance>,
+        t: &internal::PaintToken,
+        debug_on: bool,
+    ) {
+        self.child.__paint(ctx, instances, t, debug_on);
+        self.menu.paint(ctx);
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        self.child.handle(ctx);
+
+        let mouse = ctx.ui.mouse_pos;
+        let inside = self.contains(mouse) && !ctx.ui.is_occluded(self.id, mouse);
+        if let Some(msg) = self.menu.handle(ctx.ui, inside) {
+            ctx.ui.emit(msg);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use super::*;
+
+/// Which way horizontal content flows. Right-to-left mirrors the order of `Row` children,
+/// padding sides and alignments across the horizontal axis and right-aligns text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    #[default]
+    LeftToRight,
+    RightToLeft,
+}
+
+impl Direction {
+    /// Where a child `width` wide laid out at `x` ends up inside the span starting at `left`
+    /// and `span` wide.
+    pub fn flow(self, left: i32, span: i32, x: i32, width: i32) -> i32 {
+        match self {
+            Direction::LeftToRight => x,
+            Direction::RightToLeft => 2 * left + span - x - width,
+        }
+    }
+}
+
+/// Lays a subtree out in its own direction. Built with [`Widget::direction`] or
+/// [`Element::direction`].
+pub(crate) struct Directional<M> {
+    id: Id,
+    child: Element<M>,
+    direction: Direction,
+}
+
+impl<M> Directional<M> {
+    pub(crate) fn new(child: Element<M>, direction: Direction) -> Self {
+        Self {
+            id: crate::context::next_id(),
+            child,
+            direction,
+        }
+    }
+}
+
+impl<M: 'static> Widget<M> for Directional<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        self.child.position()
+    }
+    fn layout(&self) -> &Layout {
+        self.child.layout()
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.child.layout_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        f(self.child.as_ref());
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        f(self.child.as_mut());
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let prev = ctx.ui.replace_direction(self.direction);
+        let l = self.child.fit_width(ctx);
+        ctx.ui.replace_direction(prev);
+        l
+    }
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let prev = ctx.ui.replace_direction(self.direction);
+        self.child.grow_width(ctx, parent_width);
+        ctx.ui.replace_direction(prev);
+    }
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let prev = ctx.ui.replace_direction(self.direction);
+        let l = self.child.fit_height(ctx);
+        ctx.ui.replace_direction(prev);
+        l
+    }
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let prev = ctx.ui.replace_direction(self.direction);
+        self.child.grow_height(ctx, parent_height);
+        ctx.ui.replace_direction(prev);
+    }
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        let prev = ctx.ui.replace_direction(self.direction);
+        let size = self.child.place(ctx, position);
+        ctx.ui.replace_direction(prev);
+        size
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        let prev = ctx.ui.replace_direction(self.direction);
+        self.child.handle(ctx);
+        ctx.ui.replace_direction(prev);
+    }
+}
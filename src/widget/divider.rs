@@ -0,0 +1,153 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// A thin line separating siblings. A horizontal divider grows to the width of its parent and
+/// a vertical one to its height, so they fit a `Column` or a `Row` without sizing.
+pub struct Divider {
+    layout: Option<Layout>,
+
+    id: Id,
+    position: Position<i32>,
+    axis: Axis,
+    thickness: i32,
+    inset: i32,
+    margin: i32,
+    color: Color,
+}
+
+impl Divider {
+    fn new(axis: Axis) -> Self {
+        Self {
+            layout: None,
+
+            id: crate::context::next_id(),
+            position: Position::splat(0),
+            axis,
+            thickness: 1,
+            inset: 0,
+            margin: 0,
+            color: Color::rgba(255, 255, 255, 40),
+        }
+    }
+
+    /// A line running left to right, for use in a `Column`.
+    pub fn horizontal() -> Self {
+        Self::new(Axis::Horizontal)
+    }
+
+    /// A line running top to bottom, for use in a `Row`.
+    pub fn vertical() -> Self {
+        Self::new(Axis::Vertical)
+    }
+
+    pub fn thickness(mut self, thickness: i32) -> Self {
+        self.thickness = thickness.max(0);
+        self
+    }
+    /// Space left empty at both ends of the line.
+    pub fn inset(mut self, inset: i32) -> Self {
+        self.inset = inset.max(0);
+        self
+    }
+    /// Space on both sides of the line, across it.
+    pub fn margin(mut self, margin: i32) -> Self {
+        self.margin = margin.max(0);
+        self
+    }
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    fn size(&self) -> Size<Length<i32>> {
+        let across = Length::Fixed(self.thickness + 2 * self.margin);
+        match self.axis {
+            Axis::Horizontal => Size::new(Length::Grow(1), across),
+            Axis::Vertical => Size::new(across, Length::Grow(1)),
+        }
+    }
+}
+
+impl<M> Widget<M> for Divider {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn fit_width(&mut self, _ctx: &mut LayoutCtx<M>) -> Layout {
+        let size = self.size();
+        let cur_w = match size.width {
+            Length::Fixed(w) => w,
+            _ => 0,
+        };
+
+        let l = Layout {
+            size,
+            current_size: Size::new(cur_w, 0),
+            min: Size::new(cur_w, 0),
+            max: Size::splat(i32::MAX),
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, _ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+        if let Length::Grow(_) = l.size.width {
+            l.current_size.width = parent_width;
+        }
+    }
+
+    fn fit_height(&mut self, _ctx: &mut LayoutCtx<M>) -> Layout {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+        let cur_h = match l.size.height {
+            Length::Fixed(h) => h,
+            _ => 0,
+        };
+        l.current_size.height = cur_h;
+        l.min.height = cur_h;
+        *l
+    }
+
+    fn grow_height(&mut self, _ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+        if let Length::Grow(_) = l.size.height {
+            l.current_size.height = parent_height;
+        }
+    }
+
+    fn place(&mut self, _ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+        <Divider as Widget<M>>::layout(self).current_size
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        let size = <Divider as Widget<M>>::layout(self).current_size;
+        let (pos, line) = match self.axis {
+            Axis::Horizontal => (
+                Position::new(self.position.x + self.inset, self.position.y + self.margin),
+                Size::new(size.width - 2 * self.inset, self.thickness),
+            ),
+            Axis::Vertical => (
+                Position::new(self.position.x + self.margin, self.position.y + self.inset),
+                Size::new(self.thickness, size.height - 2 * self.inset),
+            ),
+        };
+        if line.width > 0 && line.height > 0 && self.color.a() > 0 {
+            instances.push(Instance::ui(pos, line, self.color));
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use crate::event::{KeyEvent, KeyState, LogicalKey};
+
+/// What kind of change an [`Edit`] was; consecutive edits of the same kind are merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditKind {
+    /// Typing, merged while the caret keeps moving forward.
+    Insert,
+    /// Backspace, merged while deleting backwards.
+    DeleteBackward,
+    /// Delete key, merged while deleting at the same position.
+    DeleteForward,
+    /// Paste, cut, replacing a selection; never merged.
+    Replace,
+}
+
+/// A single reversible change to a UTF-8 buffer. `at` is a byte offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub kind: EditKind,
+    pub at: usize,
+    pub removed: String,
+    pub inserted: String,
+    /// (anchor, caret) byte offsets before and after the change.
+    pub selection_before: (usize, usize),
+    pub selection_after: (usize, usize),
+}
+
+impl Edit {
+    /// Re-applies the change to `text`.
+    pub fn apply(&self, text: &mut String) {
+        text.replace_range(self.at..self.at + self.removed.len(), &self.inserted);
+    }
+
+    /// Reverts the change on `text`.
+    pub fn revert(&self, text: &mut String) {
+        text.replace_range(self.at..self.at + self.inserted.len(), &self.removed);
+    }
+
+    fn try_merge(&mut self, next: &Edit) -> bool {
+        if self.kind != next.kind {
+            return false;
+        }
+        match self.kind {
+            EditKind::Insert
+                if self.removed.is_empty()
+                    && next.removed.is_empty()
+                    && next.at == self.at + self.inserted.len() =>
+            {
+                self.inserted.push_str(&next.inserted);
+            }
+            EditKind::DeleteBackward if next.at + next.removed.len() == self.at => {
+                self.removed.insert_str(0, &next.removed);
+                self.at = next.at;
+            }
+            EditKind::DeleteForward if next.at == self.at => {
+                self.removed.push_str(&next.removed);
+            }
+            _ => return false,
+        }
+        self.selection_after = next.selection_after;
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryAction {
+    Undo,
+    Redo,
+}
+
+impl HistoryAction {
+    /// Maps Ctrl+Z to undo and Ctrl+Shift+Z / Ctrl+Y to redo (Super instead of Ctrl works too).
+    pub fn from_key(key: &KeyEvent) -> Option<Self> {
+        let m = &key.modifiers;
+        if key.state != KeyState::Pressed || !(m.control || m.super_) {
+            return None;
+        }
+        match &key.logical_key {
+            LogicalKey::Character(s) if s.eq_ignore_ascii_case("z") => {
+                Some(if m.shift { Self::Redo } else { Self::Undo })
+            }
+            LogicalKey::Character(s) if s.eq_ignore_ascii_case("y") => Some(Self::Redo),
+            _ => None,
+        }
+    }
+}
+
+/// Undo/redo stacks for text editing widgets.
+///
+/// Edits of the same [`EditKind`] recorded within the coalescing window are merged so undo
+/// steps over words rather than single characters.
+#[derive(Debug, Clone)]
+pub struct EditHistory {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    limit: usize,
+    coalesce_window: f32,
+    last_time: Option<f32>,
+    sealed: bool,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EditHistory {
+    pub fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            limit: 256,
+            coalesce_window: 1.0,
+            last_time: None,
+            sealed: false,
+        }
+    }
+
+    /// Maximum number of undo steps kept; the oldest are dropped first.
+    pub fn limit(mut self, steps: usize) -> Self {
+        self.limit = steps.max(1);
+        self
+    }
+
+    /// Seconds between edits after which they are no longer merged.
+    pub fn coalesce_window(mut self, seconds: f32) -> Self {
+        self.coalesce_window = seconds;
+        self
+    }
+
+    /// Records `edit` made at `time` (e.g. `Globals::time`) and drops the redo stack.
+    pub fn record(&mut self, edit: Edit, time: f32) {
+        self.redo.clear();
+
+        let recent = self
+            .last_time
+            .is_some_and(|t| time - t <= self.coalesce_window);
+        self.last_time = Some(time);
+
+        if recent
+            && !self.sealed
+            && let Some(last) = self.undo.last_mut()
+            && last.try_merge(&edit)
+        {
+            return;
+        }
+
+        self.sealed = false;
+        self.undo.push(edit);
+        if self.undo.len() > self.limit {
+            let excess = self.undo.len() - self.limit;
+            self.undo.drain(..excess);
+        }
+    }
+
+    /// Stops the next edit from merging into the previous one, e.g. after the caret moved.
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// Pops the latest edit; the caller reverts it with [`Edit::revert`] and restores
+    /// `selection_before`.
+    pub fn undo(&mut self) -> Option<&Edit> {
+        let edit = self.undo.pop()?;
+        self.sealed = true;
+        self.redo.push(edit);
+        self.redo.last()
+    }
+
+    /// Pops the latest undone edit; the caller re-applies it with [`Edit::apply`] and
+    /// restores `selection_after`.
+    pub fn redo(&mut self) -> Option<&Edit> {
+        let edit = self.redo.pop()?;
+        self.sealed = true;
+        self.undo.push(edit);
+        self.undo.last()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last_time = None;
+        self.sealed = false;
+    }
+}
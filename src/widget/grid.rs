@@ -0,0 +1,435 @@
+use super::*;
+use crate::widget::helpers::{Track, distribute, offer};
+
+struct Cell<M> {
+    child: Element<M>,
+    column_span: usize,
+    row_span: usize,
+    // Top-left track, assigned when the cells are laid out.
+    row: usize,
+    column: usize,
+}
+
+/// Lays children out left to right in rows of columns, wrapping to the next row when one is
+/// full. Children may span several columns and rows; they take the next free spot they fit.
+pub struct Grid<M> {
+    layout: Option<Layout>,
+
+    id: Id,
+    cells: Vec<Cell<M>>,
+    columns: Vec<Length<i32>>,
+    row_spacing: i32,
+    column_spacing: i32,
+    position: Position<i32>,
+    size: Size<Length<i32>>,
+    color: Color,
+    padding: Vec4<i32>,
+    min: Size<i32>,
+    max: Size<i32>,
+
+    column_tracks: Vec<Track>,
+    row_tracks: Vec<Track>,
+    column_widths: Vec<i32>,
+    row_heights: Vec<i32>,
+}
+
+impl<M> Grid<M> {
+    /// A grid of `columns` columns, each as wide as its widest child.
+    pub fn new(columns: usize) -> Self {
+        Self::with_template(vec![Length::Fit; columns])
+    }
+
+    /// A grid with one column per entry of `columns`, sized like a child of a `Row` would be.
+    pub fn with_template(mut columns: Vec<Length<i32>>) -> Self {
+        if columns.is_empty() {
+            columns.push(Length::Fit);
+        }
+        Self {
+            layout: None,
+
+            id: crate::context::next_id(),
+            cells: Vec::new(),
+            columns,
+            row_spacing: 0,
+            column_spacing: 0,
+            position: Position::splat(0),
+            size: Size::splat(Length::Fit),
+            color: Color::TRANSPARENT,
+            padding: Vec4::splat(0),
+            min: Size::splat(0),
+            max: Size::splat(i32::MAX),
+
+            column_tracks: Vec::new(),
+            row_tracks: Vec::new(),
+            column_widths: Vec::new(),
+            row_heights: Vec::new(),
+        }
+    }
+
+    pub fn push(self, child: Element<M>) -> Self {
+        self.push_span(child, 1, 1)
+    }
+
+    /// Adds a child covering `columns` columns and `rows` rows.
+    pub fn push_span(mut self, child: Element<M>, columns: usize, rows: usize) -> Self {
+        self.cells.push(Cell {
+            child,
+            column_span: columns.clamp(1, self.columns.len()),
+            row_span: rows.max(1),
+            row: 0,
+            column: 0,
+        });
+        self
+    }
+
+    pub fn spacing(mut self, amount: i32) -> Self {
+        self.row_spacing = amount;
+        self.column_spacing = amount;
+        self
+    }
+
+    pub fn row_spacing(mut self, amount: i32) -> Self {
+        self.row_spacing = amount;
+        self
+    }
+
+    pub fn column_spacing(mut self, amount: i32) -> Self {
+        self.column_spacing = amount;
+        self
+    }
+
+    pub fn size(mut self, size: Size<Length<i32>>) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn padding(mut self, amount: Vec4<i32>) -> Self {
+        self.padding = amount;
+        self
+    }
+
+    pub fn min(mut self, size: Size<i32>) -> Self {
+        self.min = size;
+        self
+    }
+
+    pub fn max(mut self, size: Size<i32>) -> Self {
+        self.max = size;
+        self
+    }
+
+    /// Assigns every cell its top-left track and returns the number of rows.
+    fn arrange(&mut self) -> usize {
+        let columns = self.columns.len();
+        let mut taken: Vec<Vec<bool>> = Vec::new();
+        let (mut row, mut column) = (0, 0);
+
+        for cell in self.cells.iter_mut() {
+            loop {
+                if column + cell.column_span > columns {
+                    row += 1;
+                    column = 0;
+                    continue;
+                }
+                while taken.len() < row + cell.row_span {
+                    taken.push(vec![false; columns]);
+                }
+                let free = (row..row + cell.row_span)
+                    .all(|r| (column..column + cell.column_span).all(|c| !taken[r][c]));
+                if free {
+                    break;
+                }
+                column += 1;
+            }
+
+            for cells in &mut taken[row..row + cell.row_span] {
+                cells[column..column + cell.column_span].fill(true);
+            }
+            cell.row = row;
+            cell.column = column;
+            column += cell.column_span;
+        }
+
+        taken.len()
+    }
+}
+
+/// Raises `tracks` so the ones spanned by each `(first, span, min, current)` item together
+/// hold it, spreading any shortfall evenly.
+fn fit_tracks(tracks: &mut [Track], items: &[(usize, usize, i32, i32)], spacing: i32) {
+    fn spread(spanned: &mut [Track], need: i32, field: fn(&mut Track) -> &mut i32) {
+        let have: i32 = spanned.iter_mut().map(|t| *field(t)).sum();
+        let short = need - have;
+        if short <= 0 {
+            return;
+        }
+        let n = spanned.len() as i32;
+        for (k, t) in spanned.iter_mut().enumerate() {
+            *field(t) += short / n + i32::from((k as i32) < short % n);
+        }
+    }
+
+    // Single-track items first so spanning ones only add what is still missing.
+    let mut items = items.to_vec();
+    items.sort_by_key(|&(_, span, ..)| span);
+
+    for (first, span, min, current) in items {
+        let spanned = &mut tracks[first..first + span];
+        let gaps = (span as i32 - 1) * spacing;
+        spread(spanned, min - gaps, |t| &mut t.min);
+        spread(spanned, current - gaps, |t| &mut t.current);
+        for t in spanned.iter_mut() {
+            t.current = t.current.max(t.min);
+        }
+    }
+}
+
+fn span_size(sizes: &[i32], first: usize, span: usize, spacing: i32) -> i32 {
+    sizes[first..first + span].iter().sum::<i32>() + (span as i32 - 1) * spacing
+}
+
+fn track_total(tracks: &[Track], spacing: i32) -> (i32, i32) {
+    let gaps = (tracks.len() as i32 - 1).max(0) * spacing;
+    let min = tracks
+        .iter()
+        .map(|t| match t.length {
+            Length::Fixed(x) => x,
+            _ => t.min,
+        })
+        .sum::<i32>();
+    let current = tracks
+        .iter()
+        .map(|t| match t.length {
+            Length::Fixed(x) => x,
+            _ => t.current,
+        })
+        .sum::<i32>();
+    (min + gaps, current + gaps)
+}
+
+impl<M: 'static> Widget<M> for Grid<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        for cell in &self.cells {
+            f(cell.child.as_ref());
+        }
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        for cell in &mut self.cells {
+            f(cell.child.as_mut());
+        }
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let rows = self.arrange();
+
+        let mut items = Vec::with_capacity(self.cells.len());
+        for cell in self.cells.iter_mut() {
+            let Layout {
+                min, current_size, ..
+            } = cell.child.fit_width(ctx);
+            items.push((cell.column, cell.column_span, min.width, current_size.width));
+        }
+
+        self.column_tracks = self
+            .columns
+            .iter()
+            .map(|&length| Track {
+                length,
+                current: 0,
+                min: 0,
+                max: i32::MAX,
+            })
+            .collect();
+        fit_tracks(&mut self.column_tracks, &items, self.column_spacing);
+        self.row_tracks = vec![
+            Track {
+                length: Length::Fit,
+                current: 0,
+                min: 0,
+                max: i32::MAX,
+            };
+            rows
+        ];
+
+        let width_padding = self.padding.x + self.padding.z;
+        let (min_w, fit_w) = track_total(&self.column_tracks, self.column_spacing);
+        let min_w = min_w + width_padding;
+
+        let resolved_w = match self.size.width {
+            Length::Fixed(w) => w,
+            _ => fit_w + width_padding,
+        }
+        .clamp(min_w.max(self.min.width), self.max.width.max(min_w));
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(resolved_w, 0),
+            min: Size::new(min_w.max(self.min.width), self.min.height),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_w = match self.size.width {
+            Length::Grow(_) | Length::Percent(_) => parent_width,
+            Length::Fixed(w) => w,
+            Length::Fit => l.current_size.width,
+        }
+        .max(l.min.width)
+        .min(l.max.width)
+        .min(parent_width);
+
+        let inner_w = target_w
+            - (self.columns.len() as i32 - 1).max(0) * self.column_spacing
+            - self.padding.x
+            - self.padding.z;
+        self.column_widths = distribute(&self.column_tracks, inner_w.max(0));
+
+        for cell in self.cells.iter_mut() {
+            let w = span_size(
+                &self.column_widths,
+                cell.column,
+                cell.column_span,
+                self.column_spacing,
+            );
+            let w = offer(cell.child.layout().size.width, w);
+            cell.child.grow_width(ctx, w);
+        }
+
+        l.current_size.width = target_w;
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let mut items = Vec::with_capacity(self.cells.len());
+        for cell in self.cells.iter_mut() {
+            let Layout {
+                min, current_size, ..
+            } = cell.child.fit_height(ctx);
+            items.push((cell.row, cell.row_span, min.height, current_size.height));
+        }
+        fit_tracks(&mut self.row_tracks, &items, self.row_spacing);
+
+        let height_padding = self.padding.y + self.padding.w;
+        let (_, fit_h) = track_total(&self.row_tracks, self.row_spacing);
+        let min_h = fit_h + height_padding;
+
+        let prev = self.layout.as_ref().expect(LAYOUT_ERROR);
+        let prev_w = prev.current_size.width;
+
+        let requested_h = match self.size.height {
+            Length::Fixed(h) => h,
+            _ => min_h,
+        };
+        let resolved_h = requested_h
+            .max(self.min.height.max(min_h))
+            .min(self.max.height);
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(prev_w, resolved_h),
+            min: Size::new(prev.min.width, self.min.height.max(min_h)),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_h = match self.size.height {
+            Length::Grow(_) | Length::Percent(_) => parent_height,
+            Length::Fixed(h) => h,
+            Length::Fit => l.current_size.height,
+        }
+        .max(l.min.height)
+        .min(l.max.height)
+        .min(parent_height);
+
+        let inner_h = target_h
+            - (self.row_tracks.len() as i32 - 1).max(0) * self.row_spacing
+            - self.padding.y
+            - self.padding.w;
+        self.row_heights = distribute(&self.row_tracks, inner_h.max(0));
+
+        for cell in self.cells.iter_mut() {
+            let h = span_size(&self.row_heights, cell.row, cell.row_span, self.row_spacing);
+            let h = offer(cell.child.layout().size.height, h);
+            cell.child.grow_height(ctx, h);
+        }
+
+        l.current_size.height = target_h;
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+
+        let offsets = |sizes: &[i32], start: i32, spacing: i32| {
+            let mut at = start;
+            sizes
+                .iter()
+                .map(|&s| {
+                    let here = at;
+                    at += s + spacing;
+                    here
+                })
+                .collect::<Vec<_>>()
+        };
+        let xs = offsets(
+            &self.column_widths,
+            position.x + self.padding.x,
+            self.column_spacing,
+        );
+        let ys = offsets(
+            &self.row_heights,
+            position.y + self.padding.y,
+            self.row_spacing,
+        );
+
+        for cell in self.cells.iter_mut() {
+            let _ = cell
+                .child
+                .place(ctx, Position::new(xs[cell.column], ys[cell.row]));
+        }
+        self.layout().current_size
+    }
+
+    fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        if self.color.a() > 0 {
+            instances.push(Instance::ui(
+                self.position,
+                self.layout().current_size,
+                self.color,
+            ));
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        for cell in self.cells.iter_mut() {
+            cell.child.handle(ctx);
+        }
+    }
+}
@@ -1,5 +1,11 @@
+use cosmic_text::{Buffer, LayoutRun};
+
 use crate::{
     Size,
+    context::PaintCtx,
+    event::Preedit,
+    model::{Color, Position},
+    primitive::Instance,
     widget::{Element, Length},
 };
 
@@ -29,42 +35,84 @@ pub(in crate::widget) fn equalize_sizes<M>(
     axis_length: impl SizeField<Length<i32>>,
     inner: i32,
 ) -> Vec<(usize, i32)> {
+    let tracks = children
+        .iter()
+        .map(|child| {
+            let layout = child.layout();
+            Track {
+                length: *axis_length.get(&layout.size),
+                current: *axis.get(&layout.current_size),
+                min: *axis.get(&layout.min),
+                max: *axis.get(&layout.max),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    distribute(&tracks, inner).into_iter().enumerate().collect()
+}
+
+/// Space to hand a child sized by `length` out of `inner`: its share for `Percent`, all of
+/// it otherwise. Children treat whatever they're offered for `Percent` like `Grow`.
+pub(in crate::widget) fn offer(length: Length<i32>, inner: i32) -> i32 {
+    match length {
+        Length::Percent(p) => (inner.max(0) as f32 * p.max(0.0) / 100.0).round() as i32,
+        _ => inner,
+    }
+}
+
+/// One slot along an axis to hand space to: a child of a row or column, a grid track.
+#[derive(Debug, Clone, Copy)]
+pub(in crate::widget) struct Track {
+    pub length: Length<i32>,
+    pub current: i32,
+    pub min: i32,
+    pub max: i32,
+}
+
+/// Splits `inner` across `tracks`: fixed and fitted tracks keep their size, shrinking toward
+/// their minimum when space runs out, and growing tracks share what is left. Returns one size
+/// per track, in order.
+pub(in crate::widget) fn distribute(tracks: &[Track], inner: i32) -> Vec<i32> {
     struct Alloc {
-        index: usize,
         allocated: i32,
         min: i32,
         max: i32,
-        grows: bool,
+        // Share of the extra space relative to other growing tracks; 0 when not growing.
+        weight: u32,
     }
 
-    let mut allocs: Vec<Alloc> = Vec::with_capacity(children.len());
+    let mut allocs: Vec<Alloc> = Vec::with_capacity(tracks.len());
     let mut remaining = inner;
 
-    for (i, child) in children.iter().enumerate() {
-        let layout = child.layout();
-
-        let raw_min = *axis.get(&layout.min);
-        let raw_max = *axis.get(&layout.max);
-        let grows = matches!(axis_length.get(&layout.size), Length::Grow);
+    for track in tracks {
+        let raw_min = track.min;
+        let raw_max = track.max;
+        let weight = match track.length {
+            Length::Grow(w) => w.max(1),
+            _ => 0,
+        };
 
-        let (base, eff_min) = match *axis_length.get(&layout.size) {
+        let (base, eff_min) = match track.length {
             Length::Fixed(x) => {
                 let b = x.clamp(raw_min, raw_max);
                 (b, b)
             }
             Length::Fit => {
-                let b = (*axis.get(&layout.current_size)).clamp(raw_min, raw_max);
+                let b = track.current.clamp(raw_min, raw_max);
+                (b, raw_min)
+            }
+            Length::Grow(_) => (raw_min, raw_min),
+            Length::Percent(_) => {
+                let b = offer(track.length, inner).clamp(raw_min, raw_max);
                 (b, raw_min)
             }
-            Length::Grow => (raw_min, raw_min),
         };
 
         allocs.push(Alloc {
-            index: i,
             allocated: base,
             min: eff_min,
             max: raw_max,
-            grows,
+            weight,
         });
 
         remaining -= base;
@@ -169,61 +217,409 @@ pub(in crate::widget) fn equalize_sizes<M>(
         remaining += used;
     }
 
-    // Extra space: first level growable items up to the current max level, then grow within max.
+    // Extra space: growing items end up proportional to their weights, except where that
+    // would shrink them below what they already have or push them past their max.
     if remaining > 0 {
-        let grower_idxs: Vec<_> = (0..allocs.len()).filter(|&i| allocs[i].grows).collect();
-
-        if !grower_idxs.is_empty() {
-            let target = grower_idxs
+        let growers: Vec<usize> = (0..allocs.len())
+            .filter(|&i| allocs[i].weight > 0)
+            .collect();
+        let total: i64 = remaining as i64
+            + growers
                 .iter()
-                .map(|&i| allocs[i].allocated)
-                .max()
-                .unwrap();
-
-            // Level up growable items that are below the target level (respecting their max).
-            let level_caps: Vec<_> = grower_idxs
-                .iter()
-                .filter_map(|&i| {
-                    if allocs[i].allocated < target && allocs[i].allocated < allocs[i].max {
-                        let cap = (target.min(allocs[i].max)) - allocs[i].allocated;
-                        if cap > 0 { Some((i, cap)) } else { None }
+                .map(|&i| allocs[i].allocated as i64)
+                .sum::<i64>();
+
+        // Items pinned at their max; only ever grows, since pinning frees budget for the rest.
+        let mut at_max = vec![false; allocs.len()];
+        let targets = loop {
+            let mut at_min = vec![false; allocs.len()];
+            let level = loop {
+                let mut budget = total;
+                let mut weights = 0i64;
+                for &i in &growers {
+                    if at_max[i] {
+                        budget -= allocs[i].max as i64;
+                    } else if at_min[i] {
+                        budget -= allocs[i].allocated as i64;
                     } else {
-                        None
+                        weights += allocs[i].weight as i64;
                     }
-                })
-                .collect();
-
-            if !level_caps.is_empty() && remaining > 0 {
-                let (used, assigned) = bounded_equal_fill(level_caps, remaining);
-                for (i, add) in assigned {
-                    if add > 0 {
-                        allocs[i].allocated += add;
+                }
+                let level = if weights > 0 {
+                    budget as f64 / weights as f64
+                } else {
+                    0.0
+                };
+                let mut pinned = false;
+                for &i in &growers {
+                    if !at_max[i]
+                        && !at_min[i]
+                        && level * (allocs[i].weight as f64) < allocs[i].allocated as f64
+                    {
+                        at_min[i] = true;
+                        pinned = true;
                     }
                 }
-                remaining -= used;
+                if !pinned {
+                    break level;
+                }
+            };
+
+            let mut pinned = false;
+            for &i in &growers {
+                if !at_max[i]
+                    && !at_min[i]
+                    && level * (allocs[i].weight as f64) > allocs[i].max as f64
+                {
+                    at_max[i] = true;
+                    pinned = true;
+                }
             }
-
-            // If space remains, grow all growables up to their max.
-            if remaining > 0 {
-                let grow_caps = grower_idxs
+            if !pinned {
+                let mut targets: Vec<(usize, i32)> = growers
                     .iter()
-                    .filter_map(|&i| {
-                        let cap = allocs[i].max - allocs[i].allocated;
-                        if cap > 0 { Some((i, cap)) } else { None }
+                    .map(|&i| {
+                        let t = if at_max[i] {
+                            allocs[i].max
+                        } else if at_min[i] {
+                            allocs[i].allocated
+                        } else {
+                            (level * allocs[i].weight as f64).floor() as i32
+                        };
+                        (i, t)
                     })
-                    .collect::<Vec<_>>();
-
-                if !grow_caps.is_empty() {
-                    let (_, assigned) = bounded_equal_fill(grow_caps, remaining);
-                    for (i, add) in assigned {
-                        if add > 0 {
-                            allocs[i].allocated += add;
-                        }
+                    .collect();
+                // Flooring leaves a few pixels over; hand them out one at a time.
+                let mut leftover = total - targets.iter().map(|&(_, t)| t as i64).sum::<i64>();
+                for (i, t) in targets.iter_mut() {
+                    if leftover <= 0 {
+                        break;
+                    }
+                    if !at_max[*i] && !at_min[*i] && *t < allocs[*i].max {
+                        *t += 1;
+                        leftover -= 1;
                     }
                 }
+                break targets;
+            }
+        };
+
+        for (i, t) in targets {
+            allocs[i].allocated = t;
+        }
+    }
+
+    allocs.into_iter().map(|a| a.allocated).collect()
+}
+
+/// Widest line and total height of a shaped buffer.
+pub(in crate::widget) fn measure(buffer: &Buffer) -> (f32, f32) {
+    buffer.layout_runs().fold((0f32, 0f32), |(w, h), run| {
+        (w.max(run.line_w), h + run.line_height)
+    })
+}
+
+/// Pushes one textured instance per glyph of `buffer` drawn at `origin`. Glyphs that fall
+/// entirely outside the `clip` rect (top-left, size) are skipped.
+pub(in crate::widget) fn push_glyphs(
+    ctx: &mut PaintCtx,
+    buffer: &Buffer,
+    origin: Position<f32>,
+    clip: Option<(Position<i32>, Size<i32>)>,
+    instances: &mut Vec<Instance>,
+) {
+    for run in buffer.layout_runs() {
+        for glyph in run.glyphs {
+            push_glyph(ctx, glyph, pen(origin, run.line_y), clip, instances);
+        }
+    }
+}
+
+/// Like [`push_glyphs`], but draws glyphs from their distance fields so they stay sharp when
+/// transformed. Glyphs without one, like emoji, are drawn as bitmaps.
+pub(in crate::widget) fn push_sdf_glyphs(
+    ctx: &mut PaintCtx,
+    buffer: &Buffer,
+    origin: Position<f32>,
+    clip: Option<(Position<i32>, Size<i32>)>,
+    instances: &mut Vec<Instance>,
+) {
+    push_sdf_layer(ctx, buffer, origin, clip, None, instances);
+}
+
+/// Pushes the glyphs of `buffer` in `color`, grown by `grow` pixels and blurred over `blur`
+/// pixels, to go behind them as an outline or shadow. Glyphs without a distance field are
+/// left out.
+pub(in crate::widget) fn push_glyph_halo(
+    ctx: &mut PaintCtx,
+    buffer: &Buffer,
+    origin: Position<f32>,
+    grow: f32,
+    blur: f32,
+    color: Color,
+    instances: &mut Vec<Instance>,
+) {
+    push_sdf_layer(
+        ctx,
+        buffer,
+        origin,
+        None,
+        Some((grow, blur, color)),
+        instances,
+    );
+}
+
+fn push_sdf_layer(
+    ctx: &mut PaintCtx,
+    buffer: &Buffer,
+    origin: Position<f32>,
+    clip: Option<(Position<i32>, Size<i32>)>,
+    halo: Option<(f32, f32, Color)>,
+    instances: &mut Vec<Instance>,
+) {
+    for run in buffer.layout_runs() {
+        for glyph in run.glyphs {
+            let Some((offset, size, handle)) = ctx.text.sdf_glyph(ctx.gpu, ctx.texture, glyph)
+            else {
+                if halo.is_none() {
+                    push_glyph(ctx, glyph, pen(origin, run.line_y), clip, instances);
+                }
+                continue;
+            };
+
+            let x0 = (origin.x + glyph.x + offset.x).round() as i32;
+            let y0 = (origin.y + run.line_y + glyph.y + offset.y).round() as i32;
+            let size = Size::new(size.width.round() as i32, size.height.round() as i32);
+            if let Some((pos, clip_size)) = clip
+                && (x0 + size.width <= pos.x
+                    || x0 >= pos.x + clip_size.width
+                    || y0 + size.height <= pos.y
+                    || y0 >= pos.y + clip_size.height)
+            {
+                continue;
+            }
+
+            let position = Position::new(x0, y0);
+            let instance = match halo {
+                Some((grow, blur, color)) => {
+                    Instance::sdf(position, size, color, handle).sdf_edge(grow, blur)
+                }
+                None => {
+                    let c = glyph.color_opt.unwrap_or(BASE_COLOR);
+                    let tint = Color::rgba(c.r(), c.g(), c.b(), c.a());
+                    Instance::sdf(position, size, tint, handle)
+                }
+            };
+            instances.push(instance);
+        }
+    }
+}
+
+// Origin of the line whose baseline is `line_y` below `origin`.
+fn pen(origin: Position<f32>, line_y: f32) -> Position<f32> {
+    Position::new(origin.x, origin.y + line_y)
+}
+
+const BASE_COLOR: cosmic_text::Color = cosmic_text::Color::rgba(255, 255, 255, 255);
+
+fn push_glyph(
+    ctx: &mut PaintCtx,
+    glyph: &cosmic_text::LayoutGlyph,
+    line: Position<f32>,
+    clip: Option<(Position<i32>, Size<i32>)>,
+    instances: &mut Vec<Instance>,
+) {
+    let (top_left, Size { width, height }, cache_key) = match ctx.text.get_glyph_data(glyph, line) {
+        Some(v) => v,
+        None => return,
+    };
+    if let Some((pos, size)) = clip
+        && (top_left.x + width as i32 <= pos.x
+            || top_left.x >= pos.x + size.width
+            || top_left.y + height as i32 <= pos.y
+            || top_left.y >= pos.y + size.height)
+    {
+        return;
+    }
+
+    let handle = match ctx
+        .text
+        .upload_glyph(ctx.gpu, ctx.texture, cache_key, width, height)
+    {
+        Some(h) => h,
+        None => return,
+    };
+
+    // Emoji keep their own colors and only take the text's opacity.
+    let glyph_color = glyph.color_opt.unwrap_or(BASE_COLOR);
+    let tint = if ctx.text.is_color_glyph(cache_key) {
+        Color::rgba(255, 255, 255, glyph_color.a())
+    } else {
+        Color::rgba(
+            glyph_color.r(),
+            glyph_color.g(),
+            glyph_color.b(),
+            glyph_color.a(),
+        )
+    };
+
+    instances.push(Instance::ui_tex(
+        top_left,
+        Size::new(width as i32, height as i32),
+        tint,
+        handle,
+    ));
+}
+
+/// Horizontal offset of the caret before byte `index` of a single-line buffer.
+pub(in crate::widget) fn caret_x(buffer: &Buffer, index: usize) -> f32 {
+    let mut x = 0.0;
+    for run in buffer.layout_runs() {
+        for g in run.glyphs {
+            if index <= g.start {
+                return g.x;
+            }
+            if index < g.end {
+                // Inside a ligature; split its advance evenly.
+                let t = (index - g.start) as f32 / (g.end - g.start) as f32;
+                return g.x + g.w * t;
+            }
+            x = g.x + g.w;
+        }
+    }
+    x
+}
+
+/// Byte index of the caret position closest to `x` in a single-line buffer.
+pub(in crate::widget) fn hit_index(buffer: &Buffer, x: f32) -> usize {
+    let mut end = 0;
+    for run in buffer.layout_runs() {
+        for g in run.glyphs {
+            if x < g.x + g.w / 2.0 {
+                return g.start;
+            }
+            end = g.end;
+        }
+    }
+    end
+}
+
+/// Draws an input method composition laid out in `buffer` over the text, starting at the
+/// caret line `at` (top-left) that is `line_px` tall: underlined on a `background` patch, with
+/// a `caret_color` caret where the input method puts its cursor.
+#[allow(clippy::too_many_arguments)]
+pub(in crate::widget) fn push_preedit(
+    ctx: &mut PaintCtx,
+    buffer: &Buffer,
+    preedit: &Preedit,
+    at: Position<i32>,
+    line_px: i32,
+    background: Color,
+    color: Color,
+    caret_color: Color,
+    instances: &mut Vec<Instance>,
+) {
+    let width = measure(buffer).0.ceil() as i32;
+    instances.push(Instance::ui(at, Size::new(width + 1, line_px), background));
+    push_glyphs(
+        ctx,
+        buffer,
+        Position::new(at.x as f32, at.y as f32),
+        None,
+        instances,
+    );
+    instances.push(Instance::ui(
+        Position::new(at.x, at.y + line_px - 1),
+        Size::new(width, 1),
+        color,
+    ));
+    if let Some((start, _)) = preedit.cursor {
+        let x = at.x + caret_x(buffer, start).round() as i32;
+        instances.push(Instance::ui(
+            Position::new(x, at.y),
+            Size::new(1, line_px),
+            caret_color,
+        ));
+    }
+}
+
+/// Byte offset each line starts at, breaking lines the way cosmic-text does: on `\n`, `\r`,
+/// `\r\n` or `\n\r`.
+pub(in crate::widget) fn line_starts(text: &str) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    let mut starts = vec![0];
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], bytes.get(i + 1)) {
+            (b'\r', Some(b'\n')) | (b'\n', Some(b'\r')) => {
+                i += 2;
+                starts.push(i);
+            }
+            (b'\r' | b'\n', _) => {
+                i += 1;
+                starts.push(i);
             }
+            _ => i += 1,
+        }
+    }
+    starts
+}
+
+/// `text` with every line break cosmic-text recognises turned into `\n`.
+pub(in crate::widget) fn normalize_newlines(text: &str) -> String {
+    if !text.contains('\r') {
+        return text.to_owned();
+    }
+    let starts = line_starts(text);
+    let mut out = String::with_capacity(text.len());
+    for (i, &start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(text.len());
+        let line = &text[start..end];
+        let body = line.trim_end_matches(['\r', '\n']);
+        out.push_str(body);
+        if body.len() != line.len() {
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Line index and offset within that line of byte offset `pos`.
+pub(in crate::widget) fn locate(starts: &[usize], pos: usize) -> (usize, usize) {
+    let line = starts.partition_point(|&s| s <= pos).saturating_sub(1);
+    (line, pos - starts[line])
+}
+
+/// Byte range of its line covered by a layout run.
+pub(in crate::widget) fn run_span(run: &LayoutRun) -> (usize, usize) {
+    match (run.glyphs.first(), run.glyphs.last()) {
+        (Some(first), Some(last)) => (first.start, last.end),
+        _ => (0, 0),
+    }
+}
+
+pub(in crate::widget) fn caret_in_run(run: &LayoutRun, local: usize) -> f32 {
+    let mut x = 0.0;
+    for g in run.glyphs {
+        if local <= g.start {
+            return g.x;
+        }
+        if local < g.end {
+            let t = (local - g.start) as f32 / (g.end - g.start) as f32;
+            return g.x + g.w * t;
         }
+        x = g.x + g.w;
     }
+    x
+}
 
-    allocs.into_iter().map(|a| (a.index, a.allocated)).collect()
+pub(in crate::widget) fn hit_in_run(run: &LayoutRun, x: f32) -> usize {
+    let (_, mut end) = run_span(run);
+    for g in run.glyphs {
+        if x < g.x + g.w / 2.0 {
+            return g.start;
+        }
+        end = g.end;
+    }
+    end
 }
@@ -0,0 +1,96 @@
+use super::*;
+
+#[derive(Default)]
+struct HoverState {
+    inside: bool,
+}
+
+/// Emits messages when the pointer enters or leaves a child. Built with
+/// [`Widget::on_hover_enter`], [`Widget::on_hover_leave`] or their [`Element`] counterparts.
+pub(crate) struct Hover<M> {
+    id: Id,
+    child: Element<M>,
+    on_enter: Option<M>,
+    on_leave: Option<M>,
+}
+
+impl<M> Hover<M> {
+    pub(crate) fn new(child: Element<M>, on_enter: Option<M>, on_leave: Option<M>) -> Self {
+        Self {
+            id: crate::context::next_id(),
+            child,
+            on_enter,
+            on_leave,
+        }
+    }
+
+    fn contains(&self, p: Position<f32>) -> bool {
+        let pos = self.child.position();
+        let size = self.child.layout().current_size;
+        p.x >= pos.x as f32
+            && p.y >= pos.y as f32
+            && p.x < (pos.x + size.width) as f32
+            && p.y < (pos.y + size.height) as f32
+    }
+}
+
+impl<M: Clone + 'static> Widget<M> for Hover<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        self.child.position()
+    }
+    fn layout(&self) -> &Layout {
+        self.child.layout()
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.child.layout_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        f(self.child.as_ref());
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        f(self.child.as_mut());
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.child.fit_width(ctx)
+    }
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        self.child.grow_width(ctx, parent_width);
+    }
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.child.fit_height(ctx)
+    }
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        self.child.grow_height(ctx, parent_height);
+    }
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.child.place(ctx, position)
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        self.child.handle(ctx);
+
+        let mouse = ctx.ui.mouse_pos;
+        let inside = self.contains(mouse) && !ctx.ui.is_occluded(self.id, mouse);
+        let state = ctx.ui.state_mut::<HoverState>(self.id);
+        if state.inside == inside {
+            return;
+        }
+        state.inside = inside;
+        let message = if inside {
+            self.on_enter.clone()
+        } else {
+            self.on_leave.clone()
+        };
+        if let Some(m) = message {
+            ctx.ui.emit(m);
+        }
+    }
+}
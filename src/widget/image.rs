@@ -1,5 +1,5 @@
 use super::*;
-use crate::render::texture::TextureHandle;
+use crate::render::texture::{AnimatedHandle, TextureHandle};
 
 pub struct Image {
     layout: Option<Layout>,
@@ -10,6 +10,8 @@ pub struct Image {
     max: Size<i32>,
 
     handle: TextureHandle,
+    animation: Option<AnimatedHandle>,
+    playing: bool,
     tint: Color,
 }
 
@@ -23,9 +25,24 @@ impl Image {
             min: Size::splat(0),
             max: Size::splat(i32::MAX),
             handle,
+            animation: None,
+            playing: true,
             tint: Color::WHITE,
         }
     }
+
+    /// An image stepping through the frames of `animation`, looping on `Globals::time`.
+    pub fn animated(size: Size<Length<i32>>, animation: AnimatedHandle) -> Self {
+        let first = animation.frames().next().unwrap_or_default();
+        let mut image = Self::new(size, first);
+        image.animation = Some(animation);
+        image
+    }
+    /// Whether an animated image advances; a paused one shows its first frame.
+    pub fn playing(mut self, playing: bool) -> Self {
+        self.playing = playing;
+        self
+    }
     pub fn tint(mut self, tint: Color) -> Self {
         self.tint = tint;
         self
@@ -50,6 +67,9 @@ impl<M> Widget<M> for Image {
     fn layout(&self) -> &Layout {
         self.layout.as_ref().expect(LAYOUT_ERROR)
     }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
 
     fn fit_width(&mut self, _ctx: &mut LayoutCtx<M>) -> Layout {
         let base_w = match self.size.width {
@@ -75,7 +95,7 @@ impl<M> Widget<M> for Image {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
 
         let target_w = match self.size.width {
-            Length::Grow => parent_width,
+            Length::Grow(_) | Length::Percent(_) => parent_width,
             Length::Fixed(w) => w,
             Length::Fit => l.current_size.width,
         };
@@ -110,7 +130,7 @@ impl<M> Widget<M> for Image {
     fn grow_height(&mut self, _ctx: &mut LayoutCtx<M>, parent_height: i32) {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
         let target_h = match self.size.height {
-            Length::Grow => parent_height,
+            Length::Grow(_) | Length::Percent(_) => parent_height,
             Length::Fixed(h) => h,
             Length::Fit => l.current_size.height,
         };
@@ -128,12 +148,30 @@ impl<M> Widget<M> for Image {
         <image::Image as Widget<M>>::layout(self).current_size
     }
 
-    fn draw_self(&self, _ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+    fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        let handle = match self.animation.as_ref() {
+            Some(animation) if self.playing => animation
+                .frame_at(ctx.globals.time)
+                .map_or(self.handle, |(h, _)| h),
+            _ => self.handle,
+        };
         instances.push(Instance::ui_tex(
             self.position,
             <image::Image as Widget<M>>::layout(self).current_size,
             self.tint,
-            self.handle,
+            handle,
         ));
     }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        if self.playing
+            && let Some((_, next)) = self
+                .animation
+                .as_ref()
+                .and_then(|a| a.frame_at(ctx.globals.time))
+            && next.is_finite()
+        {
+            ctx.ui.request_redraw_at(next);
+        }
+    }
 }
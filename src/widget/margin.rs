@@ -0,0 +1,117 @@
+use super::*;
+
+/// Reserves space around a child during layout. Built with [`Widget::margin`] or
+/// [`Element::margin`].
+pub(crate) struct Margin<M> {
+    layout: Option<Layout>,
+
+    id: Id,
+    child: Element<M>,
+    margin: Vec4<i32>,
+    position: Position<i32>,
+}
+
+impl<M> Margin<M> {
+    pub(crate) fn new(child: Element<M>, margin: Vec4<i32>) -> Self {
+        Self {
+            layout: None,
+
+            id: crate::context::next_id(),
+            child,
+            margin,
+            position: Position::splat(0),
+        }
+    }
+
+    fn outer(&self, inner: Layout) -> Layout {
+        let extra = Size::new(self.margin.x + self.margin.z, self.margin.y + self.margin.w);
+        let grow = |length: Length<i32>, by: i32| match length {
+            Length::Fixed(v) => Length::Fixed(v + by),
+            other => other,
+        };
+        Layout {
+            size: Size::new(
+                grow(inner.size.width, extra.width),
+                grow(inner.size.height, extra.height),
+            ),
+            current_size: inner.current_size + extra,
+            min: inner.min + extra,
+            max: Size::new(
+                inner.max.width.saturating_add(extra.width),
+                inner.max.height.saturating_add(extra.height),
+            ),
+        }
+    }
+}
+
+impl<M: 'static> Widget<M> for Margin<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        f(self.child.as_ref());
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        f(self.child.as_mut());
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let inner = self.child.fit_width(ctx);
+        let l = self.outer(inner);
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let horizontal = self.margin.x + self.margin.z;
+        self.child
+            .grow_width(ctx, (parent_width - horizontal).max(0));
+        let width = self.child.layout().current_size.width + horizontal;
+        self.layout.as_mut().expect(LAYOUT_ERROR).current_size.width = width;
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let inner = self.child.fit_height(ctx);
+        let l = self.outer(inner);
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let vertical = self.margin.y + self.margin.w;
+        self.child
+            .grow_height(ctx, (parent_height - vertical).max(0));
+        let height = self.child.layout().current_size.height + vertical;
+        self.layout
+            .as_mut()
+            .expect(LAYOUT_ERROR)
+            .current_size
+            .height = height;
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+        let _ = self.child.place(
+            ctx,
+            Position::new(position.x + self.margin.x, position.y + self.margin.y),
+        );
+        self.layout().current_size
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        self.child.handle(ctx);
+    }
+}
@@ -0,0 +1,433 @@
+use cosmic_text::{Family, Style, Weight, Wrap};
+
+use super::*;
+
+#[derive(Debug, Clone, Default)]
+struct Inline {
+    text: String,
+    bold: bool,
+    italic: bool,
+    code: bool,
+    link: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Block {
+    Heading(u8, Vec<Inline>),
+    Paragraph(Vec<Inline>),
+    // `None` marker for bullets, the item number for ordered lists.
+    Item {
+        number: Option<u32>,
+        depth: usize,
+        spans: Vec<Inline>,
+    },
+    Code(String),
+    Rule,
+}
+
+/// Renders a subset of Markdown: `#` headings, paragraphs, `-`/`*`/`1.` lists, fenced code
+/// blocks, `---` rules and inline `**bold**`, `*italic*`, `` `code` `` and `[links](url)`.
+/// The document is turned into a `Column` of `Text`, `Row` and `Container` widgets.
+pub struct Markdown<M> {
+    id: Id,
+    position: Position<i32>,
+    root: Option<Element<M>>,
+
+    blocks: Vec<Block>,
+    size: Size<Length<i32>>,
+    font_size: f32,
+    spacing: i32,
+    indent: i32,
+    color: Color,
+    link_color: Color,
+    code_color: Color,
+    code_background: Color,
+}
+
+impl<M: 'static> Markdown<M> {
+    pub fn new(source: &str) -> Self {
+        Self {
+            id: crate::context::next_id(),
+            position: Position::splat(0),
+            root: None,
+
+            blocks: parse_blocks(source),
+            size: Size::new(Length::Grow(1), Length::Fit),
+            font_size: 16.0,
+            spacing: 10,
+            indent: 18,
+            color: Color::WHITE,
+            link_color: Color::rgb(110, 160, 255),
+            code_color: Color::rgb(220, 220, 220),
+            code_background: Color::rgba(255, 255, 255, 20),
+        }
+    }
+
+    pub fn size(mut self, size: Size<Length<i32>>) -> Self {
+        self.size = size;
+        self
+    }
+    /// Body text size; headings scale from it.
+    pub fn font_size(mut self, size: f32) -> Self {
+        self.font_size = size;
+        self
+    }
+    /// Gap between blocks.
+    pub fn spacing(mut self, amount: i32) -> Self {
+        self.spacing = amount;
+        self
+    }
+    /// Extra indentation per nested list level.
+    pub fn indent(mut self, amount: i32) -> Self {
+        self.indent = amount;
+        self
+    }
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+    pub fn link_color(mut self, color: Color) -> Self {
+        self.link_color = color;
+        self
+    }
+    pub fn code_color(mut self, color: Color) -> Self {
+        self.code_color = color;
+        self
+    }
+    pub fn code_background(mut self, color: Color) -> Self {
+        self.code_background = color;
+        self
+    }
+
+    fn text(&self, inlines: &[Inline], font_size: f32, weight: Weight) -> Text<'static> {
+        let spans = inlines
+            .iter()
+            .map(|inline| {
+                let mut span = Span::new(inline.text.clone());
+                if inline.bold {
+                    span = span.weight(Weight::BOLD);
+                }
+                if inline.italic {
+                    span = span.style(Style::Italic);
+                }
+                if inline.code {
+                    span = span.family(Family::Monospace).color(self.code_color);
+                }
+                if inline.link.is_some() {
+                    span = span.color(self.link_color);
+                }
+                span
+            })
+            .collect();
+
+        Text::rich(spans, font_size)
+            .size(Size::new(Length::Grow(1), Length::Fit))
+            .color(self.color)
+            .weight(weight)
+    }
+
+    fn build(&self) -> Element<M> {
+        let size = self.font_size;
+        let children = self
+            .blocks
+            .iter()
+            .map(|block| match block {
+                Block::Heading(level, spans) => {
+                    let scale = [2.0, 1.6, 1.35, 1.15, 1.0, 0.9][*level as usize - 1];
+                    self.text(spans, size * scale, Weight::BOLD).einto()
+                }
+                Block::Paragraph(spans) => self.text(spans, size, Weight::NORMAL).einto(),
+                Block::Item {
+                    number,
+                    depth,
+                    spans,
+                } => {
+                    let marker = match number {
+                        Some(n) => format!("{n}."),
+                        None => "•".to_string(),
+                    };
+                    Row::new(vec![
+                        Text::new(marker, size)
+                            .color(self.color)
+                            .size(Size::new(Length::Fixed((size * 1.4) as i32), Length::Fit))
+                            .einto(),
+                        self.text(spans, size, Weight::NORMAL).einto(),
+                    ])
+                    .padding(Vec4::new(self.indent * *depth as i32, 0, 0, 0))
+                    .size(Size::new(Length::Grow(1), Length::Fit))
+                    .einto()
+                }
+                Block::Code(code) => {
+                    let pad = (size * 0.6) as i32;
+                    Container::new(vec![
+                        Text::new(code.clone(), size * 0.9)
+                            .family(Family::Monospace)
+                            .color(self.code_color)
+                            .wrap(Wrap::None)
+                            .einto(),
+                    ])
+                    .color(self.code_background)
+                    .padding(Vec4::splat(pad))
+                    .size(Size::new(Length::Grow(1), Length::Fit))
+                    .einto()
+                }
+                Block::Rule => Divider::horizontal().color(self.code_background).einto(),
+            })
+            .collect();
+
+        Column::new(children)
+            .spacing(self.spacing)
+            .size(self.size)
+            .einto()
+    }
+}
+
+impl<M: 'static> Widget<M> for Markdown<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        self.root.as_ref().map_or(&self.position, |r| r.position())
+    }
+    fn layout(&self) -> &Layout {
+        self.root.as_ref().expect(LAYOUT_ERROR).layout()
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.root.as_mut()?.layout_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        if let Some(root) = &self.root {
+            f(root.as_ref());
+        }
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        if let Some(root) = &mut self.root {
+            f(root.as_mut());
+        }
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        if self.root.is_none() {
+            self.root = Some(self.build());
+        }
+        self.root.as_mut().expect(LAYOUT_ERROR).fit_width(ctx)
+    }
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        self.root
+            .as_mut()
+            .expect(LAYOUT_ERROR)
+            .grow_width(ctx, parent_width);
+    }
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.root.as_mut().expect(LAYOUT_ERROR).fit_height(ctx)
+    }
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        self.root
+            .as_mut()
+            .expect(LAYOUT_ERROR)
+            .grow_height(ctx, parent_height);
+    }
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.root.as_mut().expect(LAYOUT_ERROR).place(ctx, position)
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        if let Some(root) = &mut self.root {
+            root.handle(ctx);
+        }
+    }
+}
+
+fn parse_blocks(source: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(parse_inline(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    };
+
+    for line in source.lines() {
+        let trimmed = line.trim_start();
+        if let Some(lines) = &mut code {
+            if trimmed.starts_with("```") {
+                blocks.push(Block::Code(lines.join("\n")));
+                code = None;
+            } else {
+                lines.push(line);
+            }
+            continue;
+        }
+
+        let indent = line[..line.len() - trimmed.len()]
+            .chars()
+            .map(|c| if c == '\t' { 4 } else { 1 })
+            .sum::<usize>();
+        let trimmed = trimmed.trim_end();
+
+        if trimmed.starts_with("```") {
+            flush(&mut paragraph, &mut blocks);
+            code = Some(Vec::new());
+        } else if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else if let Some((level, rest)) = heading(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Heading(level, parse_inline(rest)));
+        } else if is_rule(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Rule);
+        } else if let Some((number, rest)) = list_item(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Item {
+                number,
+                depth: indent / 2,
+                spans: parse_inline(rest),
+            });
+        } else if let (true, true, Some(Block::Item { spans, .. })) =
+            (paragraph.is_empty(), indent > 0, blocks.last_mut())
+        {
+            // Indented continuation of a list item.
+            spans.push(Inline {
+                text: " ".to_string(),
+                ..Inline::default()
+            });
+            spans.extend(parse_inline(trimmed));
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+
+    if let Some(lines) = code {
+        blocks.push(Block::Code(lines.join("\n")));
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+fn heading(line: &str) -> Option<(u8, &str)> {
+    let level = line.bytes().take_while(|&b| b == b'#').count();
+    let rest = &line[level..];
+    if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')) {
+        Some((level as u8, rest.trim().trim_end_matches('#').trim_end()))
+    } else {
+        None
+    }
+}
+
+fn is_rule(line: &str) -> bool {
+    let mut chars = line.chars().filter(|c| !c.is_whitespace());
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    matches!(first, '-' | '*' | '_') && chars.clone().count() >= 2 && chars.all(|c| c == first)
+}
+
+fn list_item(line: &str) -> Option<(Option<u32>, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(bullet) {
+            return Some((None, rest.trim_start()));
+        }
+    }
+    let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+    let rest = &line[digits..];
+    if (1..=9).contains(&digits) && (rest.starts_with(". ") || rest.starts_with(") ")) {
+        return Some((line[..digits].parse().ok(), rest[2..].trim_start()));
+    }
+    None
+}
+
+fn parse_inline(text: &str) -> Vec<Inline> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spans = Vec::new();
+    let mut buf = String::new();
+    let (mut bold, mut italic) = (false, false);
+
+    let flush = |buf: &mut String, spans: &mut Vec<Inline>, bold: bool, italic: bool| {
+        if !buf.is_empty() {
+            spans.push(Inline {
+                text: std::mem::take(buf),
+                bold,
+                italic,
+                ..Inline::default()
+            });
+        }
+    };
+    let find = |from: usize, c: char| chars[from..].iter().position(|&x| x == c).map(|p| p + from);
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '\\' if next.is_some_and(|n| n.is_ascii_punctuation()) => {
+                buf.push(chars[i + 1]);
+                i += 2;
+            }
+            '`' if find(i + 1, '`').is_some() => {
+                let end = find(i + 1, '`').unwrap_or(i);
+                flush(&mut buf, &mut spans, bold, italic);
+                spans.push(Inline {
+                    text: chars[i + 1..end].iter().collect(),
+                    code: true,
+                    ..Inline::default()
+                });
+                i = end + 1;
+            }
+            '*' | '_' => {
+                let prev = i.checked_sub(1).map(|p| chars[p]);
+                // `snake_case` keeps its underscores.
+                if c == '_'
+                    && prev.is_some_and(char::is_alphanumeric)
+                    && next.is_some_and(char::is_alphanumeric)
+                {
+                    buf.push(c);
+                    i += 1;
+                    continue;
+                }
+                flush(&mut buf, &mut spans, bold, italic);
+                if next == Some(c) {
+                    bold = !bold;
+                    i += 2;
+                } else {
+                    italic = !italic;
+                    i += 1;
+                }
+            }
+            '[' => {
+                let link = find(i + 1, ']')
+                    .filter(|&close| chars.get(close + 1) == Some(&'('))
+                    .and_then(|close| find(close + 2, ')').map(|end| (close, end)));
+                match link {
+                    Some((close, end)) => {
+                        flush(&mut buf, &mut spans, bold, italic);
+                        spans.push(Inline {
+                            text: chars[i + 1..close].iter().collect(),
+                            bold,
+                            italic,
+                            code: false,
+                            link: Some(chars[close + 2..end].iter().collect()),
+                        });
+                        i = end + 1;
+                    }
+                    None => {
+                        buf.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            _ => {
+                buf.push(c);
+                i += 1;
+            }
+        }
+    }
+    flush(&mut buf, &mut spans, bold, italic);
+    spans
+}
@@ -0,0 +1,215 @@
+use std::{
+    cell::RefCell,
+    hash::{DefaultHasher, Hash, Hasher},
+    rc::Rc,
+};
+
+use super::*;
+use crate::context::with_id_scope;
+
+struct Slot<M> {
+    key: u64,
+    child: Element<M>,
+    fit_w: Layout,
+    fit_h: Layout,
+    width: Option<i32>,
+    height: Option<i32>,
+    // Set when the content asked for a redraw while handling events, e.g. a section
+    // expanding, which its size may depend on.
+    stale: bool,
+}
+
+struct MemoState<M> {
+    slot: Option<Rc<RefCell<Slot<M>>>>,
+}
+
+impl<M> Default for MemoState<M> {
+    fn default() -> Self {
+        Self { slot: None }
+    }
+}
+
+/// Keeps its content and the layout computed for it across frames while `key` stays the
+/// same. The content is only rebuilt when the key changes, and only measured again when the
+/// space offered to it does or it asked for a redraw, so unchanged parts of a large tree cost
+/// next to nothing at 60fps. It's still placed every frame, which is what scrolling moves.
+/// The key has to cover everything the content is built from.
+///
+/// The rest of the tree is built and laid out again on every redraw; wrap the parts that are
+/// expensive and rarely change.
+pub struct Memo<M> {
+    id: Id,
+    key: u64,
+    build: Option<Box<dyn FnOnce() -> Element<M>>>,
+
+    slot: Option<Rc<RefCell<Slot<M>>>>,
+    // Whether the content has to go through every pass this frame.
+    dirty: bool,
+    layout: Option<Layout>,
+    position: Position<i32>,
+}
+
+impl<M: 'static> Memo<M> {
+    pub fn new(key: impl Hash, build: impl FnOnce() -> Element<M> + 'static) -> Self {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        Self {
+            id: crate::context::next_id(),
+            key: hasher.finish(),
+            build: Some(Box::new(build)),
+
+            slot: None,
+            dirty: false,
+            layout: None,
+            position: Position::splat(0),
+        }
+    }
+
+    fn slot(&self) -> std::cell::RefMut<'_, Slot<M>> {
+        self.slot.as_ref().expect(LAYOUT_ERROR).borrow_mut()
+    }
+
+    // Catches the content up on the passes it skipped earlier this frame.
+    fn refresh(&mut self, ctx: &mut LayoutCtx<M>, through_height: bool) {
+        if self.dirty {
+            return;
+        }
+        self.dirty = true;
+        let mut slot = self.slot();
+        let slot = &mut *slot;
+        slot.fit_w = slot.child.fit_width(ctx);
+        if let Some(w) = slot.width {
+            slot.child.grow_width(ctx, w);
+        }
+        if through_height {
+            slot.fit_h = slot.child.fit_height(ctx);
+        }
+    }
+}
+
+impl<M: 'static> Widget<M> for Memo<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        if let Some(slot) = &self.slot {
+            f(slot.borrow().child.as_ref());
+        }
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        if let Some(slot) = &self.slot {
+            f(slot.borrow_mut().child.as_mut());
+        }
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let cached = ctx
+            .ui
+            .state_mut::<MemoState<M>>(self.id)
+            .slot
+            .clone()
+            .filter(|slot| slot.borrow().key == self.key);
+
+        let l = match cached {
+            Some(slot) => {
+                let stale = std::mem::take(&mut slot.borrow_mut().stale);
+                self.slot = Some(slot);
+                self.dirty = false;
+                if stale {
+                    self.dirty = true;
+                    let mut slot = self.slot();
+                    slot.fit_w = slot.child.fit_width(ctx);
+                }
+                self.slot().fit_w
+            }
+            None => {
+                let build = self.build.take().expect("memo built twice");
+                let mut child = with_id_scope(self.id, build);
+                let l = child.fit_width(ctx);
+                let slot = Rc::new(RefCell::new(Slot {
+                    key: self.key,
+                    child,
+                    fit_w: l,
+                    fit_h: l,
+                    width: None,
+                    height: None,
+                    stale: false,
+                }));
+                ctx.ui.state_mut::<MemoState<M>>(self.id).slot = Some(slot.clone());
+                self.slot = Some(slot);
+                self.dirty = true;
+                l
+            }
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        if self.slot().width != Some(parent_width) {
+            self.refresh(ctx, false);
+        }
+        let mut slot = self.slot();
+        if self.dirty {
+            slot.child.grow_width(ctx, parent_width);
+            slot.width = Some(parent_width);
+        }
+        let width = slot.child.layout().current_size.width;
+        drop(slot);
+        self.layout.as_mut().expect(LAYOUT_ERROR).current_size.width = width;
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let mut slot = self.slot();
+        if self.dirty {
+            slot.fit_h = slot.child.fit_height(ctx);
+        }
+        let l = slot.fit_h;
+        drop(slot);
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        if self.slot().height != Some(parent_height) {
+            self.refresh(ctx, true);
+        }
+        let mut slot = self.slot();
+        if self.dirty {
+            slot.child.grow_height(ctx, parent_height);
+            slot.height = Some(parent_height);
+        }
+        let size = slot.child.layout().current_size;
+        drop(slot);
+        self.layout.as_mut().expect(LAYOUT_ERROR).current_size = size;
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+        let mut slot = self.slot();
+        let _ = slot.child.place(ctx, position);
+        slot.child.layout().current_size
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        if let Some(slot) = &self.slot {
+            let requested = ctx.ui.take_redraw();
+            let mut slot = slot.borrow_mut();
+            slot.child.handle(ctx);
+            slot.stale |= ctx.ui.redraw_pending();
+            if requested {
+                ctx.ui.request_redraw();
+            }
+        }
+    }
+}
@@ -1,9 +1,25 @@
 #![allow(unused_variables)]
 use std::ops::{Deref, DerefMut};
 
-use crate::{context::*, model::*, primitive::Instance};
-
+use crate::{
+    context::*,
+    event::{Phase, RoutedInput},
+    model::*,
+    primitive::Instance,
+};
+
+mod edit_history;
 mod helpers;
+mod scroll_physics;
+mod selection;
+mod text_action;
+mod text_edit;
+
+pub use edit_history::{Edit, EditHistory, EditKind, HistoryAction};
+pub use scroll_physics::{ScrollPhysics, ScrollTuning};
+pub use selection::{SelectionMode, SelectionModel};
+pub use text_action::{TextAction, TextMenuItem};
+pub use text_edit::{EditOutcome, TextEditState};
 
 pub const LAYOUT_ERROR: &str = "Layout not set during fit_width!";
 
@@ -37,7 +53,10 @@ impl Layout {
 pub enum Length<U> {
     Fit,
     Fixed(U),
-    Grow,
+    /// Takes a share of the leftover space proportional to its weight.
+    Grow(u32),
+    /// Percentage of the parent's inner size, e.g. `Percent(30.0)` for 30%.
+    Percent(f32),
 }
 
 impl<U> Size<Length<U>> {
@@ -71,6 +90,10 @@ pub trait Widget<M> {
     fn id(&self) -> Id;
     fn position(&self) -> &Position<i32>;
     fn layout(&self) -> &Layout;
+    /// Lets a parent adjust the size it laid the widget out at, e.g. to stretch it.
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        None
+    }
 
     /* ----- layout ----- */
     fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout;
@@ -87,6 +110,10 @@ pub trait Widget<M> {
         let _ = f;
     }
     #[doc(hidden)]
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        let _ = f;
+    }
+    #[doc(hidden)]
     fn after_draw(
         &self,
         ctx: &mut PaintCtx,
@@ -123,12 +150,79 @@ pub trait Widget<M> {
     /* ----- interaction ----- */
     fn handle(&mut self, ctx: &mut EventCtx<M>) {}
 
+    /// Called with `input` of the event pass before any `handle`, on the widgets from the root
+    /// down to its target and back up, while none of them [consumed](Context::consume) it.
+    fn on_event(&mut self, ctx: &mut EventCtx<M>, input: RoutedInput, phase: Phase) {}
+
     fn einto(self) -> Element<M>
     where
         Self: Sized + 'static,
     {
         Element::new(self)
     }
+
+    /// Reserves `margin` (left, top, right, bottom) of empty space around the widget.
+    fn margin(self, margin: Vec4<i32>) -> Element<M>
+    where
+        Self: Sized + 'static,
+        M: 'static,
+    {
+        self.einto().margin(margin)
+    }
+
+    /// Draws the widget above (positive) or below (negative) siblings regardless of tree order.
+    fn z_index(self, z: i32) -> Element<M>
+    where
+        Self: Sized + 'static,
+        M: 'static,
+    {
+        self.einto().z_index(z)
+    }
+
+    /// Lays the widget and its children out in `direction` instead of the target's.
+    fn direction(self, direction: Direction) -> Element<M>
+    where
+        Self: Sized + 'static,
+        M: 'static,
+    {
+        self.einto().direction(direction)
+    }
+
+    /// Rotates and scales the widget when painted, without changing its layout.
+    fn transform(self, transform: Transform) -> Element<M>
+    where
+        Self: Sized + 'static,
+        M: 'static,
+    {
+        self.einto().transform(transform)
+    }
+
+    /// Paints the widget and its children at `opacity`, from 0 to 1.
+    fn opacity(self, opacity: f32) -> Element<M>
+    where
+        Self: Sized + 'static,
+        M: 'static,
+    {
+        self.einto().opacity(opacity)
+    }
+
+    /// Emits `message` whenever the pointer moves onto the widget.
+    fn on_hover_enter(self, message: M) -> Element<M>
+    where
+        Self: Sized + 'static,
+        M: Clone + 'static,
+    {
+        self.einto().on_hover_enter(message)
+    }
+
+    /// Emits `message` whenever the pointer moves off the widget.
+    fn on_hover_leave(self, message: M) -> Element<M>
+    where
+        Self: Sized + 'static,
+        M: Clone + 'static,
+    {
+        self.einto().on_hover_leave(message)
+    }
 }
 
 pub struct Element<M>(Box<dyn Widget<M>>);
@@ -140,6 +234,62 @@ impl<M> Element<M> {
     {
         Element(Box::new(widget))
     }
+
+    /// Reserves `margin` (left, top, right, bottom) of empty space around the element.
+    pub fn margin(self, margin: Vec4<i32>) -> Self
+    where
+        M: 'static,
+    {
+        Element::new(margin::Margin::new(self, margin))
+    }
+
+    /// Draws the element above (positive) or below (negative) siblings regardless of tree order.
+    pub fn z_index(self, z: i32) -> Self
+    where
+        M: 'static,
+    {
+        Element::new(z_index::ZIndex::new(self, z))
+    }
+
+    /// Lays the element and its children out in `direction` instead of the target's.
+    pub fn direction(self, direction: Direction) -> Self
+    where
+        M: 'static,
+    {
+        Element::new(direction::Directional::new(self, direction))
+    }
+
+    /// Rotates and scales the element when painted, without changing its layout.
+    pub fn transform(self, transform: Transform) -> Self
+    where
+        M: 'static,
+    {
+        Element::new(transform::Transformed::new(self, transform))
+    }
+
+    /// Paints the element and its children at `opacity`, from 0 to 1.
+    pub fn opacity(self, opacity: f32) -> Self
+    where
+        M: 'static,
+    {
+        Element::new(opacity::Opacity::new(self, opacity))
+    }
+
+    /// Emits `message` whenever the pointer moves onto the element.
+    pub fn on_hover_enter(self, message: M) -> Self
+    where
+        M: Clone + 'static,
+    {
+        Element::new(hover::Hover::new(self, Some(message), None))
+    }
+
+    /// Emits `message` whenever the pointer moves off the element.
+    pub fn on_hover_leave(self, message: M) -> Self
+    where
+        M: Clone + 'static,
+    {
+        Element::new(hover::Hover::new(self, None, Some(message)))
+    }
 }
 
 impl<M> AsRef<dyn Widget<M> + 'static> for Element<M> {
@@ -168,6 +318,9 @@ impl<M> DerefMut for Element<M> {
     }
 }
 
+mod style;
+pub use style::{Border, FocusRing, Gradient, Shadow};
+
 mod rectangle;
 pub use rectangle::Rectangle;
 
@@ -187,10 +340,99 @@ mod button;
 pub use button::Button;
 
 mod simple_canvas;
-pub use simple_canvas::SimpleCanvas;
+pub use simple_canvas::{CANVAS_DATA_SIZE, SimpleCanvas};
 
 mod image;
 pub use image::Image;
 
 mod text;
-pub use text::Text;
+pub use text::{Decoration, Orientation, Overflow, Span, Text};
+
+mod text_input;
+pub use text_input::TextInput;
+
+mod text_editor;
+pub use text_editor::{EditorContent, TextEditor};
+
+mod checkbox;
+pub use checkbox::{CheckState, Checkbox};
+
+mod switch;
+pub use switch::Switch;
+
+mod select;
+pub use select::Select;
+
+mod tooltip;
+pub use tooltip::Tooltip;
+
+mod tabs;
+pub use tabs::Tabs;
+
+mod modal;
+pub use modal::Modal;
+
+mod context_menu;
+pub use context_menu::ContextMenu;
+
+mod grid;
+pub use grid::Grid;
+
+mod stack;
+pub use stack::{Alignment, Stack};
+
+mod tree_view;
+pub use tree_view::{TreeNode, TreeView};
+
+mod toast;
+pub use toast::{Toast, ToastHost};
+
+mod number_input;
+pub use number_input::NumberInput;
+
+mod divider;
+pub use divider::Divider;
+
+#[cfg(feature = "svg")]
+mod svg;
+#[cfg(feature = "svg")]
+pub use svg::Svg;
+
+#[cfg(feature = "canvas")]
+mod canvas;
+#[cfg(feature = "canvas")]
+pub use canvas::Canvas;
+
+#[cfg(feature = "rope")]
+mod text_buffer;
+#[cfg(feature = "rope")]
+pub use text_buffer::TextBuffer;
+
+mod markdown;
+pub use markdown::Markdown;
+
+mod hover;
+mod margin;
+mod opacity;
+mod z_index;
+
+mod align;
+pub use align::{Align, Justify};
+
+mod direction;
+pub use direction::Direction;
+
+mod responsive;
+pub use responsive::{Responsive, SizeClass};
+
+mod memo;
+pub use memo::Memo;
+
+pub(crate) mod query;
+pub use query::{Measurement, measure};
+
+mod transform;
+pub use transform::Transform;
+
+mod scrollable;
+pub use scrollable::Scrollable;
@@ -0,0 +1,185 @@
+use super::{helpers::offer, *};
+use crate::event::{KeyState, LogicalKey};
+
+/// Shows `dialog` centered above `content` while open. The dialog lives in its own overlay
+/// layer behind which a backdrop covers the window and swallows all pointer input.
+pub struct Modal<M> {
+    id: Id,
+    content: Element<M>,
+    dialog: Element<M>,
+
+    open: bool,
+    backdrop: Color,
+    on_dismiss: Option<M>,
+}
+
+impl<M: Clone + 'static> Modal<M> {
+    pub fn new(content: Element<M>, dialog: Element<M>) -> Self {
+        Self {
+            id: crate::context::next_id(),
+            content,
+            dialog,
+
+            open: false,
+            backdrop: Color::rgba(0, 0, 0, 140),
+            on_dismiss: None,
+        }
+    }
+
+    pub fn open(mut self, open: bool) -> Self {
+        self.open = open;
+        self
+    }
+    pub fn backdrop(mut self, c: Color) -> Self {
+        self.backdrop = c;
+        self
+    }
+    /// Emitted when the backdrop is clicked or Escape is pressed while open.
+    pub fn on_dismiss(mut self, msg: M) -> Self {
+        self.on_dismiss = Some(msg);
+        self
+    }
+
+    #[inline]
+    fn dialog_contains(&self, p: Position<f32>) -> bool {
+        let pos = self.dialog.position();
+        let sz = self.dialog.layout().current_size;
+        let l = pos.x as f32;
+        let t = pos.y as f32;
+        let r = l + sz.width as f32;
+        let b = t + sz.height as f32;
+        p.x >= l && p.x < r && p.y >= t && p.y < b
+    }
+
+    fn dismiss(&self, ctx: &mut EventCtx<M>) {
+        if let Some(m) = self.on_dismiss.clone() {
+            ctx.ui.emit(m);
+        }
+    }
+}
+
+impl<M: Clone + 'static> Widget<M> for Modal<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        self.content.position()
+    }
+    fn layout(&self) -> &Layout {
+        self.content.layout()
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.content.layout_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        f(self.content.as_ref());
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        f(self.content.as_mut());
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        if self.open {
+            self.dialog.fit_width(ctx);
+        }
+        self.content.fit_width(ctx)
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        if self.open {
+            let w = offer(
+                self.dialog.layout().size.width,
+                ctx.globals.window_size().width,
+            );
+            self.dialog.grow_width(ctx, w);
+        }
+        self.content.grow_width(ctx, parent_width);
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        if self.open {
+            self.dialog.fit_height(ctx);
+        }
+        self.content.fit_height(ctx)
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        if self.open {
+            let h = offer(
+                self.dialog.layout().size.height,
+                ctx.globals.window_size().height,
+            );
+            self.dialog.grow_height(ctx, h);
+        }
+        self.content.grow_height(ctx, parent_height);
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        let size = self.content.place(ctx, position);
+
+        if self.open {
+            let window = ctx.globals.window_size();
+            let dialog = self.dialog.layout().current_size;
+            let pos = Position::new(
+                ((window.width - dialog.width) / 2).max(0),
+                ((window.height - dialog.height) / 2).max(0),
+            );
+            let _ = self.dialog.place(ctx, pos);
+        }
+
+        size
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn __paint(
+        &self,
+        ctx: &mut PaintCtx,
+        instances: &mut Vec<Instance>,
+        t: &internal::PaintToken,
+        debug_on: bool,
+    ) {
+        self.content.__paint(ctx, instances, t, debug_on);
+        if self.open {
+            let window = ctx.globals.window_size();
+            ctx.overlay(|ctx, out| {
+                out.push(Instance::ui(Position::splat(0), window, self.backdrop));
+                self.dialog.__paint(ctx, out, t, debug_on);
+            });
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        if self.open {
+            ctx.ui.push_layer();
+            self.dialog.handle(ctx);
+            ctx.ui.pop_layer();
+
+            let window = ctx.globals.window_size();
+            ctx.ui.add_overlay(self.id, Position::splat(0), window);
+
+            // Popups opened from within the dialog may extend past it.
+            let mouse = ctx.ui.mouse_pos;
+            let outside = !self.dialog_contains(mouse) && !ctx.ui.is_occluded(self.id, mouse);
+            if ctx.ui.mouse_pressed && outside && ctx.ui.active_item != Some(self.id) {
+                ctx.ui.active_item = Some(self.id);
+                self.dismiss(ctx);
+            }
+            if ctx.ui.mouse_released && ctx.ui.active_item == Some(self.id) {
+                ctx.ui.active_item = None;
+            }
+            let escape = ctx
+                .ui
+                .keys()
+                .iter()
+                .any(|k| k.state == KeyState::Pressed && k.logical_key == LogicalKey::Escape);
+            if escape {
+                self.dismiss(ctx);
+            }
+        }
+
+        self.content.handle(ctx);
+    }
+}
@@ -0,0 +1,592 @@
+use std::sync::Arc;
+
+use cosmic_text::{Attrs, Buffer, Family, Shaping, Wrap};
+
+use super::{
+    helpers::{caret_x, hit_index, push_glyphs},
+    text_edit::{EditOutcome, TextEditState},
+    *,
+};
+use crate::{
+    event::{KeyState, LogicalKey},
+    render::text::LayoutKey,
+};
+
+#[derive(Default)]
+struct NumberState {
+    edit: TextEditState,
+    // Text being typed while focused; committed on Enter or when focus moves away.
+    draft: Option<String>,
+    selecting: bool,
+}
+
+/// Numeric field with decrement and increment buttons on either side. Typed text is parsed
+/// and clamped when committed with Enter or by leaving the field; Up and Down step the value.
+/// The value is owned by the application and reported through [`NumberInput::on_change`].
+pub struct NumberInput<M> {
+    layout: Option<Layout>,
+    buffer: Option<Arc<Buffer>>,
+
+    id: Id,
+    position: Position<i32>,
+    size: Size<Length<i32>>,
+
+    value: f64,
+    range: (f64, f64),
+    step: f64,
+    precision: Option<usize>,
+
+    font_size: f32,
+    line_height: f32,
+    padding: i32,
+    attributes: Attrs<'static>,
+
+    color: Color,
+    background: Color,
+    focus_color: Color,
+    button_color: Color,
+    hover_color: Color,
+    selection_color: Color,
+    caret_color: Color,
+
+    text: String,
+    focused: bool,
+    caret: usize,
+    anchor: usize,
+    hovered: Option<i32>,
+
+    on_change: Option<Box<dyn Fn(f64) -> M>>,
+}
+
+impl<M: 'static> NumberInput<M> {
+    pub fn new(value: f64) -> Self {
+        Self {
+            layout: None,
+            buffer: None,
+
+            id: crate::context::next_id(),
+            position: Position::splat(0),
+            size: Size::new(Length::Fixed(140), Length::Fit),
+
+            value,
+            range: (f64::NEG_INFINITY, f64::INFINITY),
+            step: 1.0,
+            precision: None,
+
+            font_size: 14.0,
+            line_height: 1.2,
+            padding: 6,
+            attributes: Attrs::new(),
+
+            color: Color::WHITE,
+            background: Color::rgba(40, 40, 40, 255),
+            focus_color: Color::rgba(55, 55, 55, 255),
+            button_color: Color::rgba(60, 60, 60, 255),
+            hover_color: Color::rgba(80, 80, 80, 255),
+            selection_color: Color::rgba(60, 110, 200, 160),
+            caret_color: Color::WHITE,
+
+            text: String::new(),
+            focused: false,
+            caret: 0,
+            anchor: 0,
+            hovered: None,
+
+            on_change: None,
+        }
+    }
+
+    /// Values outside `min..=max` are clamped.
+    pub fn range(mut self, min: f64, max: f64) -> Self {
+        self.range = (min, max.max(min));
+        self
+    }
+    /// Amount the buttons and arrow keys change the value by.
+    pub fn step(mut self, step: f64) -> Self {
+        self.step = step.abs();
+        self
+    }
+    /// Decimal places shown; defaults to those of the step.
+    pub fn precision(mut self, digits: usize) -> Self {
+        self.precision = Some(digits);
+        self
+    }
+    pub fn font_size(mut self, font_size: f32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+    pub fn family(mut self, family: Family<'static>) -> Self {
+        self.attributes.family = family;
+        self
+    }
+    pub fn padding(mut self, padding: i32) -> Self {
+        self.padding = padding;
+        self
+    }
+    pub fn size(mut self, size: Size<Length<i32>>) -> Self {
+        self.size = size;
+        self
+    }
+    pub fn color(mut self, c: Color) -> Self {
+        self.color = c;
+        self
+    }
+    pub fn background(mut self, c: Color) -> Self {
+        self.background = c;
+        self
+    }
+    pub fn focus_color(mut self, c: Color) -> Self {
+        self.focus_color = c;
+        self
+    }
+    pub fn button_color(mut self, c: Color) -> Self {
+        self.button_color = c;
+        self
+    }
+    pub fn hover_color(mut self, c: Color) -> Self {
+        self.hover_color = c;
+        self
+    }
+    pub fn selection_color(mut self, c: Color) -> Self {
+        self.selection_color = c;
+        self
+    }
+    pub fn caret_color(mut self, c: Color) -> Self {
+        self.caret_color = c;
+        self
+    }
+    pub fn on_change(mut self, f: impl Fn(f64) -> M + 'static) -> Self {
+        self.on_change = Some(Box::new(f));
+        self
+    }
+
+    fn line_px(&self) -> i32 {
+        (self.font_size * self.line_height).ceil() as i32
+    }
+
+    fn format(&self, value: f64) -> String {
+        let digits = self.precision.unwrap_or_else(|| {
+            let step = self.step.to_string();
+            step.split_once('.').map_or(0, |(_, frac)| frac.len())
+        });
+        format!("{value:.digits$}")
+    }
+
+    /// Clamps `value` and rounds it to the shown precision.
+    fn normalize(&self, value: f64) -> f64 {
+        let value = value.clamp(self.range.0, self.range.1);
+        self.format(value).parse().unwrap_or(value)
+    }
+
+    /// Width of each of the two buttons.
+    fn button_w(&self) -> i32 {
+        self.layout().current_size.height
+    }
+
+    /// Which button is under `p`: -1 for decrement, 1 for increment.
+    fn button_at(&self, p: Position<f32>) -> Option<i32> {
+        if !self.contains(p) {
+            return None;
+        }
+        let x = p.x - self.position.x as f32;
+        let w = self.layout().current_size.width as f32;
+        let b = self.button_w() as f32;
+        if x < b {
+            Some(-1)
+        } else if x >= w - b {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    /// Top-left corner of the text.
+    fn text_origin(&self) -> Position<i32> {
+        let h = self.layout().current_size.height;
+        Position::new(
+            self.position.x + self.button_w() + self.padding,
+            self.position.y + (h - self.line_px()) / 2,
+        )
+    }
+
+    fn text_width(&self) -> i32 {
+        (self.layout().current_size.width - 2 * self.button_w() - 2 * self.padding).max(0)
+    }
+
+    fn hit(&self, x: f32) -> usize {
+        match self.buffer.as_ref() {
+            Some(buffer) if !self.text.is_empty() => hit_index(buffer, x),
+            _ => 0,
+        }
+    }
+
+    fn caret_x(&self, index: usize) -> f32 {
+        match self.buffer.as_ref() {
+            Some(buffer) if !self.text.is_empty() => caret_x(buffer, index),
+            _ => 0.0,
+        }
+    }
+
+    fn emit(&self, ctx: &mut EventCtx<M>, value: f64) {
+        if value != self.value
+            && let Some(f) = self.on_change.as_ref()
+        {
+            ctx.ui.emit(f(value));
+        }
+    }
+
+    /// Parses and emits the draft, if any, and drops it.
+    fn commit(&self, ctx: &mut EventCtx<M>, state: &mut NumberState) {
+        if let Some(draft) = state.draft.take()
+            && let Ok(v) = draft.trim().parse::<f64>()
+            && v.is_finite()
+        {
+            self.emit(ctx, self.normalize(v));
+        }
+        ctx.ui.request_redraw();
+    }
+
+    fn step_by(&self, ctx: &mut EventCtx<M>, state: &mut NumberState, dir: f64) {
+        let base = state
+            .draft
+            .as_deref()
+            .and_then(|d| d.trim().parse::<f64>().ok())
+            .filter(|v| v.is_finite())
+            .unwrap_or(self.value);
+        let value = self.normalize(base + dir * self.step);
+        self.emit(ctx, value);
+        if state.draft.is_some() {
+            let text = self.format(value);
+            state.edit.select_all(&text);
+            state.draft = Some(text);
+        }
+        ctx.ui.request_redraw();
+    }
+
+    #[inline]
+    fn contains(&self, p: Position<f32>) -> bool {
+        let sz = self.layout().current_size;
+        let l = self.position.x as f32;
+        let t = self.position.y as f32;
+        let r = l + sz.width as f32;
+        let b = t + sz.height as f32;
+        p.x >= l && p.x < r && p.y >= t && p.y < b
+    }
+}
+
+fn is_numeric(s: &str) -> bool {
+    s.chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E'))
+}
+
+impl<M: 'static> Widget<M> for NumberInput<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.text = match ctx.ui.state_mut::<NumberState>(self.id).draft.clone() {
+            Some(draft) => draft,
+            None => self.format(self.value),
+        };
+        let attrs = self.attributes.clone().color(cosmic_text::Color::rgba(
+            self.color.r(),
+            self.color.g(),
+            self.color.b(),
+            self.color.a(),
+        ));
+        self.buffer = Some(ctx.text.layout(LayoutKey::new(
+            &self.text,
+            &attrs,
+            self.font_size,
+            self.line_height,
+            Wrap::None,
+            Shaping::Basic,
+            None,
+        )));
+
+        // Room for the buttons, which are as wide as the field is tall.
+        let button_w = self.line_px() + 2 * self.padding;
+        let min_w = 2 * button_w + 2 * self.padding + 1;
+        let resolved_w = match self.size.width {
+            Length::Fixed(w) => w,
+            _ => min_w + 4 * self.font_size as i32,
+        }
+        .max(min_w);
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(resolved_w, 0),
+            min: Size::new(min_w, 0),
+            max: Size::splat(i32::MAX),
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_w = match self.size.width {
+            Length::Grow(_) | Length::Percent(_) => parent_width,
+            Length::Fixed(w) => w,
+            Length::Fit => l.current_size.width,
+        }
+        .max(l.min.width)
+        .min(parent_width);
+
+        l.current_size.width = target_w;
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let content_h = self.line_px() + 2 * self.padding;
+        let prev = self.layout.as_ref().expect(LAYOUT_ERROR);
+
+        let resolved_h = match self.size.height {
+            Length::Fixed(h) => h,
+            _ => content_h,
+        }
+        .max(content_h);
+
+        let l = Layout {
+            current_size: Size::new(prev.current_size.width, resolved_h),
+            min: Size::new(prev.min.width, content_h),
+            ..*prev
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_h = match self.size.height {
+            Length::Grow(_) | Length::Percent(_) => parent_height,
+            Length::Fixed(h) => h,
+            Length::Fit => l.current_size.height,
+        }
+        .max(l.min.height)
+        .min(parent_height);
+
+        l.current_size.height = target_h;
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+        self.layout().current_size
+    }
+
+    fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        let size = self.layout().current_size;
+        let background = if self.focused {
+            self.focus_color
+        } else {
+            self.background
+        };
+        instances.push(Instance::ui(self.position, size, background));
+
+        // Minus on the left, plus on the right.
+        let b = self.button_w();
+        for dir in [-1, 1] {
+            let x = if dir < 0 {
+                self.position.x
+            } else {
+                self.position.x + size.width - b
+            };
+            let color = if self.hovered == Some(dir) {
+                self.hover_color
+            } else {
+                self.button_color
+            };
+            instances.push(Instance::ui(
+                Position::new(x, self.position.y),
+                Size::new(b, size.height),
+                color,
+            ));
+
+            let arm = (b / 3).max(3);
+            let bar = (arm / 5).max(1);
+            let center = Position::new(x + b / 2, self.position.y + size.height / 2);
+            instances.push(Instance::ui(
+                Position::new(center.x - arm / 2, center.y - bar / 2),
+                Size::new(arm, bar),
+                self.color,
+            ));
+            if dir > 0 {
+                instances.push(Instance::ui(
+                    Position::new(center.x - bar / 2, center.y - arm / 2),
+                    Size::new(bar, arm),
+                    self.color,
+                ));
+            }
+        }
+
+        let origin = self.text_origin();
+        let x1 = origin.x + self.text_width();
+        let to_screen = |x: f32| (origin.x as f32 + x).round() as i32;
+
+        if self.focused && self.caret != self.anchor {
+            let start = to_screen(self.caret_x(self.caret.min(self.anchor))).max(origin.x);
+            let end = to_screen(self.caret_x(self.caret.max(self.anchor))).min(x1);
+            if end > start {
+                instances.push(Instance::ui(
+                    Position::new(start, origin.y),
+                    Size::new(end - start, self.line_px()),
+                    self.selection_color,
+                ));
+            }
+        }
+
+        if let Some(buffer) = self.buffer.as_ref() {
+            let clip = (origin, Size::new(self.text_width(), self.line_px()));
+            push_glyphs(
+                ctx,
+                buffer,
+                Position::new(origin.x as f32, origin.y as f32),
+                Some(clip),
+                instances,
+            );
+        }
+
+        if self.focused {
+            let x = to_screen(self.caret_x(self.caret)).clamp(origin.x, x1.max(origin.x + 1) - 1);
+            instances.push(Instance::ui(
+                Position::new(x, origin.y),
+                Size::new(1, self.line_px()),
+                self.caret_color,
+            ));
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        ctx.ui.register_focusable(self.id);
+        let mouse = ctx.ui.mouse_pos;
+        let inside = self.contains(mouse) && !ctx.ui.is_occluded(self.id, mouse);
+        let button = if inside { self.button_at(mouse) } else { None };
+        if inside {
+            ctx.ui.hot_item = Some(self.id);
+        }
+
+        let mut state = std::mem::take(ctx.ui.state_mut::<NumberState>(self.id));
+        let before = (state.edit.caret(), state.edit.anchor(), self.focused);
+        let time = ctx.globals.time;
+        let local_x = mouse.x - self.text_origin().x as f32;
+
+        if ctx.ui.mouse_pressed && ctx.ui.active_item != Some(self.id) {
+            if inside {
+                ctx.ui.active_item = Some(self.id);
+                if let Some(dir) = button {
+                    self.step_by(ctx, &mut state, dir as f64);
+                } else {
+                    ctx.ui.kbd_focus_item = Some(self.id);
+                    if state.draft.is_none() {
+                        state.draft = Some(self.text.clone());
+                        state.edit.select_all(&self.text);
+                    } else {
+                        let pos = self.hit(local_x);
+                        state
+                            .edit
+                            .set_caret(&self.text, pos, ctx.ui.modifiers.shift);
+                        state.selecting = true;
+                    }
+                }
+            } else if ctx.ui.kbd_focus_item == Some(self.id) {
+                ctx.ui.kbd_focus_item = None;
+            }
+        }
+        if ctx.ui.active_item == Some(self.id) {
+            if !ctx.ui.mouse_down {
+                ctx.ui.active_item = None;
+                state.selecting = false;
+            } else if state.selecting {
+                let pos = self.hit(local_x);
+                state.edit.set_caret(&self.text, pos, true);
+            }
+        }
+
+        self.focused = ctx.ui.kbd_focus_item == Some(self.id);
+
+        if self.focused {
+            // Focus gained without a click, or the draft was just committed.
+            let mut draft = state.draft.take().unwrap_or_else(|| {
+                state.edit.select_all(&self.text);
+                self.text.clone()
+            });
+            state.edit.clamp(&draft);
+            let mut changed = false;
+            let mut submit = false;
+            let mut step = 0.0;
+            for key in ctx.ui.keys().to_vec() {
+                if key.state != KeyState::Pressed {
+                    continue;
+                }
+                match &key.logical_key {
+                    LogicalKey::ArrowUp => step += 1.0,
+                    LogicalKey::ArrowDown => step -= 1.0,
+                    LogicalKey::Escape => {
+                        // Drop the typed text and keep the current value.
+                        state.edit = TextEditState::default();
+                        ctx.ui.kbd_focus_item = None;
+                        self.focused = false;
+                        ctx.ui.request_redraw();
+                        break;
+                    }
+                    LogicalKey::Character(s)
+                        if !is_numeric(s) && TextAction::from_key(&key).is_none() => {}
+                    _ => match state.edit.handle_key(&mut draft, &key, false, time, ctx.ui) {
+                        EditOutcome::Changed => changed = true,
+                        EditOutcome::Submit => submit = true,
+                        EditOutcome::Moved | EditOutcome::Ignored => {}
+                    },
+                }
+            }
+
+            let committed: String = ctx
+                .ui
+                .text()
+                .chars()
+                .filter(|c| is_numeric(&c.to_string()))
+                .collect();
+            if !committed.is_empty() {
+                state.edit.insert(&mut draft, &committed, time);
+                changed = true;
+            }
+
+            if self.focused {
+                state.draft = Some(draft);
+                if step != 0.0 {
+                    self.step_by(ctx, &mut state, step);
+                } else if submit {
+                    self.commit(ctx, &mut state);
+                }
+                if changed {
+                    ctx.ui.request_redraw();
+                }
+            }
+        } else if state.draft.is_some() {
+            self.commit(ctx, &mut state);
+        }
+
+        self.caret = state.edit.caret();
+        self.anchor = state.edit.anchor();
+        let hovered = if ctx.ui.is_occluded(self.id, mouse) {
+            None
+        } else {
+            button
+        };
+        if (self.caret, self.anchor, self.focused) != before || hovered != self.hovered {
+            ctx.ui.request_redraw();
+        }
+        self.hovered = hovered;
+        *ctx.ui.state_mut::<NumberState>(self.id) = state;
+    }
+}
@@ -0,0 +1,79 @@
+use super::*;
+
+/// Fades a child and everything it paints by a factor. Built with [`Widget::opacity`] or
+/// [`Element::opacity`].
+pub(crate) struct Opacity<M> {
+    id: Id,
+    child: Element<M>,
+    opacity: f32,
+}
+
+impl<M> Opacity<M> {
+    pub(crate) fn new(child: Element<M>, opacity: f32) -> Self {
+        Self {
+            id: crate::context::next_id(),
+            child,
+            opacity,
+        }
+    }
+}
+
+impl<M: 'static> Widget<M> for Opacity<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        self.child.position()
+    }
+    fn layout(&self) -> &Layout {
+        self.child.layout()
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.child.layout_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        f(self.child.as_ref());
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        f(self.child.as_mut());
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.child.fit_width(ctx)
+    }
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        self.child.grow_width(ctx, parent_width);
+    }
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.child.fit_height(ctx)
+    }
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        self.child.grow_height(ctx, parent_height);
+    }
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.child.place(ctx, position)
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn __paint(
+        &self,
+        ctx: &mut PaintCtx,
+        instances: &mut Vec<Instance>,
+        t: &internal::PaintToken,
+        debug_on: bool,
+    ) {
+        if self.opacity <= 0.0 {
+            return;
+        }
+        ctx.faded(self.opacity, instances, |ctx, instances| {
+            self.child.__paint(ctx, instances, t, debug_on)
+        });
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        self.child.handle(ctx);
+    }
+}
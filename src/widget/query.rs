@@ -0,0 +1,129 @@
+use super::*;
+
+fn contains<M>(widget: &dyn Widget<M>, p: Position<f32>) -> bool {
+    let pos = widget.position();
+    let size = widget.layout().current_size;
+    p.x >= pos.x as f32
+        && p.y >= pos.y as f32
+        && p.x < (pos.x + size.width) as f32
+        && p.y < (pos.y + size.height) as f32
+}
+
+/// The innermost widget under `p`, preferring later (drawn on top) siblings. Children that
+/// stick out of their parent are still found.
+pub(crate) fn hit_test<M>(widget: &dyn Widget<M>, p: Position<f32>) -> Option<Id> {
+    path_at(widget, p).last().copied()
+}
+
+/// Ids from `widget` down to the one [`hit_test`] finds under `p`.
+fn path_at<M>(widget: &dyn Widget<M>, p: Position<f32>) -> Vec<Id> {
+    let mut path = Vec::new();
+    widget.for_each_child(&mut |child| {
+        let hit = path_at(child, p);
+        if !hit.is_empty() {
+            path = hit;
+        }
+    });
+    if !path.is_empty() || contains(widget, p) {
+        path.insert(0, widget.id());
+    }
+    path
+}
+
+/// Ids from `widget` down to `id`, or none if it isn't in the tree.
+fn path_to<M>(widget: &dyn Widget<M>, id: Id) -> Vec<Id> {
+    if widget.id() == id {
+        return vec![id];
+    }
+    let mut path = Vec::new();
+    widget.for_each_child(&mut |child| {
+        if path.is_empty() {
+            path = path_to(child, id);
+        }
+    });
+    if !path.is_empty() {
+        path.insert(0, widget.id());
+    }
+    path
+}
+
+/// Routes the pointer input of the event pass to the widget that captured the pointer or the
+/// one under it, and key input to the focused widget, see [`Widget::on_event`].
+pub(crate) fn dispatch<M>(root: &mut Element<M>, ctx: &mut EventCtx<M>) {
+    if ctx.ui.has_input(RoutedInput::Pointer) {
+        let path = match ctx.ui.pointer_capture() {
+            Some(id) => path_to(root.as_ref(), id),
+            None => path_at(root.as_ref(), ctx.ui.mouse_pos),
+        };
+        route(root.as_mut(), &path, ctx, RoutedInput::Pointer);
+    }
+    if ctx.ui.has_input(RoutedInput::Keys)
+        && let Some(id) = ctx.ui.kbd_focus_item
+    {
+        let path = path_to(root.as_ref(), id);
+        route(root.as_mut(), &path, ctx, RoutedInput::Keys);
+    }
+}
+
+fn route<M>(widget: &mut dyn Widget<M>, path: &[Id], ctx: &mut EventCtx<M>, input: RoutedInput) {
+    let Some((_, rest)) = path.split_first() else {
+        return;
+    };
+    let Some(&next) = rest.first() else {
+        widget.on_event(ctx, input, Phase::Target);
+        return;
+    };
+
+    widget.on_event(ctx, input, Phase::Capture);
+    if ctx.ui.is_consumed(input) {
+        return;
+    }
+    widget.for_each_child_mut(&mut |child| {
+        if child.id() == next && !ctx.ui.is_consumed(input) {
+            route(child, rest, ctx, input);
+        }
+    });
+    if !ctx.ui.is_consumed(input) {
+        widget.on_event(ctx, input, Phase::Bubble);
+    }
+}
+
+/// Where `id` was placed in the last layout, as its top-left corner and size.
+pub(crate) fn bounds<M>(widget: &dyn Widget<M>, id: Id) -> Option<(Position<i32>, Size<i32>)> {
+    if widget.id() == id {
+        return Some((*widget.position(), widget.layout().current_size));
+    }
+    let mut found = None;
+    widget.for_each_child(&mut |child| {
+        if found.is_none() {
+            found = bounds(child, id);
+        }
+    });
+    found
+}
+
+/// Sizes an element tree would take, as found by [`measure`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    /// Size at its natural width, wrapping only past the `max` it was measured against.
+    pub preferred: Size<i32>,
+    /// Smallest size it can shrink to.
+    pub min: Size<i32>,
+}
+
+/// Lays `element` out on its own, without drawing it, to find how much space it wants within
+/// `max`, e.g. to size a layer-shell surface to its content before committing it.
+pub fn measure<M>(element: &mut Element<M>, ctx: &mut LayoutCtx<M>, max: Size<i32>) -> Measurement {
+    let fit_w = element.fit_width(ctx);
+    let width = fit_w.current_size.width.min(max.width).max(0);
+    element.grow_width(ctx, width);
+
+    let fit_h = element.fit_height(ctx);
+    let height = fit_h.current_size.height.min(max.height).max(0);
+    element.grow_height(ctx, height);
+
+    Measurement {
+        preferred: element.layout().current_size,
+        min: Size::new(fit_w.min.width, fit_h.min.height),
+    }
+}
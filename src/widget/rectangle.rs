@@ -7,6 +7,10 @@ pub struct Rectangle {
     position: Position<i32>,
     size: Size<Length<i32>>,
     color: Color,
+    shadow: Option<Shadow>,
+    radii: Vec4<f32>,
+    border: Option<Border>,
+    gradient: Option<Gradient>,
 
     min: Size<i32>,
     max: Size<i32>,
@@ -21,6 +25,10 @@ impl Rectangle {
             position: Position::splat(0),
             size,
             color,
+            shadow: None,
+            radii: Vec4::splat(0.0),
+            border: None,
+            gradient: None,
             min: Size::splat(0),
             max: Size::splat(i32::MAX),
         }
@@ -34,6 +42,28 @@ impl Rectangle {
         self.max = size;
         self
     }
+    /// Rounds every corner by `radius` pixels.
+    pub fn radius(self, radius: f32) -> Self {
+        self.radii(Vec4::splat(radius))
+    }
+    /// Rounds each corner on its own: top-left, top-right, bottom-right, bottom-left.
+    pub fn radii(mut self, radii: Vec4<f32>) -> Self {
+        self.radii = radii;
+        self
+    }
+    pub fn border(mut self, border: Border) -> Self {
+        self.border = Some(border);
+        self
+    }
+    pub fn shadow(mut self, shadow: Shadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+    /// Fills the rectangle with `gradient` instead of its color.
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
 }
 
 impl<M> Widget<M> for Rectangle {
@@ -44,6 +74,9 @@ impl<M> Widget<M> for Rectangle {
     fn layout(&self) -> &Layout {
         self.layout.as_ref().expect(LAYOUT_ERROR)
     }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
 
     fn id(&self) -> Id {
         self.id
@@ -73,7 +106,7 @@ impl<M> Widget<M> for Rectangle {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
 
         let target_w = match self.size.width {
-            Length::Grow => parent_width,
+            Length::Grow(_) | Length::Percent(_) => parent_width,
             Length::Fixed(w) => w,
             Length::Fit => l.current_size.width,
         };
@@ -108,7 +141,7 @@ impl<M> Widget<M> for Rectangle {
     fn grow_height(&mut self, _ctx: &mut LayoutCtx<M>, parent_height: i32) {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
         let target_h = match self.size.height {
-            Length::Grow => parent_height,
+            Length::Grow(_) | Length::Percent(_) => parent_height,
             Length::Fixed(h) => h,
             Length::Fit => l.current_size.height,
         };
@@ -127,12 +160,17 @@ impl<M> Widget<M> for Rectangle {
     }
 
     fn draw_self(&self, _ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
-        if self.color.a() != Color::TRANSPARENT.a() {
-            instances.push(Instance::ui(
-                self.position,
-                <Rectangle as Widget<M>>::layout(self).current_size,
-                self.color,
-            ));
+        let size = <Rectangle as Widget<M>>::layout(self).current_size;
+        if let Some(shadow) = &self.shadow {
+            instances.push(shadow.instance(self.position, size, self.radii));
+        }
+        if let Some(gradient) = &self.gradient {
+            instances.push(gradient.instance(self.position, size).radii(self.radii));
+        } else if self.color.a() != Color::TRANSPARENT.a() {
+            instances.push(Instance::ui(self.position, size, self.color).radii(self.radii));
+        }
+        if let Some(border) = &self.border {
+            border.push(self.position, size, self.radii, instances);
         }
     }
 }
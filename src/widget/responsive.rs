@@ -0,0 +1,150 @@
+use super::*;
+
+/// Coarse width buckets of a target, for views that switch layout between a narrow bar and a
+/// full panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SizeClass {
+    /// Narrower than 600 pixels.
+    Compact,
+    /// 600 to 1024 pixels.
+    Medium,
+    /// 1024 pixels and wider.
+    Expanded,
+}
+
+impl SizeClass {
+    pub fn of(width: i32) -> Self {
+        if width >= SizeClass::Expanded.min_width() {
+            SizeClass::Expanded
+        } else if width >= SizeClass::Medium.min_width() {
+            SizeClass::Medium
+        } else {
+            SizeClass::Compact
+        }
+    }
+
+    /// Narrowest width in the class.
+    pub fn min_width(self) -> i32 {
+        match self {
+            SizeClass::Compact => 0,
+            SizeClass::Medium => 600,
+            SizeClass::Expanded => 1024,
+        }
+    }
+}
+
+type Build<M> = Box<dyn FnOnce() -> Element<M>>;
+
+/// Builds its content from the closure of the widest breakpoint the target's width reaches.
+/// The closure runs during layout, so only the branch that is shown is ever built.
+pub struct Responsive<M> {
+    id: Id,
+    position: Position<i32>,
+    child: Option<Element<M>>,
+
+    fallback: Option<Build<M>>,
+    breakpoints: Vec<(i32, Build<M>)>,
+}
+
+impl<M: 'static> Responsive<M> {
+    /// `build` makes the content shown below every breakpoint.
+    pub fn new(build: impl FnOnce() -> Element<M> + 'static) -> Self {
+        Self {
+            id: crate::context::next_id(),
+            position: Position::splat(0),
+            child: None,
+
+            fallback: Some(Box::new(build)),
+            breakpoints: Vec::new(),
+        }
+    }
+
+    /// Content shown once the target is at least `min_width` pixels wide.
+    pub fn at(mut self, min_width: i32, build: impl FnOnce() -> Element<M> + 'static) -> Self {
+        self.breakpoints.push((min_width, Box::new(build)));
+        self
+    }
+
+    /// Content shown from the start of `class` on.
+    pub fn class(self, class: SizeClass, build: impl FnOnce() -> Element<M> + 'static) -> Self {
+        self.at(class.min_width(), build)
+    }
+
+    fn build(&mut self, width: i32) -> Element<M> {
+        let picked = self
+            .breakpoints
+            .iter()
+            .enumerate()
+            .filter(|(_, (min, _))| width >= *min)
+            .max_by_key(|(_, (min, _))| *min)
+            .map(|(i, _)| i);
+        match picked {
+            Some(i) => (self.breakpoints.swap_remove(i).1)(),
+            None => (self.fallback.take().expect("content already built"))(),
+        }
+    }
+}
+
+impl<M: 'static> Widget<M> for Responsive<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        self.child.as_ref().map_or(&self.position, |c| c.position())
+    }
+    fn layout(&self) -> &Layout {
+        self.child.as_ref().expect(LAYOUT_ERROR).layout()
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.child.as_mut()?.layout_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        if let Some(child) = &self.child {
+            f(child.as_ref());
+        }
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        if let Some(child) = &mut self.child {
+            f(child.as_mut());
+        }
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        if self.child.is_none() {
+            let child = self.build(ctx.globals.window_size().width);
+            self.child = Some(child);
+        }
+        self.child.as_mut().expect(LAYOUT_ERROR).fit_width(ctx)
+    }
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        self.child
+            .as_mut()
+            .expect(LAYOUT_ERROR)
+            .grow_width(ctx, parent_width);
+    }
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.child.as_mut().expect(LAYOUT_ERROR).fit_height(ctx)
+    }
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        self.child
+            .as_mut()
+            .expect(LAYOUT_ERROR)
+            .grow_height(ctx, parent_height);
+    }
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.child
+            .as_mut()
+            .expect(LAYOUT_ERROR)
+            .place(ctx, position)
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        if let Some(child) = &mut self.child {
+            child.handle(ctx);
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use super::*;
-use crate::widget::helpers::{Width, equalize_sizes};
+use crate::widget::helpers::{Width, equalize_sizes, offer};
 
 pub struct Row<M> {
     layout: Option<Layout>,
@@ -7,6 +7,8 @@ pub struct Row<M> {
     id: Id,
     children: Vec<Element<M>>,
     spacing: i32,
+    align: Align,
+    justify: Justify,
     position: Position<i32>,
     size: Size<Length<i32>>,
     color: Color,
@@ -23,6 +25,8 @@ impl<M> Row<M> {
             id: crate::context::next_id(),
             children,
             spacing: 0,
+            align: Align::Start,
+            justify: Justify::Start,
             position: Position::splat(0),
             size: Size::splat(Length::Fit),
             color: Color::TRANSPARENT,
@@ -37,6 +41,18 @@ impl<M> Row<M> {
         self
     }
 
+    /// Where children sit across the row.
+    pub fn align_items(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// How space left along the row is spread between children.
+    pub fn justify_content(mut self, justify: Justify) -> Self {
+        self.justify = justify;
+        self
+    }
+
     pub fn size(mut self, size: Size<Length<i32>>) -> Self {
         self.size = size;
         self
@@ -73,6 +89,9 @@ impl<M: 'static> Widget<M> for Row<M> {
     fn layout(&self) -> &Layout {
         self.layout.as_ref().expect(LAYOUT_ERROR)
     }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
 
     fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
         for child in &self.children {
@@ -80,6 +99,12 @@ impl<M: 'static> Widget<M> for Row<M> {
         }
     }
 
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        for child in &mut self.children {
+            f(child.as_mut());
+        }
+    }
+
     fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
         let width_padding = self.padding.x + self.padding.z;
         let spacing = (self.children.len() as i32 - 1).max(0) * self.spacing;
@@ -110,7 +135,7 @@ impl<M: 'static> Widget<M> for Row<M> {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
 
         let target_w = match self.size.width {
-            Length::Grow => parent_width,
+            Length::Grow(_) | Length::Percent(_) => parent_width,
             Length::Fixed(w) => w,
             Length::Fit => l.current_size.width,
         }
@@ -166,7 +191,7 @@ impl<M: 'static> Widget<M> for Row<M> {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
 
         let target_h = match self.size.height {
-            Length::Grow => parent_height,
+            Length::Grow(_) | Length::Percent(_) => parent_height,
             Length::Fixed(h) => h,
             Length::Fit => l.current_size.height,
         }
@@ -176,7 +201,22 @@ impl<M: 'static> Widget<M> for Row<M> {
 
         let inner_h = (target_h - self.padding.y - self.padding.w).max(0);
         for child in self.children.iter_mut() {
-            child.grow_height(ctx, inner_h);
+            let h = offer(child.layout().size.height, inner_h);
+            let stretch = self.align == Align::Stretch
+                && !matches!(
+                    child.layout().size.height,
+                    Length::Fixed(_) | Length::Percent(_)
+                );
+            // Grown from the stretched height, so what the child holds fills it too.
+            let stretch_to = |child: &mut Element<M>| {
+                if stretch && let Some(cl) = child.layout_mut() {
+                    cl.current_size.height =
+                        inner_h.clamp(cl.min.height, cl.max.height.max(cl.min.height));
+                }
+            };
+            stretch_to(child);
+            child.grow_height(ctx, h);
+            stretch_to(child);
         }
 
         l.current_size.height = target_h;
@@ -184,15 +224,34 @@ impl<M: 'static> Widget<M> for Row<M> {
 
     fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
         self.position = position;
-        let mut cursor = Position::new(
-            self.position.x + self.padding.x,
-            self.position.y + self.padding.y,
+        let size = self.layout().current_size;
+        let inner = Size::new(
+            size.width - self.padding.x - self.padding.z,
+            size.height - self.padding.y - self.padding.w,
         );
-        for child in self.children.iter_mut() {
+        let used = self
+            .children
+            .iter()
+            .map(|c| c.layout().current_size.width)
+            .sum::<i32>()
+            + (self.children.len() as i32 - 1).max(0) * self.spacing;
+        let offsets = self
+            .justify
+            .offsets(inner.width - used, self.children.len());
+
+        let direction = ctx.ui.direction();
+        let mut x = self.position.x + self.padding.x;
+        for (child, offset) in self.children.iter_mut().zip(offsets) {
+            let child_size = child.layout().current_size;
+            let free = inner.height - child_size.height;
+            let cursor = Position::new(
+                direction.flow(position.x, size.width, x + offset, child_size.width),
+                self.position.y + self.padding.y + self.align.offset(free),
+            );
             let child_size = child.place(ctx, cursor);
-            cursor.x += child_size.width + self.spacing;
+            x += child_size.width + self.spacing;
         }
-        self.layout().current_size
+        size
     }
 
     fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
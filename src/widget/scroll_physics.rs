@@ -0,0 +1,355 @@
+use std::collections::VecDeque;
+
+/// Tuning knobs for [`ScrollPhysics`]. Distances are in pixels, times in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollTuning {
+    /// Exponential velocity decay per second while coasting.
+    pub friction: f32,
+    /// Speed below which coasting stops.
+    pub min_velocity: f32,
+    /// Fling speeds are clamped to this.
+    pub max_velocity: f32,
+    /// How far content can be pulled past its bounds.
+    pub overscroll: f32,
+    /// Stiffness of the spring used for bounce-back and snapping.
+    pub spring: f32,
+    /// How much pointer history is used to estimate fling velocity.
+    pub velocity_window: f32,
+}
+
+impl ScrollTuning {
+    /// Defaults that roughly match the native feel of the current platform.
+    pub fn platform() -> Self {
+        if cfg!(target_os = "macos") {
+            Self {
+                friction: 2.2,
+                overscroll: 160.0,
+                spring: 14.0,
+                ..Self::default()
+            }
+        } else if cfg!(target_os = "windows") {
+            Self {
+                friction: 5.0,
+                overscroll: 0.0,
+                ..Self::default()
+            }
+        } else {
+            Self::default()
+        }
+    }
+}
+
+impl Default for ScrollTuning {
+    fn default() -> Self {
+        Self {
+            friction: 3.5,
+            min_velocity: 8.0,
+            max_velocity: 8000.0,
+            overscroll: 96.0,
+            spring: 18.0,
+            velocity_window: 0.1,
+        }
+    }
+}
+
+/// Momentum, rubber-banding and snap-point logic shared by scrollable widgets.
+///
+/// The owner feeds it drag/wheel input and calls [`ScrollPhysics::step`] once per frame with
+/// `Globals::delta_time`, requesting a redraw for as long as it reports motion.
+#[derive(Debug, Clone)]
+pub struct ScrollPhysics {
+    tuning: ScrollTuning,
+    reduced_motion: bool,
+
+    offset: f32,
+    velocity: f32,
+    min: f32,
+    max: f32,
+    snap_points: Vec<f32>,
+    target: Option<f32>,
+
+    dragging: bool,
+    samples: VecDeque<(f32, f32)>,
+}
+
+impl Default for ScrollPhysics {
+    fn default() -> Self {
+        Self::new(ScrollTuning::platform())
+    }
+}
+
+impl ScrollPhysics {
+    pub fn new(tuning: ScrollTuning) -> Self {
+        Self {
+            tuning,
+            reduced_motion: crate::consts::reduced_motion(),
+
+            offset: 0.0,
+            velocity: 0.0,
+            min: 0.0,
+            max: 0.0,
+            snap_points: Vec::new(),
+            target: None,
+
+            dragging: false,
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn tuning(&self) -> &ScrollTuning {
+        &self.tuning
+    }
+
+    pub fn set_tuning(&mut self, tuning: ScrollTuning) {
+        self.tuning = tuning;
+    }
+
+    /// Overrides the `UI_REDUCED_MOTION` environment default.
+    pub fn set_reduced_motion(&mut self, on: bool) {
+        self.reduced_motion = on;
+        if on {
+            self.settle();
+        }
+    }
+
+    pub fn reduced_motion(&self) -> bool {
+        self.reduced_motion
+    }
+
+    /// Scrollable range for `content` pixels shown through a `viewport`-sized window.
+    pub fn set_extent(&mut self, content: f32, viewport: f32) {
+        self.set_bounds(0.0, (content - viewport).max(0.0));
+    }
+
+    pub fn set_bounds(&mut self, min: f32, max: f32) {
+        self.min = min;
+        self.max = max.max(min);
+        if !self.dragging && self.target.is_none() && self.overshoot() != 0.0 {
+            self.target = Some(self.clamp(self.offset));
+            if self.reduced_motion {
+                self.settle();
+            }
+        }
+    }
+
+    pub fn set_snap_points(&mut self, points: Vec<f32>) {
+        self.snap_points = points;
+    }
+
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+
+    pub fn velocity(&self) -> f32 {
+        self.velocity
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    pub fn is_animating(&self) -> bool {
+        !self.dragging && (self.target.is_some() || self.velocity != 0.0)
+    }
+
+    /// Jumps to `offset` (clamped), cancelling any motion.
+    pub fn set_offset(&mut self, offset: f32) {
+        self.offset = self.clamp(offset);
+        self.velocity = 0.0;
+        self.target = None;
+    }
+
+    /// Moves to `offset` (clamped) using the spring, or instantly with reduced motion.
+    pub fn scroll_to(&mut self, offset: f32) {
+        if self.reduced_motion {
+            self.set_offset(offset);
+        } else {
+            self.target = Some(self.clamp(offset));
+        }
+    }
+
+    /// Scrolls the minimum amount needed so the item spanning `start..end` (content
+    /// coordinates) is fully visible in a `viewport`-sized window. Returns whether it moved.
+    pub fn reveal(&mut self, start: f32, end: f32, viewport: f32) -> bool {
+        let current = self.target.unwrap_or(self.offset);
+        let wanted = if end - start > viewport || start < current {
+            start
+        } else if end > current + viewport {
+            end - viewport
+        } else {
+            return false;
+        };
+        if self.clamp(wanted) == current {
+            return false;
+        }
+        self.scroll_to(wanted);
+        true
+    }
+
+    /// Discrete scroll step, e.g. a mouse wheel notch. Positive values scroll forward.
+    pub fn scroll_by(&mut self, delta: f32) {
+        let base = self.target.unwrap_or(self.offset);
+        self.scroll_to(base + delta);
+    }
+
+    /// Starts coasting with `velocity` pixels per second.
+    pub fn fling(&mut self, velocity: f32) {
+        if self.reduced_motion {
+            self.settle();
+            return;
+        }
+        let max = self.tuning.max_velocity;
+        self.velocity = velocity.clamp(-max, max);
+        self.target = self.snap_target(self.offset + self.velocity / self.tuning.friction);
+    }
+
+    pub fn begin_drag(&mut self, time: f32) {
+        self.dragging = true;
+        self.velocity = 0.0;
+        self.target = None;
+        self.samples.clear();
+        self.samples.push_back((time, self.offset));
+    }
+
+    /// Applies a pointer movement of `delta` pixels, damped past the bounds.
+    pub fn drag_by(&mut self, delta: f32, time: f32) {
+        if !self.dragging {
+            self.begin_drag(time);
+        }
+
+        let overscroll = if self.reduced_motion {
+            0.0
+        } else {
+            self.tuning.overscroll
+        };
+        let next = self.offset + delta;
+        let past = if next < self.min {
+            self.min - next
+        } else if next > self.max {
+            next - self.max
+        } else {
+            0.0
+        };
+
+        self.offset = if past == 0.0 {
+            next
+        } else if overscroll <= 0.0 {
+            self.clamp(next)
+        } else {
+            // Resistance grows as the content is pulled further out.
+            let damped = overscroll * (1.0 - 1.0 / (past / overscroll + 1.0));
+            if next < self.min {
+                self.min - damped
+            } else {
+                self.max + damped
+            }
+        };
+
+        self.samples.push_back((time, self.offset));
+        while let Some(&(t, _)) = self.samples.front() {
+            if time - t > self.tuning.velocity_window && self.samples.len() > 2 {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn end_drag(&mut self, time: f32) {
+        if !self.dragging {
+            return;
+        }
+        self.dragging = false;
+
+        let velocity = match (self.samples.front(), self.samples.back()) {
+            (Some(&(t0, p0)), Some(&(t1, p1))) if time - t1 < self.tuning.velocity_window => {
+                let dt = (t1 - t0).max(f32::EPSILON);
+                (p1 - p0) / dt
+            }
+            _ => 0.0,
+        };
+        self.samples.clear();
+
+        if self.overshoot() != 0.0 {
+            self.velocity = 0.0;
+            self.target = Some(self.clamp(self.offset));
+            if self.reduced_motion {
+                self.settle();
+            }
+        } else {
+            self.fling(velocity);
+            if self.velocity == 0.0 && self.target.is_none() {
+                self.target = self.snap_target(self.offset);
+            }
+        }
+    }
+
+    /// Advances the simulation by `dt` seconds. Returns `true` while still moving.
+    pub fn step(&mut self, dt: f32) -> bool {
+        if self.dragging || dt <= 0.0 {
+            return false;
+        }
+
+        if let Some(target) = self.target
+            && self.velocity.abs() < self.tuning.min_velocity * 4.0
+        {
+            self.spring_towards(target, dt);
+        } else if self.velocity != 0.0 {
+            self.offset += self.velocity * dt;
+            self.velocity *= (-self.tuning.friction * dt).exp();
+
+            if self.overshoot() != 0.0 {
+                self.velocity *= (-self.tuning.spring * dt).exp();
+                self.target = Some(self.clamp(self.offset));
+            }
+            if self.velocity.abs() < self.tuning.min_velocity {
+                self.velocity = 0.0;
+                if self.target.is_none() {
+                    self.target = self.snap_target(self.offset);
+                }
+            }
+        } else if self.overshoot() != 0.0 {
+            self.target = Some(self.clamp(self.offset));
+        }
+
+        self.is_animating()
+    }
+
+    fn spring_towards(&mut self, target: f32, dt: f32) {
+        // Critically damped spring, integrated semi-implicitly for stability at low fps.
+        let k = self.tuning.spring;
+        let accel = k * k * (target - self.offset) - 2.0 * k * self.velocity;
+        self.velocity += accel * dt;
+        self.offset += self.velocity * dt;
+
+        if (target - self.offset).abs() < 0.5 && self.velocity.abs() < self.tuning.min_velocity {
+            self.offset = target;
+            self.velocity = 0.0;
+            self.target = None;
+        }
+    }
+
+    fn settle(&mut self) {
+        let rest = self
+            .target
+            .or_else(|| self.snap_target(self.offset))
+            .unwrap_or(self.offset);
+        self.set_offset(rest);
+    }
+
+    fn snap_target(&self, rest: f32) -> Option<f32> {
+        let rest = self.clamp(rest);
+        self.snap_points
+            .iter()
+            .map(|&p| self.clamp(p))
+            .min_by(|a, b| (a - rest).abs().total_cmp(&(b - rest).abs()))
+    }
+
+    fn overshoot(&self) -> f32 {
+        self.offset - self.clamp(self.offset)
+    }
+
+    fn clamp(&self, v: f32) -> f32 {
+        v.clamp(self.min, self.max)
+    }
+}
@@ -0,0 +1,329 @@
+use super::{ScrollPhysics, ScrollTuning, helpers::offer, *};
+use crate::event::{ScrollDelta, TouchPhase};
+
+#[derive(Default)]
+struct ScrollState {
+    physics: ScrollPhysics,
+    // Frame the physics was last stepped in; handle can run more than once per frame.
+    frame: u32,
+    // When the last touchpad delta arrived, to end gestures whose `Ended` never comes.
+    last_touch: f32,
+}
+
+/// Shows a vertical slice of a taller child, scrolled by the wheel or touchpad. Wheel notches
+/// glide to their target, touchpad swipes keep coasting with momentum once released.
+pub struct Scrollable<M> {
+    layout: Option<Layout>,
+
+    id: Id,
+    child: Element<M>,
+    position: Position<i32>,
+    size: Size<Length<i32>>,
+    min: Size<i32>,
+    max: Size<i32>,
+
+    tuning: ScrollTuning,
+    line_height: f32,
+    offset: f32,
+    radii: Vec4<f32>,
+}
+
+impl<M: 'static> Scrollable<M> {
+    pub fn new(child: Element<M>) -> Self {
+        Self {
+            layout: None,
+
+            id: crate::context::next_id(),
+            child,
+            position: Position::splat(0),
+            size: Size::new(Length::Fit, Length::Grow(1)),
+            min: Size::splat(0),
+            max: Size::splat(i32::MAX),
+
+            tuning: ScrollTuning::platform(),
+            line_height: 40.0,
+            offset: 0.0,
+            radii: Vec4::splat(0.0),
+        }
+    }
+
+    pub fn size(mut self, size: Size<Length<i32>>) -> Self {
+        self.size = size;
+        self
+    }
+    pub fn min(mut self, size: Size<i32>) -> Self {
+        self.min = size;
+        self
+    }
+    pub fn max(mut self, size: Size<i32>) -> Self {
+        self.max = size;
+        self
+    }
+    pub fn tuning(mut self, tuning: ScrollTuning) -> Self {
+        self.tuning = tuning;
+        self
+    }
+    /// How quickly a fling slows down, as exponential decay per second.
+    pub fn friction(mut self, friction: f32) -> Self {
+        self.tuning.friction = friction;
+        self
+    }
+    /// Pixels scrolled per wheel notch.
+    pub fn line_height(mut self, px: f32) -> Self {
+        self.line_height = px;
+        self
+    }
+    /// Rounds every corner of the viewport by `radius` pixels.
+    pub fn radius(self, radius: f32) -> Self {
+        self.radii(Vec4::splat(radius))
+    }
+    /// Rounds each corner of the viewport on its own: top-left, top-right, bottom-right,
+    /// bottom-left. Content is cut off along them when stencil clipping is on.
+    pub fn radii(mut self, radii: Vec4<f32>) -> Self {
+        self.radii = radii;
+        self
+    }
+
+    #[inline]
+    fn contains(&self, p: Position<f32>) -> bool {
+        let sz = self.layout().current_size;
+        let l = self.position.x as f32;
+        let t = self.position.y as f32;
+        let r = l + sz.width as f32;
+        let b = t + sz.height as f32;
+        p.x >= l && p.x < r && p.y >= t && p.y < b
+    }
+}
+
+impl<M: 'static> Widget<M> for Scrollable<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        f(self.child.as_ref());
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        f(self.child.as_mut());
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let min_w = self.child.fit_width(ctx).min.width.max(self.min.width);
+        let fit_w = self.child.layout().current_size.width;
+
+        let resolved_w = match self.size.width {
+            Length::Fixed(w) => w,
+            _ => fit_w,
+        }
+        .clamp(min_w, self.max.width.max(min_w));
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(resolved_w, 0),
+            min: Size::new(min_w, self.min.height),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_w = match self.size.width {
+            Length::Grow(_) | Length::Percent(_) => parent_width,
+            Length::Fixed(w) => w,
+            Length::Fit => l.current_size.width,
+        }
+        .max(l.min.width)
+        .min(l.max.width)
+        .min(parent_width);
+
+        let w = offer(self.child.layout().size.width, target_w);
+        self.child.grow_width(ctx, w);
+
+        l.current_size.width = target_w;
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let content_h = self.child.fit_height(ctx).current_size.height;
+
+        let prev = self.layout.as_ref().expect(LAYOUT_ERROR);
+        let prev_w = prev.current_size.width;
+        let min_w = prev.min.width;
+
+        // The content scrolls, so it doesn't hold the viewport open.
+        let resolved_h = match self.size.height {
+            Length::Fixed(h) => h,
+            _ => content_h,
+        }
+        .clamp(self.min.height, self.max.height.max(self.min.height));
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(prev_w, resolved_h),
+            min: Size::new(min_w, self.min.height),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_h = match self.size.height {
+            Length::Grow(_) | Length::Percent(_) => parent_height,
+            Length::Fixed(h) => h,
+            Length::Fit => l.current_size.height,
+        }
+        .max(l.min.height)
+        .min(l.max.height)
+        .min(parent_height);
+
+        // Growing content fills the viewport but never shrinks below what it needs.
+        let content_h = self.child.layout().current_size.height;
+        let h = offer(self.child.layout().size.height, target_h).max(content_h);
+        self.child.grow_height(ctx, h);
+
+        l.current_size.height = target_h;
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+        let size = self.layout().current_size;
+        let content_h = self.child.layout().current_size.height;
+
+        let state = ctx.ui.state_mut::<ScrollState>(self.id);
+        state
+            .physics
+            .set_extent(content_h as f32, size.height as f32);
+        self.offset = state.physics.offset();
+
+        let direction = ctx.ui.direction();
+        let x = direction.flow(
+            position.x,
+            size.width,
+            position.x,
+            self.child.layout().current_size.width,
+        );
+        let _ = self.child.place(
+            ctx,
+            Position::new(x, position.y - self.offset.round() as i32),
+        );
+        size
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn __paint(
+        &self,
+        ctx: &mut PaintCtx,
+        instances: &mut Vec<Instance>,
+        t: &internal::PaintToken,
+        debug_on: bool,
+    ) {
+        let size = self.layout().current_size;
+        ctx.clipped_rounded(
+            self.position,
+            size,
+            self.radii,
+            instances,
+            |ctx, instances| self.child.__paint(ctx, instances, t, debug_on),
+        );
+
+        if debug_on {
+            self.after_draw(ctx, instances, t);
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        // Nested scrollables and other children get the wheel first.
+        self.child.handle(ctx);
+
+        let overflows = self.child.layout().current_size.height > self.layout().current_size.height;
+        let inside =
+            self.contains(ctx.ui.mouse_pos) && !ctx.ui.is_occluded(self.id, ctx.ui.mouse_pos);
+        let wheel = if overflows && inside && !ctx.ui.wheel().is_empty() {
+            ctx.ui.take_wheel()
+        } else {
+            Vec::new()
+        };
+
+        // A span some descendant wants in view, in content coordinates.
+        let viewport = self.layout().current_size.height as f32;
+        let origin = self.child.position().y;
+        let reveal = ctx
+            .ui
+            .take_reveal()
+            .map(|(top, bottom)| ((top - origin) as f32, (bottom - origin) as f32));
+
+        let time = ctx.globals.time;
+        let dt = ctx.globals.delta_time;
+        let frame = ctx.globals.frame;
+        let line_height = self.line_height;
+        let tuning = self.tuning;
+
+        let state = ctx.ui.state_mut::<ScrollState>(self.id);
+        let physics = &mut state.physics;
+        if *physics.tuning() != tuning {
+            physics.set_tuning(tuning);
+        }
+
+        // Deltas are positive towards the top, where the offset is smaller.
+        for (delta, phase) in wheel {
+            match delta {
+                ScrollDelta::Lines(p) => physics.scroll_by(-p.y * line_height),
+                ScrollDelta::Pixels(p) => {
+                    state.last_touch = time;
+                    if phase == TouchPhase::Started {
+                        physics.begin_drag(time);
+                    }
+                    if p.y != 0.0 {
+                        physics.drag_by(-p.y, time);
+                    }
+                    if matches!(phase, TouchPhase::Ended | TouchPhase::Cancelled) {
+                        physics.end_drag(time);
+                    }
+                }
+            }
+        }
+
+        if let Some((start, end)) = reveal {
+            physics.reveal(start, end, viewport);
+        }
+
+        // Not every platform reports when a touchpad swipe ends.
+        let window = tuning.velocity_window;
+        if physics.is_dragging() && time - state.last_touch > window {
+            physics.end_drag(time);
+        }
+
+        if state.frame != frame {
+            state.frame = frame;
+            physics.step(dt);
+        }
+
+        let dragging = physics.is_dragging();
+        let moving = physics.is_animating();
+        let offset = physics.offset();
+        let last_touch = state.last_touch;
+
+        if dragging {
+            ctx.ui.request_redraw_at(last_touch + window);
+        }
+        if moving || offset != self.offset {
+            ctx.ui.request_redraw();
+        }
+    }
+}
@@ -0,0 +1,703 @@
+use std::sync::Arc;
+
+use cosmic_text::{Attrs, Buffer, Family, Shaping, Wrap};
+
+use super::{
+    helpers::{measure, push_glyphs},
+    *,
+};
+use crate::{
+    event::{KeyState, LogicalKey, ScrollDelta},
+    render::text::LayoutKey,
+};
+
+#[derive(Default)]
+struct SelectState {
+    open: bool,
+    // The cursor of the popup list, and where a Shift range starts.
+    highlighted: Option<usize>,
+    anchor: Option<usize>,
+    // Scrolls the popup list when it has more options than rows.
+    scroll: ScrollPhysics,
+    // Frame the scroll was last stepped in.
+    frame: u32,
+}
+
+/// Shows the selected option and opens a popup list of the options when clicked. The popup
+/// is drawn in the overlay layer, above every other widget, and scrolls once there are more
+/// options than [`Select::max_rows`]. While focused, the arrows, Home/End and
+/// PageUp/PageDown move through the options.
+///
+/// In [`SelectionMode::Multi`] the list follows [`SelectionModel`]: Ctrl-click toggles an
+/// option and Shift-click or Shift with the keys selects a range, keeping the popup open,
+/// while a plain click picks one option and closes it.
+pub struct Select<M> {
+    layout: Option<Layout>,
+    buffers: Vec<Arc<Buffer>>,
+    placeholder_buffer: Option<Arc<Buffer>>,
+    // Every selected label, shown when more than one is selected.
+    summary_buffer: Option<Arc<Buffer>>,
+
+    id: Id,
+    position: Position<i32>,
+    size: Size<Length<i32>>,
+
+    options: Vec<String>,
+    selected: Vec<usize>,
+    selection_mode: SelectionMode,
+    placeholder: String,
+    font_size: f32,
+    line_height: f32,
+    padding: i32,
+    attributes: Attrs<'static>,
+
+    color: Color,
+    placeholder_color: Color,
+    background: Color,
+    hover_color: Color,
+    border_color: Color,
+    focus_color: Color,
+    popup_color: Color,
+    highlight_color: Color,
+    selected_color: Color,
+
+    max_rows: usize,
+    popup_position: Position<i32>,
+    popup_size: Size<i32>,
+    scroll: f32,
+
+    open: bool,
+    highlighted: Option<usize>,
+    hovered: bool,
+    focused: bool,
+
+    on_select: Option<Box<dyn Fn(usize) -> M>>,
+    on_selection_change: Option<Box<dyn Fn(Vec<usize>) -> M>>,
+}
+
+impl<M: Clone + 'static> Select<M> {
+    pub fn new<S: Into<String>>(
+        options: impl IntoIterator<Item = S>,
+        selected: Option<usize>,
+    ) -> Self {
+        let options: Vec<String> = options.into_iter().map(Into::into).collect();
+        Self {
+            layout: None,
+            buffers: Vec::new(),
+            placeholder_buffer: None,
+            summary_buffer: None,
+
+            id: crate::context::next_id(),
+            position: Position::splat(0),
+            size: Size::splat(Length::Fit),
+
+            selected: selected
+                .filter(|&i| i < options.len())
+                .into_iter()
+                .collect(),
+            selection_mode: SelectionMode::Single,
+            options,
+            placeholder: String::new(),
+            font_size: 16.0,
+            line_height: 1.2,
+            padding: 6,
+            attributes: Attrs::new(),
+
+            color: Color::WHITE,
+            placeholder_color: Color::rgba(255, 255, 255, 110),
+            background: Color::rgba(40, 40, 40, 255),
+            hover_color: Color::rgba(55, 55, 55, 255),
+            border_color: Color::rgba(120, 120, 120, 255),
+            focus_color: Color::rgba(90, 150, 240, 255),
+            popup_color: Color::rgba(30, 30, 30, 255),
+            highlight_color: Color::rgba(60, 110, 200, 255),
+            selected_color: Color::rgba(60, 110, 200, 110),
+
+            max_rows: 8,
+            popup_position: Position::splat(0),
+            popup_size: Size::splat(0),
+            scroll: 0.0,
+
+            open: false,
+            highlighted: None,
+            hovered: false,
+            focused: false,
+
+            on_select: None,
+            on_selection_change: None,
+        }
+    }
+
+    /// Shown while nothing is selected.
+    pub fn placeholder(mut self, text: impl Into<String>) -> Self {
+        self.placeholder = text.into();
+        self
+    }
+    pub fn font_size(mut self, font_size: f32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+    pub fn family(mut self, family: Family<'static>) -> Self {
+        self.attributes.family = family;
+        self
+    }
+    pub fn line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+    pub fn padding(mut self, padding: i32) -> Self {
+        self.padding = padding;
+        self
+    }
+    pub fn size(mut self, size: Size<Length<i32>>) -> Self {
+        self.size = size;
+        self
+    }
+    pub fn color(mut self, c: Color) -> Self {
+        self.color = c;
+        self
+    }
+    pub fn placeholder_color(mut self, c: Color) -> Self {
+        self.placeholder_color = c;
+        self
+    }
+    pub fn background(mut self, c: Color) -> Self {
+        self.background = c;
+        self
+    }
+    pub fn hover_color(mut self, c: Color) -> Self {
+        self.hover_color = c;
+        self
+    }
+    pub fn border_color(mut self, c: Color) -> Self {
+        self.border_color = c;
+        self
+    }
+    pub fn focus_color(mut self, c: Color) -> Self {
+        self.focus_color = c;
+        self
+    }
+    pub fn popup_color(mut self, c: Color) -> Self {
+        self.popup_color = c;
+        self
+    }
+    pub fn highlight_color(mut self, c: Color) -> Self {
+        self.highlight_color = c;
+        self
+    }
+    /// Background of selected options in the popup, for [`SelectionMode::Multi`].
+    pub fn selected_color(mut self, c: Color) -> Self {
+        self.selected_color = c;
+        self
+    }
+    pub fn selection_mode(mut self, mode: SelectionMode) -> Self {
+        self.selection_mode = mode;
+        self
+    }
+    /// Every selected option, for [`SelectionMode::Multi`].
+    pub fn selected_indices(mut self, indices: impl IntoIterator<Item = usize>) -> Self {
+        let len = self.options.len();
+        self.selected = indices.into_iter().filter(|&i| i < len).collect();
+        self.selected.sort_unstable();
+        self.selected.dedup();
+        self
+    }
+    /// Most options the popup shows at once before it scrolls.
+    pub fn max_rows(mut self, rows: usize) -> Self {
+        self.max_rows = rows.max(1);
+        self
+    }
+    /// Called with the index of the chosen option when it differs from the current one.
+    pub fn on_select(mut self, f: impl Fn(usize) -> M + 'static) -> Self {
+        self.on_select = Some(Box::new(f));
+        self
+    }
+    /// Called with every selected index whenever the selection changes.
+    pub fn on_selection_change(mut self, f: impl Fn(Vec<usize>) -> M + 'static) -> Self {
+        self.on_selection_change = Some(Box::new(f));
+        self
+    }
+
+    fn line_px(&self) -> i32 {
+        (self.font_size * self.line_height).ceil() as i32
+    }
+
+    fn row_height(&self) -> i32 {
+        self.line_px() + self.padding
+    }
+
+    fn shape(&self, ctx: &mut LayoutCtx<M>, text: &str, color: Color) -> Arc<Buffer> {
+        let attrs = self.attributes.clone().color(cosmic_text::Color::rgba(
+            color.r(),
+            color.g(),
+            color.b(),
+            color.a(),
+        ));
+        let key = LayoutKey::new(
+            text,
+            &attrs,
+            self.font_size,
+            self.line_height,
+            Wrap::None,
+            Shaping::Advanced,
+            None,
+        );
+        ctx.text.layout(key)
+    }
+
+    fn choose(&mut self, ctx: &mut EventCtx<M>, index: usize) {
+        self.commit(ctx, vec![index]);
+    }
+
+    /// Makes `selected` the selection, telling the application when it changed.
+    fn commit(&mut self, ctx: &mut EventCtx<M>, selected: Vec<usize>) {
+        if self.selected != selected {
+            if let Some(f) = self.on_select.as_ref()
+                && let [index] = selected[..]
+            {
+                ctx.ui.emit(f(index));
+            }
+            if let Some(f) = self.on_selection_change.as_ref() {
+                ctx.ui.emit(f(selected.clone()));
+            }
+            self.selected = selected;
+        }
+        ctx.ui.request_redraw();
+    }
+
+    /// The popup list as a selection model. A single-choice list only highlights options
+    /// until one is chosen, so its model selects the highlighted one.
+    fn model(&self, state: &SelectState) -> SelectionModel {
+        let mut model = SelectionModel::new(self.selection_mode);
+        model.set_item_count(self.options.len());
+        match self.selection_mode {
+            SelectionMode::Multi => model.restore(
+                self.selected.iter().copied(),
+                state.anchor,
+                state.highlighted,
+            ),
+            _ => model.restore(state.highlighted, state.highlighted, state.highlighted),
+        }
+        model
+    }
+
+    /// Index of the popup row under `p`.
+    fn option_at(&self, p: Position<f32>) -> Option<usize> {
+        let l = self.popup_position.x as f32;
+        let t = self.popup_position.y as f32;
+        let r = l + self.popup_size.width as f32;
+        let b = t + self.popup_size.height as f32;
+        if p.x < l || p.x >= r || p.y < t || p.y >= b {
+            return None;
+        }
+        let row = ((p.y - t + self.scroll) / self.row_height() as f32) as usize;
+        (row < self.options.len()).then_some(row)
+    }
+
+    /// Highlights option `index` and scrolls the popup list to show it.
+    fn highlight(&self, state: &mut SelectState, index: Option<usize>) {
+        state.highlighted = index;
+        if let Some(i) = index {
+            let row_h = self.row_height() as f32;
+            let top = i as f32 * row_h;
+            state
+                .scroll
+                .reveal(top, top + row_h, self.popup_size.height as f32);
+        }
+    }
+
+    /// Opens the popup on the selected option, scrolled to the middle of the list.
+    fn open_popup(&self, state: &mut SelectState) {
+        let first = self.selected.first().copied();
+        state.open = true;
+        state.highlighted = first;
+        state.anchor = first;
+        let row_h = self.row_height() as f32;
+        let top = first.unwrap_or(0) as f32 * row_h;
+        state
+            .scroll
+            .set_offset(top - (self.popup_size.height as f32 - row_h) / 2.0);
+    }
+
+    #[inline]
+    fn contains(&self, p: Position<f32>) -> bool {
+        let sz = self.layout().current_size;
+        let l = self.position.x as f32;
+        let t = self.position.y as f32;
+        let r = l + sz.width as f32;
+        let b = t + sz.height as f32;
+        p.x >= l && p.x < r && p.y >= t && p.y < b
+    }
+}
+
+impl<M: Clone + 'static> Widget<M> for Select<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let mut buffers = Vec::with_capacity(self.options.len());
+        for option in &self.options {
+            buffers.push(self.shape(ctx, option, self.color));
+        }
+        let placeholder = self.shape(ctx, &self.placeholder, self.placeholder_color);
+
+        let text_w = buffers
+            .iter()
+            .chain(std::iter::once(&placeholder))
+            .map(|b| measure(b).0.ceil() as i32)
+            .max()
+            .unwrap_or(0);
+        self.buffers = buffers;
+        self.placeholder_buffer = Some(placeholder);
+        self.summary_buffer = (self.selected.len() > 1).then(|| {
+            let labels: Vec<&str> = self
+                .selected
+                .iter()
+                .map(|&i| self.options[i].as_str())
+                .collect();
+            self.shape(ctx, &labels.join(", "), self.color)
+        });
+
+        // Room for the text, the arrow and padding on both sides of each.
+        let min_w = text_w + self.line_px() / 2 + 3 * self.padding;
+        let resolved_w = match self.size.width {
+            Length::Fixed(w) => w,
+            _ => min_w,
+        };
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(resolved_w, 0),
+            min: Size::new(min_w.min(resolved_w), 0),
+            max: Size::splat(i32::MAX),
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_w = match self.size.width {
+            Length::Grow(_) | Length::Percent(_) => parent_width,
+            Length::Fixed(w) => w,
+            Length::Fit => l.current_size.width,
+        }
+        .max(l.min.width)
+        .min(parent_width);
+
+        l.current_size.width = target_w;
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let content_h = self.line_px() + 2 * self.padding;
+        let prev = self.layout.as_ref().expect(LAYOUT_ERROR);
+
+        let resolved_h = match self.size.height {
+            Length::Fixed(h) => h,
+            _ => content_h,
+        };
+
+        let l = Layout {
+            current_size: Size::new(prev.current_size.width, resolved_h),
+            min: Size::new(prev.min.width, content_h.min(resolved_h)),
+            ..*prev
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_h = match self.size.height {
+            Length::Grow(_) | Length::Percent(_) => parent_height,
+            Length::Fixed(h) => h,
+            Length::Fit => l.current_size.height,
+        }
+        .max(l.min.height)
+        .min(parent_height);
+
+        l.current_size.height = target_h;
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+        let size = self.layout().current_size;
+
+        // Open below the box, or above it when the window has no room left underneath.
+        let rows = self.options.len().min(self.max_rows);
+        let popup_h = rows as i32 * self.row_height();
+        let window_h = ctx.globals.window_size().height;
+        let below = position.y + size.height;
+        let y = if below + popup_h > window_h && position.y - popup_h >= 0 {
+            position.y - popup_h
+        } else {
+            below
+        };
+        self.popup_position = Position::new(position.x, y);
+        self.popup_size = Size::new(size.width, popup_h);
+
+        let content_h = (self.options.len() as i32 * self.row_height()) as f32;
+        let state = ctx.ui.state_mut::<SelectState>(self.id);
+        state.scroll.set_extent(content_h, popup_h as f32);
+        self.scroll = state.scroll.offset();
+
+        size
+    }
+
+    fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        let size = self.layout().current_size;
+        let border = if self.focused {
+            self.focus_color
+        } else {
+            self.border_color
+        };
+        let fill = if self.hovered || self.open {
+            self.hover_color
+        } else {
+            self.background
+        };
+        instances.push(Instance::ui(self.position, size, border));
+        instances.push(Instance::ui(self.position + Size::splat(1), size - 2, fill));
+
+        // Downward arrow, built from shrinking rows.
+        let arrow = (self.line_px() / 2).max(4);
+        let ax = self.position.x + size.width - self.padding - arrow;
+        let ay = self.position.y + (size.height - arrow / 2) / 2;
+        for row in 0..arrow / 2 {
+            instances.push(Instance::ui(
+                Position::new(ax + row, ay + row),
+                Size::new(arrow - 2 * row, 1),
+                self.color,
+            ));
+        }
+
+        let text_w = ax - self.position.x - 2 * self.padding;
+        let label = match self.selected[..] {
+            [] => self.placeholder_buffer.as_ref(),
+            [i] => self.buffers.get(i),
+            _ => self.summary_buffer.as_ref(),
+        };
+        if let Some(buffer) = label {
+            let origin = Position::new(
+                self.position.x + self.padding,
+                self.position.y + (size.height - self.line_px()) / 2,
+            );
+            let clip = (origin, Size::new(text_w.max(0), self.line_px()));
+            push_glyphs(
+                ctx,
+                buffer,
+                Position::new(origin.x as f32, origin.y as f32),
+                Some(clip),
+                instances,
+            );
+        }
+
+        if !self.open {
+            return;
+        }
+        ctx.overlay(|ctx, out| {
+            let pos = self.popup_position;
+            let popup = self.popup_size;
+            out.push(Instance::ui(pos, popup, self.border_color));
+            out.push(Instance::ui(
+                pos + Size::splat(1),
+                popup - 2,
+                self.popup_color,
+            ));
+
+            // Rows scrolled partly out of the popup are cut at its edges.
+            let row_h = self.row_height();
+            let (top, bottom) = (pos.y, pos.y + popup.height);
+            let scroll = self.scroll.round() as i32;
+            for (i, buffer) in self.buffers.iter().enumerate() {
+                let row = Position::new(pos.x, pos.y + i as i32 * row_h - scroll);
+                if row.y + row_h <= top || row.y >= bottom {
+                    continue;
+                }
+                let (y0, y1) = (row.y.max(top), (row.y + row_h).min(bottom));
+                let fill = if self.highlighted == Some(i) {
+                    Some(self.highlight_color)
+                } else if self.selection_mode == SelectionMode::Multi && self.selected.contains(&i)
+                {
+                    Some(self.selected_color)
+                } else {
+                    None
+                };
+                if let Some(c) = fill {
+                    out.push(Instance::ui(
+                        Position::new(row.x + 1, y0),
+                        Size::new(popup.width - 2, y1 - y0),
+                        c,
+                    ));
+                }
+                let origin = Position::new(row.x + self.padding, row.y + self.padding / 2);
+                let (c0, c1) = (origin.y.max(top), (origin.y + self.line_px()).min(bottom));
+                let clip = (
+                    Position::new(origin.x, c0),
+                    Size::new((popup.width - 2 * self.padding).max(0), (c1 - c0).max(0)),
+                );
+                push_glyphs(
+                    ctx,
+                    buffer,
+                    Position::new(origin.x as f32, origin.y as f32),
+                    Some(clip),
+                    out,
+                );
+            }
+        });
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        ctx.ui.register_focusable(self.id);
+        let mouse = ctx.ui.mouse_pos;
+        let inside = self.contains(mouse) && !ctx.ui.is_occluded(self.id, mouse);
+        if inside {
+            ctx.ui.hot_item = Some(self.id);
+        }
+
+        let mut state = std::mem::take(ctx.ui.state_mut::<SelectState>(self.id));
+        let before = (state.open, state.highlighted, self.hovered, self.focused);
+        self.open = state.open;
+        let hovered_option = if state.open {
+            self.option_at(mouse)
+        } else {
+            None
+        };
+
+        if ctx.ui.mouse_pressed && ctx.ui.active_item != Some(self.id) {
+            if inside {
+                ctx.ui.active_item = Some(self.id);
+                ctx.ui.kbd_focus_item = Some(self.id);
+                if state.open {
+                    state.open = false;
+                } else {
+                    self.open_popup(&mut state);
+                }
+            } else if hovered_option.is_some() {
+                ctx.ui.active_item = Some(self.id);
+            } else {
+                state.open = false;
+                if ctx.ui.kbd_focus_item == Some(self.id) {
+                    ctx.ui.kbd_focus_item = None;
+                }
+            }
+        }
+        if let Some(i) = hovered_option {
+            state.highlighted = Some(i);
+        }
+        if ctx.ui.mouse_released && ctx.ui.active_item == Some(self.id) {
+            ctx.ui.active_item = None;
+            if let Some(i) = hovered_option {
+                let m = ctx.ui.modifiers;
+                if self.selection_mode == SelectionMode::Multi {
+                    // Ctrl and Shift build up a selection, a plain click settles on one.
+                    let mut model = self.model(&state);
+                    model.click(i, &m);
+                    state.anchor = model.anchor();
+                    state.open = m.control || m.super_ || m.shift;
+                    self.commit(ctx, model.selected().collect());
+                } else {
+                    state.open = false;
+                    self.choose(ctx, i);
+                }
+            }
+        }
+
+        // The wheel scrolls the open list while over it.
+        if hovered_option.is_some() {
+            let row_h = self.row_height() as f32;
+            for (delta, _) in ctx.ui.take_wheel() {
+                match delta {
+                    ScrollDelta::Lines(p) => state.scroll.scroll_by(-p.y * row_h),
+                    ScrollDelta::Pixels(p) => state.scroll.scroll_by(-p.y),
+                }
+            }
+        }
+
+        self.focused = ctx.ui.kbd_focus_item == Some(self.id);
+        if self.focused && !self.options.is_empty() {
+            let multi = self.selection_mode == SelectionMode::Multi;
+            let page = self.max_rows.min(self.options.len());
+            for key in ctx.ui.keys().to_vec() {
+                if key.state != KeyState::Pressed {
+                    continue;
+                }
+                match key.logical_key {
+                    LogicalKey::Escape => state.open = false,
+                    LogicalKey::ArrowDown | LogicalKey::ArrowUp if !state.open => {
+                        self.open_popup(&mut state);
+                    }
+                    LogicalKey::Enter | LogicalKey::Space if !state.open => {
+                        self.open_popup(&mut state);
+                    }
+                    LogicalKey::Enter if multi => state.open = false,
+                    LogicalKey::Enter | LogicalKey::Space if !multi => {
+                        state.open = false;
+                        if let Some(i) = state.highlighted {
+                            self.choose(ctx, i);
+                        }
+                    }
+                    _ if !state.open && multi => {}
+                    _ => {
+                        // Closed, a single-choice select picks what the keys land on.
+                        let mut model = match state.open {
+                            true => self.model(&state),
+                            false => self.model(&SelectState {
+                                highlighted: self.selected.first().copied(),
+                                ..SelectState::default()
+                            }),
+                        };
+                        if !model.handle_key(&key, page) {
+                            continue;
+                        }
+                        state.anchor = model.anchor();
+                        if !state.open {
+                            if let Some(i) = model.first() {
+                                self.choose(ctx, i);
+                            }
+                        } else {
+                            self.highlight(&mut state, model.cursor());
+                            if multi {
+                                self.commit(ctx, model.selected().collect());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if state.frame != ctx.globals.frame {
+            state.frame = ctx.globals.frame;
+            state.scroll.step(ctx.globals.delta_time);
+        }
+        if state.scroll.is_animating() || state.scroll.offset() != self.scroll {
+            ctx.ui.request_redraw();
+        }
+
+        if state.open {
+            ctx.ui
+                .add_overlay(self.id, self.popup_position, self.popup_size);
+        }
+        state.highlighted = state.highlighted.filter(|&i| i < self.options.len());
+
+        self.hovered = inside;
+        self.open = state.open;
+        self.highlighted = state.highlighted;
+        if (state.open, state.highlighted, self.hovered, self.focused) != before {
+            ctx.ui.request_redraw();
+        }
+        *ctx.ui.state_mut::<SelectState>(self.id) = state;
+    }
+}
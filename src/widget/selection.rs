@@ -0,0 +1,258 @@
+use std::collections::BTreeSet;
+
+use crate::event::{KeyEvent, KeyState, LogicalKey, Modifiers};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionMode {
+    None,
+    #[default]
+    Single,
+    Multi,
+}
+
+/// Index-based selection state shared by list-like widgets.
+///
+/// Clicks follow the usual desktop conventions: plain click selects one item, Ctrl (or Super)
+/// toggles an item, Shift extends a range from the anchor. The cursor is the keyboard-focused
+/// item and may differ from the selection when moved with Ctrl held.
+#[derive(Debug, Clone, Default)]
+pub struct SelectionModel {
+    mode: SelectionMode,
+    selected: BTreeSet<usize>,
+    anchor: Option<usize>,
+    cursor: Option<usize>,
+    len: usize,
+}
+
+impl SelectionModel {
+    pub fn new(mode: SelectionMode) -> Self {
+        Self {
+            mode,
+            ..Self::default()
+        }
+    }
+
+    pub fn mode(&self) -> SelectionMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: SelectionMode) {
+        self.mode = mode;
+        match mode {
+            SelectionMode::None => self.selected.clear(),
+            SelectionMode::Single => {
+                let keep = self.cursor.filter(|c| self.selected.contains(c));
+                self.selected.clear();
+                self.selected.extend(keep);
+            }
+            SelectionMode::Multi => {}
+        }
+    }
+
+    /// Number of items in the backing collection; out of range entries are dropped.
+    pub fn set_item_count(&mut self, count: usize) {
+        self.len = count;
+        self.selected.retain(|&i| i < count);
+        self.anchor = self.anchor.filter(|&i| i < count);
+        self.cursor = self.cursor.filter(|&i| i < count);
+    }
+
+    pub fn item_count(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selected.is_empty()
+    }
+
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected.contains(&index)
+    }
+
+    pub fn selected(&self) -> impl Iterator<Item = usize> + '_ {
+        self.selected.iter().copied()
+    }
+
+    /// First selected index, convenient for single selection.
+    pub fn first(&self) -> Option<usize> {
+        self.selected.first().copied()
+    }
+
+    pub fn anchor(&self) -> Option<usize> {
+        self.anchor
+    }
+
+    pub fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    /// Replaces the whole state, e.g. to rebuild the model each frame from a selection the
+    /// application owns. Indices past the item count are dropped.
+    pub fn restore(
+        &mut self,
+        selected: impl IntoIterator<Item = usize>,
+        anchor: Option<usize>,
+        cursor: Option<usize>,
+    ) {
+        self.selected.clear();
+        if self.mode != SelectionMode::None {
+            self.selected
+                .extend(selected.into_iter().filter(|&i| i < self.len));
+        }
+        if self.mode == SelectionMode::Single {
+            let keep = self.selected.first().copied();
+            self.selected.clear();
+            self.selected.extend(keep);
+        }
+        self.anchor = anchor.filter(|&i| i < self.len);
+        self.cursor = cursor.filter(|&i| i < self.len);
+    }
+
+    pub fn clear(&mut self) -> bool {
+        let changed = !self.selected.is_empty();
+        self.selected.clear();
+        self.anchor = None;
+        changed
+    }
+
+    /// Replaces the selection with `index`.
+    pub fn select(&mut self, index: usize) -> bool {
+        if self.mode == SelectionMode::None || index >= self.len {
+            return false;
+        }
+        let changed = self.selected.len() != 1 || !self.selected.contains(&index);
+        self.selected.clear();
+        self.selected.insert(index);
+        self.anchor = Some(index);
+        self.cursor = Some(index);
+        changed
+    }
+
+    pub fn toggle(&mut self, index: usize) -> bool {
+        if index >= self.len {
+            return false;
+        }
+        match self.mode {
+            SelectionMode::None => return false,
+            SelectionMode::Single => {
+                if self.selected.contains(&index) {
+                    self.selected.clear();
+                } else {
+                    self.selected.clear();
+                    self.selected.insert(index);
+                }
+            }
+            SelectionMode::Multi => {
+                if !self.selected.remove(&index) {
+                    self.selected.insert(index);
+                }
+            }
+        }
+        self.anchor = Some(index);
+        self.cursor = Some(index);
+        true
+    }
+
+    /// Selects the inclusive range between the anchor and `index`.
+    pub fn extend_to(&mut self, index: usize) -> bool {
+        if self.mode != SelectionMode::Multi {
+            return self.select(index);
+        }
+        if index >= self.len {
+            return false;
+        }
+        let anchor = self.anchor.unwrap_or(index);
+        let before = self.selected.clone();
+        self.selected.clear();
+        self.selected.extend(anchor.min(index)..=anchor.max(index));
+        self.anchor = Some(anchor);
+        self.cursor = Some(index);
+        before != self.selected
+    }
+
+    pub fn select_all(&mut self) -> bool {
+        if self.mode != SelectionMode::Multi {
+            return false;
+        }
+        let changed = self.selected.len() != self.len;
+        self.selected.extend(0..self.len);
+        changed
+    }
+
+    /// Applies a pointer click on `index` honouring Ctrl/Shift. Returns whether the selection
+    /// changed.
+    pub fn click(&mut self, index: usize, modifiers: &Modifiers) -> bool {
+        let toggle = modifiers.control || modifiers.super_;
+        if modifiers.shift && self.mode == SelectionMode::Multi {
+            if toggle {
+                // Ctrl+Shift adds the range without dropping the existing selection.
+                let anchor = self.anchor.unwrap_or(index);
+                let before = self.selected.len();
+                self.selected.extend(anchor.min(index)..=anchor.max(index));
+                self.cursor = Some(index);
+                return before != self.selected.len();
+            }
+            self.extend_to(index)
+        } else if toggle {
+            self.toggle(index)
+        } else {
+            self.select(index)
+        }
+    }
+
+    /// Moves the cursor to `index`; Shift extends the selection, Ctrl only moves the cursor.
+    pub fn move_cursor(&mut self, index: usize, modifiers: &Modifiers) -> bool {
+        if self.len == 0 {
+            return false;
+        }
+        let index = index.min(self.len - 1);
+        if modifiers.shift {
+            self.extend_to(index)
+        } else if modifiers.control || modifiers.super_ {
+            let changed = self.cursor != Some(index);
+            self.cursor = Some(index);
+            changed
+        } else {
+            self.select(index)
+        }
+    }
+
+    /// Keyboard navigation: arrows/Home/End/PageUp/PageDown move the cursor (`page` items per
+    /// page), Space toggles the cursor item and Ctrl+A selects everything. Returns whether the
+    /// selection or cursor changed.
+    pub fn handle_key(&mut self, key: &KeyEvent, page: usize) -> bool {
+        if key.state != KeyState::Pressed || self.len == 0 {
+            return false;
+        }
+        let m = &key.modifiers;
+        let cur = self.cursor.unwrap_or(0);
+        let last = self.len - 1;
+        let page = page.max(1);
+
+        match &key.logical_key {
+            LogicalKey::ArrowUp | LogicalKey::ArrowLeft if self.cursor.is_some() => {
+                self.move_cursor(cur.saturating_sub(1), m)
+            }
+            LogicalKey::ArrowDown | LogicalKey::ArrowRight if self.cursor.is_some() => {
+                self.move_cursor((cur + 1).min(last), m)
+            }
+            LogicalKey::ArrowUp
+            | LogicalKey::ArrowLeft
+            | LogicalKey::ArrowDown
+            | LogicalKey::ArrowRight => self.move_cursor(0, m),
+            LogicalKey::PageUp => self.move_cursor(cur.saturating_sub(page), m),
+            LogicalKey::PageDown => self.move_cursor((cur + page).min(last), m),
+            LogicalKey::Home => self.move_cursor(0, m),
+            LogicalKey::End => self.move_cursor(last, m),
+            LogicalKey::Space => match self.cursor {
+                Some(c) if m.control || m.super_ => self.toggle(c),
+                Some(c) => self.select(c),
+                None => false,
+            },
+            LogicalKey::Character(s) if (m.control || m.super_) && s.eq_ignore_ascii_case("a") => {
+                self.select_all()
+            }
+            _ => false,
+        }
+    }
+}
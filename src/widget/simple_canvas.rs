@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use super::*;
 use crate::render::pipeline::PipelineKey;
 
@@ -5,28 +7,51 @@ pub struct SimpleCanvas<M> {
     layout: Option<Layout>,
 
     id: Id,
-    key: &'static str,
+    key: Cow<'static, str>,
     with_handle: Option<fn(&mut EventCtx<M>)>,
     position: Position<i32>,
     size: Size<Length<i32>>,
+    data: [u32; 12],
 }
 
+/// Bytes of user data a [`SimpleCanvas`] can hand its pipeline.
+pub const CANVAS_DATA_SIZE: usize = 48;
+
 impl<M> SimpleCanvas<M> {
     pub fn new(
         size: Size<Length<i32>>,
-        pipeline_key: &'static str,
+        pipeline_key: impl Into<Cow<'static, str>>,
         with_handle: Option<fn(&mut EventCtx<M>)>,
     ) -> Self {
         Self {
             layout: None,
 
             id: crate::context::next_id(),
-            key: pipeline_key,
+            key: pipeline_key.into(),
             with_handle,
             position: Position::splat(0),
             size,
+            data: [0; 12],
         }
     }
+
+    /// Hands `data` to the canvas's pipeline, e.g. parameters of a shader from app state. It
+    /// arrives in the instance's three data slots, at shader locations 2 to 4 as
+    /// `vec4<u32>`s to `bitcast` back, so each canvas drawn with the pipeline gets its own.
+    ///
+    /// # Panics
+    /// If `T` is larger than [`CANVAS_DATA_SIZE`].
+    pub fn data<T: bytemuck::Pod>(mut self, data: T) -> Self {
+        let bytes = bytemuck::bytes_of(&data);
+        assert!(
+            bytes.len() <= CANVAS_DATA_SIZE,
+            "canvas data is limited to {CANVAS_DATA_SIZE} bytes, got {}",
+            bytes.len()
+        );
+        self.data = [0; 12];
+        bytemuck::cast_slice_mut::<u32, u8>(&mut self.data)[..bytes.len()].copy_from_slice(bytes);
+        self
+    }
 }
 
 impl<M> Widget<M> for SimpleCanvas<M> {
@@ -39,6 +64,9 @@ impl<M> Widget<M> for SimpleCanvas<M> {
     fn layout(&self) -> &Layout {
         self.layout.as_ref().expect(LAYOUT_ERROR)
     }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
 
     fn fit_width(&mut self, _ctx: &mut LayoutCtx<M>) -> Layout {
         let cur_w = match self.size.width {
@@ -60,7 +88,7 @@ impl<M> Widget<M> for SimpleCanvas<M> {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
 
         let target_w = match self.size.width {
-            Length::Grow => parent_width,
+            Length::Grow(_) | Length::Percent(_) => parent_width,
             Length::Fixed(w) => w,
             Length::Fit => l.current_size.width,
         };
@@ -91,7 +119,7 @@ impl<M> Widget<M> for SimpleCanvas<M> {
     fn grow_height(&mut self, _ctx: &mut LayoutCtx<M>, parent_height: i32) {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
         let target_h = match self.size.height {
-            Length::Grow => parent_height,
+            Length::Grow(_) | Length::Percent(_) => parent_height,
             Length::Fixed(h) => h,
             Length::Fit => l.current_size.height,
         };
@@ -107,13 +135,17 @@ impl<M> Widget<M> for SimpleCanvas<M> {
     }
 
     fn draw_self(&self, _ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
-        instances.push(Instance::new(
-            PipelineKey::Other(self.key),
-            self.position,
-            self.layout().current_size,
-            [0, 0, 0, 0],
-            [0, 0, 0, 0],
-        ));
+        let [data1, data2, data3]: [[u32; 4]; 3] = bytemuck::cast(self.data);
+        instances.push(
+            Instance::new(
+                PipelineKey::Other(self.key.clone()),
+                self.position,
+                self.layout().current_size,
+                data1,
+                data2,
+            )
+            .data3(data3),
+        );
     }
 
     fn handle(&mut self, ctx: &mut EventCtx<M>) {
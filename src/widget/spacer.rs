@@ -30,6 +30,9 @@ impl<M> Widget<M> for Spacer {
     fn layout(&self) -> &Layout {
         self.layout.as_ref().expect(LAYOUT_ERROR)
     }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
 
     fn fit_width(&mut self, _ctx: &mut LayoutCtx<M>) -> Layout {
         let cur_w = match self.size.width {
@@ -51,7 +54,7 @@ impl<M> Widget<M> for Spacer {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
 
         let target_w = match self.size.width {
-            Length::Grow => parent_width,
+            Length::Grow(_) | Length::Percent(_) => parent_width,
             Length::Fixed(w) => w,
             Length::Fit => l.current_size.width,
         };
@@ -82,7 +85,7 @@ impl<M> Widget<M> for Spacer {
     fn grow_height(&mut self, _ctx: &mut LayoutCtx<M>, parent_height: i32) {
         let l = self.layout.as_mut().expect(LAYOUT_ERROR);
         let target_h = match self.size.height {
-            Length::Grow => parent_height,
+            Length::Grow(_) | Length::Percent(_) => parent_height,
             Length::Fixed(h) => h,
             Length::Fit => l.current_size.height,
         };
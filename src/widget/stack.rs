@@ -0,0 +1,268 @@
+use super::{helpers::offer, *};
+
+/// Where a child of a [`Stack`], or an absolute child of a [`Container`], sits inside the free
+/// space around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Alignment {
+    /// Offset of a child inside an area with `free` space left around it.
+    pub fn offset(self, free: Size<i32>) -> Position<i32> {
+        let (fx, fy) = match self {
+            Alignment::TopLeft => (0, 0),
+            Alignment::Top => (1, 0),
+            Alignment::TopRight => (2, 0),
+            Alignment::Left => (0, 1),
+            Alignment::Center => (1, 1),
+            Alignment::Right => (2, 1),
+            Alignment::BottomLeft => (0, 2),
+            Alignment::Bottom => (1, 2),
+            Alignment::BottomRight => (2, 2),
+        };
+        Position::new(free.width.max(0) * fx / 2, free.height.max(0) * fy / 2)
+    }
+}
+
+/// Layers children on top of each other, later children above earlier ones, each aligned
+/// within the padded area on its own.
+pub struct Stack<M> {
+    layout: Option<Layout>,
+
+    id: Id,
+    children: Vec<(Element<M>, Option<Alignment>)>,
+    align: Alignment,
+    position: Position<i32>,
+    size: Size<Length<i32>>,
+    color: Color,
+    padding: Vec4<i32>,
+    min: Size<i32>,
+    max: Size<i32>,
+}
+
+impl<M> Stack<M> {
+    pub fn new(children: Vec<Element<M>>) -> Self {
+        Self {
+            layout: None,
+
+            id: crate::context::next_id(),
+            children: children.into_iter().map(|c| (c, None)).collect(),
+            align: Alignment::TopLeft,
+            position: Position::splat(0),
+            size: Size::splat(Length::Fit),
+            color: Color::TRANSPARENT,
+            padding: Vec4::splat(0),
+            min: Size::splat(0),
+            max: Size::splat(i32::MAX),
+        }
+    }
+
+    /// Adds a child aligned on its own instead of by [`Stack::align`].
+    pub fn push(mut self, child: Element<M>, align: Alignment) -> Self {
+        self.children.push((child, Some(align)));
+        self
+    }
+    /// Alignment of the children that weren't given one.
+    pub fn align(mut self, align: Alignment) -> Self {
+        self.align = align;
+        self
+    }
+    pub fn size(mut self, size: Size<Length<i32>>) -> Self {
+        self.size = size;
+        self
+    }
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+    pub fn padding(mut self, amount: Vec4<i32>) -> Self {
+        self.padding = amount;
+        self
+    }
+    pub fn min(mut self, size: Size<i32>) -> Self {
+        self.min = size;
+        self
+    }
+    pub fn max(mut self, size: Size<i32>) -> Self {
+        self.max = size;
+        self
+    }
+}
+
+impl<M: 'static> Widget<M> for Stack<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        for (child, _) in &self.children {
+            f(child.as_ref());
+        }
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        for (child, _) in &mut self.children {
+            f(child.as_mut());
+        }
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let width_padding = self.padding.x + self.padding.z;
+
+        let mut min_w = 0;
+        for (child, _) in self.children.iter_mut() {
+            let Layout { current_size, .. } = child.fit_width(ctx);
+            min_w = min_w.max(current_size.width);
+        }
+        min_w += width_padding;
+
+        let resolved_w = self
+            .size
+            .into_fixed()
+            .width
+            .clamp(min_w.max(self.min.width), self.max.width);
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(resolved_w, 0),
+            min: Size::new(min_w.max(self.min.width), self.min.height),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_w = match self.size.width {
+            Length::Grow(_) | Length::Percent(_) => parent_width,
+            Length::Fixed(w) => w,
+            Length::Fit => l.current_size.width,
+        }
+        .max(l.min.width)
+        .min(l.max.width)
+        .min(parent_width);
+
+        let inner_w = (target_w - self.padding.x - self.padding.z).max(0);
+        for (child, _) in self.children.iter_mut() {
+            let w = offer(child.layout().size.width, inner_w);
+            child.grow_width(ctx, w);
+        }
+
+        l.current_size.width = target_w;
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let height_padding = self.padding.y + self.padding.w;
+
+        let mut max_child_h = 0;
+        for (child, _) in self.children.iter_mut() {
+            let Layout { current_size, .. } = child.fit_height(ctx);
+            max_child_h = max_child_h.max(current_size.height);
+        }
+        let min_h = max_child_h + height_padding;
+
+        let prev = self.layout.as_ref().expect(LAYOUT_ERROR);
+        let prev_w = prev.current_size.width;
+
+        let requested_h = match self.size.height {
+            Length::Fixed(h) => h,
+            _ => min_h,
+        };
+        let resolved_h = requested_h
+            .max(self.min.height.max(min_h))
+            .min(self.max.height);
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(prev_w, resolved_h),
+            min: Size::new(prev.min.width, self.min.height.max(min_h)),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_h = match self.size.height {
+            Length::Grow(_) | Length::Percent(_) => parent_height,
+            Length::Fixed(h) => h,
+            Length::Fit => l.current_size.height,
+        }
+        .max(l.min.height)
+        .min(l.max.height)
+        .min(parent_height);
+
+        let inner_h = (target_h - self.padding.y - self.padding.w).max(0);
+        for (child, _) in self.children.iter_mut() {
+            let h = offer(child.layout().size.height, inner_h);
+            child.grow_height(ctx, h);
+        }
+
+        l.current_size.height = target_h;
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+        let size = self.layout().current_size;
+        let inner = Size::new(
+            size.width - self.padding.x - self.padding.z,
+            size.height - self.padding.y - self.padding.w,
+        );
+        let direction = ctx.ui.direction();
+        for (child, align) in self.children.iter_mut() {
+            let child_size = child.layout().current_size;
+            let offset = align.unwrap_or(self.align).offset(Size::new(
+                inner.width - child_size.width,
+                inner.height - child_size.height,
+            ));
+            let x = position.x + self.padding.x + offset.x;
+            let _ = child.place(
+                ctx,
+                Position::new(
+                    direction.flow(position.x, size.width, x, child_size.width),
+                    position.y + self.padding.y + offset.y,
+                ),
+            );
+        }
+        size
+    }
+
+    fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        if self.color.a() > 0 {
+            instances.push(Instance::ui(
+                self.position,
+                self.layout().current_size,
+                self.color,
+            ));
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        for (child, _) in self.children.iter_mut() {
+            child.handle(ctx);
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use super::*;
+
+/// Drop shadow painted behind a widget's background, like a CSS box shadow.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shadow {
+    offset: Position<i32>,
+    blur: f32,
+    spread: i32,
+    color: Color,
+}
+
+impl Shadow {
+    pub fn new(color: Color) -> Self {
+        Self {
+            offset: Position::splat(0),
+            blur: 0.0,
+            spread: 0,
+            color,
+        }
+    }
+
+    pub fn offset(mut self, x: i32, y: i32) -> Self {
+        self.offset = Position::new(x, y);
+        self
+    }
+    /// How far the shadow's edge fades out, in pixels.
+    pub fn blur(mut self, blur: f32) -> Self {
+        self.blur = blur;
+        self
+    }
+    /// Grows (or with a negative amount shrinks) the shadow on every side before blurring.
+    pub fn spread(mut self, spread: i32) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    /// The shadow of a widget at `position` with `size` and corner `radii`.
+    pub(crate) fn instance(
+        &self,
+        position: Position<i32>,
+        size: Size<i32>,
+        radii: Vec4<f32>,
+    ) -> Instance {
+        let s = self.spread;
+        let spread = |r: f32| {
+            if r > 0.0 {
+                (r + s as f32).max(0.0)
+            } else {
+                0.0
+            }
+        };
+        Instance::shadow(
+            Position::new(
+                position.x + self.offset.x - s,
+                position.y + self.offset.y - s,
+            ),
+            Size::new((size.width + 2 * s).max(0), (size.height + 2 * s).max(0)),
+            self.blur,
+            self.color,
+        )
+        .radii(<[f32; 4]>::from(radii).map(spread).into())
+    }
+}
+
+/// Border drawn along the inside of a widget's bounds, following its corner radii. Widths and
+/// colors are per side: left, top, right, bottom.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Border {
+    widths: Vec4<i32>,
+    colors: Vec4<Color>,
+}
+
+impl Border {
+    pub fn new(width: i32, color: Color) -> Self {
+        Self {
+            widths: Vec4::splat(width),
+            colors: Vec4::splat(color),
+        }
+    }
+
+    /// Width of each side, at most 255 pixels.
+    pub fn widths(mut self, widths: Vec4<i32>) -> Self {
+        self.widths = widths;
+        self
+    }
+    pub fn colors(mut self, colors: Vec4<Color>) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Pushes the border of a widget at `position` with `size` and corner `radii`.
+    pub(crate) fn push(
+        &self,
+        position: Position<i32>,
+        size: Size<i32>,
+        radii: Vec4<f32>,
+        instances: &mut Vec<Instance>,
+    ) {
+        let colors = <[Color; 4]>::from(self.colors);
+        let widths = <[i32; 4]>::from(self.widths);
+        if colors.iter().all(|&c| c == colors[0]) {
+            if colors[0].a() > 0 && widths.iter().any(|&w| w > 0) {
+                instances.push(
+                    Instance::border(position, size, self.widths, colors[0], None).radii(radii),
+                );
+            }
+            return;
+        }
+        for (side, (color, width)) in colors.into_iter().zip(widths).enumerate() {
+            if color.a() > 0 && width > 0 {
+                instances.push(
+                    Instance::border(position, size, self.widths, color, Some(side)).radii(radii),
+                );
+            }
+        }
+    }
+}
+
+/// Outline drawn around a widget that has keyboard focus, outside its bounds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FocusRing {
+    width: i32,
+    offset: i32,
+    color: Color,
+}
+
+impl Default for FocusRing {
+    fn default() -> Self {
+        Self {
+            width: 2,
+            offset: 2,
+            color: Color::rgba(80, 140, 255, 255),
+        }
+    }
+}
+
+impl FocusRing {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            ..Self::default()
+        }
+    }
+
+    pub fn width(mut self, width: i32) -> Self {
+        self.width = width;
+        self
+    }
+    /// Gap between the widget and the ring.
+    pub fn offset(mut self, offset: i32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// The ring around a widget at `position` with `size` and corner `radii`.
+    pub fn instance(&self, position: Position<i32>, size: Size<i32>, radii: Vec4<f32>) -> Instance {
+        let grow = self.offset + self.width;
+        let round = |r: f32| if r > 0.0 { r + grow as f32 } else { 0.0 };
+        Instance::border(
+            Position::new(position.x - grow, position.y - grow),
+            Size::new(size.width + 2 * grow, size.height + 2 * grow),
+            Vec4::splat(self.width),
+            self.color,
+            None,
+        )
+        .radii(<[f32; 4]>::from(radii).map(round).into())
+    }
+}
+
+/// Background that blends between two colors, used in place of a solid color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gradient {
+    /// From `start` to `end` along `angle` radians clockwise from the positive x axis.
+    Linear {
+        start: Color,
+        end: Color,
+        angle: f32,
+    },
+    /// From `inner` at the center to `outer` at the middle of the edges.
+    Radial { inner: Color, outer: Color },
+}
+
+impl Gradient {
+    pub fn linear(start: Color, end: Color, angle: f32) -> Self {
+        Self::Linear { start, end, angle }
+    }
+    /// Top to bottom.
+    pub fn vertical(start: Color, end: Color) -> Self {
+        Self::linear(start, end, std::f32::consts::FRAC_PI_2)
+    }
+    pub fn radial(inner: Color, outer: Color) -> Self {
+        Self::Radial { inner, outer }
+    }
+
+    pub(crate) fn instance(&self, position: Position<i32>, size: Size<i32>) -> Instance {
+        match *self {
+            Gradient::Linear { start, end, angle } => {
+                Instance::linear_gradient(position, size, start, end, angle)
+            }
+            Gradient::Radial { inner, outer } => {
+                Instance::radial_gradient(position, size, inner, outer)
+            }
+        }
+    }
+}
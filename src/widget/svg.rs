@@ -0,0 +1,154 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::render::svg::SvgCache;
+
+/// Draws an SVG document, rasterized at the size it is laid out at so it stays sharp at any
+/// scale. `Length::Fit` takes the document's own size.
+pub struct Svg {
+    layout: Option<Layout>,
+    id: Id,
+    position: Position<i32>,
+    size: Size<Length<i32>>,
+    min: Size<i32>,
+    max: Size<i32>,
+
+    data: Arc<[u8]>,
+    key: u64,
+    tint: Color,
+}
+
+impl Svg {
+    pub fn new(size: Size<Length<i32>>, data: impl Into<Arc<[u8]>>) -> Self {
+        let data = data.into();
+        Self {
+            layout: None,
+            id: crate::context::next_id(),
+            position: Position::splat(0),
+            size,
+            min: Size::splat(0),
+            max: Size::splat(i32::MAX),
+            key: SvgCache::key(&data),
+            data,
+            tint: Color::WHITE,
+        }
+    }
+    pub fn tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+    pub fn min(mut self, size: Size<i32>) -> Self {
+        self.min = size;
+        self
+    }
+    pub fn max(mut self, size: Size<i32>) -> Self {
+        self.max = size;
+        self
+    }
+}
+
+impl<M> Widget<M> for Svg {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let natural = ctx
+            .svg
+            .size(self.key, &self.data)
+            .map_or(Size::splat(0), |s| {
+                Size::new(s.width.ceil() as i32, s.height.ceil() as i32)
+            });
+        let base_w = match self.size.width {
+            Length::Fixed(w) => w,
+            Length::Fit => natural.width,
+            Length::Grow(_) | Length::Percent(_) => 0,
+        };
+        let cur_w = base_w.clamp(self.min.width, self.max.width.max(self.min.width));
+        // Remembered for fit_height.
+        let cur_h = natural.height;
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(cur_w, cur_h),
+            min: Size::new(
+                match self.size.width {
+                    Length::Grow(_) | Length::Percent(_) => self.min.width,
+                    _ => cur_w,
+                },
+                self.min.height,
+            ),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, _ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_w = match self.size.width {
+            Length::Grow(_) | Length::Percent(_) => parent_width,
+            _ => l.current_size.width,
+        };
+
+        l.current_size.width = target_w
+            .max(self.min.width)
+            .min(self.max.width)
+            .min(parent_width);
+    }
+
+    fn fit_height(&mut self, _ctx: &mut LayoutCtx<M>) -> Layout {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+        let base_h = match self.size.height {
+            Length::Fixed(h) => h,
+            Length::Fit => l.current_size.height,
+            Length::Grow(_) | Length::Percent(_) => 0,
+        };
+        let cur_h = base_h.clamp(self.min.height, self.max.height.max(self.min.height));
+
+        l.current_size.height = cur_h;
+        if !matches!(self.size.height, Length::Grow(_) | Length::Percent(_)) {
+            l.min.height = cur_h;
+        }
+        *l
+    }
+
+    fn grow_height(&mut self, _ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+        let target_h = match self.size.height {
+            Length::Grow(_) | Length::Percent(_) => parent_height,
+            _ => l.current_size.height,
+        };
+
+        l.current_size.height = target_h
+            .max(self.min.height)
+            .min(self.max.height)
+            .min(parent_height);
+    }
+
+    fn place(&mut self, _ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+        <Svg as Widget<M>>::layout(self).current_size
+    }
+
+    fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        let size = <Svg as Widget<M>>::layout(self).current_size;
+        let px = Size::new(size.width.max(0) as u32, size.height.max(0) as u32);
+        if let Some(handle) = ctx
+            .svg
+            .raster(ctx.gpu, ctx.texture, self.key, &self.data, px)
+        {
+            instances.push(Instance::ui_tex(self.position, size, self.tint, handle));
+        }
+    }
+}
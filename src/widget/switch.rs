@@ -0,0 +1,259 @@
+use super::*;
+use crate::event::{KeyState, LogicalKey};
+
+#[derive(Default)]
+struct SwitchState {
+    // 0.0 = off, 1.0 = on; `None` until first seen so a new switch doesn't animate in.
+    progress: Option<f32>,
+    // Frame the progress was last advanced in; handle can run more than once per frame.
+    frame: u32,
+}
+
+/// On/off toggle drawn as a pill track with a sliding thumb.
+pub struct Switch<M> {
+    layout: Option<Layout>,
+
+    id: Id,
+    position: Position<i32>,
+    size: Size<i32>,
+
+    on: bool,
+    duration: f32,
+
+    track_color: Color,
+    active_color: Color,
+    thumb_color: Color,
+    focus_color: Color,
+
+    progress: f32,
+    hovered: bool,
+    focused: bool,
+
+    on_toggle: Option<Box<dyn Fn(bool) -> M>>,
+}
+
+impl<M: Clone + 'static> Switch<M> {
+    pub fn new(on: bool) -> Self {
+        Self {
+            layout: None,
+
+            id: crate::context::next_id(),
+            position: Position::splat(0),
+            size: Size::new(40, 22),
+
+            on,
+            duration: 0.15,
+
+            track_color: Color::rgba(70, 70, 70, 255),
+            active_color: Color::rgba(60, 110, 200, 255),
+            thumb_color: Color::WHITE,
+            focus_color: Color::rgba(90, 150, 240, 255),
+
+            progress: if on { 1.0 } else { 0.0 },
+            hovered: false,
+            focused: false,
+
+            on_toggle: None,
+        }
+    }
+
+    pub fn size(mut self, size: Size<i32>) -> Self {
+        self.size = size;
+        self
+    }
+    /// Seconds the thumb takes to slide across.
+    pub fn duration(mut self, seconds: f32) -> Self {
+        self.duration = seconds.max(0.0);
+        self
+    }
+    pub fn track_color(mut self, c: Color) -> Self {
+        self.track_color = c;
+        self
+    }
+    pub fn active_color(mut self, c: Color) -> Self {
+        self.active_color = c;
+        self
+    }
+    pub fn thumb_color(mut self, c: Color) -> Self {
+        self.thumb_color = c;
+        self
+    }
+    pub fn focus_color(mut self, c: Color) -> Self {
+        self.focus_color = c;
+        self
+    }
+    /// Called with the new value when clicked or toggled with Space.
+    pub fn on_toggle(mut self, f: impl Fn(bool) -> M + 'static) -> Self {
+        self.on_toggle = Some(Box::new(f));
+        self
+    }
+
+    fn toggle(&mut self, ctx: &mut EventCtx<M>) {
+        self.on = !self.on;
+        if let Some(f) = self.on_toggle.as_ref() {
+            ctx.ui.emit(f(self.on));
+        }
+        ctx.ui.request_redraw();
+    }
+
+    #[inline]
+    fn contains(&self, p: Position<f32>) -> bool {
+        let sz = self.layout().current_size;
+        let l = self.position.x as f32;
+        let t = self.position.y as f32;
+        let r = l + sz.width as f32;
+        let b = t + sz.height as f32;
+        p.x >= l && p.x < r && p.y >= t && p.y < b
+    }
+}
+
+fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    let channel = |shift: u32| {
+        let (x, y) = ((a.0 >> shift) & 0xFF, (b.0 >> shift) & 0xFF);
+        ((x as f32 + (y as f32 - x as f32) * t).round() as u32) << shift
+    };
+    Color(channel(0) | channel(8) | channel(16) | channel(24))
+}
+
+impl<M: Clone + 'static> Widget<M> for Switch<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let l = Layout {
+            size: self.size.from_fixed(),
+            current_size: Size::new(self.size.width, 0),
+            min: self.size,
+            max: self.size,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {}
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+        l.current_size.height = self.size.height;
+        *l
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {}
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+        self.layout().current_size
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        let size = self.layout().current_size;
+        let radius = size.height as f32 / 2.0;
+        // Smoothstep so the thumb eases in and out.
+        let t = self.progress * self.progress * (3.0 - 2.0 * self.progress);
+
+        if self.focused {
+            instances.push(
+                Instance::ui(self.position - Size::splat(2), size + 4, self.focus_color)
+                    .radius(radius + 2.0),
+            );
+        }
+        let track = lerp_color(self.track_color, self.active_color, t);
+        instances.push(Instance::ui(self.position, size, track).radius(radius));
+
+        let inset = (size.height / 8).max(2);
+        let thumb = size.height - 2 * inset;
+        let travel = (size.width - 2 * inset - thumb) as f32;
+        let thumb_pos = Position::new(
+            self.position.x + inset + (travel * t).round() as i32,
+            self.position.y + inset,
+        );
+        let thumb_color = if self.hovered {
+            lerp_color(self.thumb_color, Color::BLACK, 0.08)
+        } else {
+            self.thumb_color
+        };
+        instances.push(
+            Instance::ui(thumb_pos, Size::splat(thumb), thumb_color).radius(thumb as f32 / 2.0),
+        );
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        ctx.ui.register_focusable(self.id);
+        let was_hovered = self.hovered;
+        let was_focused = self.focused;
+
+        let inside =
+            self.contains(ctx.ui.mouse_pos) && !ctx.ui.is_occluded(self.id, ctx.ui.mouse_pos);
+        self.hovered = inside;
+        if inside {
+            ctx.ui.hot_item = Some(self.id);
+        }
+
+        if ctx.ui.mouse_pressed {
+            if inside {
+                ctx.ui.active_item = Some(self.id);
+                ctx.ui.kbd_focus_item = Some(self.id);
+            } else if ctx.ui.kbd_focus_item == Some(self.id) {
+                ctx.ui.kbd_focus_item = None;
+            }
+        }
+        if ctx.ui.mouse_released && ctx.ui.active_item == Some(self.id) {
+            ctx.ui.active_item = None;
+            if inside {
+                self.toggle(ctx);
+            }
+        }
+
+        self.focused = ctx.ui.kbd_focus_item == Some(self.id);
+        if self.focused {
+            let toggles = ctx
+                .ui
+                .keys()
+                .iter()
+                .filter(|k| k.state == KeyState::Pressed && k.logical_key == LogicalKey::Space)
+                .count();
+            for _ in 0..toggles {
+                self.toggle(ctx);
+            }
+        }
+
+        // Slide toward the current value by one delta_time step per frame.
+        let target = if self.on { 1.0 } else { 0.0 };
+        let instant = self.duration == 0.0 || crate::consts::reduced_motion();
+        let frame = ctx.globals.frame;
+        let step = ctx.globals.delta_time / self.duration.max(f32::EPSILON);
+        let state = ctx.ui.state_mut::<SwitchState>(self.id);
+        let fresh = state.frame != frame;
+        state.frame = frame;
+        let progress = match state.progress {
+            Some(p) if !fresh && !instant => p,
+            Some(p) if !instant => {
+                if p < target {
+                    (p + step).min(target)
+                } else {
+                    (p - step).max(target)
+                }
+            }
+            _ => target,
+        };
+        state.progress = Some(progress);
+        if progress != self.progress {
+            self.progress = progress;
+            ctx.ui.request_redraw();
+        }
+
+        if self.hovered != was_hovered || self.focused != was_focused {
+            ctx.ui.request_redraw();
+        }
+    }
+}
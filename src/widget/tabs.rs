@@ -0,0 +1,366 @@
+use super::{helpers::offer, *};
+
+struct Tab<M, K> {
+    key: K,
+    header: Element<M>,
+    page: Element<M>,
+}
+
+/// A tab bar above the page of the active tab. Every page is built each frame but only the
+/// active one is laid out, drawn and handled, so widget ids (and the state keyed by them)
+/// stay the same when switching tabs.
+pub struct Tabs<M, K = usize> {
+    layout: Option<Layout>,
+
+    id: Id,
+    position: Position<i32>,
+    size: Size<Length<i32>>,
+    tabs: Vec<Tab<M, K>>,
+    active: K,
+
+    padding: Vec2<i32>,
+    bar_color: Color,
+    tab_color: Color,
+    hover_color: Color,
+    active_color: Color,
+    indicator_color: Color,
+    min: Size<i32>,
+    max: Size<i32>,
+
+    bar_height: i32,
+    header_rects: Vec<(Position<i32>, Size<i32>)>,
+    hovered: Option<usize>,
+
+    on_select: Option<Box<dyn Fn(K) -> M>>,
+}
+
+impl<M: 'static, K: PartialEq + Clone + 'static> Tabs<M, K> {
+    pub fn new(active: K) -> Self {
+        Self {
+            layout: None,
+
+            id: crate::context::next_id(),
+            position: Position::splat(0),
+            size: Size::splat(Length::Fit),
+            tabs: Vec::new(),
+            active,
+
+            padding: Vec2::new(12, 6),
+            bar_color: Color::rgba(30, 30, 30, 255),
+            tab_color: Color::TRANSPARENT,
+            hover_color: Color::rgba(50, 50, 50, 255),
+            active_color: Color::rgba(45, 45, 45, 255),
+            indicator_color: Color::rgba(60, 110, 200, 255),
+            min: Size::splat(0),
+            max: Size::splat(i32::MAX),
+
+            bar_height: 0,
+            header_rects: Vec::new(),
+            hovered: None,
+
+            on_select: None,
+        }
+    }
+
+    /// Adds a tab identified by `key`, shown as `header` in the bar.
+    pub fn tab(mut self, key: K, header: Element<M>, page: Element<M>) -> Self {
+        self.tabs.push(Tab { key, header, page });
+        self
+    }
+    pub fn size(mut self, size: Size<Length<i32>>) -> Self {
+        self.size = size;
+        self
+    }
+    /// Space around each header, horizontal and vertical.
+    pub fn padding(mut self, padding: Vec2<i32>) -> Self {
+        self.padding = padding;
+        self
+    }
+    pub fn bar_color(mut self, c: Color) -> Self {
+        self.bar_color = c;
+        self
+    }
+    pub fn tab_color(mut self, c: Color) -> Self {
+        self.tab_color = c;
+        self
+    }
+    pub fn hover_color(mut self, c: Color) -> Self {
+        self.hover_color = c;
+        self
+    }
+    pub fn active_color(mut self, c: Color) -> Self {
+        self.active_color = c;
+        self
+    }
+    pub fn indicator_color(mut self, c: Color) -> Self {
+        self.indicator_color = c;
+        self
+    }
+    pub fn min(mut self, size: Size<i32>) -> Self {
+        self.min = size;
+        self
+    }
+    pub fn max(mut self, size: Size<i32>) -> Self {
+        self.max = size;
+        self
+    }
+    /// Called with the key of a tab whose header was clicked while another tab was active. The
+    /// active tab is owned by the application and passed back through [`Tabs::new`].
+    pub fn on_select(mut self, f: impl Fn(K) -> M + 'static) -> Self {
+        self.on_select = Some(Box::new(f));
+        self
+    }
+
+    fn active_index(&self) -> Option<usize> {
+        self.tabs.iter().position(|t| t.key == self.active)
+    }
+
+    fn active_page(&mut self) -> Option<&mut Element<M>> {
+        let i = self.active_index()?;
+        Some(&mut self.tabs[i].page)
+    }
+
+    fn header_at(&self, p: Position<f32>) -> Option<usize> {
+        self.header_rects.iter().position(|&(pos, size)| {
+            p.x >= pos.x as f32
+                && p.x < (pos.x + size.width) as f32
+                && p.y >= pos.y as f32
+                && p.y < (pos.y + size.height) as f32
+        })
+    }
+}
+
+impl<M: 'static, K: PartialEq + Clone + 'static> Widget<M> for Tabs<M, K> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        for tab in &self.tabs {
+            f(tab.header.as_ref());
+        }
+        if let Some(i) = self.active_index() {
+            f(self.tabs[i].page.as_ref());
+        }
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        for tab in &mut self.tabs {
+            f(tab.header.as_mut());
+        }
+        if let Some(i) = self.active_index() {
+            f(self.tabs[i].page.as_mut());
+        }
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let mut bar_w = 0;
+        for tab in self.tabs.iter_mut() {
+            bar_w += tab.header.fit_width(ctx).current_size.width + 2 * self.padding.x;
+        }
+        let page_w = self
+            .active_page()
+            .map_or(0, |page| page.fit_width(ctx).current_size.width);
+        let min_w = bar_w.max(page_w);
+
+        let resolved_w = self
+            .size
+            .into_fixed()
+            .width
+            .clamp(min_w.max(self.min.width), self.max.width);
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(resolved_w, 0),
+            min: Size::new(min_w.max(self.min.width), self.min.height),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_w = match self.size.width {
+            Length::Grow(_) | Length::Percent(_) => parent_width,
+            Length::Fixed(w) => w,
+            Length::Fit => l.current_size.width,
+        }
+        .max(l.min.width)
+        .min(l.max.width)
+        .min(parent_width);
+        l.current_size.width = target_w;
+
+        for tab in self.tabs.iter_mut() {
+            let w = tab.header.layout().current_size.width;
+            tab.header.grow_width(ctx, w);
+        }
+        if let Some(page) = self.active_page() {
+            let w = offer(page.layout().size.width, target_w);
+            page.grow_width(ctx, w);
+        }
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let mut header_h = 0;
+        for tab in self.tabs.iter_mut() {
+            header_h = header_h.max(tab.header.fit_height(ctx).current_size.height);
+        }
+        self.bar_height = header_h + 2 * self.padding.y;
+        let page_h = self
+            .active_page()
+            .map_or(0, |page| page.fit_height(ctx).current_size.height);
+        let min_h = self.bar_height + page_h;
+
+        let prev = self.layout.as_ref().expect(LAYOUT_ERROR);
+        let requested_h = match self.size.height {
+            Length::Fixed(h) => h,
+            _ => min_h,
+        };
+        let resolved_h = requested_h
+            .max(self.min.height.max(min_h))
+            .min(self.max.height);
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(prev.current_size.width, resolved_h),
+            min: Size::new(prev.min.width, self.min.height.max(min_h)),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_h = match self.size.height {
+            Length::Grow(_) | Length::Percent(_) => parent_height,
+            Length::Fixed(h) => h,
+            Length::Fit => l.current_size.height,
+        }
+        .max(l.min.height)
+        .min(l.max.height)
+        .min(parent_height);
+        l.current_size.height = target_h;
+
+        let header_h = self.bar_height - 2 * self.padding.y;
+        for tab in self.tabs.iter_mut() {
+            tab.header.grow_height(ctx, header_h);
+        }
+        let page_h = (target_h - self.bar_height).max(0);
+        if let Some(page) = self.active_page() {
+            let h = offer(page.layout().size.height, page_h);
+            page.grow_height(ctx, h);
+        }
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+
+        self.header_rects.clear();
+        let mut x = position.x;
+        for tab in self.tabs.iter_mut() {
+            let size = tab.header.layout().current_size;
+            let header_pos = Position::new(
+                x + self.padding.x,
+                position.y + (self.bar_height - size.height) / 2,
+            );
+            let _ = tab.header.place(ctx, header_pos);
+
+            let w = size.width + 2 * self.padding.x;
+            self.header_rects
+                .push((Position::new(x, position.y), Size::new(w, self.bar_height)));
+            x += w;
+        }
+
+        let page_pos = Position::new(position.x, position.y + self.bar_height);
+        if let Some(page) = self.active_page() {
+            let _ = page.place(ctx, page_pos);
+        }
+
+        self.layout().current_size
+    }
+
+    fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        let width = self.layout().current_size.width;
+        if self.bar_color.a() > 0 {
+            instances.push(Instance::ui(
+                self.position,
+                Size::new(width, self.bar_height),
+                self.bar_color,
+            ));
+        }
+
+        let active = self.active_index();
+        for (i, &(pos, size)) in self.header_rects.iter().enumerate() {
+            let color = if active == Some(i) {
+                self.active_color
+            } else if self.hovered == Some(i) {
+                self.hover_color
+            } else {
+                self.tab_color
+            };
+            if color.a() > 0 {
+                instances.push(Instance::ui(pos, size, color));
+            }
+            if active == Some(i) {
+                instances.push(Instance::ui(
+                    Position::new(pos.x, pos.y + size.height - 2),
+                    Size::new(size.width, 2),
+                    self.indicator_color,
+                ));
+            }
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        for tab in self.tabs.iter_mut() {
+            tab.header.handle(ctx);
+        }
+        if let Some(page) = self.active_page() {
+            page.handle(ctx);
+        }
+
+        let mouse = ctx.ui.mouse_pos;
+        let hovered = if ctx.ui.is_occluded(self.id, mouse) {
+            None
+        } else {
+            self.header_at(mouse)
+        };
+        if hovered.is_some() {
+            ctx.ui.hot_item = Some(self.id);
+        }
+
+        if ctx.ui.mouse_pressed && hovered.is_some() {
+            ctx.ui.active_item = Some(self.id);
+        }
+        if ctx.ui.mouse_released && ctx.ui.active_item == Some(self.id) {
+            ctx.ui.active_item = None;
+            if let Some(i) = hovered
+                && self.active_index() != Some(i)
+            {
+                // The new page isn't laid out yet; the next view is expected to make it active.
+                if let Some(f) = self.on_select.as_ref() {
+                    ctx.ui.emit(f(self.tabs[i].key.clone()));
+                }
+                ctx.ui.request_redraw();
+            }
+        }
+
+        if hovered != self.hovered {
+            self.hovered = hovered;
+            ctx.ui.request_redraw();
+        }
+    }
+}
@@ -1,11 +1,210 @@
-use std::borrow::Cow;
+use std::{any::Any, borrow::Cow, fmt, ops::Range, sync::Arc};
 
-use super::*;
+use super::{
+    TextAction,
+    helpers::{
+        caret_in_run, hit_in_run, line_starts, measure, push_glyph_halo, push_glyphs,
+        push_sdf_glyphs, run_span,
+    },
+    *,
+};
+use crate::{event::CursorIcon, render::text::LayoutKey};
 use cosmic_text::{Attrs, Buffer, Family, Metrics, Shaping, Style, Weight, Wrap};
 
+/// A run of text inside a rich [`Text`]. Anything left unset falls back to the `Text`'s own
+/// attributes.
+#[derive(Debug, Clone)]
+pub struct Span<'a> {
+    text: Cow<'static, str>,
+    color: Option<Color>,
+    weight: Option<Weight>,
+    style: Option<Style>,
+    family: Option<Family<'a>>,
+    font_size: Option<f32>,
+    decoration: Option<Decoration>,
+    link: Option<LinkMessage>,
+}
+
+impl<'a> Span<'a> {
+    pub fn new<S: Into<Cow<'static, str>>>(content: S) -> Self {
+        Self {
+            text: content.into(),
+            color: None,
+            weight: None,
+            style: None,
+            family: None,
+            font_size: None,
+            decoration: None,
+            link: None,
+        }
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+    pub fn weight(mut self, weight: Weight) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+    pub fn family(mut self, family: Family<'a>) -> Self {
+        self.family = Some(family);
+        self
+    }
+    pub fn font_size(mut self, size: f32) -> Self {
+        self.font_size = Some(size);
+        self
+    }
+    pub fn decoration(mut self, decoration: Decoration) -> Self {
+        self.decoration = Some(decoration);
+        self
+    }
+    /// Makes the span a link emitting `msg` when clicked, see [`Text::link`].
+    pub fn link<M: Clone + 'static>(mut self, msg: M) -> Self {
+        self.link = Some(LinkMessage::new(msg));
+        self
+    }
+}
+
+// A link's message with its type erased, so texts stay usable in any tree.
+#[derive(Clone)]
+struct LinkMessage(Arc<dyn Fn() -> Box<dyn Any>>);
+
+impl LinkMessage {
+    fn new<M: Clone + 'static>(msg: M) -> Self {
+        Self(Arc::new(move || Box::new(msg.clone())))
+    }
+
+    fn get<M: 'static>(&self) -> Option<M> {
+        (self.0)().downcast().ok().map(|m| *m)
+    }
+}
+
+impl fmt::Debug for LinkMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LinkMessage")
+    }
+}
+
+impl From<&'static str> for Span<'_> {
+    fn from(text: &'static str) -> Self {
+        Span::new(text)
+    }
+}
+
+impl From<String> for Span<'_> {
+    fn from(text: String) -> Self {
+        Span::new(text)
+    }
+}
+
+/// Lines drawn along text in its color, combined with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Decoration {
+    pub underline: bool,
+    pub strikethrough: bool,
+    pub overline: bool,
+}
+
+impl Decoration {
+    pub const NONE: Self = Self {
+        underline: false,
+        strikethrough: false,
+        overline: false,
+    };
+    pub const UNDERLINE: Self = Self {
+        underline: true,
+        ..Self::NONE
+    };
+    pub const STRIKETHROUGH: Self = Self {
+        strikethrough: true,
+        ..Self::NONE
+    };
+    pub const OVERLINE: Self = Self {
+        overline: true,
+        ..Self::NONE
+    };
+
+    fn is_none(self) -> bool {
+        self == Self::NONE
+    }
+}
+
+impl std::ops::BitOr for Decoration {
+    type Output = Decoration;
+    fn bitor(self, rhs: Decoration) -> Decoration {
+        Decoration {
+            underline: self.underline || rhs.underline,
+            strikethrough: self.strikethrough || rhs.strikethrough,
+            overline: self.overline || rhs.overline,
+        }
+    }
+}
+
+// A decoration line being extended glyph by glyph.
+#[derive(Clone, Copy)]
+struct DecorationLine {
+    x0: f32,
+    x1: f32,
+    y: i32,
+    thickness: i32,
+    color: Color,
+}
+
+/// Which way [`Text`] runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    #[default]
+    Horizontal,
+    /// Upright characters stacked top to bottom, as on narrow side tabs.
+    Vertical,
+    /// Turned a quarter clockwise, reading top to bottom.
+    Clockwise,
+    /// Turned a quarter counterclockwise, reading bottom to top like a chart's axis label.
+    CounterClockwise,
+}
+
+impl Orientation {
+    // Clockwise rotation in radians the text is drawn with.
+    fn rotation(self) -> f32 {
+        match self {
+            Self::Clockwise => std::f32::consts::FRAC_PI_2,
+            Self::CounterClockwise => -std::f32::consts::FRAC_PI_2,
+            Self::Horizontal | Self::Vertical => 0.0,
+        }
+    }
+}
+
+// Layout width of stacked text; narrower than any glyph, so each gets a line of its own.
+const STACK_WIDTH: f32 = 0.5;
+
+/// What [`Text`] does with content past its `max_lines` or wider than it can be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Cuts the text off at its bounds.
+    #[default]
+    Clip,
+    /// Ends the last visible line with "…".
+    Ellipsis,
+}
+
+// Byte offsets of a selectable text's selection, the anchor staying put while dragging, and
+// the links under the pointer and that a press started on.
+#[derive(Default, Clone, Copy)]
+struct TextState {
+    anchor: usize,
+    caret: usize,
+    hovered_link: Option<usize>,
+    pressed_link: Option<usize>,
+}
+
 pub struct Text<'a> {
     layout: Option<Layout>,
-    buffer: Option<Buffer>,
+    buffer: Option<Arc<Buffer>>,
     preferred_size: Option<Size<i32>>,
     wrapped_size: Option<Size<i32>>,
 
@@ -14,11 +213,34 @@ pub struct Text<'a> {
     font_size: f32,
     line_height: f32,
     atributes: Attrs<'a>,
+    spans: Vec<Span<'a>>,
     wrap: Wrap,
+    shaping: Shaping,
+    direction: Option<Direction>,
+    decoration: Decoration,
+    letter_spacing: f32,
+    word_spacing: f32,
+    sdf: bool,
+    shadow: Option<(Position<i32>, f32, Color)>,
+    outline: Option<(f32, Color)>,
+    orientation: Orientation,
     position: Position<i32>,
     size: Size<Length<i32>>,
     min: Size<i32>,
     max: Size<i32>,
+
+    selectable: bool,
+    selection_color: Color,
+    selection: Range<usize>,
+
+    links: Vec<(Range<usize>, LinkMessage)>,
+    hovered_link: Option<usize>,
+
+    max_lines: Option<usize>,
+    overflow: Overflow,
+    // Whether the laid out text didn't fit, and how much of it is shown before an ellipsis.
+    truncated: bool,
+    visible_len: usize,
 }
 
 impl<'a> Text<'a> {
@@ -34,12 +256,51 @@ impl<'a> Text<'a> {
             font_size,
             line_height: 1.2,
             atributes: Attrs::new(),
+            spans: Vec::new(),
             wrap: Wrap::Word,
+            shaping: Shaping::Basic,
+            direction: None,
+            decoration: Decoration::NONE,
+            letter_spacing: 0.0,
+            word_spacing: 0.0,
+            sdf: false,
+            shadow: None,
+            outline: None,
+            orientation: Orientation::Horizontal,
             position: Position::splat(0),
             size: Size::splat(Length::Fit),
             min: Size::splat(0),
             max: Size::splat(i32::MAX),
+
+            selectable: false,
+            selection_color: Color::rgba(60, 110, 200, 160),
+            selection: 0..0,
+
+            links: Vec::new(),
+            hovered_link: None,
+
+            max_lines: None,
+            overflow: Overflow::Clip,
+            truncated: false,
+            visible_len: 0,
+        }
+    }
+
+    /// Mixed-style text made of `spans`, laid out as one paragraph. `font_size` and the
+    /// other attribute setters give the defaults spans fall back to.
+    pub fn rich(spans: Vec<Span<'a>>, font_size: f32) -> Self {
+        let text: String = spans.iter().map(|s| s.text.as_ref()).collect();
+        let mut this = Self::new(text, font_size);
+        let mut start = 0;
+        for span in &spans {
+            let range = start..start + span.text.len();
+            start = range.end;
+            if let Some(link) = &span.link {
+                this.links.push((range, link.clone()));
+            }
         }
+        this.spans = spans;
+        this
     }
 
     pub fn family(mut self, family: Family<'a>) -> Self {
@@ -77,6 +338,66 @@ impl<'a> Text<'a> {
         self
     }
 
+    /// `Shaping::Advanced` runs full shaping, which Arabic, Hebrew and Indic scripts and
+    /// ligatures need; the default `Shaping::Basic` is faster but only maps characters to
+    /// glyphs.
+    pub fn shaping(mut self, shaping: Shaping) -> Self {
+        self.shaping = shaping;
+        self
+    }
+
+    /// Aligns every paragraph to the start of `direction` instead of following the tree's
+    /// direction. Without either, paragraphs align to the side of their first strong
+    /// character, so Hebrew or Arabic paragraphs start on the right.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    pub fn decoration(mut self, decoration: Decoration) -> Self {
+        self.decoration = decoration;
+        self
+    }
+
+    /// Extra pixels after every character; negative values tighten the text.
+    pub fn letter_spacing(mut self, px: f32) -> Self {
+        self.letter_spacing = px;
+        self
+    }
+
+    /// Extra pixels after every space, on top of the letter spacing.
+    pub fn word_spacing(mut self, px: f32) -> Self {
+        self.word_spacing = px;
+        self
+    }
+
+    /// Draws glyphs from distance fields, which stay sharp when the text is scaled instead of
+    /// blurring, at the cost of slightly softer corners at small sizes.
+    pub fn sdf(mut self, on: bool) -> Self {
+        self.sdf = on;
+        self
+    }
+
+    /// Draws a copy of the glyphs `offset` pixels away behind the text, blurred over `blur`
+    /// pixels, to keep it readable over busy backgrounds. Emoji cast no shadow.
+    pub fn shadow(mut self, offset: Position<i32>, blur: f32, color: Color) -> Self {
+        self.shadow = Some((offset, blur, color));
+        self
+    }
+
+    /// Rings the glyphs with a `width` pixel outline, up to a sixth of the font size.
+    pub fn outline(mut self, width: f32, color: Color) -> Self {
+        self.outline = Some((width, color));
+        self
+    }
+
+    /// Runs the text vertically or turns it on its side. Text that isn't horizontal keeps its
+    /// natural size instead of wrapping, and ignores `max_lines`.
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
     pub fn size(mut self, size: Size<Length<i32>>) -> Self {
         self.size = size;
         self
@@ -90,9 +411,458 @@ impl<'a> Text<'a> {
         self.max = size;
         self
     }
+
+    /// Shows at most `lines` lines and keeps the text from growing wider than it is offered,
+    /// handling the rest as set by [`Text::overflow`].
+    pub fn max_lines(mut self, lines: usize) -> Self {
+        self.max_lines = Some(lines.max(1));
+        self
+    }
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Makes the bytes in `range` a link: hovering it shows a hand and underlines it, clicking
+    /// it emits `msg`. `M` has to be the message type of the tree the text ends up in.
+    pub fn link<M: Clone + 'static>(mut self, range: Range<usize>, msg: M) -> Self {
+        self.links.push((range, LinkMessage::new(msg)));
+        self
+    }
+
+    /// Lets the text be selected by dragging and copied with Ctrl+C.
+    pub fn selectable(mut self, selectable: bool) -> Self {
+        self.selectable = selectable;
+        self
+    }
+    pub fn selection_color(mut self, color: Color) -> Self {
+        self.selection_color = color;
+        self
+    }
+
+    /// Byte offset closest to the point (`x`, `y`) relative to the text's top-left corner.
+    fn hit(&self, x: f32, y: f32) -> usize {
+        let Some(buffer) = self.buffer.as_ref() else {
+            return 0;
+        };
+        let starts = line_starts(&self.text);
+        let mut best = None;
+        for run in buffer.layout_runs() {
+            let line_start = starts.get(run.line_i).copied().unwrap_or(0);
+            best = Some(line_start + hit_in_run(&run, x));
+            if y < run.line_top + run.line_height {
+                break;
+            }
+        }
+        best.unwrap_or(0).min(self.visible_len)
+    }
+
+    /// Link under the point (`x`, `y`) relative to the text's top-left corner.
+    fn link_at(&self, x: f32, y: f32) -> Option<usize> {
+        let buffer = self.buffer.as_ref()?;
+        let starts = line_starts(&self.text);
+        let run = buffer
+            .layout_runs()
+            .find(|r| y >= r.line_top && y < r.line_top + r.line_height)?;
+        let glyph = run.glyphs.iter().find(|g| x >= g.x && x < g.x + g.w)?;
+        let at = starts.get(run.line_i).copied().unwrap_or(0) + glyph.start;
+        if at >= self.visible_len {
+            return None;
+        }
+        self.links.iter().position(|(range, _)| range.contains(&at))
+    }
+
+    /// Decoration of the character at byte offset `at`; hovered links are underlined.
+    fn decoration_at(&self, at: usize) -> Decoration {
+        let mut decoration = self.decoration;
+        let mut end = 0;
+        for span in &self.spans {
+            end += span.text.len();
+            if at < end {
+                decoration = span.decoration.unwrap_or(self.decoration);
+                break;
+            }
+        }
+        match self.hovered_link.and_then(|i| self.links.get(i)) {
+            Some((range, _)) if range.contains(&at) => decoration | Decoration::UNDERLINE,
+            _ => decoration,
+        }
+    }
+
+    fn push_decorations(
+        &self,
+        ctx: &mut PaintCtx,
+        buffer: &Buffer,
+        origin: Position<f32>,
+        instances: &mut Vec<Instance>,
+    ) {
+        const BASE_COLOR: cosmic_text::Color = cosmic_text::Color::rgba(255, 255, 255, 255);
+        let flush = |line: &mut Option<DecorationLine>, instances: &mut Vec<Instance>| {
+            if let Some(l) = line.take() {
+                let x0 = l.x0.round() as i32;
+                instances.push(Instance::ui(
+                    Position::new(x0, l.y),
+                    Size::new(l.x1.round() as i32 - x0, l.thickness),
+                    l.color,
+                ));
+            }
+        };
+
+        let starts = line_starts(&self.text);
+        // Underline, strikethrough and overline, merged across glyphs that line up.
+        let mut lines: [Option<DecorationLine>; 3] = [None; 3];
+        for run in buffer.layout_runs() {
+            let line_start = starts.get(run.line_i).copied().unwrap_or(0);
+            for glyph in run.glyphs {
+                let decoration = self.decoration_at(line_start + glyph.start);
+                if decoration.is_none() {
+                    lines.iter_mut().for_each(|l| flush(l, instances));
+                    continue;
+                }
+                let Some(metrics) = ctx.text.decoration_metrics(glyph.font_id) else {
+                    continue;
+                };
+
+                let c = glyph.color_opt.unwrap_or(BASE_COLOR);
+                let color = Color::rgba(c.r(), c.g(), c.b(), c.a());
+                let baseline = origin.y + run.line_y + glyph.y;
+                let thickness = (metrics.thickness * glyph.font_size).round().max(1.0) as i32;
+                let x0 = origin.x + glyph.x;
+                let x1 = x0 + glyph.w;
+                let wanted = [
+                    (decoration.underline, metrics.underline),
+                    (decoration.strikethrough, metrics.strikethrough),
+                    (decoration.overline, metrics.overline),
+                ];
+                for (line, (on, offset)) in lines.iter_mut().zip(wanted) {
+                    if !on {
+                        flush(line, instances);
+                        continue;
+                    }
+                    let y = (baseline - offset * glyph.font_size).round() as i32 - thickness / 2;
+                    match line {
+                        Some(l)
+                            if l.y == y
+                                && l.thickness == thickness
+                                && l.color == color
+                                && x0 <= l.x1 + 0.5
+                                && x1 >= l.x0 - 0.5 =>
+                        {
+                            l.x0 = l.x0.min(x0);
+                            l.x1 = l.x1.max(x1);
+                        }
+                        _ => {
+                            flush(line, instances);
+                            *line = Some(DecorationLine {
+                                x0,
+                                x1,
+                                y,
+                                thickness,
+                                color,
+                            });
+                        }
+                    }
+                }
+            }
+            lines.iter_mut().for_each(|l| flush(l, instances));
+        }
+    }
+
+    fn center(&self) -> Position<f32> {
+        let size = self.layout.as_ref().expect(LAYOUT_ERROR).current_size;
+        Position::new(
+            self.position.x as f32 + size.width as f32 / 2.0,
+            self.position.y as f32 + size.height as f32 / 2.0,
+        )
+    }
+
+    // Where the buffer's top-left corner goes before the text is turned. Text that isn't
+    // horizontal is centered in the widget.
+    fn buffer_origin(&self) -> Position<f32> {
+        let position = Position::new(self.position.x as f32, self.position.y as f32);
+        let center = self.center();
+        let natural = self.preferred_size.unwrap_or(Size::splat(0));
+        match self.orientation {
+            Orientation::Horizontal => position,
+            Orientation::Vertical => Position::new(center.x - STACK_WIDTH / 2.0, position.y),
+            Orientation::Clockwise | Orientation::CounterClockwise => Position::new(
+                center.x - natural.width as f32 / 2.0,
+                center.y - natural.height as f32 / 2.0,
+            ),
+        }
+    }
+
+    // The window point `p` relative to the buffer's top-left corner, undoing any turn.
+    fn to_buffer(&self, p: Position<f32>) -> (f32, f32) {
+        let origin = self.buffer_origin();
+        let rotation = self.orientation.rotation();
+        if rotation == 0.0 {
+            return (p.x - origin.x, p.y - origin.y);
+        }
+        let center = self.center();
+        let (sin, cos) = (-rotation).sin_cos();
+        let (dx, dy) = (p.x - center.x, p.y - center.y);
+        (
+            center.x + dx * cos - dy * sin - origin.x,
+            center.y + dx * sin + dy * cos - origin.y,
+        )
+    }
+
+    // Lays out text that isn't horizontal, which keeps its natural size whatever it's offered.
+    fn fit_turned<M>(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let stacked = self.orientation == Orientation::Vertical;
+        let key = if stacked {
+            self.layout_key(&self.text, Some(STACK_WIDTH))
+                .align(Some(cosmic_text::Align::Center))
+        } else {
+            self.layout_key(&self.text, None)
+        };
+        let buffer = ctx.text.layout(key);
+        let (w, h) = measure(&buffer);
+        let (w, h) = (w.ceil() as i32, h.ceil() as i32);
+        self.buffer = Some(buffer);
+        self.truncated = false;
+        self.visible_len = self.text.len();
+        self.preferred_size = Some(Size::new(w, h));
+
+        let natural = if stacked {
+            Size::new(w, h)
+        } else {
+            Size::new(h, w)
+        };
+        self.wrapped_size = Some(natural);
+        let min_w = natural.width.max(self.min.width).min(self.max.width);
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(min_w, natural.height),
+            min: Size::new(min_w, self.min.height.min(self.max.height)),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn draw_text(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        let buffer = self.buffer.as_ref().expect("draw called before fit");
+        let origin = self.buffer_origin();
+
+        if !self.selection.is_empty() {
+            let starts = line_starts(&self.text);
+            for run in buffer.layout_runs() {
+                let line_start = starts.get(run.line_i).copied().unwrap_or(0);
+                let (span_start, span_end) = run_span(&run);
+                let start = self.selection.start.max(line_start + span_start);
+                let end = self.selection.end.min(line_start + span_end);
+                if start >= end {
+                    continue;
+                }
+                let x0 = (origin.x + caret_in_run(&run, start - line_start)).round() as i32;
+                let x1 = (origin.x + caret_in_run(&run, end - line_start)).round() as i32;
+                instances.push(Instance::ui(
+                    Position::new(x0, (origin.y + run.line_top).round() as i32),
+                    Size::new(x1 - x0, run.line_height.ceil() as i32),
+                    self.selection_color,
+                ));
+            }
+        }
+
+        let decorated = !self.decoration.is_none()
+            || self.hovered_link.is_some()
+            || self
+                .spans
+                .iter()
+                .any(|s| s.decoration.is_some_and(|d| !d.is_none()));
+        let draw = |ctx: &mut PaintCtx, instances: &mut Vec<Instance>| {
+            // The shadow falls from the outline too.
+            let grow = self.outline.map_or(0.0, |(width, _)| width);
+            if let Some((offset, blur, color)) = self.shadow {
+                let at = Position::new(origin.x + offset.x as f32, origin.y + offset.y as f32);
+                push_glyph_halo(ctx, buffer, at, grow, blur, color, instances);
+            }
+            if let Some((width, color)) = self.outline {
+                push_glyph_halo(ctx, buffer, origin, width, 0.0, color, instances);
+            }
+            if self.sdf {
+                push_sdf_glyphs(ctx, buffer, origin, None, instances);
+            } else {
+                push_glyphs(ctx, buffer, origin, None, instances);
+            }
+            if decorated {
+                self.push_decorations(ctx, buffer, origin, instances);
+            }
+        };
+        if self.truncated {
+            let size = self.layout.as_ref().expect(LAYOUT_ERROR).current_size;
+            ctx.clipped(self.position, size, instances, draw);
+        } else {
+            draw(ctx, instances);
+        }
+    }
+
+    fn contains(&self, p: Position<f32>) -> bool {
+        let Some(l) = self.layout.as_ref() else {
+            return false;
+        };
+        let sz = l.current_size;
+        let x = p.x - self.position.x as f32;
+        let y = p.y - self.position.y as f32;
+        x >= 0.0 && x < sz.width as f32 && y >= 0.0 && y < sz.height as f32
+    }
+
+    // cosmic-text takes spacing in ems of the font it applies to.
+    fn spaced(&self, attrs: Attrs<'a>, font_size: f32, extra: f32) -> Attrs<'a> {
+        let px = self.letter_spacing + extra;
+        if px == 0.0 {
+            attrs
+        } else {
+            attrs.letter_spacing(px / font_size)
+        }
+    }
+
+    fn layout_key(&self, text: &str, width: Option<f32>) -> LayoutKey {
+        let defaults = self.spaced(self.atributes.clone(), self.font_size, 0.0);
+        let wrap = match self.orientation {
+            Orientation::Vertical => Wrap::Glyph,
+            _ => self.wrap,
+        };
+        let key = LayoutKey::new(
+            text,
+            &defaults,
+            self.font_size,
+            self.line_height,
+            wrap,
+            self.shaping,
+            width,
+        );
+        if self.spans.is_empty() && self.word_spacing == 0.0 {
+            return key;
+        }
+
+        // Spans past a truncated text are cut off with it.
+        let mut runs = Vec::with_capacity(self.spans.len() + 1);
+        let mut start = 0;
+        for span in &self.spans {
+            let range = start.min(text.len())..(start + span.text.len()).min(text.len());
+            start += span.text.len();
+            if range.is_empty() {
+                continue;
+            }
+
+            let mut attrs = self.atributes.clone();
+            if let Some(c) = span.color {
+                attrs = attrs.color(cosmic_text::Color::rgba(c.r(), c.g(), c.b(), c.a()));
+            }
+            if let Some(weight) = span.weight {
+                attrs = attrs.weight(weight);
+            }
+            if let Some(style) = span.style {
+                attrs = attrs.style(style);
+            }
+            if let Some(family) = span.family {
+                attrs = attrs.family(family);
+            }
+            let mut font_size = self.font_size;
+            if let Some(size) = span.font_size {
+                attrs = attrs.metrics(Metrics::relative(size, self.line_height));
+                font_size = size;
+            }
+            runs.push((range, attrs, font_size));
+        }
+        let covered = runs.last().map_or(0, |(r, ..)| r.end);
+        if covered < text.len() {
+            runs.push((covered..text.len(), self.atributes.clone(), self.font_size));
+        }
+
+        // Spaces get their own runs to carry the word spacing.
+        let word_spacing = self.word_spacing;
+        key.spans(runs.into_iter().flat_map(|(range, attrs, font_size)| {
+            let mut split = Vec::new();
+            let mut start = range.start;
+            if word_spacing != 0.0 {
+                for (i, c) in text[range.clone()].char_indices() {
+                    if !matches!(c, ' ' | '\u{a0}') {
+                        continue;
+                    }
+                    let at = range.start + i;
+                    if at > start {
+                        split.push((start..at, self.spaced(attrs.clone(), font_size, 0.0)));
+                    }
+                    start = at + c.len_utf8();
+                    let spaced = self.spaced(attrs.clone(), font_size, word_spacing);
+                    split.push((at..start, spaced));
+                }
+            }
+            if start < range.end {
+                split.push((start..range.end, self.spaced(attrs, font_size, 0.0)));
+            }
+            split
+        }))
+    }
+
+    /// Cuts `buffer` down to `max_lines` that fit in `width`, ending it with "…" when asked
+    /// to. Returns the buffer to draw and its height.
+    fn truncate<M>(
+        &mut self,
+        ctx: &mut LayoutCtx<M>,
+        buffer: Arc<Buffer>,
+        width: i32,
+        align: Option<cosmic_text::Align>,
+    ) -> (Arc<Buffer>, f32) {
+        self.truncated = false;
+        self.visible_len = self.text.len();
+        let (shaped_w, total_h) = measure(&buffer);
+        let Some(max_lines) = self.max_lines else {
+            return (buffer, total_h);
+        };
+
+        let runs: Vec<(usize, f32, f32)> = buffer
+            .layout_runs()
+            .map(|r| (r.line_i, r.line_top, r.line_height))
+            .collect();
+        if runs.len() <= max_lines && shaped_w <= width as f32 {
+            return (buffer, total_h);
+        }
+        self.truncated = true;
+        let height = runs
+            .get(max_lines - 1)
+            .or(runs.last())
+            .map_or(total_h, |&(_, top, h)| top + h);
+        if self.overflow == Overflow::Clip {
+            return (buffer, height);
+        }
+
+        const ELLIPSIS: &str = "\u{2026}";
+        let ellipsis_w = measure(&ctx.text.layout(self.layout_key(ELLIPSIS, None))).0;
+        let starts = line_starts(&self.text);
+        let Some(last) = buffer.layout_runs().nth(max_lines.min(runs.len()) - 1) else {
+            return (buffer, height);
+        };
+        let line_start = starts.get(last.line_i).copied().unwrap_or(0);
+        let mut cut = line_start + run_span(&last).0;
+        for g in last.glyphs {
+            if g.x + g.w + ellipsis_w > width as f32 {
+                break;
+            }
+            cut = line_start + g.end;
+        }
+        let kept = self.text[..cut].trim_end();
+        self.visible_len = kept.len();
+
+        let text = format!("{kept}{ELLIPSIS}");
+        let truncated = ctx
+            .text
+            .layout(self.layout_key(&text, Some(width as f32)).align(align));
+        let height = truncated
+            .layout_runs()
+            .take(max_lines)
+            .last()
+            .map_or(height, |r| r.line_top + r.line_height);
+        (truncated, height)
+    }
 }
 
-impl<'a, M> Widget<M> for Text<'a> {
+impl<'a, M: 'static> Widget<M> for Text<'a> {
     fn id(&self) -> Id {
         self.id
     }
@@ -102,29 +872,18 @@ impl<'a, M> Widget<M> for Text<'a> {
     fn layout(&self) -> &Layout {
         self.layout.as_ref().expect(LAYOUT_ERROR)
     }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
 
     fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
-        let fs = ctx.text.font_system_mut();
-
-        if self.buffer.is_none() {
-            let metrics = Metrics::relative(self.font_size, self.line_height);
-            self.buffer = Some(Buffer::new(fs, metrics));
+        if self.orientation != Orientation::Horizontal {
+            return self.fit_turned(ctx);
         }
-        let buffer = self.buffer.as_mut().unwrap();
-
-        buffer.set_wrap(fs, self.wrap);
-        buffer.set_text(fs, &self.text, &self.atributes, Shaping::Basic);
 
         // Preferred
-        buffer.set_size(fs, None, None);
-        buffer.shape_until_scroll(fs, false);
-
-        let mut pref_w = 0f32;
-        let mut line_h = 0f32;
-        for run in buffer.layout_runs() {
-            pref_w = pref_w.max(run.line_w);
-            line_h += run.line_height;
-        }
+        let buffer = ctx.text.layout(self.layout_key(&self.text, None));
+        let (pref_w, line_h) = measure(&buffer);
         let pref_w = pref_w.ceil() as i32;
         let line_h = line_h.ceil() as i32;
         self.preferred_size = Some(Size::new(pref_w, line_h));
@@ -143,43 +902,62 @@ impl<'a, M> Widget<M> for Text<'a> {
     }
 
     fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
-        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
-        let fs = ctx.text.font_system_mut();
-        let buffer = self.buffer.as_mut().expect("fit_width must run first");
+        if self.orientation != Orientation::Horizontal {
+            let natural_w = self.wrapped_size.map_or(0, |s| s.width);
+            let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+            let target_w = match self.size.width {
+                Length::Fixed(w) => w,
+                Length::Fit => natural_w,
+                Length::Grow(_) | Length::Percent(_) => parent_width,
+            };
+            l.current_size.width = target_w
+                .max(l.min.width)
+                .min(self.max.width)
+                .min(parent_width);
+            return;
+        }
+
+        let min_w = self.layout.as_ref().expect(LAYOUT_ERROR).min.width;
         let pref = self
             .preferred_size
             .as_ref()
             .expect("preferred_size missing");
 
         let parent_cap = parent_width.min(self.max.width);
-        let lower_bound = l.min.width.min(parent_cap);
+        let lower_bound = min_w.min(parent_cap);
 
         let target_w = match self.size.width {
             Length::Fixed(w) => w.min(parent_cap).max(lower_bound),
             Length::Fit => pref.width.min(parent_cap).max(lower_bound),
-            Length::Grow => parent_cap.max(lower_bound),
+            Length::Grow(_) | Length::Percent(_) => parent_cap.max(lower_bound),
         };
 
-        buffer.set_size(fs, Some(target_w as f32), None);
-        buffer.shape_until_scroll(fs, false);
-
-        let mut shaped_w = 0f32;
-        let mut total_h = 0f32;
-        for run in buffer.layout_runs() {
-            shaped_w = shaped_w.max(run.line_w);
-            total_h += run.line_height;
-        }
-        let shaped_w = shaped_w.ceil() as i32;
+        let align = match self.direction {
+            Some(Direction::LeftToRight) => Some(cosmic_text::Align::Left),
+            Some(Direction::RightToLeft) => Some(cosmic_text::Align::Right),
+            None => {
+                (ctx.ui.direction() == Direction::RightToLeft).then_some(cosmic_text::Align::Right)
+            }
+        };
+        let buffer = ctx.text.layout(
+            self.layout_key(&self.text, Some(target_w as f32))
+                .align(align),
+        );
+        let shaped_w = measure(&buffer).0.ceil() as i32;
+        let (buffer, total_h) = self.truncate(ctx, buffer, target_w, align);
+        self.buffer = Some(buffer);
         let natural_h = total_h.ceil() as i32;
 
-        let final_w = target_w
-            .max(shaped_w)
-            .max(l.min.width)
-            .min(self.max.width)
-            .min(parent_width);
+        // Limited text stays within what it's offered instead of pushing the layout.
+        let wanted_w = if self.max_lines.is_some() {
+            target_w
+        } else {
+            target_w.max(shaped_w)
+        };
+        let final_w = wanted_w.max(min_w).min(self.max.width).min(parent_width);
         self.wrapped_size = Some(Size::new(final_w, natural_h));
 
-        l.current_size.width = final_w;
+        self.layout.as_mut().expect(LAYOUT_ERROR).current_size.width = final_w;
     }
 
     fn fit_height(&mut self, _ctx: &mut LayoutCtx<M>) -> Layout {
@@ -210,7 +988,7 @@ impl<'a, M> Widget<M> for Text<'a> {
         let mut target_h = match self.size.height {
             Length::Fixed(h) => h,
             Length::Fit => natural_h,
-            Length::Grow => parent_height,
+            Length::Grow(_) | Length::Percent(_) => parent_height,
         };
 
         target_h = target_h
@@ -228,46 +1006,103 @@ impl<'a, M> Widget<M> for Text<'a> {
     }
 
     fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
-        const BASE_COLOR: cosmic_text::Color = cosmic_text::Color::rgba(255, 255, 255, 255);
-        let buffer = self.buffer.as_ref().expect("draw called before fit");
-        let size = <Text as Widget<M>>::layout(self).current_size;
-        for run in buffer.layout_runs() {
-            for glyph in run.glyphs {
-                let (Position { x: left, y: top }, Size { width, height }, cache_key) =
-                    match ctx.text.get_glyph_data(glyph) {
-                        Some(v) => v,
-                        None => continue,
-                    };
-
-                let top_left = Position::new(
-                    (self.position.x as f32 + glyph.x).round() as i32 + left,
-                    (self.position.y as f32 + glyph.y + run.line_y).round() as i32 - top,
-                );
-
-                let glyph_color = glyph.color_opt.unwrap_or(BASE_COLOR);
-                let tint = Color::rgba(
-                    glyph_color.r(),
-                    glyph_color.g(),
-                    glyph_color.b(),
-                    glyph_color.a(),
-                );
-
-                let handle =
-                    match ctx
-                        .text
-                        .upload_glyph(ctx.gpu, ctx.texture, cache_key, width, height)
-                    {
-                        Some(h) => h,
-                        None => continue,
-                    };
-
-                instances.push(Instance::ui_tex(
-                    top_left,
-                    Size::new(width as i32, height as i32),
-                    tint,
-                    handle,
-                ));
+        let rotation = self.orientation.rotation();
+        if rotation == 0.0 {
+            self.draw_text(ctx, instances);
+        } else {
+            let pivot = self.center();
+            ctx.transformed(rotation, 1.0, pivot, instances, |ctx, instances| {
+                self.draw_text(ctx, instances)
+            });
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        if !self.selectable && self.links.is_empty() {
+            return;
+        }
+        let inside =
+            self.contains(ctx.ui.mouse_pos) && !ctx.ui.is_occluded(self.id, ctx.ui.mouse_pos);
+        let mouse = ctx.ui.mouse_pos;
+        let local = self.to_buffer(mouse);
+
+        let mut state = *ctx.ui.state_mut::<TextState>(self.id);
+        // The text may have changed since the selection was made.
+        let valid = |pos: usize| pos <= self.text.len() && self.text.is_char_boundary(pos);
+        if !valid(state.anchor) || !valid(state.caret) {
+            state = TextState::default();
+        }
+
+        // Links fire when released over the one they were pressed on.
+        let hovered = inside.then(|| self.link_at(local.0, local.1)).flatten();
+        if hovered.is_some() {
+            ctx.ui.set_cursor(CursorIcon::Pointer);
+        }
+        if ctx.ui.mouse_pressed {
+            state.pressed_link = hovered;
+        }
+        if ctx.ui.mouse_released
+            && let Some(i) = state.pressed_link.take()
+            && hovered == Some(i)
+            && let Some(msg) = self.links[i].1.get::<M>()
+        {
+            ctx.ui.emit(msg);
+        }
+        self.hovered_link = hovered;
+        if hovered != state.hovered_link {
+            state.hovered_link = hovered;
+            ctx.ui.request_redraw();
+        }
+        if !self.selectable {
+            *ctx.ui.state_mut::<TextState>(self.id) = state;
+            return;
+        }
+
+        // Press starts a selection, dragging extends it; pressing elsewhere clears it.
+        if ctx.ui.mouse_pressed {
+            if inside && ctx.ui.active_item != Some(self.id) {
+                ctx.ui.active_item = Some(self.id);
+                ctx.ui.kbd_focus_item = Some(self.id);
+                state.caret = self.hit(local.0, local.1);
+                if !ctx.ui.modifiers.shift {
+                    state.anchor = state.caret;
+                }
+            } else if !inside {
+                if ctx.ui.kbd_focus_item == Some(self.id) {
+                    ctx.ui.kbd_focus_item = None;
+                }
+                state.anchor = state.caret;
             }
         }
+        if ctx.ui.active_item == Some(self.id) {
+            if ctx.ui.mouse_down {
+                state.caret = self.hit(local.0, local.1);
+            } else {
+                ctx.ui.active_item = None;
+            }
+        }
+
+        let range = state.anchor.min(state.caret)..state.anchor.max(state.caret);
+        if ctx.ui.kbd_focus_item == Some(self.id) {
+            for key in ctx.ui.keys().to_vec() {
+                match TextAction::from_key(&key) {
+                    Some(TextAction::Copy) if !range.is_empty() => {
+                        ctx.ui.copy(&self.text[range.clone()]);
+                    }
+                    Some(TextAction::SelectAll) => {
+                        state.anchor = 0;
+                        state.caret = self.text.len();
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let range = state.anchor.min(state.caret)..state.anchor.max(state.caret);
+        if range != self.selection {
+            self.selection = range;
+            ctx.ui.request_redraw();
+        }
+        *ctx.ui.state_mut::<TextState>(self.id) = state;
     }
 }
@@ -0,0 +1,145 @@
+use super::{context_menu::MenuPopup, *};
+use crate::{
+    context::Context,
+    event::{KeyEvent, KeyState, LogicalKey},
+};
+
+/// Standard editing commands offered by text widgets, both as shortcuts and as the entries of
+/// their default right-click menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextAction {
+    Cut,
+    Copy,
+    Paste,
+    SelectAll,
+}
+
+impl TextAction {
+    /// Entries of the default context menu, in display order.
+    pub const DEFAULT_MENU: [TextAction; 4] = [
+        TextAction::Cut,
+        TextAction::Copy,
+        TextAction::Paste,
+        TextAction::SelectAll,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TextAction::Cut => "Cut",
+            TextAction::Copy => "Copy",
+            TextAction::Paste => "Paste",
+            TextAction::SelectAll => "Select All",
+        }
+    }
+
+    /// Whether the action does anything for the given editor state.
+    pub fn is_enabled(&self, has_selection: bool, read_only: bool) -> bool {
+        match self {
+            TextAction::Cut => has_selection && !read_only,
+            TextAction::Copy => has_selection,
+            TextAction::Paste => !read_only,
+            TextAction::SelectAll => true,
+        }
+    }
+
+    /// Maps Ctrl+X/C/V/A (or Super on macOS-style layouts), Ctrl+Insert and Shift+Insert to an
+    /// action.
+    pub fn from_key(key: &KeyEvent) -> Option<Self> {
+        let m = &key.modifiers;
+        if key.state != KeyState::Pressed || m.alt {
+            return None;
+        }
+        if key.logical_key == LogicalKey::Insert && m.shift && !m.control {
+            return Some(TextAction::Paste);
+        }
+        if !(m.control || m.super_) {
+            return None;
+        }
+        match &key.logical_key {
+            LogicalKey::Character(s) => match s.to_ascii_lowercase().as_str() {
+                "x" => Some(TextAction::Cut),
+                "c" => Some(TextAction::Copy),
+                "v" => Some(TextAction::Paste),
+                "a" => Some(TextAction::SelectAll),
+                _ => None,
+            },
+            LogicalKey::Insert if !m.shift => Some(TextAction::Copy),
+            _ => None,
+        }
+    }
+}
+
+/// An entry of a text widget's right-click menu: one of its own actions, or an entry added by
+/// the application that emits a message.
+#[derive(Debug, Clone)]
+pub enum TextMenuItem<M> {
+    Action(TextAction),
+    Custom(String, M),
+}
+
+impl<M> TextMenuItem<M> {
+    /// The entries shown when the application doesn't change them.
+    pub fn defaults() -> Vec<Self> {
+        TextAction::DEFAULT_MENU
+            .into_iter()
+            .map(TextMenuItem::Action)
+            .collect()
+    }
+}
+
+/// The right-click menu of the editable text widgets.
+pub(in crate::widget) struct TextMenu<M> {
+    items: Vec<TextMenuItem<M>>,
+    // Chooses an index into `items`.
+    popup: MenuPopup<usize>,
+}
+
+impl<M: Clone + 'static> TextMenu<M> {
+    pub(in crate::widget) fn new() -> Self {
+        Self {
+            items: TextMenuItem::defaults(),
+            popup: MenuPopup::new(),
+        }
+    }
+
+    pub(in crate::widget) fn set_items(&mut self, items: Vec<TextMenuItem<M>>) {
+        self.items = items;
+    }
+
+    /// Shapes the entries, greying out actions that do nothing right now.
+    pub(in crate::widget) fn layout(&mut self, ctx: &mut LayoutCtx<M>, has_selection: bool) {
+        self.popup.clear();
+        for (i, item) in self.items.iter().enumerate() {
+            match item {
+                TextMenuItem::Action(action) => {
+                    self.popup
+                        .push(action.label(), i, action.is_enabled(has_selection, false))
+                }
+                TextMenuItem::Custom(label, _) => self.popup.push(label.clone(), i, true),
+            }
+        }
+        self.popup.layout(ctx);
+    }
+
+    pub(in crate::widget) fn place(&mut self, ctx: &mut LayoutCtx<M>) {
+        self.popup.place(ctx);
+    }
+
+    pub(in crate::widget) fn paint(&self, ctx: &mut PaintCtx) {
+        self.popup.paint(ctx);
+    }
+
+    pub(in crate::widget) fn contains(&self, p: Position<f32>) -> bool {
+        self.popup.contains(p)
+    }
+
+    /// Tracks the menu like [`MenuPopup::handle`] and returns the entry chosen this pass.
+    pub(in crate::widget) fn handle(
+        &mut self,
+        ui: &mut Context<M>,
+        inside: bool,
+    ) -> Option<TextMenuItem<M>> {
+        let i = self.popup.handle(ui, inside)?;
+        self.items.get(i).cloned()
+    }
+}
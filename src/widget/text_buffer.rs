@@ -0,0 +1,125 @@
+use std::{borrow::Cow, fmt, ops::Range, sync::Arc};
+
+use cosmic_text::Buffer;
+use ropey::Rope;
+
+use crate::render::text::{TextStyle, TextSystem};
+
+/// Text kept in a rope, for documents too large to copy on every edit. Offsets are byte
+/// indices on char boundaries, and lines end at `\n`.
+///
+/// Lines are shaped one at a time through [`TextBuffer::layout_line`], so an edit only
+/// reshapes the lines it touched while the rest come from the layout cache.
+#[derive(Debug, Clone, Default)]
+pub struct TextBuffer {
+    rope: Rope,
+    revision: u64,
+}
+
+impl TextBuffer {
+    pub fn new(text: &str) -> Self {
+        Self {
+            rope: Rope::from_str(text),
+            revision: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rope.len_bytes()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rope.len_bytes() == 0
+    }
+
+    /// Goes up on every edit, so views can tell whether they're out of date.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn insert(&mut self, at: usize, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let at = self.rope.byte_to_char(at);
+        self.rope.insert(at, text);
+        self.revision += 1;
+    }
+
+    pub fn delete(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let start = self.rope.byte_to_char(range.start);
+        let end = self.rope.byte_to_char(range.end);
+        self.rope.remove(start..end);
+        self.revision += 1;
+    }
+
+    pub fn replace(&mut self, range: Range<usize>, text: &str) {
+        let start = range.start;
+        self.delete(range);
+        self.insert(start, text);
+    }
+
+    /// Number of lines, counting the empty one after a trailing `\n`.
+    pub fn line_count(&self) -> usize {
+        self.rope.len_lines()
+    }
+
+    /// Line `index` without its `\n`; borrowed unless it spans rope chunks.
+    pub fn line(&self, index: usize) -> Cow<'_, str> {
+        let line = self.rope.line(index);
+        let len = line.len_bytes();
+        let end = if len > 0 && line.byte(len - 1) == b'\n' {
+            len - 1
+        } else {
+            len
+        };
+        line.byte_slice(..end).into()
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        (0..self.line_count()).map(|i| self.line(i))
+    }
+
+    /// Byte offset where line `index` starts.
+    pub fn line_start(&self, index: usize) -> usize {
+        self.rope.line_to_byte(index)
+    }
+
+    /// Line holding byte `at`.
+    pub fn line_of(&self, at: usize) -> usize {
+        self.rope.byte_to_line(at)
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> Cow<'_, str> {
+        self.rope.byte_slice(range).into()
+    }
+
+    /// Line `index` shaped in `style`, wrapped at `width` if given.
+    pub fn layout_line(
+        &self,
+        text: &mut TextSystem,
+        index: usize,
+        style: &TextStyle,
+        width: Option<f32>,
+    ) -> Arc<Buffer> {
+        text.layout(style.key(&self.line(index), width))
+    }
+}
+
+impl From<&str> for TextBuffer {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl fmt::Display for TextBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.rope.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
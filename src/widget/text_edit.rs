@@ -0,0 +1,321 @@
+use std::ops::Range;
+
+use super::{Edit, EditHistory, EditKind, HistoryAction, TextAction};
+use crate::{
+    context::Context,
+    event::{KeyEvent, KeyState, LogicalKey},
+};
+
+/// What a key press did to a [`TextEditState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditOutcome {
+    /// The key isn't an editing key; the widget may handle it itself.
+    Ignored,
+    /// Only the caret or selection moved.
+    Moved,
+    /// The text changed.
+    Changed,
+    /// Enter in a single-line editor.
+    Submit,
+}
+
+/// Caret, selection and undo history of an editable string, shared by the text editing
+/// widgets. Offsets are byte indices on char boundaries.
+#[derive(Debug, Clone, Default)]
+pub struct TextEditState {
+    caret: usize,
+    anchor: usize,
+    history: EditHistory,
+}
+
+impl TextEditState {
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    pub fn anchor(&self) -> usize {
+        self.anchor
+    }
+
+    pub fn selection(&self) -> Range<usize> {
+        self.caret.min(self.anchor)..self.caret.max(self.anchor)
+    }
+
+    pub fn has_selection(&self) -> bool {
+        self.caret != self.anchor
+    }
+
+    /// Keeps caret and anchor valid after `text` was replaced from outside.
+    pub fn clamp(&mut self, text: &str) {
+        self.caret = floor_boundary(text, self.caret);
+        self.anchor = floor_boundary(text, self.anchor);
+    }
+
+    /// Moves the caret to `pos`, keeping the anchor when `extend` is set.
+    pub fn set_caret(&mut self, text: &str, pos: usize, extend: bool) {
+        self.caret = floor_boundary(text, pos);
+        if !extend {
+            self.anchor = self.caret;
+        }
+        self.history.seal();
+    }
+
+    pub fn select_all(&mut self, text: &str) {
+        self.anchor = 0;
+        self.caret = text.len();
+        self.history.seal();
+    }
+
+    /// Stops the next edit from merging into the previous undo step.
+    pub fn seal(&mut self) {
+        self.history.seal();
+    }
+
+    /// Replaces the selection with `s`.
+    pub fn insert(&mut self, text: &mut String, s: &str, time: f32) {
+        let kind = if self.has_selection() || s.chars().count() > 1 {
+            EditKind::Replace
+        } else {
+            EditKind::Insert
+        };
+        let range = self.selection();
+        self.replace(text, range, s, kind, time);
+    }
+
+    pub fn delete_backward(&mut self, text: &mut String, word: bool, time: f32) -> bool {
+        let range = match self.has_selection() {
+            true => self.selection(),
+            false if word => word_start(text, self.caret)..self.caret,
+            false => prev_boundary(text, self.caret)..self.caret,
+        };
+        if range.is_empty() {
+            return false;
+        }
+        self.replace(text, range, "", EditKind::DeleteBackward, time);
+        true
+    }
+
+    pub fn delete_forward(&mut self, text: &mut String, word: bool, time: f32) -> bool {
+        let range = match self.has_selection() {
+            true => self.selection(),
+            false if word => self.caret..word_end(text, self.caret),
+            false => self.caret..next_boundary(text, self.caret),
+        };
+        if range.is_empty() {
+            return false;
+        }
+        self.replace(text, range, "", EditKind::DeleteForward, time);
+        true
+    }
+
+    fn replace(
+        &mut self,
+        text: &mut String,
+        range: Range<usize>,
+        s: &str,
+        kind: EditKind,
+        time: f32,
+    ) {
+        let caret = range.start + s.len();
+        let edit = Edit {
+            kind,
+            at: range.start,
+            removed: text[range.clone()].to_owned(),
+            inserted: s.to_owned(),
+            selection_before: (self.anchor, self.caret),
+            selection_after: (caret, caret),
+        };
+        edit.apply(text);
+        self.history.record(edit, time);
+        self.caret = caret;
+        self.anchor = caret;
+    }
+
+    pub fn undo(&mut self, text: &mut String) -> bool {
+        let Some(edit) = self.history.undo() else {
+            return false;
+        };
+        edit.revert(text);
+        (self.anchor, self.caret) = edit.selection_before;
+        true
+    }
+
+    pub fn redo(&mut self, text: &mut String) -> bool {
+        let Some(edit) = self.history.redo() else {
+            return false;
+        };
+        edit.apply(text);
+        (self.anchor, self.caret) = edit.selection_after;
+        true
+    }
+
+    /// Runs `action` on the selection. Cut and copy put the selected text on the clipboard
+    /// through `ui`; paste asks for the clipboard, whose text arrives as committed text.
+    pub fn apply<M>(
+        &mut self,
+        text: &mut String,
+        action: TextAction,
+        ui: &mut Context<M>,
+        time: f32,
+    ) -> EditOutcome {
+        match action {
+            TextAction::Cut if self.has_selection() => {
+                let range = self.selection();
+                ui.copy(&text[range.clone()]);
+                self.replace(text, range, "", EditKind::Replace, time);
+                EditOutcome::Changed
+            }
+            TextAction::Copy if self.has_selection() => {
+                ui.copy(&text[self.selection()]);
+                EditOutcome::Moved
+            }
+            TextAction::Paste => {
+                ui.request_paste();
+                EditOutcome::Moved
+            }
+            TextAction::SelectAll => {
+                self.select_all(text);
+                EditOutcome::Moved
+            }
+            TextAction::Cut | TextAction::Copy => EditOutcome::Moved,
+        }
+    }
+
+    /// Applies the editing keys shared by all text widgets: typing, deletion, horizontal
+    /// movement, Home/End, clipboard shortcuts and undo/redo. Vertical movement is left to the
+    /// widget since it depends on layout.
+    pub fn handle_key<M>(
+        &mut self,
+        text: &mut String,
+        key: &KeyEvent,
+        multiline: bool,
+        time: f32,
+        ui: &mut Context<M>,
+    ) -> EditOutcome {
+        if key.state != KeyState::Pressed {
+            return EditOutcome::Ignored;
+        }
+        let m = key.modifiers;
+        let word = m.control || m.alt;
+
+        if let Some(action) = HistoryAction::from_key(key) {
+            let changed = match action {
+                HistoryAction::Undo => self.undo(text),
+                HistoryAction::Redo => self.redo(text),
+            };
+            return if changed {
+                EditOutcome::Changed
+            } else {
+                EditOutcome::Moved
+            };
+        }
+        if let Some(action) = TextAction::from_key(key) {
+            return self.apply(text, action, ui, time);
+        }
+
+        let changed = |b: bool| match b {
+            true => EditOutcome::Changed,
+            false => EditOutcome::Moved,
+        };
+        match &key.logical_key {
+            LogicalKey::Character(s) if !(m.control || m.alt || m.super_) => {
+                self.insert(text, s, time);
+                EditOutcome::Changed
+            }
+            LogicalKey::Space if !(m.control || m.super_) => {
+                self.insert(text, " ", time);
+                EditOutcome::Changed
+            }
+            LogicalKey::Enter if multiline => {
+                self.insert(text, "\n", time);
+                EditOutcome::Changed
+            }
+            LogicalKey::Enter => EditOutcome::Submit,
+            LogicalKey::Backspace => changed(self.delete_backward(text, word, time)),
+            LogicalKey::Delete => changed(self.delete_forward(text, word, time)),
+            LogicalKey::ArrowLeft => {
+                let pos = match (self.has_selection() && !m.shift, word) {
+                    (true, _) => self.selection().start,
+                    (false, true) => word_start(text, self.caret),
+                    (false, false) => prev_boundary(text, self.caret),
+                };
+                self.set_caret(text, pos, m.shift);
+                EditOutcome::Moved
+            }
+            LogicalKey::ArrowRight => {
+                let pos = match (self.has_selection() && !m.shift, word) {
+                    (true, _) => self.selection().end,
+                    (false, true) => word_end(text, self.caret),
+                    (false, false) => next_boundary(text, self.caret),
+                };
+                self.set_caret(text, pos, m.shift);
+                EditOutcome::Moved
+            }
+            LogicalKey::Home => {
+                let pos = if m.control {
+                    0
+                } else {
+                    line_start(text, self.caret)
+                };
+                self.set_caret(text, pos, m.shift);
+                EditOutcome::Moved
+            }
+            LogicalKey::End => {
+                let pos = if m.control {
+                    text.len()
+                } else {
+                    line_end(text, self.caret)
+                };
+                self.set_caret(text, pos, m.shift);
+                EditOutcome::Moved
+            }
+            _ => EditOutcome::Ignored,
+        }
+    }
+}
+
+fn floor_boundary(text: &str, pos: usize) -> usize {
+    let mut pos = pos.min(text.len());
+    while !text.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    pos
+}
+
+fn prev_boundary(text: &str, pos: usize) -> usize {
+    text[..pos].char_indices().next_back().map_or(0, |(i, _)| i)
+}
+
+fn next_boundary(text: &str, pos: usize) -> usize {
+    text[pos..]
+        .chars()
+        .next()
+        .map_or(pos, |c| pos + c.len_utf8())
+}
+
+/// Start of the word before `pos`, skipping whitespace first.
+fn word_start(text: &str, pos: usize) -> usize {
+    let before = text[..pos].trim_end();
+    before
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map_or(0, |(i, c)| i + c.len_utf8())
+}
+
+/// End of the word after `pos`, skipping whitespace first.
+fn word_end(text: &str, pos: usize) -> usize {
+    let after = &text[pos..];
+    let skipped = after.len() - after.trim_start().len();
+    after[skipped..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |i| pos + skipped + i)
+}
+
+pub(crate) fn line_start(text: &str, pos: usize) -> usize {
+    text[..pos].rfind('\n').map_or(0, |i| i + 1)
+}
+
+pub(crate) fn line_end(text: &str, pos: usize) -> usize {
+    text[pos..].find('\n').map_or(text.len(), |i| pos + i)
+}
@@ -0,0 +1,823 @@
+use std::{ops::Range, sync::Arc};
+
+use cosmic_text::{Attrs, Buffer, Family, LayoutRun, Shaping, Wrap};
+
+use super::{
+    helpers::{
+        caret_in_run, hit_in_run, line_starts, locate, measure, normalize_newlines, push_glyphs,
+        push_preedit, run_span,
+    },
+    text_action::TextMenu,
+    text_edit::{EditOutcome, TextEditState, line_start},
+    *,
+};
+use crate::{
+    event::{LogicalKey, Preedit, ScrollDelta},
+    render::text::LayoutKey,
+};
+
+/// Text, caret, selection and undo history of a [`TextEditor`], owned by the application.
+/// Edits made through the widget come back as a new value from `on_change`; the same methods
+/// let the application move the caret or edit programmatically.
+#[derive(Debug, Clone, Default)]
+pub struct EditorContent {
+    text: String,
+    edit: TextEditState,
+}
+
+impl EditorContent {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: normalize_newlines(&text.into()),
+            edit: TextEditState::default(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the whole text, resetting caret and history.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = normalize_newlines(&text.into());
+        self.edit = TextEditState::default();
+    }
+
+    pub fn caret(&self) -> usize {
+        self.edit.caret()
+    }
+
+    pub fn selection(&self) -> Range<usize> {
+        self.edit.selection()
+    }
+
+    pub fn selected_text(&self) -> &str {
+        &self.text[self.edit.selection()]
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.text.split('\n').count()
+    }
+
+    /// Zero-based line and column (in chars) of the caret.
+    pub fn caret_line_col(&self) -> (usize, usize) {
+        let before = &self.text[..self.edit.caret()];
+        let line = before.matches('\n').count();
+        let col = before[line_start(before, before.len())..].chars().count();
+        (line, col)
+    }
+
+    /// Moves the caret to byte offset `pos`, extending the selection when `extend` is set.
+    pub fn set_caret(&mut self, pos: usize, extend: bool) {
+        self.edit.set_caret(&self.text, pos, extend);
+    }
+
+    pub fn select(&mut self, range: Range<usize>) {
+        self.edit.set_caret(&self.text, range.start, false);
+        self.edit.set_caret(&self.text, range.end, true);
+    }
+
+    pub fn select_all(&mut self) {
+        self.edit.select_all(&self.text);
+    }
+
+    /// Replaces the selection with `s` as its own undo step.
+    pub fn insert(&mut self, s: &str) {
+        self.edit.seal();
+        self.edit
+            .insert(&mut self.text, &normalize_newlines(s), 0.0);
+        self.edit.seal();
+    }
+
+    pub fn delete_backward(&mut self) -> bool {
+        self.edit.seal();
+        let deleted = self.edit.delete_backward(&mut self.text, false, 0.0);
+        self.edit.seal();
+        deleted
+    }
+
+    pub fn delete_forward(&mut self) -> bool {
+        self.edit.seal();
+        let deleted = self.edit.delete_forward(&mut self.text, false, 0.0);
+        self.edit.seal();
+        deleted
+    }
+
+    pub fn undo(&mut self) -> bool {
+        self.edit.undo(&mut self.text)
+    }
+
+    pub fn redo(&mut self) -> bool {
+        self.edit.redo(&mut self.text)
+    }
+}
+
+/// Multi-line text editor with optional line wrapping and a line-number gutter. Scrolls with
+/// the wheel and to keep the caret in view, sideways too when lines don't wrap.
+pub struct TextEditor<M> {
+    layout: Option<Layout>,
+    buffer: Option<Arc<Buffer>>,
+    line_starts: Vec<usize>,
+    gutter_width: i32,
+    // Input method composition shown at the caret while focused.
+    preedit: Option<(Arc<Buffer>, Preedit)>,
+
+    id: Id,
+    position: Position<i32>,
+    size: Size<Length<i32>>,
+    min: Size<i32>,
+    max: Size<i32>,
+
+    content: EditorContent,
+    font_size: f32,
+    line_height: f32,
+    padding: i32,
+    attributes: Attrs<'static>,
+    wrap: bool,
+    line_numbers: bool,
+
+    color: Color,
+    background: Color,
+    gutter_color: Color,
+    line_number_color: Color,
+    selection_color: Color,
+    caret_color: Color,
+
+    focused: bool,
+    scroll: f32,
+    scroll_x: f32,
+    menu: TextMenu<M>,
+
+    on_change: Option<Box<dyn Fn(EditorContent) -> M>>,
+}
+
+impl<M: Clone + 'static> TextEditor<M> {
+    pub fn new(content: &EditorContent, font_size: f32) -> Self {
+        Self {
+            layout: None,
+            buffer: None,
+            line_starts: line_starts(&content.text),
+            gutter_width: 0,
+            preedit: None,
+
+            id: crate::context::next_id(),
+            position: Position::splat(0),
+            size: Size::splat(Length::Grow(1)),
+            min: Size::splat(0),
+            max: Size::splat(i32::MAX),
+
+            content: content.clone(),
+            font_size,
+            line_height: 1.3,
+            padding: 6,
+            attributes: Attrs::new().family(Family::Monospace),
+            wrap: true,
+            line_numbers: true,
+
+            color: Color::WHITE,
+            background: Color::rgba(30, 30, 30, 255),
+            gutter_color: Color::rgba(38, 38, 38, 255),
+            line_number_color: Color::rgba(255, 255, 255, 90),
+            selection_color: Color::rgba(60, 110, 200, 160),
+            caret_color: Color::WHITE,
+
+            focused: false,
+            scroll: 0.0,
+            scroll_x: 0.0,
+            menu: TextMenu::new(),
+
+            on_change: None,
+        }
+    }
+
+    pub fn family(mut self, family: Family<'static>) -> Self {
+        self.attributes.family = family;
+        self
+    }
+    pub fn line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+    pub fn padding(mut self, padding: i32) -> Self {
+        self.padding = padding;
+        self
+    }
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+    pub fn line_numbers(mut self, show: bool) -> Self {
+        self.line_numbers = show;
+        self
+    }
+    pub fn color(mut self, c: Color) -> Self {
+        self.color = c;
+        self
+    }
+    pub fn background(mut self, c: Color) -> Self {
+        self.background = c;
+        self
+    }
+    pub fn gutter_color(mut self, c: Color) -> Self {
+        self.gutter_color = c;
+        self
+    }
+    pub fn line_number_color(mut self, c: Color) -> Self {
+        self.line_number_color = c;
+        self
+    }
+    pub fn selection_color(mut self, c: Color) -> Self {
+        self.selection_color = c;
+        self
+    }
+    pub fn caret_color(mut self, c: Color) -> Self {
+        self.caret_color = c;
+        self
+    }
+    pub fn size(mut self, size: Size<Length<i32>>) -> Self {
+        self.size = size;
+        self
+    }
+    pub fn min(mut self, size: Size<i32>) -> Self {
+        self.min = size;
+        self
+    }
+    pub fn max(mut self, size: Size<i32>) -> Self {
+        self.max = size;
+        self
+    }
+    pub fn on_change(mut self, f: impl Fn(EditorContent) -> M + 'static) -> Self {
+        self.on_change = Some(Box::new(f));
+        self
+    }
+    /// Changes the right-click menu: `f` gets the default entries and returns the ones to show.
+    pub fn context_menu(
+        mut self,
+        f: impl FnOnce(Vec<TextMenuItem<M>>) -> Vec<TextMenuItem<M>>,
+    ) -> Self {
+        self.menu.set_items(f(TextMenuItem::defaults()));
+        self
+    }
+
+    fn key(&self, text: &str, color: Color, width: Option<f32>) -> LayoutKey {
+        let attrs = self.attributes.clone().color(cosmic_text::Color::rgba(
+            color.r(),
+            color.g(),
+            color.b(),
+            color.a(),
+        ));
+        let wrap = if self.wrap {
+            Wrap::WordOrGlyph
+        } else {
+            Wrap::None
+        };
+        LayoutKey::new(
+            text,
+            &attrs,
+            self.font_size,
+            self.line_height,
+            wrap,
+            Shaping::Basic,
+            width,
+        )
+    }
+
+    /// Top-left corner and size of the text area, right of the gutter.
+    fn text_rect(&self) -> (Position<i32>, Size<i32>) {
+        let size = self.layout().current_size;
+        let pos = Position::new(
+            self.position.x + self.gutter_width + self.padding,
+            self.position.y + self.padding,
+        );
+        let size = Size::new(
+            size.width - self.gutter_width - 2 * self.padding,
+            size.height - 2 * self.padding,
+        );
+        (pos, size.max(Size::splat(0)))
+    }
+
+    fn content_height(&self) -> f32 {
+        self.buffer.as_ref().map_or(0.0, |b| measure(b).1)
+    }
+
+    fn content_width(&self) -> f32 {
+        self.buffer.as_ref().map_or(0.0, |b| measure(b).0)
+    }
+
+    /// Caret x, line top and line height for byte offset `pos`, in text-area coordinates.
+    fn caret_point(&self, pos: usize) -> (f32, f32, f32) {
+        let line_px = self.font_size * self.line_height;
+        let Some(buffer) = self.buffer.as_ref() else {
+            return (0.0, 0.0, line_px);
+        };
+        let (line, local) = locate(&self.line_starts, pos);
+        let mut found = None;
+        for run in buffer.layout_runs().filter(|r| r.line_i == line) {
+            let (_, end) = run_span(&run);
+            found = Some((caret_in_run(&run, local), run.line_top, run.line_height));
+            if local < end {
+                break;
+            }
+        }
+        found.unwrap_or((0.0, line as f32 * line_px, line_px))
+    }
+
+    /// Byte offset closest to the text-area point (`x`, `y`).
+    fn hit(&self, x: f32, y: f32) -> usize {
+        let Some(buffer) = self.buffer.as_ref() else {
+            return 0;
+        };
+        let mut best = None;
+        for run in buffer.layout_runs() {
+            let line_start = self.line_starts.get(run.line_i).copied().unwrap_or(0);
+            let pos = line_start + hit_in_run(&run, x);
+            best = Some(pos);
+            if y < run.line_top + run.line_height {
+                break;
+            }
+        }
+        best.unwrap_or(0).min(self.content.text.len())
+    }
+
+    fn vertical_move(&self, pos: usize, lines: f32) -> usize {
+        let (x, top, height) = self.caret_point(pos);
+        let y = top + height / 2.0 + lines * height;
+        if y < 0.0 {
+            0
+        } else if y >= self.content_height() {
+            self.content.text.len()
+        } else {
+            self.hit(x, y)
+        }
+    }
+
+    #[inline]
+    fn contains(&self, p: Position<f32>) -> bool {
+        let sz = self.layout().current_size;
+        let l = self.position.x as f32;
+        let t = self.position.y as f32;
+        let r = l + sz.width as f32;
+        let b = t + sz.height as f32;
+        p.x >= l && p.x < r && p.y >= t && p.y < b
+    }
+}
+
+/// `pos` moved `lines` lines up or down in `text`, keeping its column where the line allows.
+fn logical_vertical_move(text: &str, pos: usize, lines: f32) -> usize {
+    let starts = line_starts(text);
+    let (line, col) = locate(&starts, pos);
+    let target = (line as f32 + lines).clamp(0.0, (starts.len() - 1) as f32) as usize;
+    let start = starts[target];
+    let end = starts.get(target + 1).map_or(text.len(), |&next| next - 1);
+    let mut pos = (start + col).min(end);
+    while !text.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    pos
+}
+
+#[derive(Default)]
+struct EditorState {
+    scroll: f32,
+    scroll_x: f32,
+    // The caret moved or the text changed, so the view should follow the caret once the
+    // buffer is laid out again.
+    follow: bool,
+}
+
+impl<M: Clone + 'static> Widget<M> for TextEditor<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.menu.layout(ctx, self.content.edit.has_selection());
+        self.gutter_width = if self.line_numbers {
+            let digits = self.line_starts.len().to_string().len().max(2);
+            let sample =
+                ctx.text
+                    .layout(self.key(&"0".repeat(digits), self.line_number_color, None));
+            measure(&sample).0.ceil() as i32 + 2 * self.padding
+        } else {
+            0
+        };
+
+        let chrome = self.gutter_width + 2 * self.padding;
+        let min_w = self.min.width.max(chrome + 1);
+        let resolved_w = match self.size.width {
+            Length::Fixed(w) => w,
+            Length::Fit => {
+                let natural = ctx
+                    .text
+                    .layout(self.key(&self.content.text, self.color, None));
+                measure(&natural).0.ceil() as i32 + chrome + 1
+            }
+            Length::Grow(_) | Length::Percent(_) => min_w,
+        }
+        .clamp(min_w, self.max.width.max(min_w));
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(resolved_w, 0),
+            min: Size::new(min_w, self.min.height),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_w = match self.size.width {
+            Length::Grow(_) | Length::Percent(_) => parent_width,
+            Length::Fixed(w) => w,
+            Length::Fit => l.current_size.width,
+        }
+        .max(l.min.width)
+        .min(l.max.width)
+        .min(parent_width);
+        l.current_size.width = target_w;
+
+        let text_w = (target_w - self.gutter_width - 2 * self.padding).max(1) as f32;
+        let width = self.wrap.then_some(text_w);
+        self.buffer = Some(
+            ctx.text
+                .layout(self.key(&self.content.text, self.color, width)),
+        );
+
+        let preedit = ctx.ui.preedit().filter(|_| ctx.ui.is_focused(self.id));
+        self.preedit = preedit
+            .cloned()
+            .map(|p| (ctx.text.layout(self.key(&p.text, self.color, None)), p));
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let prev = self.layout.as_ref().expect(LAYOUT_ERROR);
+        let line_px = (self.font_size * self.line_height).ceil() as i32;
+        let min_h = self.min.height.max(line_px + 2 * self.padding);
+
+        let resolved_h = match self.size.height {
+            Length::Fixed(h) => h,
+            Length::Fit => self.content_height().ceil() as i32 + 2 * self.padding,
+            Length::Grow(_) | Length::Percent(_) => min_h,
+        }
+        .max(min_h)
+        .min(self.max.height);
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(prev.current_size.width, resolved_h),
+            min: Size::new(prev.min.width, min_h),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_h = match self.size.height {
+            Length::Grow(_) | Length::Percent(_) => parent_height,
+            Length::Fixed(h) => h,
+            Length::Fit => l.current_size.height,
+        }
+        .max(l.min.height)
+        .min(l.max.height)
+        .min(parent_height);
+
+        l.current_size.height = target_h;
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+        self.menu.place(ctx);
+        self.layout().current_size
+    }
+
+    fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        self.menu.paint(ctx);
+        let size = self.layout().current_size;
+        instances.push(Instance::ui(self.position, size, self.background));
+        if self.gutter_width > 0 {
+            instances.push(Instance::ui(
+                self.position,
+                Size::new(self.gutter_width, size.height),
+                self.gutter_color,
+            ));
+        }
+
+        let Some(buffer) = self.buffer.as_ref() else {
+            return;
+        };
+        let (origin, area) = self.text_rect();
+        let top = origin.y as f32 - self.scroll;
+        let left = origin.x as f32 - self.scroll_x;
+        let visible = |y: f32, h: f32| {
+            top + y + h > origin.y as f32 && top + y < (origin.y + area.height) as f32
+        };
+
+        // Selection and line numbers, one layout run at a time.
+        let selection = self.content.selection();
+        let runs: Vec<LayoutRun> = buffer.layout_runs().collect();
+        let mut last_line = None;
+        for (i, run) in runs.iter().enumerate() {
+            if !visible(run.line_top, run.line_height) {
+                continue;
+            }
+            let y = (top + run.line_top).round() as i32;
+            let line_start = self.line_starts.get(run.line_i).copied().unwrap_or(0);
+
+            if self.focused && !selection.is_empty() {
+                let (span_start, span_end) = run_span(run);
+                let run_end = line_start + span_end;
+                let start = selection.start.max(line_start + span_start);
+                let end = selection.end.min(run_end);
+                // A selected line break shows as a small block after the line.
+                let last_of_line = runs.get(i + 1).is_none_or(|n| n.line_i != run.line_i);
+                let newline = last_of_line && selection.start <= run_end && selection.end > run_end;
+                if start < end || newline {
+                    let x0 = caret_in_run(run, start - line_start);
+                    let mut x1 = caret_in_run(run, end - line_start);
+                    if newline {
+                        x1 += self.font_size / 3.0;
+                    }
+                    // Scrolled sideways, the part left of the text area is cut off.
+                    let sx0 = ((left + x0).round() as i32).max(origin.x);
+                    let sx1 = ((left + x1).round() as i32).min(origin.x + area.width);
+                    if sx1 > sx0 {
+                        instances.push(Instance::ui(
+                            Position::new(sx0, y),
+                            Size::new(sx1 - sx0, run.line_height.ceil() as i32),
+                            self.selection_color,
+                        ));
+                    }
+                }
+            }
+
+            if self.gutter_width > 0 && last_line != Some(run.line_i) {
+                let number = ctx.text.layout(self.key(
+                    &(run.line_i + 1).to_string(),
+                    self.line_number_color,
+                    None,
+                ));
+                let w = measure(&number).0;
+                let x = self.position.x + self.gutter_width - self.padding - w.ceil() as i32;
+                push_glyphs(
+                    ctx,
+                    &number,
+                    Position::new(x as f32, y as f32),
+                    Some((
+                        Position::new(self.position.x, origin.y),
+                        Size::new(self.gutter_width, area.height),
+                    )),
+                    instances,
+                );
+            }
+            last_line = Some(run.line_i);
+        }
+
+        push_glyphs(
+            ctx,
+            buffer,
+            Position::new(left, top),
+            Some((origin, area)),
+            instances,
+        );
+
+        if self.focused {
+            let (x, y, h) = self.caret_point(self.content.caret());
+            let beside = left + x >= origin.x as f32 && left + x <= (origin.x + area.width) as f32;
+            if visible(y, h) && beside {
+                let at = Position::new((left + x).round() as i32, (top + y).round() as i32);
+                match &self.preedit {
+                    Some((buffer, preedit)) => push_preedit(
+                        ctx,
+                        buffer,
+                        preedit,
+                        at,
+                        h.ceil() as i32,
+                        self.background,
+                        self.color,
+                        self.caret_color,
+                        instances,
+                    ),
+                    None => instances.push(Instance::ui(
+                        at,
+                        Size::new(1, h.ceil() as i32),
+                        self.caret_color,
+                    )),
+                }
+            }
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        ctx.ui.register_focusable(self.id);
+        let inside =
+            self.contains(ctx.ui.mouse_pos) && !ctx.ui.is_occluded(self.id, ctx.ui.mouse_pos);
+        if inside {
+            ctx.ui.hot_item = Some(self.id);
+        }
+        let chosen = self.menu.handle(ctx.ui, inside);
+        if inside && ctx.ui.right_pressed {
+            ctx.ui.kbd_focus_item = Some(self.id);
+        }
+
+        let mut state = std::mem::take(ctx.ui.state_mut::<EditorState>(self.id));
+        self.content.edit.clamp(&self.content.text);
+        let before = (self.content.selection(), self.focused);
+        let time = ctx.globals.time;
+        let (origin, area) = self.text_rect();
+        let mouse = ctx.ui.mouse_pos;
+        let local = |state: &EditorState| {
+            (
+                mouse.x - origin.x as f32 + state.scroll_x,
+                mouse.y - origin.y as f32 + state.scroll,
+            )
+        };
+
+        // Mouse: press places the caret and focuses, dragging extends the selection.
+        if ctx.ui.mouse_pressed {
+            if inside && ctx.ui.active_item != Some(self.id) {
+                ctx.ui.active_item = Some(self.id);
+                ctx.ui.kbd_focus_item = Some(self.id);
+                let (x, y) = local(&state);
+                let pos = self.hit(x, y);
+                let extend = ctx.ui.modifiers.shift;
+                self.content.edit.set_caret(&self.content.text, pos, extend);
+            } else if !inside
+                && !self.menu.contains(mouse)
+                && ctx.ui.kbd_focus_item == Some(self.id)
+            {
+                ctx.ui.kbd_focus_item = None;
+            }
+        }
+        if ctx.ui.active_item == Some(self.id) {
+            if ctx.ui.mouse_down {
+                let (x, y) = local(&state);
+                let pos = self.hit(x, y);
+                self.content.edit.set_caret(&self.content.text, pos, true);
+            } else {
+                ctx.ui.active_item = None;
+            }
+        }
+
+        self.focused = ctx.ui.kbd_focus_item == Some(self.id);
+
+        let mut changed = false;
+        match chosen {
+            Some(TextMenuItem::Action(action)) => {
+                let outcome = self
+                    .content
+                    .edit
+                    .apply(&mut self.content.text, action, ctx.ui, time);
+                changed = outcome == EditOutcome::Changed;
+            }
+            Some(TextMenuItem::Custom(_, msg)) => ctx.ui.emit(msg),
+            None => {}
+        }
+        if self.focused {
+            let page = (area.height as f32 / (self.font_size * self.line_height)).max(1.0);
+            for key in ctx.ui.keys().to_vec() {
+                // Plain Tab indents instead of moving focus; Shift+Tab still leaves.
+                let m = key.modifiers;
+                if key.logical_key == LogicalKey::Tab
+                    && !(m.shift || m.control || m.alt || m.super_)
+                {
+                    ctx.ui.claim_tab();
+                    if key.state == crate::event::KeyState::Pressed {
+                        self.content.edit.insert(&mut self.content.text, "\t", time);
+                        changed = true;
+                    }
+                    continue;
+                }
+                let outcome =
+                    self.content
+                        .edit
+                        .handle_key(&mut self.content.text, &key, true, time, ctx.ui);
+                if outcome == EditOutcome::Changed {
+                    changed = true;
+                }
+                if outcome != EditOutcome::Ignored || key.state != crate::event::KeyState::Pressed {
+                    continue;
+                }
+                let lines = match key.logical_key {
+                    LogicalKey::ArrowUp => -1.0,
+                    LogicalKey::ArrowDown => 1.0,
+                    LogicalKey::PageUp => -page,
+                    LogicalKey::PageDown => page,
+                    _ => continue,
+                };
+                // After an edit the laid-out buffer is stale, so move by logical lines instead.
+                let pos = if changed {
+                    logical_vertical_move(&self.content.text, self.content.caret(), lines)
+                } else {
+                    self.vertical_move(self.content.caret(), lines)
+                };
+                let extend = key.modifiers.shift;
+                self.content.edit.set_caret(&self.content.text, pos, extend);
+            }
+
+            let committed = ctx.ui.text();
+            if !committed.is_empty() {
+                let committed = normalize_newlines(committed);
+                self.content
+                    .edit
+                    .insert(&mut self.content.text, &committed, time);
+                changed = true;
+            }
+        }
+
+        let line_px = self.font_size * self.line_height;
+        // Scrolling an editor that has nothing hidden is left to whatever encloses it.
+        let overflows = self.content_height() > area.height as f32
+            || (!self.wrap && self.content_width() + 1.0 > area.width as f32);
+        if inside && overflows {
+            let wheel = ctx.ui.take_wheel();
+            for (delta, _) in wheel {
+                let (dx, dy) = match delta {
+                    ScrollDelta::Lines(p) => (p.x * line_px, p.y * line_px),
+                    ScrollDelta::Pixels(p) => (p.x, p.y),
+                };
+                state.scroll -= dy;
+                if !self.wrap {
+                    state.scroll_x -= dx;
+                }
+            }
+        }
+
+        state.follow |= changed || self.content.selection() != before.0;
+        if changed {
+            if let Some(f) = self.on_change.as_ref() {
+                ctx.ui.emit(f(self.content.clone()));
+            }
+            ctx.ui.request_redraw();
+        } else {
+            // The buffer only matches the text when nothing changed during this pass.
+            let (x, y, h) = self.caret_point(self.content.caret());
+            let (view_w, view_h) = (area.width as f32, area.height as f32);
+            if std::mem::take(&mut state.follow) {
+                if y + h - state.scroll > view_h {
+                    state.scroll = y + h - view_h;
+                } else if y < state.scroll {
+                    state.scroll = y;
+                }
+                if !self.wrap {
+                    if x + 1.0 - state.scroll_x > view_w {
+                        state.scroll_x = x + 1.0 - view_w;
+                    } else if x < state.scroll_x {
+                        state.scroll_x = x;
+                    }
+                }
+            }
+            state.scroll = state
+                .scroll
+                .min((self.content_height() - view_h).max(0.0))
+                .max(0.0);
+            state.scroll_x = if self.wrap {
+                0.0
+            } else {
+                state
+                    .scroll_x
+                    .min((self.content_width() + 1.0 - view_w).max(0.0))
+                    .max(0.0)
+            };
+        }
+
+        if self.content.selection() != before.0 && !changed {
+            // Caret moves the application should know about, e.g. for a status bar.
+            if let Some(f) = self.on_change.as_ref() {
+                ctx.ui.emit(f(self.content.clone()));
+            }
+        }
+        if (self.content.selection(), self.focused) != before
+            || (state.scroll, state.scroll_x) != (self.scroll, self.scroll_x)
+        {
+            ctx.ui.request_redraw();
+        }
+        self.scroll = state.scroll;
+        self.scroll_x = state.scroll_x;
+        if self.focused {
+            let (x, y, h) = self.caret_point(self.content.caret());
+            ctx.ui.request_ime(
+                Position::new(
+                    (origin.x as f32 + x - self.scroll_x).round() as i32,
+                    (origin.y as f32 + y - self.scroll).round() as i32,
+                ),
+                Size::new(1, h.ceil() as i32),
+            );
+        }
+        *ctx.ui.state_mut::<EditorState>(self.id) = state;
+    }
+}
@@ -0,0 +1,499 @@
+use std::sync::Arc;
+
+use cosmic_text::{Attrs, Buffer, Family, Shaping, Wrap};
+
+use super::{
+    helpers::{caret_x, hit_index, measure, push_glyphs, push_preedit},
+    text_action::TextMenu,
+    text_edit::{EditOutcome, TextEditState},
+    *,
+};
+use crate::{event::Preedit, render::text::LayoutKey};
+
+#[derive(Default)]
+struct InputState {
+    edit: TextEditState,
+    scroll: f32,
+}
+
+/// Single-line editable text. The value is owned by the application: changes are reported
+/// through [`TextInput::on_change`] and the next view is expected to pass the new value back.
+pub struct TextInput<M> {
+    layout: Option<Layout>,
+    buffer: Option<Arc<Buffer>>,
+    // Input method composition shown at the caret while focused.
+    preedit: Option<(Arc<Buffer>, Preedit)>,
+
+    id: Id,
+    position: Position<i32>,
+    size: Size<Length<i32>>,
+    min: Size<i32>,
+    max: Size<i32>,
+
+    value: String,
+    placeholder: String,
+    font_size: f32,
+    line_height: f32,
+    padding: i32,
+    attributes: Attrs<'static>,
+
+    color: Color,
+    placeholder_color: Color,
+    background: Color,
+    focus_color: Color,
+    selection_color: Color,
+    caret_color: Color,
+
+    focused: bool,
+    caret: usize,
+    anchor: usize,
+    scroll: f32,
+    menu: TextMenu<M>,
+
+    on_change: Option<Box<dyn Fn(String) -> M>>,
+    on_submit: Option<M>,
+}
+
+impl<M: Clone + 'static> TextInput<M> {
+    pub fn new(value: impl Into<String>, font_size: f32) -> Self {
+        Self {
+            layout: None,
+            buffer: None,
+            preedit: None,
+
+            id: crate::context::next_id(),
+            position: Position::splat(0),
+            size: Size::new(Length::Fixed(200), Length::Fit),
+            min: Size::splat(0),
+            max: Size::splat(i32::MAX),
+
+            value: value.into(),
+            placeholder: String::new(),
+            font_size,
+            line_height: 1.2,
+            padding: 6,
+            attributes: Attrs::new(),
+
+            color: Color::WHITE,
+            placeholder_color: Color::rgba(255, 255, 255, 110),
+            background: Color::rgba(40, 40, 40, 255),
+            focus_color: Color::rgba(55, 55, 55, 255),
+            selection_color: Color::rgba(60, 110, 200, 160),
+            caret_color: Color::WHITE,
+
+            focused: false,
+            caret: 0,
+            anchor: 0,
+            scroll: 0.0,
+            menu: TextMenu::new(),
+
+            on_change: None,
+            on_submit: None,
+        }
+    }
+
+    pub fn placeholder(mut self, text: impl Into<String>) -> Self {
+        self.placeholder = text.into();
+        self
+    }
+    pub fn family(mut self, family: Family<'static>) -> Self {
+        self.attributes.family = family;
+        self
+    }
+    pub fn line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+    pub fn padding(mut self, padding: i32) -> Self {
+        self.padding = padding;
+        self
+    }
+    pub fn color(mut self, c: Color) -> Self {
+        self.color = c;
+        self
+    }
+    pub fn placeholder_color(mut self, c: Color) -> Self {
+        self.placeholder_color = c;
+        self
+    }
+    pub fn background(mut self, c: Color) -> Self {
+        self.background = c;
+        self
+    }
+    pub fn focus_color(mut self, c: Color) -> Self {
+        self.focus_color = c;
+        self
+    }
+    pub fn selection_color(mut self, c: Color) -> Self {
+        self.selection_color = c;
+        self
+    }
+    pub fn caret_color(mut self, c: Color) -> Self {
+        self.caret_color = c;
+        self
+    }
+    pub fn size(mut self, size: Size<Length<i32>>) -> Self {
+        self.size = size;
+        self
+    }
+    pub fn min(mut self, size: Size<i32>) -> Self {
+        self.min = size;
+        self
+    }
+    pub fn max(mut self, size: Size<i32>) -> Self {
+        self.max = size;
+        self
+    }
+    pub fn on_change(mut self, f: impl Fn(String) -> M + 'static) -> Self {
+        self.on_change = Some(Box::new(f));
+        self
+    }
+    pub fn on_submit(mut self, msg: M) -> Self {
+        self.on_submit = Some(msg);
+        self
+    }
+    /// Changes the right-click menu: `f` gets the default entries and returns the ones to show.
+    pub fn context_menu(
+        mut self,
+        f: impl FnOnce(Vec<TextMenuItem<M>>) -> Vec<TextMenuItem<M>>,
+    ) -> Self {
+        self.menu.set_items(f(TextMenuItem::defaults()));
+        self
+    }
+
+    fn key(&self, text: &str, color: Color) -> LayoutKey {
+        let attrs = self.attributes.clone().color(cosmic_text::Color::rgba(
+            color.r(),
+            color.g(),
+            color.b(),
+            color.a(),
+        ));
+        LayoutKey::new(
+            text,
+            &attrs,
+            self.font_size,
+            self.line_height,
+            Wrap::None,
+            Shaping::Basic,
+            None,
+        )
+    }
+
+    fn line_px(&self) -> i32 {
+        (self.font_size * self.line_height).ceil() as i32
+    }
+
+    fn inner_width(&self) -> f32 {
+        (self.layout().current_size.width - 2 * self.padding).max(0) as f32
+    }
+
+    /// Top-left corner of the text, before horizontal scrolling.
+    fn text_origin(&self) -> Position<i32> {
+        let h = self.layout().current_size.height;
+        Position::new(
+            self.position.x + self.padding,
+            self.position.y + (h - self.line_px()) / 2,
+        )
+    }
+
+    fn caret_x(&self, index: usize) -> f32 {
+        match self.buffer.as_ref() {
+            Some(buffer) if !self.value.is_empty() => caret_x(buffer, index),
+            _ => 0.0,
+        }
+    }
+
+    fn hit(&self, x: f32) -> usize {
+        match self.buffer.as_ref() {
+            Some(buffer) if !self.value.is_empty() => hit_index(buffer, x),
+            _ => 0,
+        }
+    }
+
+    #[inline]
+    fn contains(&self, p: Position<f32>) -> bool {
+        let sz = self.layout().current_size;
+        let l = self.position.x as f32;
+        let t = self.position.y as f32;
+        let r = l + sz.width as f32;
+        let b = t + sz.height as f32;
+        p.x >= l && p.x < r && p.y >= t && p.y < b
+    }
+}
+
+impl<M: Clone + 'static> Widget<M> for TextInput<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let (text, color) = if self.value.is_empty() {
+            (&self.placeholder, self.placeholder_color)
+        } else {
+            (&self.value, self.color)
+        };
+        let buffer = ctx.text.layout(self.key(text, color));
+        let (text_w, _) = measure(&buffer);
+        self.buffer = Some(buffer);
+
+        let has_selection = ctx.ui.state_mut::<InputState>(self.id).edit.has_selection();
+        self.menu.layout(ctx, has_selection);
+
+        let preedit = ctx.ui.preedit().filter(|_| ctx.ui.is_focused(self.id));
+        self.preedit = preedit
+            .cloned()
+            .map(|p| (ctx.text.layout(self.key(&p.text, self.color)), p));
+
+        let min_w = self.min.width.max(2 * self.padding + 1);
+        let resolved_w = match self.size.width {
+            Length::Fixed(w) => w,
+            _ => text_w.ceil() as i32 + 2 * self.padding + 1,
+        }
+        .clamp(min_w, self.max.width.max(min_w));
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(resolved_w, 0),
+            min: Size::new(min_w, self.min.height),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_w = match self.size.width {
+            Length::Grow(_) | Length::Percent(_) => parent_width,
+            Length::Fixed(w) => w,
+            Length::Fit => l.current_size.width,
+        }
+        .max(l.min.width)
+        .min(l.max.width)
+        .min(parent_width);
+
+        l.current_size.width = target_w;
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let content_h = self.line_px() + 2 * self.padding;
+        let prev = self.layout.as_ref().expect(LAYOUT_ERROR);
+
+        let min_h = self.min.height.max(content_h);
+        let resolved_h = match self.size.height {
+            Length::Fixed(h) => h,
+            _ => content_h,
+        }
+        .max(min_h)
+        .min(self.max.height);
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(prev.current_size.width, resolved_h),
+            min: Size::new(prev.min.width, min_h),
+            max: self.max,
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_h = match self.size.height {
+            Length::Grow(_) | Length::Percent(_) => parent_height,
+            Length::Fixed(h) => h,
+            Length::Fit => l.current_size.height,
+        }
+        .max(l.min.height)
+        .min(l.max.height)
+        .min(parent_height);
+
+        l.current_size.height = target_h;
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+        self.menu.place(ctx);
+        self.layout().current_size
+    }
+
+    fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        self.menu.paint(ctx);
+        let size = self.layout().current_size;
+        let background = if self.focused {
+            self.focus_color
+        } else {
+            self.background
+        };
+        instances.push(Instance::ui(self.position, size, background));
+
+        let origin = self.text_origin();
+        let x0 = origin.x;
+        let x1 = origin.x + self.inner_width() as i32;
+        let to_screen = |x: f32| (origin.x as f32 + x - self.scroll).round() as i32;
+
+        if self.focused && self.caret != self.anchor {
+            let start = to_screen(self.caret_x(self.caret.min(self.anchor))).max(x0);
+            let end = to_screen(self.caret_x(self.caret.max(self.anchor))).min(x1);
+            if end > start {
+                instances.push(Instance::ui(
+                    Position::new(start, origin.y),
+                    Size::new(end - start, self.line_px()),
+                    self.selection_color,
+                ));
+            }
+        }
+
+        if let Some(buffer) = self.buffer.as_ref() {
+            let text_origin = Position::new(origin.x as f32 - self.scroll, origin.y as f32);
+            let clip = (origin, Size::new(x1 - x0, self.line_px()));
+            push_glyphs(ctx, buffer, text_origin, Some(clip), instances);
+        }
+
+        if self.focused {
+            let x = to_screen(self.caret_x(self.caret)).clamp(x0, x1.max(x0 + 1) - 1);
+            match &self.preedit {
+                Some((buffer, preedit)) => push_preedit(
+                    ctx,
+                    buffer,
+                    preedit,
+                    Position::new(x, origin.y),
+                    self.line_px(),
+                    self.focus_color,
+                    self.color,
+                    self.caret_color,
+                    instances,
+                ),
+                None => instances.push(Instance::ui(
+                    Position::new(x, origin.y),
+                    Size::new(1, self.line_px()),
+                    self.caret_color,
+                )),
+            }
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        ctx.ui.register_focusable(self.id);
+        let inside =
+            self.contains(ctx.ui.mouse_pos) && !ctx.ui.is_occluded(self.id, ctx.ui.mouse_pos);
+        if inside {
+            ctx.ui.hot_item = Some(self.id);
+        }
+        let chosen = self.menu.handle(ctx.ui, inside);
+        if inside && ctx.ui.right_pressed {
+            ctx.ui.kbd_focus_item = Some(self.id);
+        }
+
+        let mut state = std::mem::take(ctx.ui.state_mut::<InputState>(self.id));
+        state.edit.clamp(&self.value);
+        let before = (state.edit.caret(), state.edit.anchor(), self.focused);
+        let time = ctx.globals.time;
+        let mouse_x = ctx.ui.mouse_pos.x;
+        let local_x = |s: &Self, scroll: f32| mouse_x - s.text_origin().x as f32 + scroll;
+
+        // Mouse: press places the caret and focuses, dragging extends the selection.
+        if ctx.ui.mouse_pressed {
+            if inside && ctx.ui.active_item != Some(self.id) {
+                ctx.ui.active_item = Some(self.id);
+                ctx.ui.kbd_focus_item = Some(self.id);
+                let pos = self.hit(local_x(self, state.scroll));
+                state
+                    .edit
+                    .set_caret(&self.value, pos, ctx.ui.modifiers.shift);
+            } else if !inside
+                && !self.menu.contains(ctx.ui.mouse_pos)
+                && ctx.ui.kbd_focus_item == Some(self.id)
+            {
+                ctx.ui.kbd_focus_item = None;
+            }
+        }
+        if ctx.ui.active_item == Some(self.id) {
+            if ctx.ui.mouse_down {
+                let pos = self.hit(local_x(self, state.scroll));
+                state.edit.set_caret(&self.value, pos, true);
+            } else {
+                ctx.ui.active_item = None;
+            }
+        }
+
+        self.focused = ctx.ui.kbd_focus_item == Some(self.id);
+
+        let mut changed = false;
+        match chosen {
+            Some(TextMenuItem::Action(action)) => {
+                changed =
+                    state.edit.apply(&mut self.value, action, ctx.ui, time) == EditOutcome::Changed;
+            }
+            Some(TextMenuItem::Custom(_, msg)) => ctx.ui.emit(msg),
+            None => {}
+        }
+        if self.focused {
+            for key in ctx.ui.keys().to_vec() {
+                match state
+                    .edit
+                    .handle_key(&mut self.value, &key, false, time, ctx.ui)
+                {
+                    EditOutcome::Changed => changed = true,
+                    EditOutcome::Submit => {
+                        if let Some(m) = self.on_submit.clone() {
+                            ctx.ui.emit(m);
+                        }
+                    }
+                    EditOutcome::Moved | EditOutcome::Ignored => {}
+                }
+            }
+
+            let committed: String = ctx.ui.text().chars().filter(|c| *c != '\n').collect();
+            if !committed.is_empty() {
+                state.edit.insert(&mut self.value, &committed, time);
+                changed = true;
+            }
+        }
+
+        if changed {
+            if let Some(f) = self.on_change.as_ref() {
+                ctx.ui.emit(f(self.value.clone()));
+            }
+            ctx.ui.request_redraw();
+        } else {
+            // The buffer only matches the value when nothing changed during this pass.
+            let caret_x = self.caret_x(state.edit.caret());
+            let inner = self.inner_width();
+            let text_w = self.caret_x(self.value.len());
+            if caret_x - state.scroll > inner {
+                state.scroll = caret_x - inner;
+            } else if caret_x < state.scroll {
+                state.scroll = caret_x;
+            }
+            state.scroll = state.scroll.min((text_w - inner).max(0.0)).max(0.0);
+        }
+
+        self.caret = state.edit.caret();
+        self.anchor = state.edit.anchor();
+        self.scroll = state.scroll;
+        if self.focused {
+            let origin = self.text_origin();
+            let x = origin.x as f32 + self.caret_x(self.caret) - self.scroll;
+            ctx.ui.request_ime(
+                Position::new(x.round() as i32, origin.y),
+                Size::new(1, self.line_px()),
+            );
+        }
+        if (self.caret, self.anchor, self.focused) != before {
+            ctx.ui.request_redraw();
+        }
+        *ctx.ui.state_mut::<InputState>(self.id) = state;
+    }
+}
@@ -0,0 +1,367 @@
+use std::sync::Arc;
+
+use cosmic_text::{Attrs, Buffer, Family, Shaping, Wrap};
+
+use super::{
+    helpers::{measure, push_glyphs},
+    *,
+};
+use crate::render::text::LayoutKey;
+
+/// A transient message shown by a [`ToastHost`]. Queue one with `Engine::toast` from
+/// `update`, or with [`Context::toast`] from a widget.
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub(crate) text: String,
+    pub(crate) duration: f32,
+}
+
+impl Toast {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            duration: 3.0,
+        }
+    }
+
+    /// Seconds the toast stays up before it starts leaving.
+    pub fn duration(mut self, seconds: f32) -> Self {
+        self.duration = seconds.max(0.0);
+        self
+    }
+}
+
+impl From<&str> for Toast {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for Toast {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+struct LiveToast {
+    text: String,
+    shown_at: f32,
+    // When the exit animation starts; moved up when the toast is clicked away.
+    hide_at: f32,
+}
+
+#[derive(Default)]
+struct ToastState {
+    toasts: Vec<LiveToast>,
+}
+
+/// Shows queued [`Toast`]s stacked in a corner of the window above `child`, newest closest
+/// to the corner. Toasts slide in, leave on their own after their duration, and can be
+/// clicked away early.
+pub struct ToastHost<M> {
+    id: Id,
+    child: Element<M>,
+
+    corner: Alignment,
+    margin: i32,
+    spacing: i32,
+    padding: i32,
+    max_width: f32,
+    transition: f32,
+
+    font_size: f32,
+    line_height: f32,
+    attributes: Attrs<'static>,
+    color: Color,
+    background: Color,
+
+    // One entry per live toast, newest first.
+    buffers: Vec<Arc<Buffer>>,
+    rects: Vec<(Position<i32>, Size<i32>)>,
+}
+
+impl<M: 'static> ToastHost<M> {
+    pub fn new(child: Element<M>) -> Self {
+        Self {
+            id: crate::context::next_id(),
+            child,
+
+            corner: Alignment::BottomRight,
+            margin: 16,
+            spacing: 8,
+            padding: 10,
+            max_width: 320.0,
+            transition: 0.25,
+
+            font_size: 14.0,
+            line_height: 1.2,
+            attributes: Attrs::new(),
+            color: Color::WHITE,
+            background: Color::rgba(40, 40, 40, 235),
+
+            buffers: Vec::new(),
+            rects: Vec::new(),
+        }
+    }
+
+    /// Corner (or edge) of the window the toasts stack from.
+    pub fn corner(mut self, corner: Alignment) -> Self {
+        self.corner = corner;
+        self
+    }
+    /// Distance between the toasts and the window edge.
+    pub fn margin(mut self, margin: i32) -> Self {
+        self.margin = margin;
+        self
+    }
+    pub fn spacing(mut self, spacing: i32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+    pub fn padding(mut self, padding: i32) -> Self {
+        self.padding = padding;
+        self
+    }
+    /// Width the text of a toast wraps at.
+    pub fn max_width(mut self, width: f32) -> Self {
+        self.max_width = width;
+        self
+    }
+    /// Seconds the entry and exit animations take.
+    pub fn transition(mut self, seconds: f32) -> Self {
+        self.transition = seconds.max(0.0);
+        self
+    }
+    pub fn font_size(mut self, font_size: f32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+    pub fn family(mut self, family: Family<'static>) -> Self {
+        self.attributes.family = family;
+        self
+    }
+    pub fn color(mut self, c: Color) -> Self {
+        self.color = c;
+        self
+    }
+    pub fn background(mut self, c: Color) -> Self {
+        self.background = c;
+        self
+    }
+
+    /// How far in a toast is, from 0 (hidden) to 1 (fully shown).
+    fn progress(&self, toast: &LiveToast, now: f32) -> f32 {
+        if self.transition <= 0.0 {
+            return if now < toast.hide_at { 1.0 } else { 0.0 };
+        }
+        let entering = (now - toast.shown_at) / self.transition;
+        let leaving = (toast.hide_at + self.transition - now) / self.transition;
+        entering.min(leaving).clamp(0.0, 1.0)
+    }
+
+    fn toast_at(&self, p: Position<f32>) -> Option<usize> {
+        self.rects.iter().position(|&(pos, size)| {
+            p.x >= pos.x as f32
+                && p.x < (pos.x + size.width) as f32
+                && p.y >= pos.y as f32
+                && p.y < (pos.y + size.height) as f32
+        })
+    }
+}
+
+impl<M: 'static> Widget<M> for ToastHost<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        self.child.position()
+    }
+    fn layout(&self) -> &Layout {
+        self.child.layout()
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.child.layout_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        f(self.child.as_ref());
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        f(self.child.as_mut());
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let now = ctx.globals.time;
+        let queued = ctx.ui.take_toasts();
+        let transition = self.transition;
+        let state = ctx.ui.state_mut::<ToastState>(self.id);
+        state.toasts.extend(queued.into_iter().map(|t| LiveToast {
+            text: t.text,
+            shown_at: now,
+            hide_at: now + t.duration + transition,
+        }));
+        state.toasts.retain(|t| now < t.hide_at + transition);
+
+        let attrs = self.attributes.clone().color(cosmic_text::Color::rgba(
+            self.color.r(),
+            self.color.g(),
+            self.color.b(),
+            self.color.a(),
+        ));
+        self.buffers = state
+            .toasts
+            .iter()
+            .rev()
+            .map(|t| {
+                ctx.text.layout(LayoutKey::new(
+                    &t.text,
+                    &attrs,
+                    self.font_size,
+                    self.line_height,
+                    Wrap::WordOrGlyph,
+                    Shaping::Advanced,
+                    Some(self.max_width),
+                ))
+            })
+            .collect();
+
+        self.child.fit_width(ctx)
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        self.child.grow_width(ctx, parent_width);
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.child.fit_height(ctx)
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        self.child.grow_height(ctx, parent_height);
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        let size = self.child.place(ctx, position);
+
+        let window = ctx.globals.window_size();
+        let now = ctx.globals.time;
+        // Which way the corner lies: -1, 0 or 1 along each axis.
+        let side = self.corner.offset(Size::splat(2));
+        let (dir_x, dir_y) = (side.x - 1, side.y - 1);
+
+        let state = ctx.ui.state_mut::<ToastState>(self.id);
+        let mut y = match dir_y {
+            1 => window.height - self.margin,
+            _ => self.margin,
+        };
+        self.rects.clear();
+        for (toast, buffer) in state.toasts.iter().rev().zip(&self.buffers) {
+            let (w, h) = measure(buffer);
+            let size = Size::new(
+                w.ceil() as i32 + 2 * self.padding,
+                h.ceil() as i32 + 2 * self.padding,
+            );
+            let free = Size::new(window.width - 2 * self.margin - size.width, 0);
+            let x = self.margin + self.corner.offset(free).x;
+            let top = if dir_y == 1 { y - size.height } else { y };
+
+            // Slide in from the nearest window edge.
+            let p = self.progress(toast, now);
+            let hidden = 1.0 - p * p * (3.0 - 2.0 * p);
+            let pos = if dir_x != 0 {
+                Position::new(
+                    x + (hidden * (size.width + self.margin) as f32) as i32 * dir_x,
+                    top,
+                )
+            } else {
+                let dir = if dir_y == 0 { -1 } else { dir_y };
+                Position::new(
+                    x,
+                    top + (hidden * (size.height + self.margin) as f32) as i32 * dir,
+                )
+            };
+            self.rects.push((pos, size));
+
+            let step = size.height + self.spacing;
+            if dir_y == 1 { y -= step } else { y += step }
+        }
+
+        size
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn __paint(
+        &self,
+        ctx: &mut PaintCtx,
+        instances: &mut Vec<Instance>,
+        t: &internal::PaintToken,
+        debug_on: bool,
+    ) {
+        self.child.__paint(ctx, instances, t, debug_on);
+        if self.rects.is_empty() {
+            return;
+        }
+
+        ctx.overlay(|ctx, out| {
+            for (&(pos, size), buffer) in self.rects.iter().zip(&self.buffers) {
+                out.push(Instance::ui(pos, size, self.background).radius(6.0));
+                let origin =
+                    Position::new((pos.x + self.padding) as f32, (pos.y + self.padding) as f32);
+                push_glyphs(ctx, buffer, origin, None, out);
+            }
+        });
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        self.child.handle(ctx);
+
+        let now = ctx.globals.time;
+        let mouse = ctx.ui.mouse_pos;
+        let hovered = if ctx.ui.is_occluded(self.id, mouse) {
+            None
+        } else {
+            self.toast_at(mouse)
+        };
+        for &(pos, size) in &self.rects {
+            ctx.ui.add_overlay(self.id, pos, size);
+        }
+        if hovered.is_some() {
+            ctx.ui.hot_item = Some(self.id);
+        }
+        let clicked = if ctx.ui.mouse_pressed && ctx.ui.active_item != Some(self.id) {
+            hovered.inspect(|_| ctx.ui.active_item = Some(self.id))
+        } else {
+            None
+        };
+        if ctx.ui.mouse_released && ctx.ui.active_item == Some(self.id) {
+            ctx.ui.active_item = None;
+        }
+
+        let transition = self.transition;
+        let state = ctx.ui.state_mut::<ToastState>(self.id);
+        if let Some(i) = clicked
+            && let Some(count) = state.toasts.len().checked_sub(1 + i)
+        {
+            let toast = &mut state.toasts[count];
+            toast.hide_at = toast.hide_at.min(now);
+        }
+
+        // Redraw every frame while animating, otherwise when the next toast starts leaving.
+        let next = state
+            .toasts
+            .iter()
+            .map(|t| {
+                if now < t.shown_at + transition || now >= t.hide_at {
+                    now
+                } else {
+                    t.hide_at
+                }
+            })
+            .reduce(f32::min);
+        if let Some(at) = next {
+            ctx.ui.request_redraw_at(at);
+        }
+    }
+}
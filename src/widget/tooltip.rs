@@ -0,0 +1,181 @@
+use super::*;
+
+#[derive(Default)]
+struct TooltipState {
+    hover_start: Option<f32>,
+    // Cursor position when the tooltip appeared; it stays there until hidden.
+    anchor: Option<Position<f32>>,
+    // A press while hovering hides the tooltip until the cursor leaves.
+    dismissed: bool,
+}
+
+/// Shows `tooltip` floating next to the cursor once `child` has been hovered for
+/// [`Tooltip::delay`] seconds. The tooltip is drawn in the overlay layer and never receives
+/// input.
+pub struct Tooltip<M> {
+    id: Id,
+    child: Element<M>,
+    tooltip: Element<M>,
+
+    delay: f32,
+    offset: Position<i32>,
+    visible: bool,
+}
+
+impl<M: 'static> Tooltip<M> {
+    pub fn new(child: Element<M>, tooltip: Element<M>) -> Self {
+        Self {
+            id: crate::context::next_id(),
+            child,
+            tooltip,
+
+            delay: 0.5,
+            offset: Position::new(12, 16),
+            visible: false,
+        }
+    }
+
+    /// Seconds the cursor has to rest on the child before the tooltip shows.
+    pub fn delay(mut self, seconds: f32) -> Self {
+        self.delay = seconds.max(0.0);
+        self
+    }
+    /// Distance from the cursor to the tooltip's top-left corner.
+    pub fn offset(mut self, offset: Position<i32>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    #[inline]
+    fn contains(&self, p: Position<f32>) -> bool {
+        let pos = self.child.position();
+        let sz = self.child.layout().current_size;
+        let l = pos.x as f32;
+        let t = pos.y as f32;
+        let r = l + sz.width as f32;
+        let b = t + sz.height as f32;
+        p.x >= l && p.x < r && p.y >= t && p.y < b
+    }
+}
+
+impl<M: 'static> Widget<M> for Tooltip<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        self.child.position()
+    }
+    fn layout(&self) -> &Layout {
+        self.child.layout()
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.child.layout_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        f(self.child.as_ref());
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        f(self.child.as_mut());
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.tooltip.fit_width(ctx);
+        self.child.fit_width(ctx)
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        self.tooltip
+            .grow_width(ctx, ctx.globals.window_size().width);
+        self.child.grow_width(ctx, parent_width);
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.tooltip.fit_height(ctx);
+        self.child.fit_height(ctx)
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        self.tooltip
+            .grow_height(ctx, ctx.globals.window_size().height);
+        self.child.grow_height(ctx, parent_height);
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        let size = self.child.place(ctx, position);
+
+        // Next to the cursor, flipped to the other side when it would leave the window.
+        let window = ctx.globals.window_size();
+        let tip = self.tooltip.layout().current_size;
+        let anchor = ctx
+            .ui
+            .state_mut::<TooltipState>(self.id)
+            .anchor
+            .unwrap_or(ctx.ui.mouse_pos);
+        let (ax, ay) = (anchor.x as i32, anchor.y as i32);
+        let mut x = ax + self.offset.x;
+        if x + tip.width > window.width {
+            x = ax - self.offset.x - tip.width;
+        }
+        let mut y = ay + self.offset.y;
+        if y + tip.height > window.height {
+            y = ay - self.offset.y - tip.height;
+        }
+        let _ = self.tooltip.place(ctx, Position::new(x.max(0), y.max(0)));
+
+        size
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn __paint(
+        &self,
+        ctx: &mut PaintCtx,
+        instances: &mut Vec<Instance>,
+        t: &internal::PaintToken,
+        debug_on: bool,
+    ) {
+        self.child.__paint(ctx, instances, t, debug_on);
+        if self.visible {
+            ctx.overlay(|ctx, out| self.tooltip.__paint(ctx, out, t, debug_on));
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        self.child.handle(ctx);
+
+        let mouse = ctx.ui.mouse_pos;
+        let inside = self.contains(mouse) && !ctx.ui.is_occluded(self.id, mouse);
+        let pressed = ctx.ui.mouse_pressed;
+        let now = ctx.globals.time;
+        let delay = self.delay;
+
+        let state = ctx.ui.state_mut::<TooltipState>(self.id);
+        if !inside {
+            *state = TooltipState::default();
+        } else if pressed {
+            state.dismissed = true;
+            state.anchor = None;
+        }
+
+        let mut due = None;
+        if inside && !state.dismissed {
+            let start = *state.hover_start.get_or_insert(now);
+            if now - start >= delay {
+                state.anchor.get_or_insert(mouse);
+            } else {
+                due = Some(start + delay);
+            }
+        }
+        let visible = state.anchor.is_some();
+
+        if let Some(t) = due {
+            ctx.ui.request_redraw_at(t);
+        }
+        if visible != self.visible {
+            self.visible = visible;
+            ctx.ui.request_redraw();
+        }
+    }
+}
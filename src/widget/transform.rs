@@ -0,0 +1,129 @@
+use super::*;
+
+/// Rotation and uniform scale applied to a widget when it is painted, around a pivot given as
+/// a fraction of its bounds. Layout and hit testing are unaffected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    rotation: f32,
+    scale: f32,
+    pivot: Position<f32>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transform {
+    /// No rotation or scaling, pivoting around the center.
+    pub fn new() -> Self {
+        Self {
+            rotation: 0.0,
+            scale: 1.0,
+            pivot: Position::splat(0.5),
+        }
+    }
+
+    /// Clockwise rotation in radians.
+    pub fn rotation(mut self, radians: f32) -> Self {
+        self.rotation = radians;
+        self
+    }
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+    /// Point the widget turns and scales around, from `(0, 0)` at its top-left corner to
+    /// `(1, 1)` at its bottom-right.
+    pub fn pivot(mut self, x: f32, y: f32) -> Self {
+        self.pivot = Position::new(x, y);
+        self
+    }
+}
+
+/// Paints a child with a [`Transform`]. Built with [`Widget::transform`] or
+/// [`Element::transform`].
+pub(crate) struct Transformed<M> {
+    id: Id,
+    child: Element<M>,
+    transform: Transform,
+}
+
+impl<M> Transformed<M> {
+    pub(crate) fn new(child: Element<M>, transform: Transform) -> Self {
+        Self {
+            id: crate::context::next_id(),
+            child,
+            transform,
+        }
+    }
+}
+
+impl<M: 'static> Widget<M> for Transformed<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        self.child.position()
+    }
+    fn layout(&self) -> &Layout {
+        self.child.layout()
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.child.layout_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        f(self.child.as_ref());
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        f(self.child.as_mut());
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.child.fit_width(ctx)
+    }
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        self.child.grow_width(ctx, parent_width);
+    }
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.child.fit_height(ctx)
+    }
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        self.child.grow_height(ctx, parent_height);
+    }
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.child.place(ctx, position)
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn __paint(
+        &self,
+        ctx: &mut PaintCtx,
+        instances: &mut Vec<Instance>,
+        t: &internal::PaintToken,
+        debug_on: bool,
+    ) {
+        let Transform {
+            rotation,
+            scale,
+            pivot,
+        } = self.transform;
+        let position = self.child.position();
+        let size = self.child.layout().current_size;
+        let pivot = Position::new(
+            position.x as f32 + size.width as f32 * pivot.x,
+            position.y as f32 + size.height as f32 * pivot.y,
+        );
+        ctx.transformed(rotation, scale, pivot, instances, |ctx, instances| {
+            self.child.__paint(ctx, instances, t, debug_on)
+        });
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        self.child.handle(ctx);
+    }
+}
@@ -0,0 +1,648 @@
+use std::{collections::HashSet, hash::Hash};
+
+use super::*;
+use crate::event::{KeyState, LogicalKey};
+
+/// A node of a [`TreeView`], identified by `key` across frames.
+pub struct TreeNode<M, K> {
+    key: K,
+    label: Element<M>,
+    children: Vec<TreeNode<M, K>>,
+    expanded: bool,
+}
+
+impl<M, K> TreeNode<M, K> {
+    pub fn new(key: K, label: Element<M>) -> Self {
+        Self {
+            key,
+            label,
+            children: Vec::new(),
+            expanded: false,
+        }
+    }
+
+    pub fn child(mut self, node: TreeNode<M, K>) -> Self {
+        self.children.push(node);
+        self
+    }
+    pub fn children(mut self, nodes: Vec<TreeNode<M, K>>) -> Self {
+        self.children.extend(nodes);
+        self
+    }
+    /// Whether the node starts out open the first time the tree is shown.
+    pub fn expanded(mut self, expanded: bool) -> Self {
+        self.expanded = expanded;
+        self
+    }
+}
+
+struct TreeState<K> {
+    open: HashSet<K>,
+    seeded: bool,
+    // Where a Shift range starts and the keyboard-focused node, kept by key since rows move
+    // as nodes open and close.
+    anchor: Option<K>,
+    cursor: Option<K>,
+}
+
+impl<K> Default for TreeState<K> {
+    fn default() -> Self {
+        Self {
+            open: HashSet::new(),
+            seeded: false,
+            anchor: None,
+            cursor: None,
+        }
+    }
+}
+
+struct FlatNode<M, K> {
+    key: K,
+    label: Element<M>,
+    depth: i32,
+    parent: Option<usize>,
+    has_children: bool,
+    expanded: bool,
+}
+
+/// Hierarchical list of nodes with expand/collapse toggles. Which nodes are open is kept
+/// across frames by key; selection is owned by the application.
+///
+/// Selection follows [`SelectionModel`] over the shown rows: Ctrl-click toggles and
+/// Shift-click selects a range in [`SelectionMode::Multi`]. Once focused by a click or Tab,
+/// the arrows, Home/End and PageUp/PageDown move the cursor, scrolling its row into view, with
+/// Shift extending the selection and Ctrl moving the cursor alone; Space selects it, and
+/// Left/Right collapse and expand nodes. Selected nodes hidden in a collapsed parent are
+/// dropped the next time the selection changes.
+pub struct TreeView<M, K> {
+    layout: Option<Layout>,
+
+    id: Id,
+    position: Position<i32>,
+    size: Size<Length<i32>>,
+    nodes: Vec<FlatNode<M, K>>,
+    selected: HashSet<K>,
+    selection_mode: SelectionMode,
+
+    indent: i32,
+    toggle_size: i32,
+    row_padding: i32,
+    color: Color,
+    hover_color: Color,
+    selected_color: Color,
+    focus_color: Color,
+    toggle_color: Color,
+
+    // Indices of the nodes whose ancestors are all open, and the top and height of their rows.
+    visible: Vec<usize>,
+    rows: Vec<(i32, i32)>,
+    hovered: Option<usize>,
+    focused: bool,
+    // Row of the keyboard cursor.
+    cursor: Option<usize>,
+
+    on_select: Option<Box<dyn Fn(K) -> M>>,
+    on_selection_change: Option<Box<dyn Fn(Vec<K>) -> M>>,
+}
+
+impl<M: 'static, K: Hash + Eq + Clone + 'static> TreeView<M, K> {
+    pub fn new(roots: Vec<TreeNode<M, K>>) -> Self {
+        fn flatten<M, K>(
+            node: TreeNode<M, K>,
+            depth: i32,
+            parent: Option<usize>,
+            out: &mut Vec<FlatNode<M, K>>,
+        ) {
+            let index = out.len();
+            out.push(FlatNode {
+                key: node.key,
+                label: node.label,
+                depth,
+                parent,
+                has_children: !node.children.is_empty(),
+                expanded: node.expanded,
+            });
+            for child in node.children {
+                flatten(child, depth + 1, Some(index), out);
+            }
+        }
+
+        let mut nodes = Vec::new();
+        for root in roots {
+            flatten(root, 0, None, &mut nodes);
+        }
+
+        Self {
+            layout: None,
+
+            id: crate::context::next_id(),
+            position: Position::splat(0),
+            size: Size::splat(Length::Fit),
+            nodes,
+            selected: HashSet::new(),
+            selection_mode: SelectionMode::Single,
+
+            indent: 16,
+            toggle_size: 10,
+            row_padding: 3,
+            color: Color::TRANSPARENT,
+            hover_color: Color::rgba(255, 255, 255, 20),
+            selected_color: Color::rgba(60, 110, 200, 160),
+            focus_color: Color::rgba(90, 150, 240, 255),
+            toggle_color: Color::rgba(200, 200, 200, 255),
+
+            visible: Vec::new(),
+            rows: Vec::new(),
+            hovered: None,
+            focused: false,
+            cursor: None,
+
+            on_select: None,
+            on_selection_change: None,
+        }
+    }
+
+    pub fn selected(mut self, key: Option<K>) -> Self {
+        self.selected = key.into_iter().collect();
+        self
+    }
+    /// Every selected node, for [`SelectionMode::Multi`].
+    pub fn selected_keys(mut self, keys: impl IntoIterator<Item = K>) -> Self {
+        self.selected = keys.into_iter().collect();
+        self
+    }
+    pub fn selection_mode(mut self, mode: SelectionMode) -> Self {
+        self.selection_mode = mode;
+        self
+    }
+    pub fn size(mut self, size: Size<Length<i32>>) -> Self {
+        self.size = size;
+        self
+    }
+    /// Horizontal offset per level of depth.
+    pub fn indent(mut self, indent: i32) -> Self {
+        self.indent = indent;
+        self
+    }
+    pub fn toggle_size(mut self, size: i32) -> Self {
+        self.toggle_size = size;
+        self
+    }
+    pub fn row_padding(mut self, padding: i32) -> Self {
+        self.row_padding = padding;
+        self
+    }
+    pub fn color(mut self, c: Color) -> Self {
+        self.color = c;
+        self
+    }
+    pub fn hover_color(mut self, c: Color) -> Self {
+        self.hover_color = c;
+        self
+    }
+    pub fn selected_color(mut self, c: Color) -> Self {
+        self.selected_color = c;
+        self
+    }
+    /// Outline of the cursor row while the tree has keyboard focus.
+    pub fn focus_color(mut self, c: Color) -> Self {
+        self.focus_color = c;
+        self
+    }
+    pub fn toggle_color(mut self, c: Color) -> Self {
+        self.toggle_color = c;
+        self
+    }
+    /// Called with the key of a node when a click or the keyboard selects it.
+    pub fn on_select(mut self, f: impl Fn(K) -> M + 'static) -> Self {
+        self.on_select = Some(Box::new(f));
+        self
+    }
+    /// Called with every selected key, in row order, whenever the selection changes.
+    pub fn on_selection_change(mut self, f: impl Fn(Vec<K>) -> M + 'static) -> Self {
+        self.on_selection_change = Some(Box::new(f));
+        self
+    }
+
+    fn row_of(&self, key: &K) -> Option<usize> {
+        self.visible.iter().position(|&i| &self.nodes[i].key == key)
+    }
+
+    /// The selection as a model over the shown rows. Without a remembered cursor, the keyboard
+    /// starts from the first selected row.
+    fn model(&self, ctx: &mut EventCtx<M>) -> SelectionModel {
+        let state = ctx.ui.state_mut::<TreeState<K>>(self.id);
+        let anchor = state.anchor.as_ref().and_then(|k| self.row_of(k));
+        let cursor = state.cursor.as_ref().and_then(|k| self.row_of(k));
+        let selected: Vec<usize> = (0..self.visible.len())
+            .filter(|&row| self.selected.contains(&self.nodes[self.visible[row]].key))
+            .collect();
+        let mut model = SelectionModel::new(self.selection_mode);
+        model.set_item_count(self.visible.len());
+        model.restore(
+            selected.iter().copied(),
+            anchor,
+            cursor.or(selected.first().copied()),
+        );
+        model
+    }
+
+    /// Left edge of the label of `node`, relative to the tree.
+    fn label_x(&self, node: &FlatNode<M, K>) -> i32 {
+        node.depth * self.indent + self.toggle_size + self.toggle_size / 2
+    }
+
+    /// Row the keyboard moves to from `row` for `key`, or `None` for keys the tree doesn't use.
+    /// Left and Right toggle the node instead where it has children to show or hide, which the
+    /// second value reports.
+    fn key_target(
+        &self,
+        ctx: &mut EventCtx<M>,
+        row: Option<usize>,
+        key: &LogicalKey,
+    ) -> Option<(usize, bool)> {
+        let last = self.visible.len() - 1;
+        // Rows that fit the window, a stand-in for the scrolled viewport.
+        let row_h = self.rows.first().map_or(1, |&(_, h)| h.max(1));
+        let page = (ctx.globals.window_size().height / row_h).max(1) as usize;
+        let Some(row) = row else {
+            return matches!(
+                key,
+                LogicalKey::ArrowUp
+                    | LogicalKey::ArrowDown
+                    | LogicalKey::Home
+                    | LogicalKey::End
+                    | LogicalKey::PageUp
+                    | LogicalKey::PageDown
+            )
+            .then_some((0, false));
+        };
+        let i = self.visible[row];
+        let node = &self.nodes[i];
+        let open = ctx
+            .ui
+            .state_mut::<TreeState<K>>(self.id)
+            .open
+            .contains(&node.key);
+        let target = match key {
+            LogicalKey::ArrowUp => row.saturating_sub(1),
+            LogicalKey::ArrowDown => (row + 1).min(last),
+            LogicalKey::Home => 0,
+            LogicalKey::End => last,
+            LogicalKey::PageUp => row.saturating_sub(page),
+            LogicalKey::PageDown => (row + page).min(last),
+            LogicalKey::ArrowRight | LogicalKey::ArrowLeft
+                if node.has_children && open == (*key == LogicalKey::ArrowLeft) =>
+            {
+                self.set_open(ctx, i, !open);
+                return Some((row, true));
+            }
+            // The first child, shown right below an open node.
+            LogicalKey::ArrowRight if node.has_children => (row + 1).min(last),
+            LogicalKey::ArrowRight => row,
+            LogicalKey::ArrowLeft => node
+                .parent
+                .and_then(|p| self.visible.iter().position(|&v| v == p))
+                .unwrap_or(row),
+            _ => return None,
+        };
+        Some((target, false))
+    }
+
+    fn set_open(&self, ctx: &mut EventCtx<M>, node: usize, open: bool) {
+        let key = self.nodes[node].key.clone();
+        let state = ctx.ui.state_mut::<TreeState<K>>(self.id);
+        if open {
+            state.open.insert(key);
+        } else {
+            state.open.remove(&key);
+        }
+        ctx.ui.request_redraw();
+    }
+
+    fn row_at(&self, p: Position<f32>) -> Option<usize> {
+        let width = self.layout().current_size.width as f32;
+        let x = p.x - self.position.x as f32;
+        let y = p.y - self.position.y as f32;
+        if x < 0.0 || x >= width {
+            return None;
+        }
+        self.rows
+            .iter()
+            .position(|&(top, h)| y >= top as f32 && y < (top + h) as f32)
+    }
+}
+
+impl<M: 'static, K: Hash + Eq + Clone + 'static> Widget<M> for TreeView<M, K> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        &self.position
+    }
+    fn layout(&self) -> &Layout {
+        self.layout.as_ref().expect(LAYOUT_ERROR)
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.layout.as_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        for &i in &self.visible {
+            f(self.nodes[i].label.as_ref());
+        }
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        for &i in &self.visible {
+            f(self.nodes[i].label.as_mut());
+        }
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let state = ctx.ui.state_mut::<TreeState<K>>(self.id);
+        if !state.seeded {
+            state.seeded = true;
+            for node in self.nodes.iter().filter(|n| n.expanded) {
+                state.open.insert(node.key.clone());
+            }
+        }
+
+        // A node shows when its parent shows and is open; parents come first.
+        let mut shown = vec![false; self.nodes.len()];
+        self.visible.clear();
+        for i in 0..self.nodes.len() {
+            shown[i] = match self.nodes[i].parent {
+                None => true,
+                Some(p) => shown[p] && state.open.contains(&self.nodes[p].key),
+            };
+            if shown[i] {
+                self.visible.push(i);
+            }
+        }
+
+        let mut min_w = 0;
+        for &i in &self.visible {
+            let x = self.label_x(&self.nodes[i]);
+            let label_w = self.nodes[i].label.fit_width(ctx).current_size.width;
+            min_w = min_w.max(x + label_w);
+        }
+
+        let resolved_w = match self.size.width {
+            Length::Fixed(w) => w,
+            _ => min_w,
+        };
+
+        let l = Layout {
+            size: self.size,
+            current_size: Size::new(resolved_w, 0),
+            min: Size::new(min_w.min(resolved_w), 0),
+            max: Size::splat(i32::MAX),
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_w = match self.size.width {
+            Length::Grow(_) | Length::Percent(_) => parent_width,
+            Length::Fixed(w) => w,
+            Length::Fit => l.current_size.width,
+        }
+        .max(l.min.width)
+        .min(parent_width);
+        l.current_size.width = target_w;
+
+        for &i in &self.visible {
+            let x = self.label_x(&self.nodes[i]);
+            self.nodes[i].label.grow_width(ctx, (target_w - x).max(0));
+        }
+    }
+
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        let mut total = 0;
+        for &i in &self.visible {
+            let h = self.nodes[i].label.fit_height(ctx).current_size.height;
+            total += h.max(self.toggle_size) + 2 * self.row_padding;
+        }
+
+        let prev = self.layout.as_ref().expect(LAYOUT_ERROR);
+        let resolved_h = match self.size.height {
+            Length::Fixed(h) => h,
+            _ => total,
+        };
+
+        let l = Layout {
+            current_size: Size::new(prev.current_size.width, resolved_h),
+            min: Size::new(prev.min.width, total.min(resolved_h)),
+            ..*prev
+        };
+        self.layout = Some(l);
+        l
+    }
+
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        let l = self.layout.as_mut().expect(LAYOUT_ERROR);
+
+        let target_h = match self.size.height {
+            Length::Grow(_) | Length::Percent(_) => parent_height,
+            Length::Fixed(h) => h,
+            Length::Fit => l.current_size.height,
+        }
+        .max(l.min.height)
+        .min(parent_height);
+        l.current_size.height = target_h;
+
+        for &i in &self.visible {
+            let label = &mut self.nodes[i].label;
+            let h = label.layout().current_size.height;
+            label.grow_height(ctx, h);
+        }
+    }
+
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.position = position;
+
+        self.rows.clear();
+        let mut y = 0;
+        for &i in &self.visible {
+            let x = self.label_x(&self.nodes[i]);
+            let label = &mut self.nodes[i].label;
+            let label_h = label.layout().current_size.height;
+            let row_h = label_h.max(self.toggle_size) + 2 * self.row_padding;
+            let _ = label.place(
+                ctx,
+                Position::new(position.x + x, position.y + y + (row_h - label_h) / 2),
+            );
+            self.rows.push((y, row_h));
+            y += row_h;
+        }
+
+        self.layout().current_size
+    }
+
+    fn draw_self(&self, ctx: &mut PaintCtx, instances: &mut Vec<Instance>) {
+        let size = self.layout().current_size;
+        if self.color.a() > 0 {
+            instances.push(Instance::ui(self.position, size, self.color));
+        }
+
+        for (row, &i) in self.visible.iter().enumerate() {
+            let node = &self.nodes[i];
+            let (top, h) = self.rows[row];
+            let row_pos = Position::new(self.position.x, self.position.y + top);
+
+            let highlight = if self.selected.contains(&node.key) {
+                Some(self.selected_color)
+            } else if self.hovered == Some(row) {
+                Some(self.hover_color)
+            } else {
+                None
+            };
+            if let Some(c) = highlight {
+                instances.push(Instance::ui(row_pos, Size::new(size.width, h), c));
+            }
+            if self.focused && self.cursor == Some(row) {
+                let w = size.width;
+                for (p, s) in [
+                    (row_pos, Size::new(w, 1)),
+                    (row_pos + Size::new(0, h - 1), Size::new(w, 1)),
+                    (row_pos, Size::new(1, h)),
+                    (row_pos + Size::new(w - 1, 0), Size::new(1, h)),
+                ] {
+                    instances.push(Instance::ui(p, s, self.focus_color));
+                }
+            }
+
+            if node.has_children {
+                // A plus while closed, a minus while open.
+                let t = self.toggle_size;
+                let tp = Position::new(
+                    row_pos.x + node.depth * self.indent,
+                    row_pos.y + (h - t) / 2,
+                );
+                let bar = (t / 5).max(1);
+                instances.push(Instance::ui(
+                    Position::new(tp.x, tp.y + (t - bar) / 2),
+                    Size::new(t, bar),
+                    self.toggle_color,
+                ));
+                let open = self
+                    .visible
+                    .get(row + 1)
+                    .is_some_and(|&n| self.nodes[n].parent == Some(i));
+                if !open {
+                    instances.push(Instance::ui(
+                        Position::new(tp.x + (t - bar) / 2, tp.y),
+                        Size::new(bar, t),
+                        self.toggle_color,
+                    ));
+                }
+            }
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        for &i in &self.visible {
+            self.nodes[i].label.handle(ctx);
+        }
+        ctx.ui.register_focusable(self.id);
+
+        let mouse = ctx.ui.mouse_pos;
+        let hovered = if ctx.ui.is_occluded(self.id, mouse) {
+            None
+        } else {
+            self.row_at(mouse)
+        };
+        if hovered.is_some() {
+            ctx.ui.hot_item = Some(self.id);
+        }
+
+        if ctx.ui.mouse_pressed && hovered.is_none() && ctx.ui.is_focused(self.id) {
+            ctx.ui.kbd_focus_item = None;
+        }
+        let mut model = self.model(ctx);
+        let before: Vec<usize> = model.selected().collect();
+        let before_cursor = model.cursor();
+        if ctx.ui.mouse_pressed
+            && ctx.ui.active_item != Some(self.id)
+            && let Some(row) = hovered
+        {
+            ctx.ui.active_item = Some(self.id);
+            ctx.ui.kbd_focus_item = Some(self.id);
+            let node = &self.nodes[self.visible[row]];
+            let toggle_end = self.position.x + node.depth * self.indent + self.toggle_size;
+            if node.has_children && mouse.x < (toggle_end + self.toggle_size / 2) as f32 {
+                let key = node.key.clone();
+                let state = ctx.ui.state_mut::<TreeState<K>>(self.id);
+                if !state.open.remove(&key) {
+                    state.open.insert(key);
+                }
+                ctx.ui.request_redraw();
+            } else {
+                model.click(row, &ctx.ui.modifiers);
+            }
+        }
+        if ctx.ui.mouse_released && ctx.ui.active_item == Some(self.id) {
+            ctx.ui.active_item = None;
+        }
+
+        let focused = ctx.ui.is_focused(self.id);
+        if focused != self.focused {
+            self.focused = focused;
+            ctx.ui.request_redraw();
+        }
+        if focused && !self.visible.is_empty() {
+            for key in ctx.ui.keys().to_vec() {
+                if key.state != KeyState::Pressed {
+                    continue;
+                }
+                match self.key_target(ctx, model.cursor(), &key.logical_key) {
+                    // Opening or closing a node changes the rows; the rest waits for layout.
+                    Some((_, true)) => break,
+                    Some((target, false)) => _ = model.move_cursor(target, &key.modifiers),
+                    // Space and select-all.
+                    None => _ = model.handle_key(&key, 1),
+                }
+            }
+        }
+
+        let selected: Vec<usize> = model.selected().collect();
+        if selected != before {
+            let keys: Vec<K> = selected
+                .iter()
+                .map(|&row| self.nodes[self.visible[row]].key.clone())
+                .collect();
+            if let Some(f) = self.on_selection_change.as_ref() {
+                ctx.ui.emit(f(keys.clone()));
+            }
+            if let Some(row) = model.cursor().filter(|&row| model.is_selected(row))
+                && let Some(f) = self.on_select.as_ref()
+            {
+                ctx.ui.emit(f(self.nodes[self.visible[row]].key.clone()));
+            }
+            self.selected = keys.into_iter().collect();
+            ctx.ui.request_redraw();
+        }
+        let key_at = |row: Option<usize>| row.map(|r| self.nodes[self.visible[r]].key.clone());
+        let state = ctx.ui.state_mut::<TreeState<K>>(self.id);
+        state.anchor = key_at(model.anchor());
+        state.cursor = key_at(model.cursor());
+        self.cursor = model.cursor();
+        if let Some(row) = self.cursor
+            && self.cursor != before_cursor
+        {
+            let (top, h) = self.rows[row];
+            ctx.ui
+                .request_reveal(self.position.y + top, self.position.y + top + h);
+            ctx.ui.request_redraw();
+        }
+
+        if hovered != self.hovered {
+            self.hovered = hovered;
+            ctx.ui.request_redraw();
+        }
+    }
+}
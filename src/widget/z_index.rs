@@ -0,0 +1,84 @@
+use super::*;
+
+/// Paints a child above (positive `z`) or below (negative `z`) the other widgets of its
+/// overlay layer instead of in tree order. Built with [`Widget::z_index`] or
+/// [`Element::z_index`]. Raised descendants are ordered among themselves within the child.
+pub(crate) struct ZIndex<M> {
+    id: Id,
+    child: Element<M>,
+    z: i32,
+}
+
+impl<M> ZIndex<M> {
+    pub(crate) fn new(child: Element<M>, z: i32) -> Self {
+        Self {
+            id: crate::context::next_id(),
+            child,
+            z,
+        }
+    }
+}
+
+impl<M: 'static> Widget<M> for ZIndex<M> {
+    fn id(&self) -> Id {
+        self.id
+    }
+    fn position(&self) -> &Position<i32> {
+        self.child.position()
+    }
+    fn layout(&self) -> &Layout {
+        self.child.layout()
+    }
+    fn layout_mut(&mut self) -> Option<&mut Layout> {
+        self.child.layout_mut()
+    }
+
+    fn for_each_child(&self, f: &mut dyn for<'a> FnMut(&'a dyn Widget<M>)) {
+        f(self.child.as_ref());
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn for<'a> FnMut(&'a mut dyn Widget<M>)) {
+        f(self.child.as_mut());
+    }
+
+    fn fit_width(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.child.fit_width(ctx)
+    }
+    fn grow_width(&mut self, ctx: &mut LayoutCtx<M>, parent_width: i32) {
+        self.child.grow_width(ctx, parent_width);
+    }
+    fn fit_height(&mut self, ctx: &mut LayoutCtx<M>) -> Layout {
+        self.child.fit_height(ctx)
+    }
+    fn grow_height(&mut self, ctx: &mut LayoutCtx<M>, parent_height: i32) {
+        self.child.grow_height(ctx, parent_height);
+    }
+    fn place(&mut self, ctx: &mut LayoutCtx<M>, position: Position<i32>) -> Size<i32> {
+        self.child.place(ctx, position)
+    }
+
+    fn draw_self(&self, _ctx: &mut PaintCtx, _instances: &mut Vec<Instance>) {}
+
+    fn __paint(
+        &self,
+        ctx: &mut PaintCtx,
+        instances: &mut Vec<Instance>,
+        t: &internal::PaintToken,
+        debug_on: bool,
+    ) {
+        let mark = ctx.raised.len();
+        let mut own = Vec::new();
+        self.child.__paint(ctx, &mut own, t, debug_on);
+        ctx.merge_raised(mark, &mut own);
+
+        if self.z == 0 {
+            instances.append(&mut own);
+        } else {
+            ctx.raise(self.z, own);
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut EventCtx<M>) {
+        self.child.handle(ctx);
+    }
+}
@@ -7,7 +7,7 @@ use std::{
 use smol_str::ToSmolStr;
 use winit::{
     application::ApplicationHandler,
-    dpi::PhysicalSize,
+    dpi::{PhysicalPosition, PhysicalSize},
     error::EventLoopError,
     event::WindowEvent,
     event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
@@ -16,10 +16,10 @@ use winit::{
 };
 
 use crate::{
-    Size,
+    Error, Size,
     event::{
-        Event, KeyEvent, KeyLocation, KeyState, LogicalKey, Modifiers, PhysicalKey, TextInput,
-        ToEvent,
+        CursorIcon, Event, KeyEvent, KeyLocation, KeyState, LogicalKey, Modifiers, MouseButton,
+        PhysicalKey, Preedit, ScrollDelta, TextInput, ToEvent, TouchPhase,
     },
     graphics::{Engine, TargetId},
     model::Position,
@@ -33,6 +33,22 @@ impl<P> From<PhysicalSize<P>> for Size<P> {
     }
 }
 
+fn map_cursor(icon: CursorIcon) -> winit::window::CursorIcon {
+    use winit::window::CursorIcon as W;
+    match icon {
+        CursorIcon::Default => W::Default,
+        CursorIcon::Pointer => W::Pointer,
+        CursorIcon::Text => W::Text,
+        CursorIcon::Grab => W::Grab,
+        CursorIcon::Grabbing => W::Grabbing,
+        CursorIcon::Move => W::Move,
+        CursorIcon::Crosshair => W::Crosshair,
+        CursorIcon::NotAllowed => W::NotAllowed,
+        CursorIcon::EwResize => W::EwResize,
+        CursorIcon::NsResize => W::NsResize,
+    }
+}
+
 fn map_winit_logical(k: &WKey) -> LogicalKey {
     use winit::keyboard::NamedKey;
     match k {
@@ -111,9 +127,32 @@ impl<M> ToEvent<M, winit::event::WindowEvent> for winit::event::WindowEvent {
             WE::CursorMoved { position, .. } => Event::CursorMoved {
                 position: Position::new(position.x as f32, position.y as f32),
             },
-            WE::MouseInput { state, .. } => Event::MouseInput {
+            WE::MouseInput { state, button, .. } => Event::MouseInput {
+                button: match button {
+                    winit::event::MouseButton::Right => MouseButton::Right,
+                    winit::event::MouseButton::Middle => MouseButton::Middle,
+                    winit::event::MouseButton::Back => MouseButton::Back,
+                    winit::event::MouseButton::Forward => MouseButton::Forward,
+                    _ => MouseButton::Left,
+                },
                 mouse_down: state.is_pressed(),
             },
+            WE::MouseWheel { delta, phase, .. } => Event::MouseWheel {
+                delta: match *delta {
+                    winit::event::MouseScrollDelta::LineDelta(x, y) => {
+                        ScrollDelta::Lines(Position::new(x, y))
+                    }
+                    winit::event::MouseScrollDelta::PixelDelta(p) => {
+                        ScrollDelta::Pixels(Position::new(p.x as f32, p.y as f32))
+                    }
+                },
+                phase: match phase {
+                    winit::event::TouchPhase::Started => TouchPhase::Started,
+                    winit::event::TouchPhase::Moved => TouchPhase::Moved,
+                    winit::event::TouchPhase::Ended => TouchPhase::Ended,
+                    winit::event::TouchPhase::Cancelled => TouchPhase::Cancelled,
+                },
+            },
             WE::KeyboardInput { event, .. } => {
                 let state = match event.state {
                     ElementState::Pressed => KeyState::Pressed,
@@ -133,7 +172,14 @@ impl<M> ToEvent<M, winit::event::WindowEvent> for winit::event::WindowEvent {
                     modifiers: Modifiers::default(),
                 })
             }
+            WE::HoveredFile(path) => Event::FileHover(path.clone()),
+            WE::DroppedFile(path) => Event::FileDrop(path.clone()),
+            WE::HoveredFileCancelled => Event::FileHoverCancelled,
             WE::Ime(winit::event::Ime::Commit(s)) => Event::Text(TextInput { text: s.clone() }),
+            WE::Ime(winit::event::Ime::Preedit(text, cursor)) => Event::ImePreedit(Preedit {
+                text: text.clone(),
+                cursor: *cursor,
+            }),
             WE::ModifiersChanged(m) => Event::ModifiersChanged(Modifiers {
                 shift: m.state().shift_key(),
                 control: m.state().control_key(),
@@ -147,18 +193,14 @@ impl<M> ToEvent<M, winit::event::WindowEvent> for winit::event::WindowEvent {
     }
 }
 
-fn frame_interval_from_monitor(window: &Window) -> Duration {
+fn frame_interval_from_monitor(window: &Window) -> Option<Duration> {
     const NS_PER_S: u128 = 1_000_000_000;
     const M_PER: u128 = 1_000;
-    const FALLBACK_NS_60HZ: u128 = NS_PER_S / 60;
 
-    let ns = window
+    window
         .current_monitor()
         .and_then(|m| m.refresh_rate_millihertz())
-        .map(|mhz| (NS_PER_S * M_PER) / (mhz as u128))
-        .unwrap_or(FALLBACK_NS_60HZ);
-
-    Duration::from_nanos(ns as u64)
+        .map(|mhz| Duration::from_nanos(((NS_PER_S * M_PER) / (mhz as u128)) as u64))
 }
 
 pub struct WinitApp<'a, M, S, V, U>
@@ -182,8 +224,9 @@ where
     view: V,
     update: U,
     window_attrs: WindowAttributes,
-    next_frame: Instant,
-    frame_interval: Duration,
+    frame_interval: Option<Duration>,
+    ime_area: Option<(Position<i32>, Size<i32>)>,
+    cursor: CursorIcon,
 }
 
 impl<'a, M, S, V, U> WinitApp<'a, M, S, V, U>
@@ -215,8 +258,9 @@ where
             view,
             update,
             window_attrs,
-            next_frame: Instant::now(),
-            frame_interval: Duration::from_millis(16),
+            frame_interval: None,
+            ime_area: None,
+            cursor: CursorIcon::Default,
         }
     }
 }
@@ -242,13 +286,16 @@ where
                     .expect("Failed to create window"),
             );
             let size = window.inner_size().into();
-            let (target, mut engine) = Engine::new_for(window.clone(), size);
+            let (target, mut engine) =
+                Engine::new_for(window.clone(), size).expect("Failed to create engine");
             if let Some(pipelines) = self.extra_pipelines.take() {
                 for (key, factory) in pipelines {
-                    engine.register_pipeline(
-                        crate::render::pipeline::PipelineKey::Other(key),
-                        factory,
-                    );
+                    engine
+                        .register_pipeline(
+                            crate::render::pipeline::PipelineKey::other(key),
+                            factory,
+                        )
+                        .expect("engine was created with a target");
                 }
             }
 
@@ -260,14 +307,19 @@ where
     }
 
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
-        let now = Instant::now();
-        if now >= self.next_frame {
-            if let Some(w) = self.window.as_ref() {
-                w.request_redraw();
+        let (Some(engine), Some(tid), Some(window)) = (&self.engine, self.target, &self.window)
+        else {
+            return;
+        };
+        // Polling happens on redraws, which the target's pacing decides the timing of.
+        match engine.next_frame(&tid, self.frame_interval) {
+            Some(next) if next <= Instant::now() => {
+                window.request_redraw();
+                event_loop.set_control_flow(ControlFlow::Wait);
             }
-            self.next_frame = now + self.frame_interval;
+            Some(next) => event_loop.set_control_flow(ControlFlow::WaitUntil(next)),
+            None => event_loop.set_control_flow(ControlFlow::Wait),
         }
-        event_loop.set_control_flow(ControlFlow::WaitUntil(self.next_frame));
     }
 
     fn window_event(
@@ -288,12 +340,67 @@ where
                     &mut self.state,
                     event_loop,
                 );
-                engine.render_if_needed(
+                let drawn = engine.render_if_needed(
                     &self.target.unwrap(),
                     should_redraw,
                     &self.view,
                     &mut self.state,
                 );
+                // Other failures only cost a frame.
+                if let Err(Error::Surface(wgpu::SurfaceError::OutOfMemory)) = drawn {
+                    event_loop.exit();
+                }
+
+                let area = engine.ime_area(&self.target.unwrap());
+                if area != self.ime_area
+                    && let Some(window) = self.window.as_ref()
+                {
+                    if area.is_some() != self.ime_area.is_some() {
+                        window.set_ime_allowed(area.is_some());
+                    }
+                    if let Some((pos, size)) = area {
+                        window.set_ime_cursor_area(
+                            PhysicalPosition::new(pos.x, pos.y),
+                            PhysicalSize::new(size.width.max(0) as u32, size.height.max(0) as u32),
+                        );
+                    }
+                    self.ime_area = area;
+                }
+
+                let cursor = engine.cursor(&self.target.unwrap());
+                if cursor != self.cursor
+                    && let Some(window) = self.window.as_ref()
+                {
+                    window.set_cursor(map_cursor(cursor));
+                    self.cursor = cursor;
+                }
+
+                // winit can't reach the clipboard, so copies and pastes go to the application.
+                let tid = self.target.unwrap();
+                if let Some(text) = engine.take_copied(&tid)
+                    && update(
+                        tid,
+                        engine,
+                        &Event::Copied(text),
+                        &mut self.state,
+                        event_loop,
+                    )
+                    && let Some(window) = self.window.as_ref()
+                {
+                    window.request_redraw();
+                }
+                if engine.take_paste_request(&tid)
+                    && update(
+                        tid,
+                        engine,
+                        &Event::PasteRequested,
+                        &mut self.state,
+                        event_loop,
+                    )
+                    && let Some(window) = self.window.as_ref()
+                {
+                    window.request_redraw();
+                }
             }
             _ => {
                 match event {
@@ -307,7 +414,7 @@ where
                     _ => (),
                 }
                 let engine = self.engine.as_mut().unwrap();
-                engine.handle_platform_event(
+                let _ = engine.handle_platform_event(
                     &self.target.unwrap(),
                     &event,
                     &mut |engine, event, state, loop_ctl| {